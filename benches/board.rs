@@ -0,0 +1,127 @@
+//! Compares the two representations of the board: rows of `BoardChar`
+//! and the bit masks of `Bitboard`.
+//!
+//! Run with `cargo bench --bench board`. Both are timed on the same random
+//! positions, checking every run for a win and placing and taking back a
+//! mark on every empty cell, as the search does.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use tic_tac_toe_rs::bitboard::Bitboard;
+use tic_tac_toe_rs::rng::Rng;
+use tic_tac_toe_rs::{Board, BoardChar, TicTacToe};
+
+/// Minimum time spent measuring each representation
+const MEASURE_TIME: Duration = Duration::from_secs(1);
+/// Random positions per board size
+const POSITIONS: usize = 1000;
+
+/// This function fills about half of the cells of `POSITIONS` boards at random
+fn positions(size: usize, rng: &mut Rng) -> Vec<Board> {
+    (0..POSITIONS)
+        .map(|_| {
+            (0..size)
+                .map(|_| {
+                    (0..size)
+                        .map(|_| match rng.below(4) {
+                            0 => BoardChar::X,
+                            1 => BoardChar::O,
+                            _ => BoardChar::Empty,
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// This function runs `work` repeatedly and returns the mean time of a run
+fn measure(mut work: impl FnMut() -> usize) -> Duration {
+    let mut runs = 0;
+    let start = Instant::now();
+
+    while start.elapsed() < MEASURE_TIME || runs == 0 {
+        black_box(work());
+        runs += 1;
+    }
+
+    start.elapsed() / runs
+}
+
+/// This function checks every run of `lines` for a win of X, placing and
+/// taking back X on every empty cell of `board` first
+fn with_rows(board: &mut Board, lines: &[Vec<(usize, usize)>]) -> usize {
+    let mut wins = 0;
+    let size = board.len();
+
+    for row in 0..size {
+        for col in 0..size {
+            if board[row][col] != BoardChar::Empty {
+                continue;
+            }
+
+            board[row][col] = BoardChar::X;
+            if lines.iter().any(|line| line.iter().all(|&(r, c)| board[r][c] == BoardChar::X)) {
+                wins += 1;
+            }
+            board[row][col] = BoardChar::Empty;
+        }
+    }
+
+    wins
+}
+
+/// This function does the same as `with_rows` on a bitboard
+fn with_bits(bits: &mut Bitboard) -> usize {
+    let mut wins = 0;
+    let size = bits.size();
+
+    for row in 0..size {
+        for col in 0..size {
+            if bits[(row, col)] != BoardChar::Empty {
+                continue;
+            }
+
+            bits.set(row, col, BoardChar::X);
+            if bits.has_won(BoardChar::X) {
+                wins += 1;
+            }
+            bits.set(row, col, BoardChar::Empty);
+        }
+    }
+
+    wins
+}
+
+fn bench(size: usize, win_len: usize, rng: &mut Rng) {
+    let lines = TicTacToe::runs(size, win_len);
+    let mut boards = positions(size, rng);
+    let mut bits: Vec<_> = boards
+        .iter()
+        .map(|board| Bitboard::from_board(board, &lines).expect("benchmark boards fit in a bitboard"))
+        .collect();
+
+    let rows = measure(|| boards.iter_mut().map(|board| with_rows(board, &lines)).sum());
+    let masks = measure(|| bits.iter_mut().map(with_bits).sum());
+
+    println!(
+        "{:>2}x{:<2} win {:<2} rows {:>12?} bits {:>12?} {:>6.1}x faster",
+        size,
+        size,
+        win_len,
+        rows,
+        masks,
+        rows.as_secs_f64() / masks.as_secs_f64()
+    );
+}
+
+fn main() {
+    let mut rng = Rng::new(0);
+
+    bench(3, 3, &mut rng);
+    bench(4, 4, &mut rng);
+    bench(5, 4, &mut rng);
+    bench(7, 5, &mut rng);
+    bench(11, 5, &mut rng);
+}
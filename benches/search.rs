@@ -0,0 +1,66 @@
+//! Times `find_best_move` on a few representative positions.
+//!
+//! Run with `cargo bench`. Each position is searched repeatedly and the
+//! mean, fastest and slowest run are reported, together with the number of
+//! positions the search visited.
+
+use std::time::{Duration, Instant};
+
+use tic_tac_toe_rs::{BoardChar, Move, Search, TicTacToe};
+
+/// Minimum time spent measuring each position
+const MEASURE_TIME: Duration = Duration::from_secs(2);
+
+/// This function builds a game where the player has X and the given moves
+/// were played alternately by the player and the machine
+fn position(moves: &[(usize, usize)]) -> TicTacToe {
+    let mut game = TicTacToe::new(BoardChar::X);
+    let mut c = BoardChar::X;
+
+    for &(row, col) in moves {
+        game.do_move(&Move { row, col }, c)
+            .expect("benchmark positions only contain legal moves");
+        c = c.to_opposite();
+    }
+
+    game
+}
+
+fn bench(name: &str, game: &TicTacToe) {
+    let mut runs = Vec::new();
+    let mut nodes = 0;
+    let start = Instant::now();
+
+    while start.elapsed() < MEASURE_TIME || runs.is_empty() {
+        let mut game = game.clone();
+        let mut search = Search::new(None, None);
+
+        let t = Instant::now();
+        let m = game.find_best_move(&mut search);
+        runs.push(t.elapsed());
+
+        assert!(m.is_some(), "{} has no move to search", name);
+        nodes = search.nodes;
+    }
+
+    let total: Duration = runs.iter().sum();
+    let mean = total / runs.len() as u32;
+
+    println!(
+        "{:<20} {:>12?} mean {:>12?} min {:>12?} max {:>10} nodes {:>6} runs",
+        name,
+        mean,
+        runs.iter().min().unwrap(),
+        runs.iter().max().unwrap(),
+        nodes,
+        runs.len()
+    );
+}
+
+fn main() {
+    bench("empty board", &position(&[]));
+    bench("center opening", &position(&[(1, 1)]));
+    bench("corner opening", &position(&[(0, 0)]));
+    bench("mid-game", &position(&[(1, 1), (0, 0), (0, 2)]));
+    bench("endgame", &position(&[(1, 1), (0, 0), (0, 2), (2, 0), (1, 0)]));
+}
@@ -0,0 +1,60 @@
+//! Times whole games the machine plays against itself.
+//!
+//! Run with `cargo bench --bench selfplay`. Each match is played one game
+//! at a time with a fixed seed, and the mean time of a game and of a move
+//! are reported, together with how the games ended.
+
+use std::time::{Duration, Instant};
+
+use tic_tac_toe_rs::rng::Rng;
+use tic_tac_toe_rs::{self_play, BoardChar, Contender, TicTacToe};
+
+/// Minimum time spent measuring each match
+const MEASURE_TIME: Duration = Duration::from_secs(2);
+
+fn bench(name: &str, board: &TicTacToe, contenders: [&str; 2]) {
+    let contenders = [
+        contenders[0].parse::<Contender>().expect("benchmark contenders are valid"),
+        contenders[1].parse::<Contender>().expect("benchmark contenders are valid"),
+    ];
+    let mut rng = Rng::new(0);
+    let mut runs = Vec::new();
+    let (mut moves, mut wins, mut draws) = (0, [0; 2], 0);
+    let start = Instant::now();
+
+    while start.elapsed() < MEASURE_TIME || runs.is_empty() {
+        let t = Instant::now();
+        let report = self_play(board, contenders, 1, &mut rng);
+        runs.push(t.elapsed());
+
+        moves += report.moves.iter().sum::<usize>();
+        wins[0] += report.wins[0];
+        wins[1] += report.wins[1];
+        draws += report.draws;
+    }
+
+    let total: Duration = runs.iter().sum();
+
+    println!(
+        "{:<24} {:>12?} per game {:>12?} per move {:>4}-{}-{} {:>6} games",
+        name,
+        total / runs.len() as u32,
+        total / moves.max(1) as u32,
+        wins[0],
+        draws,
+        wins[1],
+        runs.len()
+    );
+}
+
+fn main() {
+    let classic = TicTacToe::new(BoardChar::X);
+    let four = TicTacToe::with_size(BoardChar::X, 4, 4);
+    let five = TicTacToe::with_size(BoardChar::X, 5, 4);
+
+    bench("3x3 minimax", &classic, ["minimax", "minimax"]);
+    bench("3x3 minimax vs mcts", &classic, ["minimax", "mcts"]);
+    bench("4x4 minimax:4", &four, ["minimax:4", "minimax:4"]);
+    bench("5x5 win 4 minimax:3", &five, ["minimax:3", "minimax:3"]);
+    bench("5x5 win 4 mcts", &five, ["mcts", "mcts"]);
+}
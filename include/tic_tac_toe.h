@@ -0,0 +1,60 @@
+/* The C interface of tic_tac-toe_rs, see src/ffi.rs.
+ *
+ * Build the shared library with `cargo build --release --features ffi`
+ * and link against libtic_tac_toe_rs. Rows and columns count from 0,
+ * marks are 0 for an empty cell, 1 for X and 2 for O. */
+
+#ifndef TIC_TAC_TOE_H
+#define TIC_TAC_TOE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define TTT_OK 0
+#define TTT_ERR_NULL -1
+#define TTT_ERR_OUT_OF_BOUNDS -2
+#define TTT_ERR_OCCUPIED -3
+#define TTT_ERR_GAME_OVER -4
+#define TTT_ERR_INVALID -5
+#define TTT_ERR_BUFFER -6
+
+#define TTT_IN_PROGRESS 0
+#define TTT_X_WON 1
+#define TTT_O_WON 2
+#define TTT_DRAW 3
+
+typedef struct TttGame TttGame;
+
+/* Starts a game, `player` is 'X' or 'O', X opens. Null if the arguments make no game. */
+TttGame *ttt_new(uint32_t size, uint32_t win_len, char player, uint64_t seed);
+
+/* Releases a game, null is ignored. */
+void ttt_free(TttGame *game);
+
+/* Places the mark of the side to move. Returns TTT_OK or an error code. */
+int32_t ttt_player_move(TttGame *game, uint32_t row, uint32_t col);
+
+/* Writes the machine's move for the side to move without playing it,
+ * thinking at most `millis` milliseconds, 0 for no time limit. */
+int32_t ttt_best_move(TttGame *game, uint32_t millis, uint32_t *row, uint32_t *col);
+
+/* Writes the marks of all cells row by row into `cells`, which holds `len`
+ * bytes, and returns TTT_IN_PROGRESS, TTT_X_WON, TTT_O_WON, TTT_DRAW or an
+ * error code. */
+int32_t ttt_state(const TttGame *game, uint8_t *cells, size_t len);
+
+/* The size of the board, 0 for null. */
+uint32_t ttt_size(const TttGame *game);
+
+/* The mark of the side to move, 0 once the game is over. */
+uint8_t ttt_turn(const TttGame *game);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
@@ -0,0 +1,133 @@
+//! What the search thinks of a move, in words a player understands.
+
+use std::fmt;
+
+use crate::engine::{HEURISTIC_BOUND, WIN_SCORE};
+use crate::r#move::{column_name, Move};
+
+/// The value of a move for the side that plays it
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Evaluation {
+    /// The side wins against any defence, the game ends after this many
+    /// moves of both sides, the move itself included
+    Win(usize),
+    /// The side loses against perfect play after this many moves
+    Loss(usize),
+    Draw,
+    /// The search stopped at its depth limit, the heuristic guesses this
+    /// much, positive values favour the side
+    Estimate(i16),
+}
+
+impl Evaluation {
+    /// This function reads a score of the search from the point of view of
+    /// the side that moved. `exact` says that the search reached the end of
+    /// the game, otherwise scores close to 0 are only estimates.
+    pub fn from_score(score: i16, exact: bool) -> Evaluation {
+        if score > HEURISTIC_BOUND {
+            Evaluation::Win((WIN_SCORE - score) as usize)
+        } else if score < -HEURISTIC_BOUND {
+            Evaluation::Loss((WIN_SCORE + score) as usize)
+        } else if exact {
+            Evaluation::Draw
+        } else {
+            Evaluation::Estimate(score)
+        }
+    }
+}
+
+impl fmt::Display for Evaluation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Evaluation::Win(1) => write!(f, "wins at once"),
+            Evaluation::Win(moves) => write!(f, "wins after {} moves", moves),
+            Evaluation::Loss(moves) => write!(f, "loses after {} moves", moves),
+            Evaluation::Draw => write!(f, "draws"),
+            Evaluation::Estimate(score) => write!(f, "unclear, estimated {:+}", score),
+        }
+    }
+}
+
+/// The game-theoretic value of a position, as `TicTacToe::solve` found it
+#[derive(Debug, PartialEq, Clone)]
+pub struct Solution {
+    /// The value for the side to move, the one of its best move
+    pub value: Evaluation,
+    /// Every legal move and its value, the best first
+    pub moves: Vec<(Move, Evaluation)>,
+    /// Positions the search visited and how many of them it remembered
+    pub nodes: u64,
+    pub remembered: usize,
+}
+
+/// What a move did, as `TicTacToe::lesson` found before it was played
+#[derive(Debug, PartialEq, Clone)]
+pub struct Lesson {
+    /// The move and its value
+    pub played: (Move, Evaluation),
+    /// The best move and its value, if it is better than the one played
+    pub better: Option<(Move, Evaluation)>,
+    /// How many marks a run holds when it is one short of winning
+    pub run_len: usize,
+    /// A cell that won at once and the run it completed, if the move missed it
+    pub missed_win: Option<(Move, Vec<Move>)>,
+    /// A cell where the opponent wins next and its run, left open by the move
+    pub ignored: Option<(Move, Vec<Move>)>,
+    /// The run of the opponent the move blocked, if any
+    pub blocked: Option<Vec<Move>>,
+    /// The cells where the side wins next after the move, each with its run
+    pub created: Vec<(Move, Vec<Move>)>,
+}
+
+/// This function names the row, column or diagonal `run` lies on
+fn describe_run(run: &[Move]) -> String {
+    match (run.first(), run.get(1)) {
+        (Some(a), Some(b)) if a.row == b.row => format!("row {}", a.row + 1),
+        (Some(a), Some(b)) if a.col == b.col => format!("column {}", column_name(a.col)),
+        (Some(a), Some(b)) if b.col > a.col => "the diagonal".to_string(),
+        _ => "the anti-diagonal".to_string(),
+    }
+}
+
+impl Lesson {
+    /// This function explains the lesson in sentences, `opponent` names the
+    /// other side, e.g. "the machine"
+    pub fn explain(&self, opponent: &str) -> Vec<String> {
+        let (m, played) = self.played;
+        let marks = match self.run_len {
+            1 => "one".to_string(),
+            2 => "two".to_string(),
+            3 => "three".to_string(),
+            4 => "four".to_string(),
+            n => n.to_string(),
+        };
+        let mut sentences = Vec::new();
+
+        match self.better {
+            None => sentences.push(format!("{} was the best move: it {}.", m, played)),
+            Some((best, better)) => sentences.push(format!("{}: it {}. {} was better: it {}.", m, played, best, better)),
+        }
+        if let Some((cell, run)) = &self.missed_win {
+            sentences.push(format!("{} would have completed {} and won at once.", cell, describe_run(run)));
+        }
+        if let Some(run) = &self.blocked {
+            sentences.push(format!("It blocks {}'s {}-in-a-row on {}.", opponent, marks, describe_run(run)));
+        }
+        if let Some((cell, run)) = &self.ignored {
+            sentences.push(format!(
+                "You ignored {}'s {}-in-a-row on {}, which it completes on {}.",
+                opponent,
+                marks,
+                describe_run(run),
+                cell
+            ));
+        }
+        match &self.created[..] {
+            [] => {}
+            [(cell, run)] => sentences.push(format!("It threatens to complete {} on {}.", describe_run(run), cell)),
+            [(a, _), (b, _), ..] => sentences.push(format!("It makes two threats at once, on {} and {}.", a, b)),
+        }
+
+        sentences
+    }
+}
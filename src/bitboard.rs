@@ -0,0 +1,150 @@
+//! The board as two bit masks, one per mark, for the search.
+//!
+//! Cell `(row, col)` is bit `row * size + col`. A run of cells that wins is
+//! a mask as well, so whether a mark completed it is a single `&`, and how
+//! many marks it holds a single `count_ones`. Boards of up to 11x11 cells
+//! fit in the 128 bits of a mask.
+
+use std::ops::Index;
+
+use crate::board::{Board, BoardChar};
+
+/// Largest board side whose cells fit in a mask
+pub const MAX_BITBOARD_SIZE: usize = 11;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Bitboard {
+    size: usize,
+    x: u128,
+    o: u128,
+    /// The cells of every run that wins, in the order they were given
+    lines: Vec<u128>,
+}
+
+impl Bitboard {
+    /// This function creates an empty `size` x `size` board on which the
+    /// runs of cells in `lines` win. It returns `None` for boards larger
+    /// than `MAX_BITBOARD_SIZE`.
+    pub fn new(size: usize, lines: &[Vec<(usize, usize)>]) -> Option<Bitboard> {
+        if size > MAX_BITBOARD_SIZE {
+            return None;
+        }
+
+        let lines = lines
+            .iter()
+            .map(|line| line.iter().fold(0, |mask, &(row, col)| mask | 1 << (row * size + col)))
+            .collect();
+
+        Some(Bitboard { size, x: 0, o: 0, lines })
+    }
+
+    /// This function creates a bitboard holding the marks of `board`
+    pub fn from_board(board: &Board, lines: &[Vec<(usize, usize)>]) -> Option<Bitboard> {
+        let mut bits = Bitboard::new(board.len(), lines)?;
+
+        for (row, cells) in board.iter().enumerate() {
+            for (col, &c) in cells.iter().enumerate() {
+                bits.set(row, col, c);
+            }
+        }
+
+        Some(bits)
+    }
+
+    /// This function returns the board as rows of cells
+    pub fn to_board(&self) -> Board {
+        (0..self.size)
+            .map(|row| (0..self.size).map(|col| self.get(row, col)).collect())
+            .collect()
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    fn bit(&self, row: usize, col: usize) -> u128 {
+        1 << (row * self.size + col)
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> BoardChar {
+        let bit = self.bit(row, col);
+
+        if self.x & bit != 0 {
+            BoardChar::X
+        } else if self.o & bit != 0 {
+            BoardChar::O
+        } else {
+            BoardChar::Empty
+        }
+    }
+
+    /// This function puts `c` on a cell, `BoardChar::Empty` clears it
+    pub fn set(&mut self, row: usize, col: usize, c: BoardChar) {
+        let bit = self.bit(row, col);
+        self.x &= !bit;
+        self.o &= !bit;
+
+        match c {
+            BoardChar::X => self.x |= bit,
+            BoardChar::O => self.o |= bit,
+            BoardChar::Empty => {}
+        }
+    }
+
+    /// This function empties every cell
+    pub fn clear(&mut self) {
+        self.x = 0;
+        self.o = 0;
+    }
+
+    /// This function returns the cells holding `c`, for `BoardChar::Empty`
+    /// the empty cells
+    pub fn marks(&self, c: BoardChar) -> u128 {
+        match c {
+            BoardChar::X => self.x,
+            BoardChar::O => self.o,
+            BoardChar::Empty => self.full() & !(self.x | self.o),
+        }
+    }
+
+    /// This function returns the mask of all cells of the board
+    fn full(&self) -> u128 {
+        (1 << (self.size * self.size)) - 1
+    }
+
+    pub fn count(&self, c: BoardChar) -> usize {
+        self.marks(c).count_ones() as usize
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.x | self.o == self.full()
+    }
+
+    /// This function returns the position in `lines` of a run `c` completed
+    pub fn winning_line(&self, c: BoardChar) -> Option<usize> {
+        let marks = self.marks(c);
+
+        self.lines.iter().position(|&line| marks & line == line)
+    }
+
+    pub fn has_won(&self, c: BoardChar) -> bool {
+        self.winning_line(c).is_some()
+    }
+
+    /// This function returns the masks of the runs that win
+    pub fn lines(&self) -> &[u128] {
+        &self.lines
+    }
+}
+
+impl Index<(usize, usize)> for Bitboard {
+    type Output = BoardChar;
+
+    fn index(&self, (row, col): (usize, usize)) -> &BoardChar {
+        match self.get(row, col) {
+            BoardChar::X => &BoardChar::X,
+            BoardChar::O => &BoardChar::O,
+            BoardChar::Empty => &BoardChar::Empty,
+        }
+    }
+}
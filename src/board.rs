@@ -0,0 +1,303 @@
+//! The board and the marks placed on it.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::engine::TicTacToe;
+use crate::error::GameError;
+#[cfg(feature = "serde")]
+use crate::json::{self, FromJson};
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum BoardChar {
+    O,
+    X,
+    Empty,
+}
+
+impl BoardChar {
+    pub fn to_opposite(self) -> BoardChar {
+        match self {
+            BoardChar::Empty => BoardChar::Empty,
+            BoardChar::O => BoardChar::X,
+            BoardChar::X => BoardChar::O,
+        }
+    }
+}
+
+impl fmt::Display for BoardChar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            BoardChar::Empty => write!(f, " "),
+            BoardChar::O => write!(f, "O"),
+            BoardChar::X => write!(f, "X"),
+        }
+    }
+}
+
+impl FromStr for BoardChar {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tr = s.trim();
+        if tr.len() > 1 {
+            return Err(GameError::InvalidInput(format!("Input {} too long", tr)));
+        }
+
+        if let Some(c) = tr.chars().next() {
+            match c {
+                'X' | 'x' => return Ok(BoardChar::X),
+                'O' | 'o' => return Ok(BoardChar::O),
+                _ => return Err(GameError::InvalidInput(format!("'{}' is not one of 'X', 'x', 'O', 'o'", c))),
+            }
+        }
+
+        Err(GameError::InvalidInput(format!("Could not parse: {}", tr)))
+    }
+}
+
+pub type Board = Vec<Vec<BoardChar>>;
+
+/// A run of cells completed by one mark, as `(row, col)` pairs
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct WinLine<'a> {
+    pub mark: BoardChar,
+    pub cells: &'a [(usize, usize)],
+}
+
+impl WinLine<'_> {
+    pub fn contains(&self, row: usize, col: usize) -> bool {
+        self.cells.contains(&(row, col))
+    }
+}
+
+/// This function parses a board written as text. Cells are `X`, `O` and
+/// `.`, `-` or `_` for an empty cell, rows are separated by `/` or line
+/// breaks. Without separators the cells must fill a square board.
+/// With the `serde` feature a JSON array of rows as written by
+/// `GameSnapshot` is accepted as well.
+pub fn parse_board(s: &str) -> Result<Board, GameError> {
+    let tr = s.trim();
+
+    #[cfg(feature = "serde")]
+    if tr.starts_with('[') {
+        let rows = json::parse(tr)?;
+        let rows = rows.as_array().ok_or("The board must be an array of rows")?;
+
+        return rows
+            .iter()
+            .map(|row| {
+                let cells = row.as_array().ok_or("Every board row must be an array")?;
+                cells.iter().map(|c| Ok(BoardChar::from_json(c)?)).collect()
+            })
+            .collect();
+    }
+
+    let mut rows: Vec<Vec<char>> = tr
+        .split(['/', '\n'])
+        .map(|row| row.trim_end_matches('\r').chars().collect())
+        .collect();
+
+    if rows.len() == 1 {
+        let cells = rows.remove(0);
+        let size = (0..=cells.len()).find(|n| n * n >= cells.len()).unwrap_or(0);
+        if size == 0 || size * size != cells.len() {
+            return Err(GameError::InvalidInput(format!("{} cells do not make a square board", cells.len())));
+        }
+
+        rows = cells.chunks(size).map(<[char]>::to_vec).collect();
+    }
+
+    let mut board = Board::new();
+    for row in rows {
+        let mut cells = Vec::new();
+        for c in row {
+            cells.push(match c {
+                'X' | 'x' => BoardChar::X,
+                'O' | 'o' => BoardChar::O,
+                '.' | '-' | '_' => BoardChar::Empty,
+                _ => return Err(GameError::InvalidInput(format!("'{}' is not one of 'X', 'O', '.'", c))),
+            });
+        }
+        board.push(cells);
+    }
+
+    Ok(board)
+}
+
+/// Checks that a position can come up in a game
+pub trait Validate {
+    /// This function returns why the position cannot come up in a game won
+    /// by `win_len` marks in a row that `first` opened, with `to_move` to
+    /// move, if it cannot: the marks do not fit who opened and who moves,
+    /// both sides completed a line, or marks were placed after a line was
+    /// completed. `BoardChar::Empty` for `first` or `to_move` leaves that
+    /// side unchecked. Boards that are not square are only checked for the
+    /// number of marks.
+    fn validate(&self, win_len: usize, first: BoardChar, to_move: BoardChar) -> Result<(), GameError>;
+}
+
+impl Validate for Board {
+    fn validate(&self, win_len: usize, first: BoardChar, to_move: BoardChar) -> Result<(), GameError> {
+        let count = |c: BoardChar| self.iter().flatten().filter(|&&cell| cell == c).count();
+        let (x, o) = (count(BoardChar::X), count(BoardChar::O));
+        if x.abs_diff(o) > 1 {
+            return Err(GameError::UnbalancedMarks);
+        }
+
+        // The opener has as many marks as the other side or one more, and
+        // moves again when both have as many
+        if first != BoardChar::Empty {
+            let (opened, second) = if first == BoardChar::X { (x, o) } else { (o, x) };
+            if opened < second {
+                return Err(GameError::UnbalancedMarks);
+            }
+            let next = if opened == second { first } else { first.to_opposite() };
+            if to_move != BoardChar::Empty && to_move != next {
+                return Err(GameError::OutOfTurn);
+            }
+        }
+
+        let size = self.len();
+        if win_len == 0 || win_len > size || self.iter().any(|row| row.len() != size) {
+            return Ok(());
+        }
+
+        let runs = TicTacToe::runs(size, win_len);
+        let completed = |c: BoardChar| -> Vec<&Vec<(usize, usize)>> {
+            runs.iter().filter(|run| run.iter().all(|&(row, col)| self[row][col] == c)).collect()
+        };
+        let (x_lines, o_lines) = (completed(BoardChar::X), completed(BoardChar::O));
+
+        let (winner, lines, marks, others) = match (x_lines.is_empty(), o_lines.is_empty()) {
+            (false, false) => return Err(GameError::BothSidesWon),
+            (false, true) => (BoardChar::X, x_lines, x, o),
+            (true, false) => (BoardChar::O, o_lines, o, x),
+            (true, true) => return Ok(()),
+        };
+
+        // The side that completed a line placed the last mark, and every
+        // line it completed runs through that mark. With as many marks as
+        // the other side it moved second, or the other side moved after it
+        let last = lines[0].iter().any(|cell| lines.iter().all(|line| line.contains(cell)));
+        if marks < others || !last || (marks == others && winner == first) {
+            return Err(GameError::MoveAfterWin);
+        }
+
+        Ok(())
+    }
+}
+
+/// Positions written in one line of text, e.g. `XO./.X./..O X`: the rows of
+/// the board from the top, separated by `/`, with `.` for an empty cell,
+/// then the side to move.
+pub trait Notation: Sized {
+    /// This function reads a position and the side to move. Without a side
+    /// the one with fewer marks moves, X if both have as many.
+    fn from_notation(s: &str) -> Result<(Self, BoardChar), GameError>;
+
+    fn to_notation(&self, to_move: BoardChar) -> String;
+}
+
+impl Notation for Board {
+    fn from_notation(s: &str) -> Result<(Board, BoardChar), GameError> {
+        let mut fields = s.split_whitespace();
+        let rows = fields.next().ok_or("The position is empty")?;
+        let board = parse_board(rows)?;
+
+        let size = board.len();
+        if board.iter().any(|row| row.len() != size) {
+            return Err(GameError::InvalidInput(format!("The rows of '{}' do not make a square board", rows)));
+        }
+
+        let count = |c: BoardChar| board.iter().flatten().filter(|&&cell| cell == c).count();
+        let (x, o) = (count(BoardChar::X), count(BoardChar::O));
+        let to_move = match fields.next() {
+            Some(side) => side.parse()?,
+            None if o < x => BoardChar::O,
+            None => BoardChar::X,
+        };
+
+        if fields.next().is_some() {
+            return Err(GameError::InvalidInput("A position is the board and the side to move, e.g. XO./.X./..O X".to_string()));
+        }
+
+        // The side to move never has more marks than the other, nor fewer
+        // by more than one
+        let (moving, waiting) = if to_move == BoardChar::X { (x, o) } else { (o, x) };
+        if moving > waiting || moving + 1 < waiting {
+            return Err(GameError::InvalidInput(format!(
+                "{} cannot be to move with {} X and {} O on the board",
+                to_move, x, o
+            )));
+        }
+
+        Ok((board, to_move))
+    }
+
+    fn to_notation(&self, to_move: BoardChar) -> String {
+        let rows: Vec<String> = self
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&c| match c {
+                        BoardChar::Empty => '.',
+                        BoardChar::X => 'X',
+                        BoardChar::O => 'O',
+                    })
+                    .collect()
+            })
+            .collect();
+
+        format!("{} {}", rows.join("/"), to_move)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::engine::GameSnapshot;
+    use crate::player::Symbols;
+
+    fn position(s: &str) -> (Board, BoardChar) {
+        Board::from_notation(s).expect("the positions of a test can be read")
+    }
+
+    #[test]
+    fn a_winner_with_as_many_marks_moved_second() {
+        // X completed the row with its third mark and O moved after it
+        let (board, to_move) = position("XXX/OO./..O X");
+        assert_eq!(board.validate(3, to_move, to_move), Err(GameError::MoveAfterWin));
+
+        // O opened, so X completing the row with its third mark ended it
+        let (board, to_move) = position("XXX/OO./..O O");
+        assert_eq!(board.validate(3, BoardChar::O, to_move), Ok(()));
+    }
+
+    #[test]
+    fn marks_must_fit_the_opener_and_the_side_to_move() {
+        let (board, _) = position("X../.../... O");
+        assert_eq!(board.validate(3, BoardChar::O, BoardChar::X), Err(GameError::UnbalancedMarks));
+        assert_eq!(board.validate(3, BoardChar::X, BoardChar::X), Err(GameError::OutOfTurn));
+        assert_eq!(board.validate(3, BoardChar::X, BoardChar::O), Ok(()));
+    }
+
+    #[test]
+    fn a_position_after_the_game_was_won_is_refused() {
+        let (board, turn) = position("XXX/OO./..O X");
+        let state = GameSnapshot {
+            win_len: 3,
+            board,
+            player_char: BoardChar::X,
+            machine_char: BoardChar::O,
+            symbols: Symbols::default(),
+            turn,
+            history: Vec::new(),
+            misere: false,
+            handicap: Vec::new(),
+        };
+
+        assert_eq!(TicTacToe::from_snapshot(&state).err(), Some(GameError::MoveAfterWin));
+    }
+}
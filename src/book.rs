@@ -0,0 +1,81 @@
+//! An opening book: the best replies in the first moves of the classic 3x3
+//! game, so the machine answers them at once instead of searching a tree
+//! it has searched every game before.
+//!
+//! Every position is kept once, `lookup` also finds its rotations and
+//! reflections and the position with the marks swapped. All replies in the
+//! book are as good as the best one the solver finds: they win whenever the
+//! position is won and hold the draw otherwise.
+
+use crate::board::{Board, BoardChar, Notation};
+use crate::r#move::Move;
+
+/// The positions of the book and their replies
+const BOOK: &[(&str, &str)] = &[
+    (".../.../... X", "1A 2B"),
+    // The opponent opened
+    ("X../.../... O", "2B"),
+    (".X./.../... O", "2B"),
+    (".../.X./... O", "1A"),
+    // The machine opened in the corner
+    ("X../.O./... X", "3C"),
+    ("XO./.../... X", "2A 2B 3A"),
+    ("X.O/.../... X", "2A 3A 3C"),
+    ("X../..O/... X", "1C 2B 3A"),
+    ("X../.../..O X", "1C 3A"),
+    // The machine opened in the center
+    ("O../.X./... X", "3C"),
+    (".O./.X./... X", "1A 1C 2A 2C 3A 3C"),
+];
+
+/// The cell `(row, col)` is carried to by the symmetry `k` of a `size` x
+/// `size` board: four rotations, each with and without a reflection
+fn transform(k: usize, size: usize, row: usize, col: usize) -> (usize, usize) {
+    let (row, col) = if k >= 4 { (row, size - 1 - col) } else { (row, col) };
+
+    match k % 4 {
+        0 => (row, col),
+        1 => (col, size - 1 - row),
+        2 => (size - 1 - row, size - 1 - col),
+        _ => (size - 1 - col, row),
+    }
+}
+
+/// This function returns the replies of the book for `to_move` on `board`
+/// where `win_len` marks in a row win, empty if the position is not in it
+pub fn lookup(board: &Board, to_move: BoardChar, win_len: usize) -> Vec<Move> {
+    let size = board.len();
+    let mut replies = Vec::new();
+    if size != 3 || win_len != 3 {
+        return replies;
+    }
+
+    for (position, moves) in BOOK {
+        // The book is written for X opening, with O opening the marks swap
+        let (known, turn) = Board::from_notation(position).expect("the book is valid");
+        let mark = |c: BoardChar| if turn == to_move { c } else { c.to_opposite() };
+
+        for k in 0..8 {
+            let matches = (0..size).all(|row| {
+                (0..size).all(|col| {
+                    let (r, c) = transform(k, size, row, col);
+                    board[r][c] == mark(known[row][col])
+                })
+            });
+            if !matches {
+                continue;
+            }
+
+            for m in moves.split_whitespace() {
+                let m: Move = m.parse().expect("the book is valid");
+                let (row, col) = transform(k, size, m.row, m.col);
+                let reply = Move { row, col };
+                if !replies.contains(&reply) {
+                    replies.push(reply);
+                }
+            }
+        }
+    }
+
+    replies
+}
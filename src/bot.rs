@@ -0,0 +1,201 @@
+//! Bots written by users, run as a process of their own so that any
+//! language can be used and the crate need not be rebuilt for them.
+//!
+//! A bot reads commands from its stdin and answers on its stdout, one per
+//! line, in the protocol of `protocol.rs`, which `--engine` speaks as well.
+//! It is only sent a few of the commands:
+//!
+//! ```text
+//! engine                      -> engineok, after any id lines, e.g. id name Randy
+//! newgame <size> <win>        before every move, the board and the line that wins
+//! setoption misere yes|no     after every newgame
+//! position <position>         the board and the side to move, e.g. XO./.X./..O X
+//! go movetime <ms>            -> bestmove <move>, e.g. bestmove 2B
+//! quit                        when the bot is not needed any more
+//! ```
+//!
+//! Lines the bot writes in between, e.g. `info`, are skipped. A bot that
+//! does not answer in time, writes `bestmove` with a move that is not
+//! possible or ends fails, and loses the game as a player.
+
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::engine::TicTacToe;
+use crate::player::{Decision, Player};
+use crate::r#move::Move;
+
+/// Time a bot gets on top of its `movetime` to send the move over
+pub const BOT_GRACE: Duration = Duration::from_millis(500);
+
+/// A bot running as a process
+pub struct ExternalBot {
+    child: Child,
+    stdin: ChildStdin,
+    /// The lines the bot writes, read on a thread of their own so that
+    /// waiting for them can time out
+    lines: Receiver<String>,
+    name: String,
+    move_time: Duration,
+    /// Why the bot lost its last game, if it failed
+    failure: Option<String>,
+}
+
+impl ExternalBot {
+    /// This function starts `command` with the shell and waits for it to
+    /// introduce itself. The bot thinks no longer than `move_time` about a
+    /// move.
+    pub fn spawn(command: &str, move_time: Duration) -> io::Result<ExternalBot> {
+        let mut shell = if cfg!(windows) {
+            let mut shell = Command::new("cmd");
+            shell.args(["/C", command]);
+            shell
+        } else {
+            let mut shell = Command::new("sh");
+            shell.args(["-c", command]);
+            shell
+        };
+        let mut child = shell.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::inherit()).spawn()?;
+
+        let stdin = child.stdin.take().ok_or_else(|| io::Error::other("the bot has no stdin"))?;
+        let stdout = child.stdout.take().ok_or_else(|| io::Error::other("the bot has no stdout"))?;
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut bot = ExternalBot {
+            child,
+            stdin,
+            lines,
+            name: command.to_string(),
+            move_time,
+            failure: None,
+        };
+        bot.send("engine")?;
+        let deadline = Instant::now() + move_time + BOT_GRACE;
+        loop {
+            let line = bot.receive(deadline)?;
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next()) {
+                (Some("engineok"), _) => break,
+                (Some("id"), Some("name")) => {
+                    let name = words.collect::<Vec<_>>().join(" ");
+                    if !name.is_empty() {
+                        bot.name = name;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(bot)
+    }
+
+    /// This function returns the name the bot introduced itself with, its
+    /// command if it did not
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// This function returns why the bot lost its last game, if it failed
+    pub fn failure(&self) -> Option<&str> {
+        self.failure.as_deref()
+    }
+
+    fn send(&mut self, line: &str) -> io::Result<()> {
+        writeln!(self.stdin, "{}", line)?;
+        self.stdin.flush()
+    }
+
+    /// This function waits for the next line of the bot until `deadline`
+    fn receive(&mut self, deadline: Instant) -> io::Result<String> {
+        let left = deadline.saturating_duration_since(Instant::now());
+
+        match self.lines.recv_timeout(left) {
+            Ok(line) => Ok(line),
+            Err(RecvTimeoutError::Timeout) => Err(io::Error::new(io::ErrorKind::TimedOut, "the bot did not answer in time")),
+            Err(RecvTimeoutError::Disconnected) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the bot ended")),
+        }
+    }
+
+    /// This function asks the bot for its move in `game` and checks that it
+    /// is possible. The bot thinks until `deadline` if that comes before
+    /// its own time is up.
+    pub fn best_move(&mut self, game: &TicTacToe, deadline: Option<Instant>) -> io::Result<Move> {
+        let mut until = Instant::now() + self.move_time;
+        if let Some(deadline) = deadline {
+            until = until.min(deadline);
+        }
+        let movetime = until.saturating_duration_since(Instant::now()).as_millis();
+
+        let misere = if game.is_misere() { "yes" } else { "no" };
+        self.send(&format!("newgame {} {}", game.board().len(), game.win_len()))?;
+        self.send(&format!("setoption misere {}", misere))?;
+        self.send(&format!("position {}", game.to_notation()))?;
+        self.send(&format!("go movetime {}", movetime))?;
+
+        loop {
+            let line = self.receive(until + BOT_GRACE)?;
+            let mut words = line.split_whitespace();
+            match (words.next(), words.next(), words.next()) {
+                (Some("bestmove"), Some(m), None) => {
+                    let m: Move = m.parse().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("the bot played '{}': {}", m, e)))?;
+                    if let Err(e) = game.check_move(&m) {
+                        return Err(io::Error::new(io::ErrorKind::InvalidData, format!("the bot played {}, {}", m, e)));
+                    }
+                    return Ok(m);
+                }
+                (Some("error"), ..) => return Err(io::Error::new(io::ErrorKind::InvalidData, format!("the bot said: {}", line.trim()))),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Player for ExternalBot {
+    fn choose_move(&mut self, game: &mut TicTacToe, deadline: Option<Instant>) -> Decision {
+        match self.best_move(game, deadline) {
+            Ok(m) => Decision::Play(m),
+            Err(e) => {
+                // A bot that fails loses, too slow it loses on time
+                let turn = game.turn();
+                match e.kind() {
+                    io::ErrorKind::TimedOut => game.forfeit(turn),
+                    _ => game.resign(turn),
+                }
+                self.failure = Some(e.to_string());
+                Decision::Changed
+            }
+        }
+    }
+
+    /// The protocol has no question for the pie rule, the machine decides
+    /// for the bot within its time for a move
+    fn swap(&mut self, game: &mut TicTacToe) -> bool {
+        game.should_swap(Some(Instant::now() + self.move_time))
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        // A bot that does not quit on its own is not waited for
+        let deadline = Instant::now() + BOT_GRACE;
+        while Instant::now() < deadline {
+            match self.child.try_wait() {
+                Ok(None) => thread::sleep(Duration::from_millis(10)),
+                _ => return,
+            }
+        }
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
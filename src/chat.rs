@@ -0,0 +1,523 @@
+//! A gateway to chat platforms, so that a channel can play against the
+//! engine by typing its moves. Built only with the `chat` feature.
+//!
+//! Messages addressed to the bot start with `!ttt`, everything else said in
+//! a channel is ignored:
+//!
+//! ```text
+//! !ttt new [<size> [<win>]] [x|o] [easy|medium|hard]
+//!                             starts a game in the channel, the machine opens if the channel plays O
+//! !ttt <move>                 plays the move for the channel, e.g. !ttt 2B, the machine answers
+//! !ttt board                  shows the board again
+//! !ttt resign                 gives the game up
+//! !ttt help                   lists the commands
+//! ```
+//!
+//! The bot answers with the board drawn in ASCII and its own move. Every
+//! channel has a game of its own and a worker thread answering its
+//! messages one after the other, in the order they came, so the machine
+//! thinking in one channel does not hold up the others. No more than
+//! `MAX_SEARCHES` channels have the machine think at once, the others wait
+//! for their turn. A worker left alone for `IDLE` ends, the game stays.
+//!
+//! The gateway speaks two transports. `connect` joins channels on an IRC
+//! server as a client. `serve` answers a plain line protocol that webhook
+//! bridges to other platforms, e.g. Discord, can relay messages over: the
+//! bridge sends `<channel> <message>` lines and gets every line of the
+//! answer back as `<channel> <line>`.
+
+use std::collections::HashMap;
+use std::io::{self, BufReader, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, SendError, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::board::BoardChar;
+use crate::engine::{Difficulty, TicTacToe};
+use crate::r#move::{Move, MAX_SIZE};
+use crate::render::Theme;
+use crate::server::{read_line, SERVER_MOVE_BUDGET};
+
+/// The word messages to the bot start with
+pub const PREFIX: &str = "!ttt";
+
+/// How many channels the machine thinks for at once
+pub const MAX_SEARCHES: usize = 4;
+/// How long the worker of a channel waits for a message before it ends
+pub const IDLE: Duration = Duration::from_secs(600);
+
+/// The answer to `!ttt help`
+const HELP: [&str; 5] = [
+    "!ttt new [size [win]] [x|o] [easy|medium|hard] starts a game",
+    "!ttt <move> plays a move, e.g. !ttt 2B",
+    "!ttt board shows the board",
+    "!ttt resign gives the game up",
+    "!ttt help shows this",
+];
+
+/// A message for the worker of a channel and what to do with the answer
+type Job = (String, Box<dyn FnOnce(Vec<String>) + Send>);
+
+/// Counts the searches running, so that no more than `MAX_SEARCHES` do
+#[derive(Default)]
+struct Searches {
+    running: Mutex<usize>,
+    freed: Condvar,
+}
+
+impl Searches {
+    /// This function waits until fewer than `MAX_SEARCHES` searches run and
+    /// runs `search`
+    fn run<T>(&self, search: impl FnOnce() -> T) -> T {
+        let mut running = self.running.lock().unwrap();
+        while *running >= MAX_SEARCHES {
+            running = self.freed.wait(running).unwrap();
+        }
+        *running += 1;
+        drop(running);
+
+        let _done = Done(self);
+        search()
+    }
+}
+
+/// Counts a search as finished when dropped, even if it panicked
+struct Done<'a>(&'a Searches);
+
+impl Drop for Done<'_> {
+    fn drop(&mut self) {
+        *self.0.running.lock().unwrap() -= 1;
+        self.0.freed.notify_one();
+    }
+}
+
+/// The games of the channels, every one has its own lock, and the workers
+/// answering them
+#[derive(Clone, Default)]
+pub struct Gateway {
+    sessions: Arc<Mutex<HashMap<String, Arc<Mutex<TicTacToe>>>>>,
+    /// The queue of the worker of every channel that has one
+    workers: Arc<Mutex<HashMap<String, Sender<Job>>>>,
+    searches: Arc<Searches>,
+}
+
+impl Gateway {
+    pub fn new() -> Gateway {
+        Gateway::default()
+    }
+
+    /// This function returns how many channels have a game
+    pub fn sessions(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+
+    /// This function answers `message`, said in `channel`, with the lines
+    /// the bot replies. A message not addressed to the bot gets none.
+    pub fn handle(&self, channel: &str, message: &str) -> Vec<String> {
+        let mut words = message.split_whitespace();
+        if words.next() != Some(PREFIX) {
+            return Vec::new();
+        }
+        let words: Vec<&str> = words.collect();
+
+        let answer = match words[..] {
+            [] | ["help"] => Ok(HELP.iter().map(|line| line.to_string()).collect()),
+            ["new", ..] => self.start(channel, &words[1..]),
+            ["board"] => self.with_session(channel, |game| Ok(view(game, None))),
+            ["resign"] => self.with_session(channel, |game| {
+                if game.is_game_over() {
+                    return Err(format!("The game is over, start a new one with {} new", PREFIX));
+                }
+                game.resign(game.player_char());
+                Ok(view(game, None))
+            }),
+            [text] => self.with_session(channel, |game| self.play(game, text)),
+            _ => Err(format!("'{}' is no command, try {} help", words.join(" "), PREFIX)),
+        };
+
+        answer.unwrap_or_else(|e| vec![e])
+    }
+
+    /// This function starts the game `words` set up in `channel`, replacing
+    /// any game played there before
+    fn start(&self, channel: &str, words: &[&str]) -> Result<Vec<String>, String> {
+        let (mut sizes, mut player, mut difficulty) = (Vec::new(), BoardChar::X, None);
+        for word in words {
+            if let Ok(number) = word.parse::<usize>() {
+                sizes.push(number);
+            } else if let Ok(c) = word.parse::<BoardChar>() {
+                player = c;
+            } else if let Ok(d) = word.parse::<Difficulty>() {
+                difficulty = Some(d);
+            } else {
+                return Err(format!("'{}' is no size, symbol or difficulty", word));
+            }
+        }
+
+        let (size, win_len) = match sizes[..] {
+            [] => (3, 3),
+            [size] => (size, size),
+            [size, win_len] => (size, win_len),
+            _ => return Err("A game has a size and a length that wins, not more".to_string()),
+        };
+        if size == 0 || size > MAX_SIZE || win_len == 0 || win_len > size {
+            return Err(format!("There is no {}x{} board where {} in a row win", size, size, win_len));
+        }
+
+        let mut game = TicTacToe::with_size(player, size, win_len);
+        game.set_theme(Theme::Ascii);
+        game.set_first(BoardChar::X);
+        if let Some(difficulty) = difficulty {
+            let mut ai = game.ai();
+            ai.difficulty = difficulty;
+            game.set_ai(ai);
+        }
+        let reply = self.machine_turn(&mut game);
+        let answer = view(&game, reply);
+
+        self.sessions.lock().unwrap().insert(channel.to_string(), Arc::new(Mutex::new(game)));
+
+        Ok(answer)
+    }
+
+    /// This function looks up the game of `channel` and answers with `f`
+    fn with_session<F>(&self, channel: &str, f: F) -> Result<Vec<String>, String>
+    where
+        F: FnOnce(&mut TicTacToe) -> Result<Vec<String>, String>,
+    {
+        let game = self.sessions.lock().unwrap().get(channel).cloned();
+        let game = game.ok_or(format!("There is no game in this channel, start one with {} new", PREFIX))?;
+
+        let mut game = game.lock().unwrap();
+        f(&mut game)
+    }
+
+    /// This function plays `text`, the channel's move, and the machine's answer
+    fn play(&self, game: &mut TicTacToe, text: &str) -> Result<Vec<String>, String> {
+        let m: Move = text.parse().map_err(|_| format!("'{}' is no move, e.g. 2B, or command, try {} help", text, PREFIX))?;
+        if game.is_game_over() {
+            return Err(format!("The game is over, start a new one with {} new", PREFIX));
+        }
+        if game.turn() != game.player_char() {
+            return Err("It is not the channel's turn".to_string());
+        }
+
+        game.play_move(&m).map_err(|e| e.to_string())?;
+        let reply = self.machine_turn(game);
+
+        Ok(view(game, reply))
+    }
+
+    /// This function lets the machine move if it is its turn, once it may
+    /// think, and returns the move
+    fn machine_turn(&self, game: &mut TicTacToe) -> Option<Move> {
+        if game.is_game_over() || game.turn() != game.machine_char() {
+            return None;
+        }
+
+        self.searches.run(|| game.machine_move(SERVER_MOVE_BUDGET).ok().map(|(m, _)| m))
+    }
+
+    /// This function answers the line protocol on `listener` until it fails,
+    /// every bridge connected is served on a thread of its own
+    pub fn serve(&self, listener: &TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, _) = listener.accept()?;
+            let gateway = self.clone();
+
+            thread::spawn(move || gateway.serve_bridge(stream));
+        }
+    }
+
+    /// This function answers the `<channel> <message>` lines of a bridge
+    fn serve_bridge(&self, stream: TcpStream) -> io::Result<()> {
+        let writer = Arc::new(Mutex::new(stream.try_clone()?));
+        let mut reader = BufReader::new(stream);
+
+        while let Some(line) = read_line(&mut reader)? {
+            let (channel, message) = match line.trim().split_once(' ') {
+                Some((channel, message)) => (channel.to_string(), message.to_string()),
+                None => continue,
+            };
+
+            self.answer(&writer, channel, message, |channel, reply| format!("{} {}", channel, reply));
+        }
+
+        Ok(())
+    }
+
+    /// This function connects to the IRC server at `addr` as `nick`, joins
+    /// `channels` and plays in them until the server closes the connection.
+    /// Messages sent to the bot itself get a game of their own.
+    pub fn connect<A: ToSocketAddrs>(&self, addr: A, nick: &str, channels: &[String]) -> io::Result<()> {
+        let stream = TcpStream::connect(addr)?;
+        let writer = Arc::new(Mutex::new(stream.try_clone()?));
+        {
+            let mut writer = writer.lock().unwrap();
+            write!(writer, "NICK {}\r\nUSER {} 0 * :tic-tac-toe\r\n", nick, nick)?;
+            writer.flush()?;
+        }
+
+        let mut reader = BufReader::new(stream);
+        while let Some(line) = read_line(&mut reader)? {
+            let message = match IrcMessage::parse(&line) {
+                Some(message) => message,
+                None => continue,
+            };
+
+            match message.command {
+                "PING" => {
+                    let mut writer = writer.lock().unwrap();
+                    write!(writer, "PONG :{}\r\n", message.trailing)?;
+                    writer.flush()?;
+                }
+                // The welcome, the server is ready for the channels to be joined
+                "001" => {
+                    let mut writer = writer.lock().unwrap();
+                    for channel in channels {
+                        write!(writer, "JOIN {}\r\n", channel)?;
+                    }
+                    writer.flush()?;
+                }
+                "PRIVMSG" => {
+                    let target = if message.target == nick { message.sender } else { message.target };
+                    self.answer(&writer, target.to_string(), message.trailing.to_string(), |target, reply| {
+                        format!("PRIVMSG {} :{}", target, reply)
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// This function has the worker of `channel` answer `message` and write
+    /// every line `format` makes of the answer to `writer`
+    fn answer<F>(&self, writer: &Arc<Mutex<TcpStream>>, channel: String, message: String, format: F)
+    where
+        F: Fn(&str, &str) -> String + Send + 'static,
+    {
+        let writer = Arc::clone(writer);
+        let target = channel.clone();
+
+        self.dispatch(channel, message, move |answer| {
+            let mut writer = writer.lock().unwrap();
+            for reply in answer {
+                let _ = write!(writer, "{}\r\n", format(&target, &reply));
+            }
+            let _ = writer.flush();
+        });
+    }
+
+    /// This function queues `message` of `channel` for the worker of the
+    /// channel, which is started if there is none, and has `reply` called
+    /// with the answer. Messages not addressed to the bot are dropped.
+    pub fn dispatch<F>(&self, channel: String, message: String, reply: F)
+    where
+        F: FnOnce(Vec<String>) + Send + 'static,
+    {
+        if message.split_whitespace().next() != Some(PREFIX) {
+            return;
+        }
+
+        let mut workers = self.workers.lock().unwrap();
+        let job: Job = (message, Box::new(reply));
+        let job = match workers.get(&channel) {
+            Some(queue) => match queue.send(job) {
+                Ok(()) => return,
+                Err(SendError(job)) => job,
+            },
+            None => job,
+        };
+
+        let (queue, jobs) = mpsc::channel();
+        let _ = queue.send(job);
+        workers.insert(channel.clone(), queue);
+        let gateway = self.clone();
+        thread::spawn(move || gateway.work(&channel, jobs));
+    }
+
+    /// This function answers the messages of `channel` one after the other
+    /// until none came for `IDLE`
+    fn work(&self, channel: &str, jobs: Receiver<Job>) {
+        loop {
+            let (message, reply) = match jobs.recv_timeout(IDLE) {
+                Ok(job) => job,
+                Err(RecvTimeoutError::Timeout) => {
+                    // Messages are queued with the workers locked, so none
+                    // can slip in between the last look and the removal
+                    let mut workers = self.workers.lock().unwrap();
+                    match jobs.try_recv() {
+                        Ok(job) => job,
+                        Err(_) => {
+                            workers.remove(channel);
+                            return;
+                        }
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => return,
+            };
+
+            let answer = self.handle(channel, &message);
+            if !answer.is_empty() {
+                reply(answer);
+            }
+        }
+    }
+}
+
+/// A line from an IRC server, e.g. `:ann!ann@host PRIVMSG #games :!ttt 2B`
+struct IrcMessage<'a> {
+    /// The nick of the sender, empty for messages of the server
+    sender: &'a str,
+    command: &'a str,
+    /// The first parameter, the channel or nick a message is sent to
+    target: &'a str,
+    /// The last parameter, the text of a message
+    trailing: &'a str,
+}
+
+impl<'a> IrcMessage<'a> {
+    fn parse(line: &'a str) -> Option<IrcMessage<'a>> {
+        let line = line.trim_end();
+        let (prefix, rest) = match line.strip_prefix(':') {
+            Some(rest) => rest.split_once(' ')?,
+            None => ("", line),
+        };
+        let (params, trailing) = match rest.split_once(" :") {
+            Some((params, trailing)) => (params, trailing),
+            None => (rest, ""),
+        };
+        let mut params = params.split_whitespace();
+        let command = params.next()?;
+        let target = params.next().unwrap_or("");
+        let trailing = if trailing.is_empty() && command == "PING" { target } else { trailing };
+
+        Some(IrcMessage {
+            sender: prefix.split('!').next().unwrap_or(""),
+            command,
+            target,
+            trailing,
+        })
+    }
+}
+
+/// This function returns the lines showing `game`, the move the machine
+/// answered with and what happens next
+fn view(game: &TicTacToe, reply: Option<Move>) -> Vec<String> {
+    let mut lines: Vec<String> = game.to_string().lines().filter(|line| !line.trim().is_empty()).map(str::to_string).collect();
+
+    if let Some(m) = reply {
+        lines.push(format!("I play {}.", m));
+    }
+    lines.push(match (game.is_game_over(), game.winner()) {
+        (true, Some(c)) if c == game.player_char() => format!("{} wins, well played!", c),
+        (true, Some(c)) if game.forfeited().is_some() => format!("{} wins, the channel resigned.", c),
+        (true, Some(c)) => format!("{} wins.", c),
+        (true, None) => "It is a draw.".to_string(),
+        (false, _) => format!("Your move as {}.", game.player_char()),
+    });
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn plays_in_every_channel_on_its_own() {
+        let gateway = Gateway::new();
+        assert!(gateway.handle("#a", "hello").is_empty());
+        assert!(gateway.handle("#a", "!ttt 2B")[0].starts_with("There is no game"));
+
+        let started = gateway.handle("#a", "!ttt new");
+        assert_eq!(started.last().unwrap(), "Your move as X.");
+        let answer = gateway.handle("#a", "!ttt 2B");
+        assert!(answer.iter().any(|line| line.starts_with("I play ")), "{:?}", answer);
+        assert!(answer.iter().any(|line| line.starts_with("2| |X| |")), "{:?}", answer);
+
+        assert!(gateway.handle("#b", "!ttt board")[0].starts_with("There is no game"));
+        assert_eq!(gateway.sessions(), 1);
+    }
+
+    #[test]
+    fn won_board_has_no_escape_codes() {
+        let mut game = TicTacToe::new(BoardChar::X);
+        game.set_theme(Theme::Ascii);
+        for (row, col) in [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)] {
+            game.play_move(&Move { row, col }).unwrap();
+        }
+
+        let lines = view(&game, None);
+        assert!(lines.iter().any(|line| line == "1[X|X|X]"), "{:?}", lines);
+        assert!(lines.iter().all(|line| !line.contains('\x1b')));
+    }
+
+    #[test]
+    fn answers_a_channel_in_order() {
+        let gateway = Gateway::new();
+        let (sender, answers) = mpsc::channel();
+
+        for channel in ["#a", "#b", "#c"] {
+            for message in ["!ttt new o", "!ttt board", "!ttt resign", "!ttt help"] {
+                let sender = sender.clone();
+                gateway.dispatch(channel.to_string(), message.to_string(), move |answer| {
+                    sender.send((channel, answer)).unwrap();
+                });
+            }
+        }
+        drop(sender);
+
+        let mut last_lines: HashMap<&str, Vec<String>> = HashMap::new();
+        for (channel, answer) in answers.iter().take(12) {
+            last_lines.entry(channel).or_default().push(answer.last().unwrap().clone());
+        }
+        for lines in last_lines.values() {
+            assert_eq!(lines[0], "Your move as O.");
+            assert_eq!(lines[1], "Your move as O.");
+            assert_eq!(lines[2], "X wins, the channel resigned.");
+            assert_eq!(lines[3], HELP[4]);
+        }
+        assert_eq!(last_lines.len(), 3);
+    }
+
+    #[test]
+    fn caps_the_searches_running_at_once() {
+        let searches = Arc::new(Searches::default());
+        let (running, most) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+
+        let threads: Vec<_> = (0..3 * MAX_SEARCHES)
+            .map(|_| {
+                let (searches, running, most) = (Arc::clone(&searches), Arc::clone(&running), Arc::clone(&most));
+                thread::spawn(move || {
+                    searches.run(|| {
+                        let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        most.fetch_max(now, Ordering::SeqCst);
+                        thread::sleep(Duration::from_millis(20));
+                        running.fetch_sub(1, Ordering::SeqCst);
+                    })
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert!(most.load(Ordering::SeqCst) <= MAX_SEARCHES);
+        assert_eq!(*searches.running.lock().unwrap(), 0);
+    }
+
+    #[test]
+    fn parses_irc_lines() {
+        let message = IrcMessage::parse(":ann!a@host PRIVMSG #games :!ttt 2B\r\n").unwrap();
+        assert_eq!((message.sender, message.command, message.target, message.trailing), ("ann", "PRIVMSG", "#games", "!ttt 2B"));
+
+        let ping = IrcMessage::parse("PING :irc.example.net").unwrap();
+        assert_eq!((ping.command, ping.trailing), ("PING", "irc.example.net"));
+    }
+}
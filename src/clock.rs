@@ -0,0 +1,162 @@
+//! A chess clock: every side has a budget of thinking time for the whole
+//! game, a limit for every single move, or both. A side that runs out of
+//! time loses.
+
+use std::time::{Duration, Instant};
+
+use crate::board::BoardChar;
+use crate::error::GameError;
+
+#[derive(Debug, Clone)]
+pub struct Clock {
+    /// Time left for the whole game, X first and O second
+    remaining: Option<[Duration; 2]>,
+    per_move: Option<Duration>,
+    /// The side whose time is running and since when
+    running: Option<(BoardChar, Instant)>,
+    /// The side that ran out of time
+    flagged: Option<BoardChar>,
+}
+
+fn side(c: BoardChar) -> usize {
+    match c {
+        BoardChar::O => 1,
+        _ => 0,
+    }
+}
+
+impl Clock {
+    /// This function sets up a clock giving each side `total` for the
+    /// game and `per_move` for every move. Either may be left out.
+    pub fn new(total: Option<Duration>, per_move: Option<Duration>) -> Clock {
+        Clock {
+            remaining: total.map(|total| [total, total]),
+            per_move,
+            running: None,
+            flagged: None,
+        }
+    }
+
+    /// This function starts the time of `c`, stopping the other side's
+    pub fn start(&mut self, c: BoardChar) {
+        self.stop();
+        self.running = Some((c, Instant::now()));
+    }
+
+    /// This function stops the running time and charges it to its side. It
+    /// returns false if that side has run out of time.
+    pub fn stop(&mut self) -> bool {
+        let (c, started) = match self.running.take() {
+            Some(running) => running,
+            None => return true,
+        };
+        let used = started.elapsed();
+
+        let mut in_time = self.per_move.is_none_or(|limit| used <= limit);
+        if let Some(remaining) = &mut self.remaining {
+            let left = &mut remaining[side(c)];
+            in_time &= used <= *left;
+            *left = left.saturating_sub(used);
+        }
+
+        if !in_time {
+            self.flagged = Some(c);
+        }
+
+        in_time
+    }
+
+    /// This function returns how long `c` may still think about its
+    /// current move, `None` without a limit
+    pub fn time_left(&self, c: BoardChar) -> Option<Duration> {
+        let used = match self.running {
+            Some((running, started)) if running == c => started.elapsed(),
+            _ => Duration::default(),
+        };
+
+        let game = self.remaining.map(|remaining| remaining[side(c)].saturating_sub(used));
+        let this_move = self.per_move.map(|limit| limit.saturating_sub(used));
+
+        match (game, this_move) {
+            (Some(game), Some(this_move)) => Some(game.min(this_move)),
+            (game, this_move) => game.or(this_move),
+        }
+    }
+
+    /// This function returns when the time of the running side is up
+    pub fn deadline(&self) -> Option<Instant> {
+        let (c, _) = self.running?;
+
+        self.time_left(c).map(|left| Instant::now() + left)
+    }
+
+    /// This function returns the side whose time is running
+    pub fn running(&self) -> Option<BoardChar> {
+        self.running.map(|(c, _)| c)
+    }
+
+    /// This function returns the side that ran out of time, if any
+    pub fn flagged(&self) -> Option<BoardChar> {
+        self.flagged
+    }
+
+    /// This function returns true if the clock limits anything at all
+    pub fn is_enabled(&self) -> bool {
+        self.remaining.is_some() || self.per_move.is_some()
+    }
+}
+
+/// This function reads a duration like `30s`, `5m`, `1m30s`, `500ms` or
+/// `90`, the last one in seconds
+pub fn parse_duration(s: &str) -> Result<Duration, GameError> {
+    let tr = s.trim();
+    let invalid = || GameError::InvalidInput(format!("'{}' is no time like 30s, 5m, 1m30s or 500ms", tr));
+
+    if let Ok(secs) = tr.parse::<u64>() {
+        return Ok(Duration::from_secs(secs));
+    }
+
+    let mut total = Duration::default();
+    let mut digits = String::new();
+    let mut chars = tr.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '0'..='9' => digits.push(c),
+            'm' if !digits.is_empty() && chars.peek() == Some(&'s') => {
+                chars.next();
+                let value: u64 = digits.parse().map_err(|_| invalid())?;
+                total += Duration::from_millis(value);
+                digits.clear();
+            }
+            'h' | 'm' | 's' if !digits.is_empty() => {
+                let value: u64 = digits.parse().map_err(|_| invalid())?;
+                let unit = match c {
+                    'h' => 3600,
+                    'm' => 60,
+                    _ => 1,
+                };
+                total += Duration::from_secs(value * unit);
+                digits.clear();
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    if !digits.is_empty() || tr.is_empty() {
+        return Err(invalid());
+    }
+
+    Ok(total)
+}
+
+/// This function writes a duration as minutes and seconds, e.g. `1:05`
+pub fn format_time(d: Duration) -> String {
+    let secs = d.as_secs();
+
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+/// This function writes `d` in seconds to the hundredth, like `2.35s`
+pub fn format_split(d: Duration) -> String {
+    format!("{:.2}s", d.as_secs_f64())
+}
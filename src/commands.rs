@@ -0,0 +1,745 @@
+//! The commands of the program other than a game, like `selfplay` or
+//! `solve`. Each is listed in `COMMANDS` with the arguments it takes, which
+//! the usage is made from.
+
+use std::net::TcpListener;
+use std::str::FromStr;
+use std::time::Instant;
+use std::vec;
+
+use tic_tac_toe_rs::clock::parse_duration;
+use tic_tac_toe_rs::config::Config;
+use tic_tac_toe_rs::i18n::{fill, localize, Text};
+use tic_tac_toe_rs::input::InputSource;
+use tic_tac_toe_rs::puzzle::{self, Progress, Puzzle};
+use tic_tac_toe_rs::r#move::MAX_SIZE;
+use tic_tac_toe_rs::render::draw_board;
+use tic_tac_toe_rs::rng::Rng;
+use tic_tac_toe_rs::selfplay::{self_play, Contender};
+use tic_tac_toe_rs::server::Server;
+use tic_tac_toe_rs::stats::{self, GameRecord, Outcome, Stats};
+use tic_tac_toe_rs::terminal::{print_analysis, read_input};
+use tic_tac_toe_rs::tournament::{tournament, Entrant};
+use tic_tac_toe_rs::{verify, AiConfig, Board, BoardChar, GameSnapshot, Move, MoveFormat, Notation, Symbols, Theme, TicTacToe};
+
+use crate::options::{number, seed, Variant, MOVE_BUDGET};
+
+/// Games played by `selfplay` unless `--games` says otherwise
+const SELF_PLAY_GAMES: usize = 100;
+
+/// Games every pairing of a tournament plays unless `--games` says otherwise
+const TOURNAMENT_GAMES: usize = 10;
+
+/// Runs a command on the arguments after its name. It returns false if
+/// the command found that something does not hold.
+type Run = fn(&mut dyn InputSource, vec::IntoIter<String>) -> Result<bool, String>;
+
+/// A command of the program other than a game
+pub struct Command {
+    pub name: &'static str,
+    /// What follows the name in the usage
+    args: &'static str,
+    /// Arguments the command could be given, shown with the usage when the
+    /// ones given were wrong
+    example: &'static str,
+    /// Commands without one lead to a game and are run by `main`
+    pub run: Option<Run>,
+}
+
+impl Command {
+    /// This function returns the name of the command and its arguments
+    pub fn usage(&self) -> String {
+        format!("{} {}", self.name, self.args).trim_end().to_string()
+    }
+
+    /// This function returns the usage with an example, for a command given
+    /// the wrong arguments
+    pub fn hint(&self) -> String {
+        match self.example {
+            "" => format!("Usage: tic_tac-toe_rs {}", self.usage()),
+            example => format!("Usage: tic_tac-toe_rs {}, e.g. {}", self.usage(), example),
+        }
+    }
+}
+
+/// Every command in the order of the usage
+pub const COMMANDS: &[Command] = &[
+    Command {
+        name: "replay",
+        args: "FILE|MOVES",
+        example: "",
+        run: None,
+    },
+    Command {
+        name: "stats",
+        args: "",
+        example: "",
+        run: None,
+    },
+    Command {
+        name: "selfplay",
+        args: "[--games N] [--size N] [--win K] [--variant classic|misere|gomoku] [--seed N] CONTENDER CONTENDER",
+        example: "minimax:3 mcts",
+        run: Some(|_, args| run_self_play(args).map(|()| true)),
+    },
+    Command {
+        name: "tournament",
+        args: "[--games N] [--size N] [--win K] [--variant classic|misere|gomoku] [--seed N] [--bot-time TIME] ENTRANT ENTRANT...",
+        example: "minimax mcts \"bot:python3 mybot.py\"",
+        run: Some(|_, args| run_tournament(args).map(|()| true)),
+    },
+    Command {
+        name: "setup",
+        args: "[--size N] [--win K] [--position POSITION] [FLAGS OF THE GAME]",
+        example: "",
+        run: None,
+    },
+    Command {
+        name: "puzzle",
+        args: "[--list] [ID]",
+        example: "",
+        run: Some(|input, args| run_puzzles(input, args).map(|()| true)),
+    },
+    Command {
+        name: "daily",
+        args: "",
+        example: "",
+        run: Some(|input, args| run_daily(input, args).map(|()| true)),
+    },
+    Command {
+        name: "analyze",
+        args: "[--win K] [--depth D] POSITION",
+        example: "\"XO./.X./..O X\"",
+        run: Some(|_, args| run_analysis(args).map(|()| true)),
+    },
+    Command {
+        name: "solve",
+        args: "[--win K] [--misere] POSITION",
+        example: "\"XO./.X./..O X\"",
+        run: Some(|_, args| run_solve(args).map(|()| true)),
+    },
+    Command {
+        name: "verify",
+        args: "[--win K] [--seed N] [POSITION]",
+        example: "\"X../.O./... X\"",
+        run: Some(|_, args| run_verify(args)),
+    },
+    Command {
+        name: "serve",
+        args: "[--http] PORT",
+        example: "",
+        run: Some(|_, args| run_server(args).map(|()| true)),
+    },
+    Command {
+        name: "bot",
+        args: "PORT | bot --irc ADDR [--nick NICK] CHANNEL...",
+        example: "",
+        run: Some(|_, args| run_gateway(args).map(|()| true)),
+    },
+];
+
+/// This function returns the command called `name`, if there is one
+pub fn find(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+/// What can be typed in the board editor of `setup`
+enum Edit {
+    /// Put a mark on a cell, `BoardChar::Empty` removes the one there
+    Place(BoardChar, Move),
+    /// The side to move, `None` lets the counts of the marks decide
+    Turn(Option<BoardChar>),
+    Clear,
+    Show,
+    Analyze,
+    Play,
+    Quit,
+}
+
+impl FromStr for Edit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<String> = s.split_whitespace().map(str::to_lowercase).collect();
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+
+        match words[..] {
+            [mark @ ("x" | "o"), cell] => Ok(Edit::Place(mark.parse()?, cell.parse()?)),
+            ["." | "remove", cell] => Ok(Edit::Place(BoardChar::Empty, cell.parse()?)),
+            ["turn", "auto"] => Ok(Edit::Turn(None)),
+            ["turn", side] => Ok(Edit::Turn(Some(side.parse()?))),
+            ["clear"] => Ok(Edit::Clear),
+            ["show"] | [] => Ok(Edit::Show),
+            ["analyze"] => Ok(Edit::Analyze),
+            ["play"] => Ok(Edit::Play),
+            ["quit"] => Ok(Edit::Quit),
+            _ => Err(format!(
+                "'{}' is no edit, e.g. x 2B, o 1A, remove 2B, turn o|x|auto, clear, show, analyze, play or quit",
+                s.trim()
+            )),
+        }
+    }
+}
+
+/// What can be typed while a puzzle is shown
+enum Attempt {
+    Move(Move),
+    Skip,
+}
+
+impl FromStr for Attempt {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "s" | "skip" => Ok(Attempt::Skip),
+            tr => tr.parse().map(Attempt::Move).map_err(String::from),
+        }
+    }
+}
+
+/// This function reads the arguments of `selfplay`, lets the two
+/// contenders play each other and prints how they did
+fn run_self_play(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let (mut games, mut size, mut win_len, mut variant) = (SELF_PLAY_GAMES, None, None, Variant::Classic);
+    let mut contenders = Vec::new();
+    let mut rng = Rng::from_time();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => games = number(&arg, args.next())?,
+            "--size" => size = Some(number(&arg, args.next())?),
+            "--win" => win_len = Some(number(&arg, args.next())?),
+            "--variant" => variant = args.next().ok_or("--variant needs a value: classic, misere or gomoku")?.parse()?,
+            "--seed" => rng = Rng::new(seed(args.next())?),
+            _ if arg.starts_with("--") => return Err(format!("Unknown argument '{}'", arg)),
+            _ => contenders.push(arg.parse::<Contender>()?),
+        }
+    }
+
+    let size = size.or_else(|| variant.size()).unwrap_or(3);
+    if size == 0 || size > MAX_SIZE {
+        return Err(format!("--size must be between 1 and {}", MAX_SIZE));
+    }
+    let win_len = win_len.or_else(|| variant.win_len()).unwrap_or(size);
+    if win_len == 0 || win_len > size {
+        return Err(format!("--win must be between 1 and the board size {}", size));
+    }
+
+    if variant.own_board() {
+        return Err("selfplay plays the classic, the misere and the gomoku variant only".to_string());
+    }
+
+    let contenders = match contenders[..] {
+        [first, second] => [first, second],
+        _ => return Err("selfplay needs two contenders".to_string()),
+    };
+
+    let mut board = TicTacToe::with_size(BoardChar::X, size, win_len);
+    board.set_misere(variant == Variant::Misere);
+    print!("{}", self_play(&board, contenders, games, &mut rng));
+
+    Ok(())
+}
+
+/// This function reads the arguments of `tournament`, plays every entrant
+/// against every other and prints the standings
+fn run_tournament(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let (mut games, mut size, mut win_len, mut variant) = (TOURNAMENT_GAMES, None, None, Variant::Classic);
+    let mut bot_time = MOVE_BUDGET;
+    let mut entrants = Vec::new();
+    let mut rng = Rng::from_time();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => games = number(&arg, args.next())?,
+            "--size" => size = Some(number(&arg, args.next())?),
+            "--win" => win_len = Some(number(&arg, args.next())?),
+            "--variant" => variant = args.next().ok_or("--variant needs a value: classic, misere or gomoku")?.parse()?,
+            "--seed" => rng = Rng::new(seed(args.next())?),
+            "--bot-time" => bot_time = parse_duration(&args.next().ok_or("--bot-time needs a time, e.g. 500ms or 5s")?)?,
+            _ if arg.starts_with("--") => return Err(format!("Unknown argument '{}'", arg)),
+            _ => entrants.push(arg.parse::<Entrant>()?),
+        }
+    }
+
+    let size = size.or_else(|| variant.size()).unwrap_or(3);
+    if size == 0 || size > MAX_SIZE {
+        return Err(format!("--size must be between 1 and {}", MAX_SIZE));
+    }
+    let win_len = win_len.or_else(|| variant.win_len()).unwrap_or(size);
+    if win_len == 0 || win_len > size {
+        return Err(format!("--win must be between 1 and the board size {}", size));
+    }
+
+    if variant.own_board() {
+        return Err("tournament plays the classic, the misere and the gomoku variant only".to_string());
+    }
+
+    if entrants.len() < 2 {
+        return Err("tournament needs at least two entrants".to_string());
+    }
+
+    let mut board = TicTacToe::with_size(BoardChar::X, size, win_len);
+    board.set_misere(variant == Variant::Misere);
+    let standings = tournament(&board, &entrants, games, bot_time, &mut rng).map_err(|e| e.to_string())?;
+    print!("{}", standings);
+
+    Ok(())
+}
+
+/// This function reads the arguments of `puzzle` and presents the puzzles
+/// not solved yet, or only the one asked for, until the input ends
+fn run_puzzles(input: &mut dyn InputSource, args: impl Iterator<Item = String>) -> Result<(), String> {
+    let puzzles = puzzle::builtin();
+    let path = Progress::default_path();
+    let mut progress = match &path {
+        Some(path) => Progress::load(path)?,
+        None => Progress::default(),
+    };
+    let (mut list, mut id) = (false, None);
+
+    for arg in args {
+        match arg.as_str() {
+            "--list" => list = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown argument '{}'", arg)),
+            _ => id = Some(arg),
+        }
+    }
+
+    if list {
+        for p in &puzzles {
+            let mark = if progress.is_solved(&p.id) { 'x' } else { ' ' };
+            println!("[{}] {:<18} {}", mark, p.id, p);
+        }
+        return Ok(());
+    }
+
+    let chosen: Vec<&Puzzle> = match &id {
+        Some(id) => vec![puzzles.iter().find(|p| &p.id == id).ok_or_else(|| format!("There is no puzzle '{}'", id))?],
+        None => puzzles.iter().filter(|p| !progress.is_solved(&p.id)).collect(),
+    };
+
+    if chosen.is_empty() {
+        println!("All {} puzzles are solved, `puzzle ID` plays one of them again", puzzles.len());
+        return Ok(());
+    }
+
+    let theme = Theme::detect();
+    for p in chosen {
+        let mut game = p.game();
+        game.set_theme(theme);
+        println!("{}", p);
+        println!("{}", game);
+
+        loop {
+            let m = match read_input(input, Text::PuzzleTurn.get()) {
+                Some(Attempt::Move(m)) => m,
+                Some(Attempt::Skip) => break,
+                None => return Ok(()),
+            };
+
+            match p.check(&m) {
+                Ok(true) => {
+                    let others: Vec<String> = p.solutions().iter().filter(|&&s| s != m).map(Move::to_string).collect();
+                    if others.is_empty() {
+                        println!("{}", Text::Solved);
+                    } else {
+                        println!("{}", fill(Text::SolvedToo, &[&others.join(", ")]));
+                    }
+
+                    progress.solve(&p.id);
+                    if let Some(path) = &path {
+                        if let Err(e) = progress.save(path) {
+                            eprintln!("Could not keep the progress in {}: {}", path.display(), e);
+                        }
+                    }
+                    break;
+                }
+                Ok(false) => println!("{}", Text::NotQuite),
+                Err(e) => println!("{}", fill(Text::NotPossible, &[&localize(&e)])),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// This function plays the challenge of the day against the machine, once a
+/// day, and records it in the statistics of the configuration
+fn run_daily(input: &mut dyn InputSource, mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    if let Some(arg) = args.next() {
+        return Err(format!("Unknown argument '{}'", arg));
+    }
+
+    let config = match Config::default_path() {
+        Some(path) => Config::load(&path).map_err(|e| format!("Could not read the configuration {}: {}", path.display(), e))?,
+        None => Config::default(),
+    };
+    let path = config
+        .stats_path
+        .or_else(Stats::default_path)
+        .ok_or("There is no home directory to keep statistics in")?;
+    let today = stats::today();
+
+    let stats = Stats::load(&path)?;
+    if let Some(done) = stats.daily(&today) {
+        println!("{}", fill(Text::DailyDone, &[&today, &done.outcome.text()]));
+        println!("{}", fill(Text::DailyTomorrow, &[&stats.daily_streak(&today)]));
+        return Ok(());
+    }
+
+    let challenge = puzzle::daily(&today);
+    let mut game = challenge.game();
+    game.set_theme(config.theme.unwrap_or_else(Theme::detect));
+    println!("{}", fill(Text::DailyChallenge, &[&challenge]));
+
+    while !game.is_game_over() {
+        println!("{}", game);
+
+        if game.turn() == game.player_char() {
+            // Leaving before the end keeps the challenge open for today
+            let m: Move = match read_input(input, &format!("{}: ", Text::YourTurn)) {
+                Some(m) => m,
+                None => return Ok(()),
+            };
+            if let Err(e) = game.player_move(&m) {
+                println!("{}", fill(Text::NotPossible, &[&localize(&e)]));
+            }
+        } else {
+            let (m, _) = game.machine_move(MOVE_BUDGET)?;
+            println!("{}", fill(Text::MachineMoved, &[&m]));
+        }
+    }
+    println!("{}", game);
+
+    let mut record = GameRecord::new(&game).ok_or("The challenge is not over")?;
+    record.daily = Some(today.clone());
+    let won = record.outcome == Outcome::Win;
+    Stats::append(&path, record)?;
+
+    let streak = Stats::load(&path)?.daily_streak(&today);
+    match won {
+        true => println!("{} {}", Text::YouWon, fill(Text::DailyStreak, &[&streak])),
+        false => println!("{}", Text::DailySlipped),
+    }
+
+    Ok(())
+}
+
+/// This function proves that the hard machine never loses from a position,
+/// the empty classic board by default, and always takes an immediate win,
+/// playing either side against every defence. It returns false if it does not.
+fn run_verify(mut args: impl Iterator<Item = String>) -> Result<bool, String> {
+    let (mut win_len, mut seed, mut position) = (None, None, None);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--win" => win_len = Some(number(&arg, args.next())?),
+            "--seed" => seed = Some(number(&arg, args.next())? as u64),
+            _ if arg.starts_with("--") => return Err(format!("Unknown argument '{}'", arg)),
+            _ => position = Some(Board::from_notation(&arg)?),
+        }
+    }
+
+    let (board, turn) = position.unwrap_or_else(|| (vec![vec![BoardChar::Empty; 3]; 3], BoardChar::X));
+    let size = board.len();
+    let win_len = win_len.unwrap_or(size);
+    if win_len == 0 || win_len > size {
+        return Err(format!("--win must be between 1 and the board size {}", size));
+    }
+
+    let mut game = TicTacToe::from_snapshot(&GameSnapshot {
+        board,
+        win_len,
+        player_char: turn.to_opposite(),
+        machine_char: turn,
+        symbols: Symbols::default(),
+        turn,
+        history: Vec::new(),
+        misere: false,
+        handicap: Vec::new(),
+    })?;
+    if let Some(seed) = seed {
+        game.set_seed(seed);
+    }
+
+    let mut proven = true;
+    for side in [turn, turn.to_opposite()].iter().copied() {
+        let started = Instant::now();
+        match verify(&game, &mut AiConfig::default(), side) {
+            Ok(proof) => println!("{}: {}, in {:.2}s", side, proof, started.elapsed().as_secs_f64()),
+            Err(counterexample) => {
+                println!("{}: {}", side, counterexample);
+                proven = false;
+            }
+        }
+    }
+
+    Ok(proven)
+}
+
+/// This function reads the port of `serve` and hosts games on it until the
+/// server fails, the REST API of the `http` feature with `--http`
+fn run_server(args: impl Iterator<Item = String>) -> Result<(), String> {
+    let (mut http, mut port) = (false, None);
+    for arg in args {
+        match arg.as_str() {
+            "--http" => http = true,
+            _ if arg.starts_with("--") || port.is_some() => return Err(format!("Unknown argument '{}'", arg)),
+            _ => port = Some(arg.parse::<u16>().map_err(|_| format!("serve needs a port, not '{}'", arg))?),
+        }
+    }
+    let port = port.ok_or("serve needs a port")?;
+
+    let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|e| format!("Could not listen on port {}: {}", port, e))?;
+    if http {
+        return serve_http(&listener, port);
+    }
+    println!("Serving games on port {}", port);
+
+    Server::new().serve(&listener).map_err(|e| e.to_string())
+}
+
+/// This function answers the REST API on `listener`
+#[cfg(feature = "http")]
+fn serve_http(listener: &TcpListener, port: u16) -> Result<(), String> {
+    println!("Serving the HTTP API on port {}", port);
+
+    tic_tac_toe_rs::http::Api::new().serve(listener).map_err(|e| e.to_string())
+}
+
+#[cfg(not(feature = "http"))]
+fn serve_http(_: &TcpListener, _: u16) -> Result<(), String> {
+    Err("This build has no HTTP API, build it with --features http".to_string())
+}
+
+/// This function reads the arguments of `bot` and plays in chat channels
+/// until the connection fails: the channels of an IRC server with `--irc`,
+/// the ones bridges relay to the port given otherwise
+#[cfg(feature = "chat")]
+fn run_gateway(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let (mut irc, mut nick, mut rest) = (None, "tictactoe".to_string(), Vec::new());
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--irc" => irc = Some(args.next().ok_or("--irc needs the address of the server, e.g. irc.libera.chat:6667")?),
+            "--nick" => nick = args.next().ok_or("--nick needs the nick of the bot")?,
+            _ if arg.starts_with("--") => return Err(format!("Unknown argument '{}'", arg)),
+            _ => rest.push(arg),
+        }
+    }
+
+    let gateway = tic_tac_toe_rs::chat::Gateway::new();
+    match irc {
+        Some(addr) => {
+            if rest.is_empty() {
+                return Err("bot --irc needs the channels to join, e.g. #games".to_string());
+            }
+            println!("Playing in {} on {} as {}", rest.join(", "), addr, nick);
+
+            gateway.connect(addr.as_str(), &nick, &rest).map_err(|e| format!("Lost {}: {}", addr, e))
+        }
+        None => {
+            let port = match &rest[..] {
+                [port] => port.parse::<u16>().map_err(|_| format!("bot needs a port, not '{}'", port))?,
+                _ => return Err("bot needs a port or --irc".to_string()),
+            };
+            let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|e| format!("Could not listen on port {}: {}", port, e))?;
+            println!("Relaying chat games on port {}", port);
+
+            gateway.serve(&listener).map_err(|e| e.to_string())
+        }
+    }
+}
+
+#[cfg(not(feature = "chat"))]
+fn run_gateway(_: impl Iterator<Item = String>) -> Result<(), String> {
+    Err("This build has no chat gateway, build it with --features chat".to_string())
+}
+
+/// This function reads the arguments of `analyze` and prints the value of
+/// every move in the position given
+fn run_analysis(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let (mut win_len, mut depth, mut position) = (None, None, None);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--win" => win_len = Some(number(&arg, args.next())?),
+            "--depth" => depth = Some(number(&arg, args.next())?),
+            _ if arg.starts_with("--") => return Err(format!("Unknown argument '{}'", arg)),
+            _ => position = Some(Board::from_notation(&arg)?),
+        }
+    }
+
+    let (board, turn) = position.ok_or("analyze needs a position")?;
+    let size = board.len();
+    let win_len = win_len.unwrap_or(size);
+    if win_len == 0 || win_len > size {
+        return Err(format!("--win must be between 1 and the board size {}", size));
+    }
+
+    let mut game = TicTacToe::from_snapshot(&GameSnapshot {
+        board,
+        win_len,
+        player_char: turn.to_opposite(),
+        machine_char: turn,
+        symbols: Symbols::default(),
+        turn,
+        history: Vec::new(),
+        misere: false,
+        handicap: Vec::new(),
+    })?;
+    if depth.is_some() {
+        game.set_max_depth(depth);
+    }
+    game.set_theme(Theme::detect());
+
+    println!("{}", game);
+    if game.is_game_over() {
+        return Err("The game is already over".to_string());
+    }
+    println!("{} to move", turn);
+    print_analysis(&mut game, MoveFormat::default());
+
+    Ok(())
+}
+
+/// This function lets the player set up a position in the board editor of
+/// `setup`. It returns the arguments of the game to play from it, `None`
+/// if the player quit. Arguments other than `--size` and `--position`,
+/// the position to start from, are kept for that game.
+pub fn run_setup(input: &mut dyn InputSource, mut args: impl Iterator<Item = String>) -> Result<Option<Vec<String>>, String> {
+    let (mut size, mut win_len, mut board) = (3, None, None);
+    let mut turn = None;
+    let mut rest = Vec::new();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--size" => size = number(&arg, args.next())?,
+            "--win" => {
+                win_len = Some(number(&arg, args.next())?);
+                rest.extend(["--win".to_string(), win_len.unwrap_or_default().to_string()]);
+            }
+            "--position" => {
+                let (position, side) = Board::from_notation(&args.next().ok_or("--position needs a position, e.g. XO./.X./..O X")?)?;
+                board = Some(position);
+                turn = Some(side);
+            }
+            _ => rest.push(arg),
+        }
+    }
+
+    let mut board = board.unwrap_or_else(|| vec![vec![BoardChar::Empty; size]; size]);
+    let size = board.len();
+    if size == 0 || size > MAX_SIZE {
+        return Err(format!("--size must be between 1 and {}", MAX_SIZE));
+    }
+    let win_len = win_len.unwrap_or(size);
+    if win_len == 0 || win_len > size {
+        return Err(format!("--win must be between 1 and the board size {}", size));
+    }
+
+    let theme = Theme::detect();
+    println!("Set up a position: x 2B or o 2B puts a mark, remove 2B takes it away, turn x|o|auto picks the side to move,");
+    println!("clear empties the board, analyze rates the moves, play starts a game from the position and quit leaves");
+    loop {
+        let count = |c: BoardChar| board.iter().flatten().filter(|&&cell| cell == c).count();
+        let (x, o) = (count(BoardChar::X), count(BoardChar::O));
+        let notation = match turn {
+            Some(side) => board.to_notation(side),
+            None => {
+                let rows = board.to_notation(BoardChar::X);
+                rows.split_whitespace().next().unwrap_or_default().to_string()
+            }
+        };
+        // The notation checks that the counts of the marks fit the side to move
+        let position = Board::from_notation(&notation);
+
+        print!("{}", draw_board(&board, Symbols::default(), theme, None));
+        match &position {
+            Ok((_, side)) => println!("{} X, {} O, {} to move: {}", x, o, side, board.to_notation(*side)),
+            Err(e) => println!("{} X, {} O, no game can reach this position: {}", x, o, e),
+        }
+
+        let edit = match read_input::<Edit>(input, "setup: ") {
+            Some(edit) => edit,
+            None => return Ok(None),
+        };
+        match edit {
+            Edit::Place(mark, m) if m.row < size && m.col < size => board[m.row][m.col] = mark,
+            Edit::Place(_, m) => println!("{} is outside the board", m),
+            Edit::Turn(side) => turn = side,
+            Edit::Clear => {
+                board = vec![vec![BoardChar::Empty; size]; size];
+                turn = None;
+            }
+            Edit::Show => {}
+            Edit::Quit => return Ok(None),
+            Edit::Analyze | Edit::Play if position.is_err() => println!("Please fix the counts of the marks or the side to move first"),
+            Edit::Analyze => {
+                let (_, side) = position.map_err(|e| e.to_string())?;
+                let analysis = vec!["--win".to_string(), win_len.to_string(), board.to_notation(side)];
+                if let Err(e) = run_analysis(analysis.into_iter()) {
+                    println!("{}", e);
+                }
+            }
+            Edit::Play => {
+                let (_, side) = position.map_err(|e| e.to_string())?;
+                let mut game = vec!["--position".to_string(), board.to_notation(side)];
+                game.extend(rest);
+                return Ok(Some(game));
+            }
+        }
+    }
+}
+
+/// This function reads the arguments of `solve`, searches the position to
+/// the end of the game and prints its value and the one of every move
+fn run_solve(mut args: impl Iterator<Item = String>) -> Result<(), String> {
+    let (mut win_len, mut misere, mut position) = (None, false, None);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--win" => win_len = Some(number(&arg, args.next())?),
+            "--misere" => misere = true,
+            _ if arg.starts_with("--") => return Err(format!("Unknown argument '{}'", arg)),
+            _ => position = Some(Board::from_notation(&arg)?),
+        }
+    }
+
+    let (board, turn) = position.ok_or("solve needs a position")?;
+    let size = board.len();
+    let win_len = win_len.unwrap_or(size);
+    if win_len == 0 || win_len > size {
+        return Err(format!("--win must be between 1 and the board size {}", size));
+    }
+
+    let mut game = TicTacToe::from_snapshot(&GameSnapshot {
+        board,
+        win_len,
+        player_char: turn.to_opposite(),
+        machine_char: turn,
+        symbols: Symbols::default(),
+        turn,
+        history: Vec::new(),
+        misere,
+        handicap: Vec::new(),
+    })?;
+    game.set_theme(Theme::detect());
+
+    println!("{}", game);
+    let started = Instant::now();
+    let solution = game.solve().ok_or("The game is already over")?;
+    println!("{} to move, {} {}", turn, turn, solution.value);
+    for (m, evaluation) in &solution.moves {
+        println!("{:>5}  {}", m.to_string(), evaluation);
+    }
+    println!(
+        "{} positions searched, {} remembered, in {:.2}s",
+        solution.nodes,
+        solution.remembered,
+        started.elapsed().as_secs_f64()
+    );
+
+    Ok(())
+}
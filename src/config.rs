@@ -0,0 +1,146 @@
+//! Defaults read from a configuration file, so the settings used for every
+//! game need not be typed on the command line each time. Flags given on the
+//! command line win over the file.
+//!
+//! The file is `~/.config/tictactoe/config.toml`, or `tictactoe/config.toml`
+//! under `$XDG_CONFIG_HOME`, and holds a small part of TOML: one
+//! `key = value` per line, strings in double quotes, `#` starting a comment.
+//!
+//! ```toml
+//! symbol = "O"            # your mark, X, O or any other character
+//! difficulty = "medium"
+//! theme = "ascii"
+//! size = 4
+//! first = "random"        # player, machine or random
+//! stats = "~/games/stats.json"
+//! lang = "de"             # en, de or ru
+//! ```
+
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::engine::Difficulty;
+use crate::i18n::Lang;
+use crate::render::Theme;
+
+/// The settings of the configuration file, `None` for those it leaves out
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Config {
+    /// The symbol the player wants, asked for before every game otherwise
+    pub symbol: Option<char>,
+    pub difficulty: Option<Difficulty>,
+    pub theme: Option<Theme>,
+    pub size: Option<usize>,
+    /// Who opens the game against the machine, read by the frontend
+    pub first: Option<String>,
+    /// Where the statistics are kept instead of `Stats::default_path`
+    pub stats_path: Option<PathBuf>,
+    /// The language of the texts instead of the one of the locale
+    pub lang: Option<Lang>,
+}
+
+/// The home directory of the user, if there is one
+fn home() -> Option<PathBuf> {
+    env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")).map(PathBuf::from)
+}
+
+/// This function reads the value of a line, a string in double quotes or a
+/// bare word or number, and drops a comment after it
+fn value(raw: &str) -> Result<String, String> {
+    let raw = raw.trim();
+
+    match raw.strip_prefix('"') {
+        Some(rest) => {
+            let end = rest.find('"').ok_or("The string is not closed with a '\"'")?;
+            let after = rest[end + 1..].trim();
+            if !after.is_empty() && !after.starts_with('#') {
+                return Err(format!("Unexpected '{}' after the string", after));
+            }
+
+            Ok(rest[..end].to_string())
+        }
+        None => {
+            let bare = raw.split('#').next().unwrap_or("").trim();
+            if bare.is_empty() {
+                return Err("The value is missing".to_string());
+            }
+
+            Ok(bare.to_string())
+        }
+    }
+}
+
+impl Config {
+    /// This function returns where the configuration is looked for by
+    /// default, `~/.config/tictactoe/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        let dir = env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| home().map(|home| home.join(".config")))?;
+
+        Some(dir.join("tictactoe").join("config.toml"))
+    }
+
+    /// This function reads the configuration kept at `path`. A file that
+    /// does not exist sets nothing.
+    pub fn load(path: &Path) -> Result<Config, String> {
+        match fs::read_to_string(path) {
+            Ok(text) => Config::parse(&text),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// This function reads the text of a configuration file
+    pub fn parse(text: &str) -> Result<Config, String> {
+        let mut config = Config::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            config.set(line).map_err(|e| format!("line {}: {}", i + 1, e))?;
+        }
+
+        Ok(config)
+    }
+
+    /// This function takes over the setting of one `key = value` line
+    fn set(&mut self, line: &str) -> Result<(), String> {
+        let (key, raw) = line.split_once('=').ok_or("Expected a line like key = value")?;
+        let value = value(raw)?;
+
+        match key.trim() {
+            "symbol" => {
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) if !c.is_whitespace() => self.symbol = Some(c),
+                    _ => return Err(format!("symbol must be a single character, not '{}'", value)),
+                }
+            }
+            "difficulty" => self.difficulty = Some(value.parse().map_err(|e| format!("difficulty: {}", e))?),
+            "theme" => self.theme = Some(value.parse().map_err(|e| format!("theme: {}", e))?),
+            "size" => {
+                let size = value.parse().map_err(|_| format!("size must be a number, not '{}'", value))?;
+                self.size = Some(size);
+            }
+            "first" => self.first = Some(value),
+            "lang" => self.lang = Some(value.parse().map_err(|e| format!("lang: {}", e))?),
+            "stats" => {
+                let path = match (value.strip_prefix("~/"), home()) {
+                    (Some(rest), Some(home)) => home.join(rest),
+                    _ => PathBuf::from(value),
+                };
+                self.stats_path = Some(path);
+            }
+            key => return Err(format!("Unknown setting '{}'", key)),
+        }
+
+        Ok(())
+    }
+}
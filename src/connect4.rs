@@ -0,0 +1,444 @@
+//! Connect Four: discs are dropped into the columns of an upright 7x6 grid
+//! and fall to the lowest free cell, four in a row win. It is played by the
+//! same engines as Tic-Tac-Toe through `Game`.
+
+use std::cmp;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::board::BoardChar;
+use crate::engine::{AiConfig, Difficulty, Engine, Minimax, SearchInfo, HEURISTIC_BOUND, MEDIUM_RANDOM_PERCENT};
+use crate::error::GameError;
+use crate::game::{self, Game};
+use crate::player::{Symbols, Table};
+use crate::r#move::column_name;
+use crate::render::{draw_row, Theme};
+use crate::rng::Rng;
+
+pub const COLUMNS: usize = 7;
+pub const ROWS: usize = 6;
+/// Discs in a row that win
+const CONNECT: usize = 4;
+/// How many moves ahead the machine looks unless told otherwise
+pub const CONNECT_FOUR_DEPTH: usize = 8;
+/// The columns from the center outwards, the order moves are searched in
+const CENTER_FIRST: [usize; COLUMNS] = [3, 2, 4, 1, 5, 0, 6];
+/// The directions a run can take: along a row, down a column and both diagonals
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (1, -1)];
+/// Weight of a run of four cells holding one, two or three discs of a
+/// single side
+const RUN_WEIGHTS: [i32; CONNECT] = [0, 1, 4, 16];
+/// Weight of a disc in the center column, which takes part in the most runs
+const CENTER_WEIGHT: i32 = 3;
+
+/// A column to drop a disc into, written as its letter `A` to `G` or its
+/// number `1` to `7`
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct Column(pub usize);
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", column_name(self.0))
+    }
+}
+
+impl FromStr for Column {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tr = s.trim();
+        let mut chars = tr.chars();
+
+        let col = match (chars.next(), chars.next()) {
+            (Some(c @ 'a'..='z'), None) | (Some(c @ 'A'..='Z'), None) => c.to_ascii_uppercase() as usize - 'A' as usize,
+            (Some(c @ '1'..='9'), None) => c as usize - '1' as usize,
+            _ => return Err(GameError::InvalidInput(format!("Please name a column from A to G, not: {}", tr))),
+        };
+        if col >= COLUMNS {
+            return Err(GameError::OutOfBounds);
+        }
+
+        Ok(Column(col))
+    }
+}
+
+/// The cell `steps` steps from `(row, col)` in `direction`, if it is on the grid
+fn step(row: usize, col: usize, direction: (isize, isize), steps: isize) -> Option<(usize, usize)> {
+    let row = row as isize + direction.0 * steps;
+    let col = col as isize + direction.1 * steps;
+
+    if (0..ROWS as isize).contains(&row) && (0..COLUMNS as isize).contains(&col) {
+        Some((row as usize, col as usize))
+    } else {
+        None
+    }
+}
+
+#[derive(Clone)]
+pub struct ConnectFour {
+    /// The discs of the grid, `cells[row][col]` with row 0 at the top
+    cells: [[BoardChar; COLUMNS]; ROWS],
+    /// How many discs every column holds
+    heights: [usize; COLUMNS],
+    turn: BoardChar,
+    /// The side that opens every game
+    first: BoardChar,
+    /// The run of the side that won, if any
+    line: Option<[(usize, usize); CONNECT]>,
+    max_depth: Option<usize>,
+    player_char: BoardChar,
+    machine_char: BoardChar,
+    symbols: Symbols,
+    /// How the grid is drawn by `Display`
+    theme: Theme,
+    rng: Rng,
+    ai: AiConfig,
+}
+
+impl ConnectFour {
+    /// This function starts an empty game. X opens unless `set_first` says otherwise.
+    pub fn new(player_char: BoardChar) -> ConnectFour {
+        ConnectFour {
+            cells: [[BoardChar::Empty; COLUMNS]; ROWS],
+            heights: [0; COLUMNS],
+            turn: BoardChar::X,
+            first: BoardChar::X,
+            line: None,
+            max_depth: Some(CONNECT_FOUR_DEPTH),
+            player_char,
+            machine_char: player_char.to_opposite(),
+            symbols: Symbols::default(),
+            theme: Theme::default(),
+            rng: Rng::from_time(),
+            ai: AiConfig::default(),
+        }
+    }
+
+    /// This function limits how many moves ahead the machine looks, `None`
+    /// searches as deep as the time allows. By default it looks
+    /// `CONNECT_FOUR_DEPTH` moves ahead.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn set_ai(&mut self, ai: AiConfig) {
+        self.ai = ai;
+    }
+
+    /// This function reseeds the machine's random choices
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    pub fn set_symbols(&mut self, symbols: Symbols) {
+        self.symbols = symbols;
+    }
+
+    pub fn symbols(&self) -> Symbols {
+        self.symbols
+    }
+
+    /// This function changes how the grid is drawn
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn player_char(&self) -> BoardChar {
+        self.player_char
+    }
+
+    pub fn machine_char(&self) -> BoardChar {
+        self.machine_char
+    }
+
+    /// This function returns the side to move next
+    pub fn turn(&self) -> BoardChar {
+        self.turn
+    }
+
+    /// This function lets `c` open the game. It has no effect once a disc
+    /// has been dropped.
+    pub fn set_first(&mut self, c: BoardChar) {
+        if self.is_empty() && c != BoardChar::Empty {
+            self.turn = c;
+            self.first = c;
+        }
+    }
+
+    /// This function returns true if no disc has been dropped yet
+    pub fn is_empty(&self) -> bool {
+        self.heights.iter().all(|&height| height == 0)
+    }
+
+    /// This function empties the grid for a new game, the opener stays the same
+    pub fn reset(&mut self) {
+        self.cells = [[BoardChar::Empty; COLUMNS]; ROWS];
+        self.heights = [0; COLUMNS];
+        self.line = None;
+        self.turn = self.first;
+    }
+
+    /// This function returns the disc on the cell `(row, col)`, row 0 at the top
+    pub fn cell(&self, row: usize, col: usize) -> Option<BoardChar> {
+        self.cells.get(row)?.get(col).copied()
+    }
+
+    /// This function returns the side that connected four, if any
+    pub fn winner(&self) -> Option<BoardChar> {
+        self.line.map(|[(row, col), ..]| self.cells[row][col])
+    }
+
+    /// This function returns true if a side won or the grid is full
+    pub fn is_game_over(&self) -> bool {
+        self.line.is_some() || self.heights.iter().all(|&height| height == ROWS)
+    }
+
+    /// This function lists the columns that still take a disc, the center
+    /// ones first
+    pub fn legal_moves(&self) -> Vec<Column> {
+        if self.line.is_some() {
+            return Vec::new();
+        }
+
+        CENTER_FIRST.iter().filter(|&&col| self.heights[col] < ROWS).map(|&col| Column(col)).collect()
+    }
+
+    /// This function drops a disc of the side to move into column `m`
+    pub fn play_move(&mut self, m: &Column) -> Result<(), GameError> {
+        if self.is_game_over() {
+            return Err(GameError::GameAlreadyOver);
+        }
+        let col = m.0;
+        if col >= COLUMNS {
+            return Err(GameError::OutOfBounds);
+        }
+        if self.heights[col] == ROWS {
+            return Err(GameError::CellOccupied);
+        }
+
+        let row = ROWS - 1 - self.heights[col];
+        self.cells[row][col] = self.turn;
+        self.heights[col] += 1;
+        self.line = self.run_through(row, col);
+        self.turn = self.turn.to_opposite();
+
+        Ok(())
+    }
+
+    /// This function returns four discs in a row through the disc on `(row, col)`, if any
+    fn run_through(&self, row: usize, col: usize) -> Option<[(usize, usize); CONNECT]> {
+        let c = self.cells[row][col];
+
+        for &direction in &DIRECTIONS {
+            // Walk back to the first disc of the run, then take four from there
+            let mut start = 0;
+            while step(row, col, direction, start - 1).is_some_and(|(r, c2)| self.cells[r][c2] == c) {
+                start -= 1;
+            }
+
+            let mut run = [(row, col); CONNECT];
+            let mut found = true;
+            for (i, cell) in run.iter_mut().enumerate() {
+                match step(row, col, direction, start + i as isize) {
+                    Some((r, c2)) if self.cells[r][c2] == c => *cell = (r, c2),
+                    _ => found = false,
+                }
+            }
+            if found {
+                return Some(run);
+            }
+        }
+
+        None
+    }
+
+    /// This function makes the machine's move, thinking no longer than `budget`.
+    /// It returns the chosen column and the search depth that was completed.
+    pub fn machine_move(&mut self, budget: Duration) -> Result<(Column, usize), GameError> {
+        let (m, depth) = self.best_move(Some(Instant::now() + budget)).ok_or(GameError::GameAlreadyOver)?;
+        self.play_move(&m)?;
+
+        Ok((m, depth))
+    }
+
+    /// This function returns the move the machine would play, thinking
+    /// until `deadline`, and the search depth that was completed. It
+    /// returns `None` once the game is over.
+    pub fn best_move(&mut self, deadline: Option<Instant>) -> Option<(Column, usize)> {
+        if self.is_game_over() {
+            return None;
+        }
+
+        match self.ai.difficulty {
+            Difficulty::Easy => self.random_move().map(|m| (m, 0)),
+            Difficulty::Medium if self.rng.below(100) < MEDIUM_RANDOM_PERCENT => {
+                self.random_move().map(|m| (m, 0))
+            }
+            Difficulty::Medium | Difficulty::Hard => {
+                let mut engine = self.ai.engine(&mut self.rng, self.max_depth);
+                engine.best_move(self, deadline)
+            }
+        }
+    }
+
+    /// This function returns a random legal move
+    fn random_move(&mut self) -> Option<Column> {
+        let moves = self.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        Some(moves[self.rng.below(moves.len())])
+    }
+
+    /// This function scores a position the search could not finish from the
+    /// point of view of `me`. Every four cells in a row holding discs of a
+    /// single side count for that side, the more discs the more (see
+    /// `RUN_WEIGHTS`), and so does every disc in the center column.
+    pub fn heuristic(&self, me: BoardChar) -> i16 {
+        let mut score: i32 = 0;
+
+        for row in 0..ROWS {
+            for col in 0..COLUMNS {
+                for &direction in &DIRECTIONS {
+                    let run: Option<Vec<BoardChar>> = (0..CONNECT as isize)
+                        .map(|i| step(row, col, direction, i).map(|(r, c)| self.cells[r][c]))
+                        .collect();
+                    let run = match run {
+                        Some(run) => run,
+                        None => continue,
+                    };
+
+                    let mine = run.iter().filter(|&&c| c == me).count();
+                    let theirs = run.iter().filter(|&&c| c == me.to_opposite()).count();
+                    match (mine, theirs) {
+                        (0, 0) => {}
+                        (mine, 0) => score += RUN_WEIGHTS[mine.min(CONNECT - 1)],
+                        (0, theirs) => score -= RUN_WEIGHTS[theirs.min(CONNECT - 1)],
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        for row in &self.cells {
+            match row[COLUMNS / 2] {
+                c if c == me => score += CENTER_WEIGHT,
+                BoardChar::Empty => {}
+                _ => score -= CENTER_WEIGHT,
+            }
+        }
+
+        let bound = HEURISTIC_BOUND as i32 - 1;
+        cmp::max(-bound, cmp::min(bound, score)) as i16
+    }
+}
+
+impl Engine<ConnectFour> for Minimax {
+    fn best_move(&mut self, game: &mut ConnectFour, deadline: Option<Instant>) -> Option<(Column, usize)> {
+        let empty = ROWS * COLUMNS - game.heights.iter().sum::<usize>();
+        let full_depth = self.max_depth.map_or(empty, |max| cmp::min(max, empty));
+
+        game::iterative_deepening(game, deadline, full_depth)
+    }
+}
+
+impl Game for ConnectFour {
+    type Move = Column;
+
+    fn legal_moves(&self) -> Vec<Column> {
+        ConnectFour::legal_moves(self)
+    }
+
+    fn play(&mut self, m: &Column) -> Result<(), GameError> {
+        self.play_move(m)
+    }
+
+    fn turn(&self) -> BoardChar {
+        ConnectFour::turn(self)
+    }
+
+    fn winner(&self) -> Option<BoardChar> {
+        ConnectFour::winner(self)
+    }
+
+    fn is_game_over(&self) -> bool {
+        ConnectFour::is_game_over(self)
+    }
+
+    fn score(&self, me: BoardChar) -> i16 {
+        self.heuristic(me)
+    }
+}
+
+impl Table for ConnectFour {
+    fn player_char(&self) -> BoardChar {
+        ConnectFour::player_char(self)
+    }
+
+    fn machine_char(&self) -> BoardChar {
+        ConnectFour::machine_char(self)
+    }
+
+    fn glyph(&self, c: BoardChar) -> Option<char> {
+        Some(self.symbols.glyph(c))
+    }
+
+    fn set_first(&mut self, c: BoardChar) {
+        ConnectFour::set_first(self, c);
+    }
+
+    fn reset(&mut self) {
+        ConnectFour::reset(self);
+    }
+
+    fn machine_choice(&mut self, deadline: Option<Instant>) -> Option<(Column, SearchInfo)> {
+        let (m, depth) = self.best_move(deadline)?;
+        Some((m, SearchInfo { depth, ..SearchInfo::default() }))
+    }
+}
+
+impl fmt::Display for ConnectFour {
+    /// The grid is drawn in the frame of the theme with the column letters
+    /// above it. The four discs that won are highlighted.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frame = self.theme.frame();
+        let width = self.theme.cell_width();
+        let border = |corners: [char; 3]| {
+            let cell = frame.horizontal.to_string().repeat(width);
+            format!("{}{}{}\n", corners[0], vec![cell; COLUMNS].join(&corners[1].to_string()), corners[2])
+        };
+
+        let mut board_txt = String::new();
+        for col in 0..COLUMNS {
+            board_txt.push_str(&format!(" {:^width$}", column_name(col), width = width));
+        }
+        board_txt.push('\n');
+        board_txt.push_str(&border(frame.corners[0]));
+
+        for (row, cells) in self.cells.iter().enumerate() {
+            if row > 0 {
+                board_txt.push_str(&border(frame.corners[1]));
+            }
+
+            let cells: Vec<(String, bool)> = cells
+                .iter()
+                .enumerate()
+                .map(|(col, &cell)| {
+                    let text = format!("{:^width$}", self.symbols.glyph(cell), width = width);
+                    match self.line.is_some_and(|line| line.contains(&(row, col))) {
+                        true => (self.theme.highlight(&text, cell), true),
+                        false => (self.theme.paint(&text, cell), false),
+                    }
+                })
+                .collect();
+            board_txt.push_str(&draw_row(self.theme, &cells));
+            board_txt.push('\n');
+        }
+
+        board_txt.push_str(&border(frame.corners[2]));
+
+        writeln!(f, "{}", board_txt)
+    }
+}
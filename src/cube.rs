@@ -0,0 +1,388 @@
+//! 3D Tic-Tac-Toe: three layers of 3x3 boards stacked into a cube, three
+//! in a row along any row, column, pillar or diagonal of the cube win.
+//!
+//! Cells are numbered `layer * 9 + row * 3 + col`. The game is played by
+//! the engines through `Game`, `Minimax` with `game::iterative_deepening`.
+
+use std::cmp;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::board::{BoardChar, WinLine};
+use crate::engine::{AiConfig, Difficulty, Engine, Minimax, SearchInfo, HEURISTIC_BOUND, MEDIUM_RANDOM_PERCENT};
+use crate::error::GameError;
+use crate::game::{self, Game};
+use crate::player::{Symbols, Table};
+use crate::r#move::{CubeMove, Move};
+use crate::render::{draw_board, side_by_side, Theme};
+use crate::rng::Rng;
+
+const SIZE: usize = 3;
+const CELLS: usize = SIZE * SIZE * SIZE;
+/// The center of the cube, which lies on more lines than any other cell
+const CENTER: usize = CELLS / 2;
+/// How many moves ahead the machine looks unless told otherwise
+pub const CUBE_DEPTH: usize = 4;
+/// Weight of a line held by a single side with one and with two marks
+const LINE_WEIGHTS: [i32; 2] = [1, 10];
+
+/// This function returns the cell of the cube at `layer`, `row`, `col`
+fn index(layer: usize, row: usize, col: usize) -> usize {
+    layer * SIZE * SIZE + row * SIZE + col
+}
+
+/// This function lists the 49 lines of the cube: rows, columns and pillars,
+/// the diagonals of every face and slice and the four space diagonals
+fn lines() -> Vec<[usize; SIZE]> {
+    let mut lines = Vec::new();
+
+    // Of the two opposite directions only the one that goes forward on the
+    // first axis it moves along is taken, so every line comes up once
+    for dz in -1..=1_isize {
+        for dy in -1..=1_isize {
+            for dx in -1..=1_isize {
+                let forward = [dz, dy, dx].iter().find(|&&d| d != 0).is_some_and(|&d| d > 0);
+                if !forward {
+                    continue;
+                }
+
+                for start in 0..CELLS {
+                    let (z, y, x) = ((start / 9) as isize, (start / 3 % 3) as isize, (start % 3) as isize);
+                    let end = [z + 2 * dz, y + 2 * dy, x + 2 * dx];
+                    if end.iter().any(|&v| !(0..SIZE as isize).contains(&v)) {
+                        continue;
+                    }
+
+                    let cell = |k: isize| index((z + k * dz) as usize, (y + k * dy) as usize, (x + k * dx) as usize);
+                    lines.push([cell(0), cell(1), cell(2)]);
+                }
+            }
+        }
+    }
+
+    lines
+}
+
+#[derive(Clone)]
+pub struct Cube {
+    cells: [BoardChar; CELLS],
+    /// Every line of the cube, see `lines`
+    lines: Vec<[usize; SIZE]>,
+    turn: BoardChar,
+    /// The side that opens every game
+    first: BoardChar,
+    /// The line completed by the side that won, if any
+    line: Option<[usize; SIZE]>,
+    max_depth: Option<usize>,
+    player_char: BoardChar,
+    machine_char: BoardChar,
+    symbols: Symbols,
+    /// How the layers are drawn by `Display`
+    theme: Theme,
+    rng: Rng,
+    ai: AiConfig,
+}
+
+impl Cube {
+    /// This function starts an empty game. X opens unless `set_first` says otherwise.
+    pub fn new(player_char: BoardChar) -> Cube {
+        Cube {
+            cells: [BoardChar::Empty; CELLS],
+            lines: lines(),
+            turn: BoardChar::X,
+            first: BoardChar::X,
+            line: None,
+            max_depth: Some(CUBE_DEPTH),
+            player_char,
+            machine_char: player_char.to_opposite(),
+            symbols: Symbols::default(),
+            theme: Theme::default(),
+            rng: Rng::from_time(),
+            ai: AiConfig::default(),
+        }
+    }
+
+    /// This function limits how many moves ahead the machine looks, `None`
+    /// searches as deep as the time allows. By default it looks
+    /// `CUBE_DEPTH` moves ahead.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn set_ai(&mut self, ai: AiConfig) {
+        self.ai = ai;
+    }
+
+    /// This function reseeds the machine's random choices
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    pub fn set_symbols(&mut self, symbols: Symbols) {
+        self.symbols = symbols;
+    }
+
+    pub fn symbols(&self) -> Symbols {
+        self.symbols
+    }
+
+    /// This function changes how the layers are drawn
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn player_char(&self) -> BoardChar {
+        self.player_char
+    }
+
+    pub fn machine_char(&self) -> BoardChar {
+        self.machine_char
+    }
+
+    /// This function returns the side to move next
+    pub fn turn(&self) -> BoardChar {
+        self.turn
+    }
+
+    /// This function lets `c` open the game. It has no effect once a move
+    /// has been played.
+    pub fn set_first(&mut self, c: BoardChar) {
+        if self.is_empty() && c != BoardChar::Empty {
+            self.turn = c;
+            self.first = c;
+        }
+    }
+
+    /// This function returns true if no mark has been placed yet
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().all(|&cell| cell == BoardChar::Empty)
+    }
+
+    /// This function clears the cube for a new game, the opener stays the same
+    pub fn reset(&mut self) {
+        self.cells = [BoardChar::Empty; CELLS];
+        self.line = None;
+        self.turn = self.first;
+    }
+
+    /// This function returns the mark on the cell of `m`
+    pub fn cell(&self, m: &CubeMove) -> Option<BoardChar> {
+        let i = Cube::cell_index(m)?;
+        Some(self.cells[i])
+    }
+
+    /// This function returns the cell `m` names, if it is in the cube
+    fn cell_index(m: &CubeMove) -> Option<usize> {
+        if m.layer < SIZE && m.cell.row < SIZE && m.cell.col < SIZE {
+            Some(index(m.layer, m.cell.row, m.cell.col))
+        } else {
+            None
+        }
+    }
+
+    /// This function returns the side that completed a line, if any
+    pub fn winner(&self) -> Option<BoardChar> {
+        self.line.map(|line| self.cells[line[0]])
+    }
+
+    /// This function returns true if a side won or the cube is full
+    pub fn is_game_over(&self) -> bool {
+        self.line.is_some() || !self.cells.contains(&BoardChar::Empty)
+    }
+
+    /// This function lists the cells the side to move may play, the center
+    /// first
+    pub fn legal_moves(&self) -> Vec<CubeMove> {
+        if self.line.is_some() {
+            return Vec::new();
+        }
+
+        let mut cells: Vec<usize> = (0..CELLS).filter(|&i| self.cells[i] == BoardChar::Empty).collect();
+        if let Some(i) = cells.iter().position(|&i| i == CENTER) {
+            cells.swap(0, i);
+        }
+
+        cells
+            .into_iter()
+            .map(|i| CubeMove {
+                cell: Move { row: i / 3 % 3, col: i % 3 },
+                layer: i / 9,
+            })
+            .collect()
+    }
+
+    /// This function places the mark of the side to move on the cell of `m`
+    pub fn play_move(&mut self, m: &CubeMove) -> Result<(), GameError> {
+        if self.is_game_over() {
+            return Err(GameError::GameAlreadyOver);
+        }
+        let i = Cube::cell_index(m).ok_or(GameError::OutOfBounds)?;
+        if self.cells[i] != BoardChar::Empty {
+            return Err(GameError::CellOccupied);
+        }
+
+        let c = self.turn;
+        self.cells[i] = c;
+        self.line = self
+            .lines
+            .iter()
+            .find(|line| line.contains(&i) && line.iter().all(|&j| self.cells[j] == c))
+            .copied();
+        self.turn = c.to_opposite();
+
+        Ok(())
+    }
+
+    /// This function makes the machine's move, thinking no longer than `budget`.
+    /// It returns the chosen move and the search depth that was completed.
+    pub fn machine_move(&mut self, budget: Duration) -> Result<(CubeMove, usize), GameError> {
+        let (m, depth) = self.best_move(Some(Instant::now() + budget)).ok_or(GameError::GameAlreadyOver)?;
+        self.play_move(&m)?;
+
+        Ok((m, depth))
+    }
+
+    /// This function returns the move the machine would play, thinking
+    /// until `deadline`, and the search depth that was completed. It
+    /// returns `None` once the game is over.
+    pub fn best_move(&mut self, deadline: Option<Instant>) -> Option<(CubeMove, usize)> {
+        if self.is_game_over() {
+            return None;
+        }
+
+        match self.ai.difficulty {
+            Difficulty::Easy => self.random_move().map(|m| (m, 0)),
+            Difficulty::Medium if self.rng.below(100) < MEDIUM_RANDOM_PERCENT => {
+                self.random_move().map(|m| (m, 0))
+            }
+            Difficulty::Medium | Difficulty::Hard => {
+                let mut engine = self.ai.engine(&mut self.rng, self.max_depth);
+                engine.best_move(self, deadline)
+            }
+        }
+    }
+
+    /// This function returns a random legal move
+    fn random_move(&mut self) -> Option<CubeMove> {
+        let moves = self.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        Some(moves[self.rng.below(moves.len())])
+    }
+
+    /// This function scores a position the search could not finish from the
+    /// point of view of `me`. Every line still open for only one side counts
+    /// for that side, a line a single mark short of a win the most.
+    pub fn heuristic(&self, me: BoardChar) -> i16 {
+        let mut score: i32 = 0;
+
+        for line in &self.lines {
+            let mine = line.iter().filter(|&&i| self.cells[i] == me).count();
+            let theirs = line.iter().filter(|&&i| self.cells[i] == me.to_opposite()).count();
+
+            match (mine, theirs) {
+                (1..=2, 0) => score += LINE_WEIGHTS[mine - 1],
+                (0, 1..=2) => score -= LINE_WEIGHTS[theirs - 1],
+                _ => {}
+            }
+        }
+
+        let bound = i32::from(HEURISTIC_BOUND);
+        score.clamp(-bound, bound) as i16
+    }
+}
+
+impl Engine<Cube> for Minimax {
+    fn best_move(&mut self, game: &mut Cube, deadline: Option<Instant>) -> Option<(CubeMove, usize)> {
+        let empty = game.cells.iter().filter(|&&cell| cell == BoardChar::Empty).count();
+        let full_depth = self.max_depth.map_or(empty, |max| cmp::min(max, empty));
+
+        game::iterative_deepening(game, deadline, full_depth)
+    }
+}
+
+impl Game for Cube {
+    type Move = CubeMove;
+
+    fn legal_moves(&self) -> Vec<CubeMove> {
+        Cube::legal_moves(self)
+    }
+
+    fn play(&mut self, m: &CubeMove) -> Result<(), GameError> {
+        self.play_move(m)
+    }
+
+    fn turn(&self) -> BoardChar {
+        Cube::turn(self)
+    }
+
+    fn winner(&self) -> Option<BoardChar> {
+        Cube::winner(self)
+    }
+
+    fn is_game_over(&self) -> bool {
+        Cube::is_game_over(self)
+    }
+
+    fn score(&self, me: BoardChar) -> i16 {
+        self.heuristic(me)
+    }
+}
+
+impl Table for Cube {
+    fn player_char(&self) -> BoardChar {
+        Cube::player_char(self)
+    }
+
+    fn machine_char(&self) -> BoardChar {
+        Cube::machine_char(self)
+    }
+
+    fn glyph(&self, c: BoardChar) -> Option<char> {
+        Some(self.symbols.glyph(c))
+    }
+
+    fn set_first(&mut self, c: BoardChar) {
+        Cube::set_first(self, c);
+    }
+
+    fn reset(&mut self) {
+        Cube::reset(self);
+    }
+
+    fn machine_choice(&mut self, deadline: Option<Instant>) -> Option<(CubeMove, SearchInfo)> {
+        let (m, depth) = self.best_move(deadline)?;
+        Some((m, SearchInfo { depth, ..SearchInfo::default() }))
+    }
+}
+
+impl fmt::Display for Cube {
+    /// The three layers are drawn side by side like boards of their own,
+    /// the top layer on the left. The cells of a completed line are
+    /// highlighted in every layer it passes.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let layers: Vec<Vec<String>> = (0..SIZE)
+            .map(|layer| {
+                let board: Vec<Vec<BoardChar>> = (0..SIZE)
+                    .map(|row| (0..SIZE).map(|col| self.cells[index(layer, row, col)]).collect())
+                    .collect();
+                let cells: Vec<(usize, usize)> = self
+                    .line
+                    .iter()
+                    .flatten()
+                    .filter(|&&i| i / 9 == layer)
+                    .map(|&i| (i / 3 % 3, i % 3))
+                    .collect();
+                let line = self.winner().map(|mark| WinLine { mark, cells: &cells });
+
+                let mut lines: Vec<String> = draw_board(&board, self.symbols, self.theme, line).lines().map(String::from).collect();
+                lines.insert(0, format!("layer {}", layer + 1));
+                lines
+            })
+            .collect();
+
+        writeln!(f, "{}", side_by_side(&layers))
+    }
+}
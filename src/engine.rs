@@ -0,0 +1,2243 @@
+//! The game itself and the minimax search playing the machine's side.
+
+use std::cmp;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::analysis::{Evaluation, Lesson, Solution};
+use crate::bitboard::Bitboard;
+use crate::book;
+use crate::board::{parse_board, Board, BoardChar, Notation, Validate, WinLine};
+use crate::game::{self, Game};
+use crate::history::{History, Placement};
+#[cfg(feature = "serde")]
+use crate::json::{self, FromJson, ToJson};
+use crate::mcts::Mcts;
+use crate::observer::{Observer, Observers};
+use crate::player::{Symbols, Table};
+use crate::render::{self, draw_board_for, Grid, PlayerInfo, Theme};
+use crate::error::GameError;
+use crate::r#move::{Move, MAX_SIZE};
+use crate::rng::Rng;
+use crate::transposition::{Bound, Entry, TranspositionTable};
+
+/// Score of a game won right away. Wins further down the game tree score a
+/// little less, so the machine prefers quick wins and slow losses.
+pub const WIN_SCORE: i16 = 10000;
+/// The heuristic never leaves this range, so it stays well below any win
+pub(crate) const HEURISTIC_BOUND: i16 = WIN_SCORE / 10;
+/// Search depth used on boards where a full minimax is infeasible
+pub const DEFAULT_MAX_DEPTH: usize = 3;
+/// Largest board the search plays to the end of the game by default. The
+/// transposition table makes a full 4x4 search take well under a second.
+pub const FULL_SEARCH_SIZE: usize = 4;
+/// How often (in visited positions) the search looks at the clock
+const DEADLINE_CHECK_INTERVAL: u64 = 1024;
+/// Boards from this size on only search the cells near the marks placed
+pub const NEAR_MOVES_SIZE: usize = 9;
+/// How many cells away from a mark a cell may be to be searched there
+const NEAR_DISTANCE: usize = 2;
+/// Games won by at least this many in a row are scored by the open ends of
+/// their runs, the way Gomoku players count threes and fours
+const THREAT_WIN_LEN: usize = 5;
+/// Weights of a run one, two and three marks short of a win with both ends
+/// open and with one end open
+const OPEN_RUN_WEIGHTS: [i32; 3] = [300, 50, 4];
+const HALF_OPEN_RUN_WEIGHTS: [i32; 3] = [60, 8, 1];
+/// Weight of a fork, two cells or more that would each complete a run of
+/// the same side, of which the opponent can only block one
+const FORK_WEIGHT: i32 = 32;
+/// Weight of every run a mark lies on, open or not, so the marks in the
+/// center and the corners, which lie on the most runs, count the most
+const REACH_WEIGHT: i32 = 1;
+
+/// Limits and bookkeeping of a single search run
+pub struct Search {
+    pub max_depth: Option<usize>,
+    pub deadline: Option<Instant>,
+    pub nodes: u64,
+    pub aborted: bool,
+    /// Positions scored so far. A position always comes up at the same
+    /// depth within one search, so its score can be reused as it is.
+    pub table: TranspositionTable,
+    /// Where the nodes are counted for another thread, which may also stop
+    /// the search like a deadline
+    pub progress: Option<Arc<Progress>>,
+}
+
+impl Search {
+    pub fn new(max_depth: Option<usize>, deadline: Option<Instant>) -> Search {
+        Search {
+            max_depth,
+            deadline,
+            nodes: 0,
+            aborted: false,
+            table: TranspositionTable::new(),
+            progress: None,
+        }
+    }
+
+    /// This function counts a visited position and returns true once the
+    /// deadline has passed or the search was stopped. The clock is only
+    /// read every few positions.
+    pub(crate) fn visit(&mut self) -> bool {
+        self.nodes += 1;
+
+        if self.nodes.is_multiple_of(DEADLINE_CHECK_INTERVAL) {
+            if self.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                self.aborted = true;
+            }
+            if let Some(progress) = &self.progress {
+                progress.nodes.fetch_add(DEADLINE_CHECK_INTERVAL, Ordering::Relaxed);
+                if progress.is_stopped() {
+                    self.aborted = true;
+                }
+            }
+        }
+
+        self.aborted
+    }
+}
+
+/// What the search of the machine has found so far, shared with another
+/// thread that shows it while the search runs, see `TicTacToe::set_progress`
+#[derive(Debug, Default)]
+pub struct Progress {
+    nodes: AtomicU64,
+    /// The deepest search that finished
+    depth: AtomicUsize,
+    /// The best move of that search
+    best: Mutex<Option<Move>>,
+    stop: AtomicBool,
+}
+
+impl Progress {
+    pub fn new() -> Progress {
+        Progress::default()
+    }
+
+    /// This function returns about how many positions were searched
+    pub fn nodes(&self) -> u64 {
+        self.nodes.load(Ordering::Relaxed)
+    }
+
+    /// This function returns the depth of the deepest search that finished
+    pub fn depth(&self) -> usize {
+        self.depth.load(Ordering::Relaxed)
+    }
+
+    /// This function returns the best move of the deepest search that finished
+    pub fn best(&self) -> Option<Move> {
+        *self.best.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// This function ends the search at its next check, the move found so far
+    /// is played. The search to the first depth always finishes.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    /// This function is told about every depth the search finished
+    fn finished(&self, depth: usize, best: Move) {
+        self.depth.store(depth, Ordering::Relaxed);
+        *self.best.lock().unwrap_or_else(|e| e.into_inner()) = Some(best);
+    }
+}
+
+/// What the search behind a move of the machine did, see
+/// `TicTacToe::choose_move_with_info`
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SearchInfo {
+    /// The deepest search that finished, 0 for a move that was not searched
+    pub depth: usize,
+    /// Positions visited by the searches to every depth
+    pub nodes: u64,
+    /// Positions found in the transposition table instead of searched again
+    pub table_hits: u64,
+    pub elapsed: Duration,
+    /// The move and the replies the search expects to it, as far as it
+    /// remembered them. Searches on several threads remember none.
+    pub principal_variation: Vec<Move>,
+}
+
+impl fmt::Display for SearchInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "depth {}, {} positions, {} table hits, {} ms",
+            self.depth,
+            self.nodes,
+            self.table_hits,
+            self.elapsed.as_millis()
+        )?;
+        if !self.principal_variation.is_empty() {
+            let line: Vec<String> = self.principal_variation.iter().map(Move::to_string).collect();
+            write!(f, ", line {}", line.join(" "))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// How strong the machine plays
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Difficulty {
+    /// Any legal move, picked at random
+    Easy,
+    /// A shallow search that now and then plays a random move instead
+    Medium,
+    /// The full minimax search
+    Hard,
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "easy" => Ok(Difficulty::Easy),
+            "medium" => Ok(Difficulty::Medium),
+            "hard" => Ok(Difficulty::Hard),
+            tr => Err(GameError::InvalidInput(format!("'{}' is not one of 'easy', 'medium', 'hard'", tr))),
+        }
+    }
+}
+
+/// Search depth of the medium difficulty
+pub(crate) const MEDIUM_DEPTH: usize = 2;
+/// Chance in percent that the medium difficulty plays a random move
+pub(crate) const MEDIUM_RANDOM_PERCENT: usize = 25;
+
+/// Playouts per move of the medium difficulty when it plays with MCTS
+pub(crate) const MEDIUM_PLAYOUTS: usize = 100;
+
+/// Which search picks the machine's moves
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Algorithm {
+    /// Alpha-beta minimax, see `Minimax`
+    Minimax,
+    /// Monte Carlo Tree Search, see `Mcts`
+    Mcts,
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Algorithm::Minimax => write!(f, "minimax"),
+            Algorithm::Mcts => write!(f, "mcts"),
+        }
+    }
+}
+
+impl FromStr for Algorithm {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "minimax" => Ok(Algorithm::Minimax),
+            "mcts" => Ok(Algorithm::Mcts),
+            tr => Err(GameError::InvalidInput(format!("'{}' is not one of 'minimax', 'mcts'", tr))),
+        }
+    }
+}
+
+/// Settings of the machine opponent
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct AiConfig {
+    pub difficulty: Difficulty,
+    pub algorithm: Algorithm,
+    /// The chance that the machine plays a worse move than the best one,
+    /// between 0 and 1
+    pub blunder: Option<f64>,
+}
+
+impl Default for AiConfig {
+    fn default() -> AiConfig {
+        AiConfig {
+            difficulty: Difficulty::Hard,
+            algorithm: Algorithm::Minimax,
+            blunder: None,
+        }
+    }
+}
+
+/// Opponents with a character of their own, each a difficulty and a
+/// blunder rate
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Personality {
+    /// Plays any legal move
+    RandomRandy,
+    /// Searches, but misses something in four moves out of ten
+    SloppySam,
+    /// Searches and errs in one move out of twenty
+    CarefulCarla,
+    /// Never errs
+    PerfectPat,
+}
+
+impl Personality {
+    pub fn ai(self, algorithm: Algorithm) -> AiConfig {
+        let (difficulty, blunder) = match self {
+            Personality::RandomRandy => (Difficulty::Easy, None),
+            Personality::SloppySam => (Difficulty::Hard, Some(0.4)),
+            Personality::CarefulCarla => (Difficulty::Hard, Some(0.05)),
+            Personality::PerfectPat => (Difficulty::Hard, None),
+        };
+
+        AiConfig {
+            difficulty,
+            algorithm,
+            blunder,
+        }
+    }
+}
+
+impl fmt::Display for Personality {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Personality::RandomRandy => write!(f, "Random Randy"),
+            Personality::SloppySam => write!(f, "Sloppy Sam"),
+            Personality::CarefulCarla => write!(f, "Careful Carla"),
+            Personality::PerfectPat => write!(f, "Perfect Pat"),
+        }
+    }
+}
+
+impl FromStr for Personality {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().replace(['-', '_', ' '], "").as_str() {
+            "randy" | "randomrandy" => Ok(Personality::RandomRandy),
+            "sam" | "sloppysam" => Ok(Personality::SloppySam),
+            "carla" | "carefulcarla" => Ok(Personality::CarefulCarla),
+            "pat" | "perfectpat" => Ok(Personality::PerfectPat),
+            _ => Err(GameError::InvalidInput(format!("'{}' is not one of 'randy', 'sam', 'carla', 'pat'", s.trim()))),
+        }
+    }
+}
+
+impl AiConfig {
+    /// This function returns the engine searching the machine's move. The
+    /// medium difficulty gets a shallow minimax or only a few playouts,
+    /// `max_depth` limits the minimax search further.
+    pub(crate) fn engine<G: Game>(&self, rng: &mut Rng, max_depth: Option<usize>) -> Box<dyn Engine<G>>
+    where
+        Minimax: Engine<G>,
+    {
+        let medium = self.difficulty == Difficulty::Medium;
+
+        match self.algorithm {
+            Algorithm::Minimax if medium => Box::new(Minimax {
+                max_depth: Some(max_depth.map_or(MEDIUM_DEPTH, |max| cmp::min(max, MEDIUM_DEPTH))),
+            }),
+            Algorithm::Minimax => Box::new(Minimax { max_depth }),
+            Algorithm::Mcts => Box::new(Mcts::new(
+                Rng::new(rng.next_u64()),
+                if medium { Some(MEDIUM_PLAYOUTS) } else { None },
+            )),
+        }
+    }
+}
+
+/// A search that picks moves in a `Game`
+pub trait Engine<G: Game> {
+    /// This function returns the best move the engine finds for the side to
+    /// move in `game` before `deadline`, and how deep it searched. Without a
+    /// deadline only the engine's own limits end the search. The game is
+    /// left as it was.
+    fn best_move(&mut self, game: &mut G, deadline: Option<Instant>) -> Option<(G::Move, usize)>;
+
+    /// This function scores every legal move of the side to move in `game`
+    /// from its point of view, positive values win. Engines without a
+    /// minimax search of their own score them with a full one.
+    fn evaluate_all(&mut self, game: &mut G) -> Vec<(G::Move, i16)> {
+        game::evaluate_all(game, &mut Search::new(None, None))
+    }
+}
+
+/// Alpha-beta minimax with iterative deepening, cut off at `max_depth`
+pub struct Minimax {
+    pub max_depth: Option<usize>,
+}
+
+impl Engine<TicTacToe> for Minimax {
+    fn best_move(&mut self, game: &mut TicTacToe, deadline: Option<Instant>) -> Option<(Move, usize)> {
+        game.iterative_deepening(deadline, self.max_depth)
+    }
+
+    fn evaluate_all(&mut self, game: &mut TicTacToe) -> Vec<(Move, i16)> {
+        let c = game.turn();
+        game.score_moves(&mut Search::new(self.max_depth, None), c)
+    }
+}
+
+/// The machine playing with these settings the way it does in a game, with
+/// the opening book and the random choices of the game it is asked about
+impl Engine<TicTacToe> for AiConfig {
+    fn best_move(&mut self, game: &mut TicTacToe, deadline: Option<Instant>) -> Option<(Move, usize)> {
+        let mut machine = game.clone();
+        machine.set_ai(*self);
+
+        machine.choose_move(deadline)
+    }
+}
+
+/// How a game ended if neither a line nor the full board ended it
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Ending {
+    /// The side ran out of time
+    OutOfTime(BoardChar),
+    /// The side gave up
+    Resigned(BoardChar),
+    /// The sides agreed on a draw
+    DrawAgreed,
+}
+
+/// Where a game stands
+#[derive(Debug, PartialEq, Clone)]
+pub enum GameState {
+    InProgress { turn: BoardChar },
+    /// `line` holds the cells of the completed line, in the misère game the
+    /// loser's. It is empty if the loser ran out of time or resigned.
+    Won { winner: BoardChar, line: Vec<Move> },
+    Draw,
+}
+
+/// What a move did to the game, for frontends that react to every move
+#[derive(Debug, PartialEq, Clone)]
+pub struct MoveOutcome {
+    pub placement: Placement,
+    /// Where the game stands after the move
+    pub state: GameState,
+}
+
+/// Snapshot of a game, the shape in which it is exchanged with frontends
+#[derive(Debug, PartialEq, Clone)]
+pub struct GameSnapshot {
+    pub board: Board,
+    pub win_len: usize,
+    pub player_char: BoardChar,
+    pub machine_char: BoardChar,
+    pub symbols: Symbols,
+    /// The symbol that moves next
+    pub turn: BoardChar,
+    /// The moves that led to `board`, oldest first. Positions set up
+    /// without playing them may leave it empty.
+    pub history: Vec<Placement>,
+    /// Completing a line loses instead of winning
+    pub misere: bool,
+    /// The cells of the player's extra marks, placed before the game
+    pub handicap: Vec<Move>,
+}
+
+/// A game of tic-tac-toe. A clone copies the position, the settings and the
+/// history, but not the observers: the searches play their moves on clones,
+/// which must not be told to the observers of the game. Hand them on with
+/// `add_observer` when a copy should be observed as well.
+#[derive(Clone)]
+pub struct TicTacToe {
+    board: Board,
+    /// The same marks as `board` for the search, on boards small enough
+    bits: Option<Bitboard>,
+    win_len: usize,
+    max_depth: Option<usize>,
+    lines: Vec<Vec<(usize, usize)>>,
+    player_char: BoardChar,
+    machine_char: BoardChar,
+    symbols: Symbols,
+    /// How the board is drawn by `Display`
+    theme: Theme,
+    rng: Rng,
+    ai: AiConfig,
+    history: History,
+    first: BoardChar,
+    /// How the game ended if neither a line nor the full board ended it
+    ending: Option<Ending>,
+    /// Completing a line loses instead of winning
+    misere: bool,
+    /// The machine plays the replies of the opening book when it has some
+    book: bool,
+    /// The cells of the marks the player got before the game, see `set_handicap`
+    handicap: Vec<Move>,
+    /// How many threads share the moves at the root of the search
+    threads: usize,
+    /// Where the search tells another thread how far it got, see `set_progress`
+    progress: Option<Arc<Progress>>,
+    /// What the searches since `choose_move_with_info` was called did
+    searched: SearchInfo,
+    /// Who plays the sides, see `set_players`
+    players: Vec<PlayerInfo>,
+    /// Who is told about the events of the game, see `add_observer`. Clones
+    /// start with none.
+    observers: Observers,
+}
+
+impl TicTacToe {
+    pub fn new(player_char: BoardChar) -> TicTacToe {
+        TicTacToe::with_size(player_char, 3, 3)
+    }
+
+    /// This function creates a game on a `size`x`size` board where `win_len`
+    /// marks in a row win. Boards larger than `FULL_SEARCH_SIZE` use a
+    /// depth-limited search.
+    pub fn with_size(player_char: BoardChar, size: usize, win_len: usize) -> TicTacToe {
+        assert!(
+            size > 0 && size <= MAX_SIZE,
+            "board size {} is not between 1 and {}",
+            size,
+            MAX_SIZE
+        );
+        assert!(
+            win_len > 0 && win_len <= size,
+            "win length {} does not fit on a {}x{} board",
+            win_len,
+            size,
+            size
+        );
+
+        let lines = TicTacToe::runs(size, win_len);
+
+        TicTacToe {
+            board: vec![vec![BoardChar::Empty; size]; size],
+            bits: Bitboard::new(size, &lines),
+            win_len,
+            max_depth: if size > FULL_SEARCH_SIZE { Some(DEFAULT_MAX_DEPTH) } else { None },
+            lines,
+            player_char,
+            machine_char: player_char.to_opposite(),
+            symbols: Symbols::default(),
+            theme: Theme::default(),
+            rng: Rng::from_time(),
+            ai: AiConfig::default(),
+            history: History::new(),
+            first: player_char,
+            ending: None,
+            misere: false,
+            book: true,
+            handicap: Vec::new(),
+            threads: 1,
+            progress: None,
+            searched: SearchInfo::default(),
+            players: Vec::new(),
+            observers: Observers::default(),
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// This function returns true while no mark has been placed yet
+    pub fn is_empty(&self) -> bool {
+        self.board.iter().flatten().all(|&c| c == BoardChar::Empty)
+    }
+
+    /// This function limits how many moves ahead the machine searches,
+    /// None searches until the game is decided
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// This function switches to the misère game, where the side that
+    /// completes a line loses. It takes effect from the next move on.
+    pub fn set_misere(&mut self, misere: bool) {
+        self.misere = misere;
+    }
+
+    pub fn is_misere(&self) -> bool {
+        self.misere
+    }
+
+    /// This function returns how many marks in a row win
+    pub fn win_len(&self) -> usize {
+        self.win_len
+    }
+
+    /// This function changes how the machine plays
+    pub fn set_ai(&mut self, ai: AiConfig) {
+        self.ai = ai;
+    }
+
+    pub fn ai(&self) -> AiConfig {
+        self.ai
+    }
+
+    /// This function lets the hard machine answer the first moves of the
+    /// classic game from the opening book instead of searching them
+    pub fn set_book(&mut self, book: bool) {
+        self.book = book;
+    }
+
+    /// This function lets `threads` threads search the moves at the root at
+    /// the same time, each one a share of them. The move found is the same
+    /// as with a single thread.
+    pub fn set_threads(&mut self, threads: usize) {
+        self.threads = threads.max(1);
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// This function lets the minimax search of the machine report to
+    /// `progress` how far it got, and stop once `Progress::stop` is called
+    pub fn set_progress(&mut self, progress: Option<Arc<Progress>>) {
+        self.progress = progress;
+    }
+
+    pub fn book(&self) -> bool {
+        self.book
+    }
+
+    /// This function seeds the choice among equally good moves, so the
+    /// machine plays the same game again for the same seed
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    /// This function changes the glyphs the marks are drawn with
+    pub fn set_symbols(&mut self, symbols: Symbols) {
+        self.symbols = symbols;
+    }
+
+    pub fn symbols(&self) -> Symbols {
+        self.symbols
+    }
+
+    /// This function names the players of the sides, whose marks are drawn
+    /// in their colors. A side nobody plays is drawn in its own color.
+    pub fn set_players(&mut self, players: Vec<PlayerInfo>) {
+        self.players = players;
+    }
+
+    pub fn players(&self) -> &[PlayerInfo] {
+        &self.players
+    }
+
+    /// This function lets `observer` know about every move, the end of the
+    /// game and every search of the machine from now on. `reset` keeps the
+    /// observers, a clone of the game drops them, `Match::next_game` hands
+    /// them on to the next game.
+    pub fn add_observer(&mut self, observer: Arc<dyn Observer>) {
+        self.observers.0.push(observer);
+    }
+
+    pub fn observers(&self) -> &[Arc<dyn Observer>] {
+        &self.observers.0
+    }
+
+    /// This function tells the observers that the game is over
+    fn game_over(&self) {
+        for observer in &self.observers.0 {
+            observer.on_game_over(self);
+        }
+    }
+
+    /// This function changes how the board is drawn
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    /// This function captures the current game state
+    pub fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            board: self.board.clone(),
+            win_len: self.win_len,
+            player_char: self.player_char,
+            machine_char: self.machine_char,
+            symbols: self.symbols,
+            turn: self.turn(),
+            history: self.moves().to_vec(),
+            misere: self.misere,
+            handicap: self.handicap.clone(),
+        }
+    }
+
+    /// This function restores a game from a previously captured state
+    pub fn from_snapshot(state: &GameSnapshot) -> Result<TicTacToe, GameError> {
+        let size = state.board.len();
+        if size == 0 || state.board.iter().any(|row| row.len() != size) {
+            return Err(GameError::InvalidInput("The board must be square".to_string()));
+        }
+
+        if size > MAX_SIZE {
+            return Err(GameError::InvalidInput(format!("The board may be at most {}x{}", MAX_SIZE, MAX_SIZE)));
+        }
+
+        if state.win_len == 0 || state.win_len > size {
+            return Err(GameError::InvalidInput(format!("Win length {} does not fit on a {}x{} board", state.win_len, size, size)));
+        }
+
+        if state.player_char == BoardChar::Empty || state.machine_char != state.player_char.to_opposite() {
+            return Err(GameError::InvalidInput("Player and machine need two different symbols".to_string()));
+        }
+
+        if state.symbols.x == state.symbols.o {
+            return Err(GameError::InvalidInput("The marks need two different glyphs".to_string()));
+        }
+
+        // Every handicap cell holds a mark of its own, so there are never
+        // more of them than marks of the player
+        for (i, m) in state.handicap.iter().enumerate() {
+            if state.board.get(m.row).and_then(|row| row.get(m.col)) != Some(&state.player_char) {
+                return Err(GameError::InvalidInput(format!("The handicap mark on {} is not on the board", m)));
+            }
+            if state.handicap[..i].contains(m) {
+                return Err(GameError::InvalidInput(format!("The handicap mark on {} is given twice", m)));
+            }
+        }
+
+        // The handicap marks are no moves, so they are left out
+        let mut moves = state.board.clone();
+        for m in &state.handicap {
+            moves[m.row][m.col] = BoardChar::Empty;
+        }
+
+        let mut game = TicTacToe::with_size(state.player_char, size, state.win_len);
+        game.symbols = state.symbols;
+        game.misere = state.misere;
+
+        // The side that opened has one mark more, unless it is its turn
+        // again. The handicap marks are no moves.
+        let (mut x, mut o) = (TicTacToe::count_in(&state.board, BoardChar::X), TicTacToe::count_in(&state.board, BoardChar::O));
+        match state.player_char {
+            BoardChar::X => x = x.saturating_sub(state.handicap.len()),
+            _ => o = o.saturating_sub(state.handicap.len()),
+        }
+        game.first = match x.cmp(&o) {
+            cmp::Ordering::Equal if state.turn != BoardChar::Empty => state.turn,
+            cmp::Ordering::Less => BoardChar::O,
+            _ => BoardChar::X,
+        };
+        if let Some(p) = state.history.first() {
+            game.first = p.mark;
+        }
+        moves.validate(state.win_len, game.first, state.turn)?;
+
+        for m in &state.handicap {
+            game.set(m.row, m.col, state.player_char);
+        }
+        game.handicap = state.handicap.clone();
+
+        if !state.history.is_empty() {
+            for p in &state.history {
+                game.place(&p.at, p.mark, p.time)
+                    .map_err(|e| GameError::InvalidInput(format!("The move {} in the history is not possible, {}", p.at, e)))?;
+            }
+
+            if game.board != state.board {
+                return Err(GameError::InvalidInput("The history does not lead to the board".to_string()));
+            }
+        }
+        game.board = state.board.clone();
+        game.bits = Bitboard::from_board(&game.board, &game.lines);
+
+        Ok(game)
+    }
+
+    /// This function writes the position and the side to move like
+    /// `XO./.X./..O X`, see `Notation`
+    pub fn to_notation(&self) -> String {
+        self.board.to_notation(self.turn())
+    }
+
+    /// This function serializes the game state as JSON
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        self.snapshot().to_json().to_string()
+    }
+
+    /// This function restores a game from its JSON representation
+    #[cfg(feature = "serde")]
+    pub fn from_json(s: &str) -> Result<TicTacToe, GameError> {
+        let state = GameSnapshot::from_json(&json::parse(s)?)?;
+
+        TicTacToe::from_snapshot(&state)
+    }
+
+    /// This function returns the symbol that moves next
+    pub fn turn(&self) -> BoardChar {
+        let second = self.first.to_opposite();
+        let moves = |c: BoardChar| match c == self.player_char {
+            true => self.count(c).saturating_sub(self.handicap.len()),
+            false => self.count(c),
+        };
+
+        if moves(self.first) > moves(second) {
+            second
+        } else {
+            self.first
+        }
+    }
+
+    /// This function returns how many cells hold `c`
+    fn count(&self, c: BoardChar) -> usize {
+        match &self.bits {
+            Some(bits) => bits.count(c),
+            None => TicTacToe::count_in(&self.board, c),
+        }
+    }
+
+    fn count_in(board: &Board, c: BoardChar) -> usize {
+        board.iter().flatten().filter(|&&cell| cell == c).count()
+    }
+
+    /// This function returns the symbol that opens the game
+    pub fn first(&self) -> BoardChar {
+        self.first
+    }
+
+    /// This function decides which symbol opens the game, the player by default
+    pub fn set_first(&mut self, c: BoardChar) {
+        if c != BoardChar::Empty {
+            self.first = c;
+        }
+    }
+
+    /// This function clears the board for a new game, the chosen symbols
+    /// and board settings are kept
+    pub fn reset(&mut self) {
+        for row in self.board.iter_mut() {
+            for cell in row.iter_mut() {
+                *cell = BoardChar::Empty;
+            }
+        }
+        if let Some(bits) = &mut self.bits {
+            bits.clear();
+        }
+
+        self.history.clear();
+        self.ending = None;
+        self.handicap.clear();
+    }
+
+    /// This function gives the player a mark on each of `cells` before the
+    /// game starts, on an empty board. The marks are no moves, they cannot
+    /// be taken back, and the machine moves next.
+    pub fn set_handicap(&mut self, cells: &[Move]) -> Result<(), GameError> {
+        if !self.is_empty() {
+            return Err(GameError::InvalidInput("A handicap is only given on an empty board".to_string()));
+        }
+        if cells.len() >= self.win_len {
+            return Err(GameError::InvalidInput(format!("A handicap must be less than {} marks", self.win_len)));
+        }
+        for (i, m) in cells.iter().enumerate() {
+            if !self.is_legal(m) || cells[..i].contains(m) {
+                return Err(GameError::InvalidInput(format!("The handicap mark on {} is not possible", m)));
+            }
+        }
+
+        for m in cells {
+            self.set(m.row, m.col, self.player_char);
+        }
+        self.handicap = cells.to_vec();
+        self.first = self.machine_char;
+
+        Ok(())
+    }
+
+    /// This function returns the cells of the player's handicap marks
+    pub fn handicap(&self) -> &[Move] {
+        &self.handicap
+    }
+
+    /// This function takes back the last move and returns it
+    pub fn undo(&mut self) -> Option<Placement> {
+        let placement = self.history.undo()?;
+        self.set(placement.at.row, placement.at.col, BoardChar::Empty);
+
+        Some(placement)
+    }
+
+    /// This function plays the last move taken back by `undo` again and returns it
+    pub fn redo(&mut self) -> Option<Placement> {
+        let placement = self.history.redo()?;
+        self.set(placement.at.row, placement.at.col, placement.mark);
+
+        Some(placement)
+    }
+
+    /// This function returns the moves played so far, oldest first
+    pub fn moves(&self) -> &[Placement] {
+        self.history.played()
+    }
+
+    /// This function puts `c` on a cell of both representations of the board
+    fn set(&mut self, row: usize, col: usize, c: BoardChar) {
+        self.board[row][col] = c;
+        if let Some(bits) = &mut self.bits {
+            bits.set(row, col, c);
+        }
+    }
+
+    /// This function collects every horizontal, vertical and diagonal run
+    /// of `win_len` cells on a `size` x `size` board
+    pub fn runs(size: usize, win_len: usize) -> Vec<Vec<(usize, usize)>> {
+        let last = size - win_len;
+        let mut runs = Vec::new();
+
+        for row in 0..size {
+            for col in 0..size {
+                // Rows
+                if col <= last {
+                    runs.push((0..win_len).map(|k| (row, col + k)).collect());
+                }
+                // Columns
+                if row <= last {
+                    runs.push((0..win_len).map(|k| (row + k, col)).collect());
+                }
+                // Diagonals
+                if row <= last && col <= last {
+                    runs.push((0..win_len).map(|k| (row + k, col + k)).collect());
+                }
+                // Anti-diagonals
+                if row <= last && col + 1 >= win_len {
+                    runs.push((0..win_len).map(|k| (row + k, col - k)).collect());
+                }
+            }
+        }
+
+        runs
+    }
+
+    /// This function returns where the game stands
+    pub fn state(&self) -> GameState {
+        if let Some(winner) = self.winner() {
+            let line = self.completed_line().map_or_else(Vec::new, |line| {
+                line.cells.iter().map(|&(row, col)| Move { row, col }).collect()
+            });
+            GameState::Won { winner, line }
+        } else if self.is_game_over() {
+            GameState::Draw
+        } else {
+            GameState::InProgress { turn: self.turn() }
+        }
+    }
+
+    /// This function returns true if game is over
+    pub fn is_game_over(&self) -> bool {
+        self.ending.is_some() || !self.has_moves() || self.player_won() || self.machine_won()
+    }
+
+    /// This function ends the game as lost on time for `c`, whatever the
+    /// board says
+    pub fn forfeit(&mut self, c: BoardChar) {
+        if c != BoardChar::Empty && !self.is_game_over() {
+            self.ending = Some(Ending::OutOfTime(c));
+            self.game_over();
+        }
+    }
+
+    /// This function ends the game as lost for `c`, who gave up
+    pub fn resign(&mut self, c: BoardChar) {
+        if c != BoardChar::Empty && !self.is_game_over() {
+            self.ending = Some(Ending::Resigned(c));
+            self.game_over();
+        }
+    }
+
+    /// This function offers a draw on behalf of `c`. The other side accepts
+    /// if it cannot force a win within the depth limit of the game, the
+    /// game is then drawn. It returns true if the draw was accepted.
+    pub fn offer_draw(&mut self, c: BoardChar) -> bool {
+        if c == BoardChar::Empty || self.is_game_over() || self.can_force_win(c.to_opposite()) {
+            return false;
+        }
+
+        self.ending = Some(Ending::DrawAgreed);
+        self.game_over();
+        true
+    }
+
+    /// This function ends the game the way `ending` says, e.g. when a
+    /// record of it is read. A game that is over already is left alone.
+    pub fn end(&mut self, ending: Ending) {
+        if !self.is_game_over() {
+            self.ending = Some(ending);
+            self.game_over();
+        }
+    }
+
+    /// This function returns true if `c` wins against any defence, as far
+    /// as the search sees within the depth limit of the game
+    pub fn can_force_win(&mut self, c: BoardChar) -> bool {
+        if self.is_game_over() {
+            return self.winner() == Some(c);
+        }
+
+        let mut search = Search::new(self.max_depth, None);
+        let turn = self.turn();
+        if turn == c {
+            self.find_best_move_for(&mut search, c).is_some_and(|(_, score)| score > HEURISTIC_BOUND)
+        } else {
+            let scores = self.score_moves(&mut search, turn);
+            !scores.is_empty() && scores.iter().all(|&(_, score)| score < -HEURISTIC_BOUND)
+        }
+    }
+
+    /// This function lets the sides trade places under the pie rule: the
+    /// player takes over the mark that opened the game and the machine gets
+    /// the other one, which is to move. It is only possible right after the
+    /// first move.
+    pub fn swap_sides(&mut self) -> Result<(), GameError> {
+        if self.moves().len() != 1 || self.is_game_over() {
+            return Err(GameError::InvalidInput("The sides can only be swapped right after the first move".to_string()));
+        }
+
+        std::mem::swap(&mut self.player_char, &mut self.machine_char);
+        Ok(())
+    }
+
+    /// This function returns true if the side to move after the first move
+    /// does better by swapping sides under the pie rule than by answering
+    /// the move, that is if the opener stands better. The search stays
+    /// within the depth limit of the game and `deadline`, a search that runs
+    /// out of time keeps the sides.
+    pub fn should_swap(&mut self, deadline: Option<Instant>) -> bool {
+        if self.moves().len() != 1 || self.is_game_over() {
+            return false;
+        }
+
+        let mut search = Search::new(self.max_depth, deadline);
+        let turn = self.turn();
+        match self.find_best_move_for(&mut search, turn) {
+            Some((_, score)) => !search.aborted && score < 0,
+            None => false,
+        }
+    }
+
+    /// This function returns the side that lost on time or resigned, if any
+    pub fn forfeited(&self) -> Option<BoardChar> {
+        match self.ending {
+            Some(Ending::OutOfTime(c)) | Some(Ending::Resigned(c)) => Some(c),
+            _ => None,
+        }
+    }
+
+    /// This function returns how the game ended if neither a line nor the
+    /// full board ended it
+    pub fn ending(&self) -> Option<Ending> {
+        self.ending
+    }
+    /// This function returns true if there are moves remaining on the board.
+    /// It returns false if there are no moves left to play.    
+    pub fn has_moves(&self) -> bool {
+        match &self.bits {
+            Some(bits) => !bits.is_full(),
+            None => self.empty_cells().next().is_some(),
+        }
+    }
+    /// This function lists the cells the search tries. On boards of
+    /// `NEAR_MOVES_SIZE` and more only empty cells close to a mark are worth
+    /// a look, on an empty board the center.
+    fn candidate_cells(&self) -> Vec<(usize, usize)> {
+        let size = self.board.len();
+        if size < NEAR_MOVES_SIZE {
+            return self.empty_cells().collect();
+        }
+        if self.is_empty() {
+            return vec![(size / 2, size / 2)];
+        }
+
+        let around = |i: usize| i.saturating_sub(NEAR_DISTANCE)..cmp::min(size, i + NEAR_DISTANCE + 1);
+        self.empty_cells()
+            .filter(|&(row, col)| around(row).any(|r| around(col).any(|c| self.board[r][c] != BoardChar::Empty)))
+            .collect()
+    }
+    /// This function iterates over the (row, col) coordinates of all empty cells
+    pub fn empty_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.board.iter().enumerate().flat_map(|(row, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(_, &cell)| cell == BoardChar::Empty)
+                .map(move |(col, _)| (row, col))
+        })
+    }
+    /// This function makes the player's move
+    pub fn player_move(&mut self, m: &Move) -> Result<MoveOutcome, GameError> {
+        self.do_move(m, self.player_char)
+    }
+    /// This function makes the machine's move, thinking no longer than `budget`.
+    /// It returns the chosen move and the search depth that was completed.
+    pub fn machine_move(&mut self, budget: Duration) -> Result<(Move, usize), GameError> {
+        self.machine_move_until(Some(Instant::now() + budget))
+    }
+
+    /// This function works like `machine_move` but thinks until `deadline`.
+    /// Without a deadline the clock is never read, only the search depth
+    /// limits the search, which suits targets without a clock like wasm.
+    pub fn machine_move_until(&mut self, deadline: Option<Instant>) -> Result<(Move, usize), GameError> {
+        let (m, depth) = self.choose_move(deadline).ok_or(GameError::GameAlreadyOver)?;
+        self.do_move(&m, self.machine_char)?;
+
+        Ok((m, depth))
+    }
+
+    /// This function returns the move the machine settings pick for the
+    /// side whose turn it is, searching until `deadline`, and the search
+    /// depth that was completed. The board stays as it is.
+    pub fn choose_move(&mut self, deadline: Option<Instant>) -> Option<(Move, usize)> {
+        self.choose_move_with_info(deadline).map(|(m, info)| (m, info.depth))
+    }
+
+    /// This function picks the move of `choose_move`
+    fn pick_move(&mut self, deadline: Option<Instant>) -> Option<(Move, usize)> {
+        if let Some(p) = self.ai.blunder {
+            if self.rng.chance(p) {
+                if let Some(m) = self.blunder_move(deadline) {
+                    ai_debug!("blunders with {}", m);
+                    return Some((m, 0));
+                }
+            }
+        }
+
+        match self.ai.difficulty {
+            Difficulty::Easy => self.random_move().map(|m| (m, 0)),
+            Difficulty::Medium if self.rng.below(100) < MEDIUM_RANDOM_PERCENT => {
+                self.random_move().map(|m| (m, 0))
+            }
+            Difficulty::Hard if self.book && !self.misere => match self.book_move() {
+                Some(m) => Some((m, 0)),
+                None => self.ai.engine(&mut self.rng, self.max_depth).best_move(self, deadline),
+            },
+            Difficulty::Medium | Difficulty::Hard => {
+                let mut engine = self.ai.engine(&mut self.rng, self.max_depth);
+                engine.best_move(self, deadline)
+            }
+        }
+    }
+
+    /// This function works like `choose_move` but also tells what the search
+    /// did. A move that was not searched, from the opening book or picked at
+    /// random, comes with depth 0, and only the minimax search counts
+    /// positions.
+    pub fn choose_move_with_info(&mut self, deadline: Option<Instant>) -> Option<(Move, SearchInfo)> {
+        if self.is_game_over() {
+            return None;
+        }
+
+        for observer in &self.observers.0 {
+            observer.on_search_start(self);
+        }
+        let started = Instant::now();
+        self.searched = SearchInfo::default();
+        let (m, depth) = self.pick_move(deadline)?;
+
+        let mut info = std::mem::take(&mut self.searched);
+        info.depth = depth;
+        info.elapsed = started.elapsed();
+        if info.principal_variation.first() != Some(&m) {
+            info.principal_variation = vec![m];
+        }
+        for observer in &self.observers.0 {
+            observer.on_search_stop(self, &info);
+        }
+
+        Some((m, info))
+    }
+
+    /// This function returns a legal move picked at random among those that
+    /// score worse than the best, if there are any
+    fn blunder_move(&mut self, deadline: Option<Instant>) -> Option<Move> {
+        let c = self.turn();
+        let scores = self.score_moves(&mut Search::new(self.max_depth, deadline), c);
+        let best = scores.iter().map(|&(_, score)| score).max()?;
+        let worse: Vec<Move> = scores.into_iter().filter(|&(_, score)| score < best).map(|(m, _)| m).collect();
+        if worse.is_empty() {
+            return None;
+        }
+
+        let pick = self.rng.below(worse.len());
+        Some(worse[pick])
+    }
+
+    /// This function returns one of the replies of the opening book to the
+    /// position, picked at random
+    fn book_move(&mut self) -> Option<Move> {
+        let mut replies = book::lookup(&self.board, self.turn(), self.win_len);
+        if replies.is_empty() {
+            return None;
+        }
+
+        let pick = self.rng.below(replies.len());
+        Some(replies.swap_remove(pick))
+    }
+
+    /// This function suggests the best move for the side whose turn it is,
+    /// thinking no longer than `budget`. The board stays as it is.
+    pub fn hint(&mut self, budget: Duration) -> Option<Move> {
+        if self.is_game_over() {
+            return None;
+        }
+
+        let max_depth = self.max_depth;
+        self.iterative_deepening(Some(Instant::now() + budget), max_depth)
+            .map(|(m, _)| m)
+    }
+
+    /// This function searches the position to the end of the game, however
+    /// long that takes, and returns the value of every legal move of the
+    /// side to move. The depth limit of the game is left out. It returns
+    /// nothing if the game is over.
+    pub fn solve(&mut self) -> Option<Solution> {
+        if self.is_game_over() {
+            return None;
+        }
+
+        let mut search = Search::new(None, None);
+        let mut scores = self.score_moves(&mut search, self.turn());
+        scores.sort_by_key(|&(_, score)| cmp::Reverse(score));
+
+        let moves: Vec<(Move, Evaluation)> = scores
+            .into_iter()
+            .map(|(m, score)| (m, Evaluation::from_score(score, true)))
+            .collect();
+
+        Some(Solution {
+            value: moves.first()?.1,
+            moves,
+            nodes: search.nodes,
+            remembered: search.table.len(),
+        })
+    }
+
+    /// This function rates every legal move of the side to move, the best
+    /// first. Boards the search cannot finish are rated by the heuristic at
+    /// the depth limit of the game.
+    pub fn analyze(&mut self) -> Vec<(Move, Evaluation)> {
+        if self.is_game_over() {
+            return Vec::new();
+        }
+
+        let max_depth = self.max_depth;
+        let exact = max_depth.is_none_or(|max| max >= self.empty_cells().count());
+        let mut scores = self.score_moves(&mut Search::new(max_depth, None), self.turn());
+        scores.sort_by_key(|&(_, score)| cmp::Reverse(score));
+
+        scores
+            .into_iter()
+            .map(|(m, score)| (m, Evaluation::from_score(score, exact)))
+            .collect()
+    }
+
+    /// This function returns the cells where `c` would complete a run with
+    /// its next mark, each with the cells of that run
+    pub fn threats(&self, c: BoardChar) -> Vec<(Move, Vec<Move>)> {
+        let mut threats = Vec::new();
+
+        for line in &self.lines {
+            let marks = line.iter().filter(|&&(row, col)| self.board[row][col] == c).count();
+            let mut empty = line.iter().filter(|&&(row, col)| self.board[row][col] == BoardChar::Empty);
+
+            if let (Some(&(row, col)), None, true) = (empty.next(), empty.next(), marks + 1 == line.len()) {
+                let run = line.iter().map(|&(row, col)| Move { row, col }).collect();
+                threats.push((Move { row, col }, run));
+            }
+        }
+
+        threats
+    }
+
+    /// This function explains `m` before the side to move plays it: how it
+    /// compares to the best move and which threats it makes, misses or
+    /// leaves open. In the misère game only the comparison is made.
+    pub fn lesson(&mut self, m: &Move) -> Option<Lesson> {
+        if self.is_game_over() || !self.is_legal(m) {
+            return None;
+        }
+
+        let c = self.turn();
+        let exact = self.max_depth.is_none_or(|max| max >= self.empty_cells().count());
+        let mut scores = self.score_moves(&mut Search::new(self.max_depth, None), c);
+        scores.sort_by_key(|&(_, score)| cmp::Reverse(score));
+
+        let played = scores.iter().find(|(n, _)| n == m)?.1;
+        let (best, best_score) = scores[0];
+        let mut lesson = Lesson {
+            played: (*m, Evaluation::from_score(played, exact)),
+            better: (best_score > played).then(|| (best, Evaluation::from_score(best_score, exact))),
+            run_len: self.win_len - 1,
+            missed_win: None,
+            ignored: None,
+            blocked: None,
+            created: Vec::new(),
+        };
+        if self.misere {
+            return Some(lesson);
+        }
+
+        let wins = self.threats(c);
+        let wins_now = wins.iter().any(|(cell, _)| cell == m);
+        if !wins_now {
+            lesson.missed_win = wins.into_iter().next();
+        }
+
+        let dangers = self.threats(c.to_opposite());
+        lesson.blocked = dangers.iter().find(|(cell, _)| cell == m).map(|(_, run)| run.clone());
+        if !wins_now && lesson.blocked.is_none() && lesson.missed_win.is_none() {
+            lesson.ignored = dangers.into_iter().next();
+        }
+
+        let mut after = self.clone();
+        after.set(m.row, m.col, c);
+        if after.winner().is_none() {
+            for (cell, run) in after.threats(c) {
+                if !lesson.created.iter().any(|(other, _)| *other == cell) {
+                    lesson.created.push((cell, run));
+                }
+            }
+        }
+
+        Some(lesson)
+    }
+
+    /// This function places the mark of the side whose turn it is, which
+    /// lets two people share the board without a machine
+    pub fn play_move(&mut self, m: &Move) -> Result<MoveOutcome, GameError> {
+        self.do_move(m, self.turn())
+    }
+
+    /// This function places the mark of the side whose turn it is like
+    /// `play_move` and records that it took `time` to find the move
+    pub fn play_timed(&mut self, m: &Move, time: Duration) -> Result<MoveOutcome, GameError> {
+        self.place(m, self.turn(), Some(time))
+    }
+
+    /// This function returns true if `m` is on the board and its cell is
+    /// still empty. It never changes the board.
+    pub fn is_legal(&self, m: &Move) -> bool {
+        self.board
+            .get(m.row)
+            .and_then(|row| row.get(m.col))
+            .is_some_and(|&cell| cell == BoardChar::Empty)
+    }
+
+    /// This function returns why `m` cannot be played, if it cannot. It
+    /// never changes the board.
+    pub fn check_move(&self, m: &Move) -> Result<(), GameError> {
+        if self.is_game_over() {
+            return Err(GameError::GameAlreadyOver);
+        }
+
+        if !self.is_legal(m) {
+            if m.row >= self.board.len() || m.col >= self.board.len() {
+                return Err(GameError::OutOfBounds);
+            }
+
+            return Err(GameError::CellOccupied);
+        }
+
+        Ok(())
+    }
+
+    /// This function places `c` on the cell of `m`. Moves after the game is
+    /// over, out of turn, outside the board or onto an occupied cell are
+    /// refused and leave the board unchanged.
+    pub fn do_move(&mut self, m: &Move, c: BoardChar) -> Result<MoveOutcome, GameError> {
+        self.place(m, c, None)
+    }
+
+    /// This function places `c` on the cell of `m` like `do_move` and
+    /// records the time spent on the move with it
+    fn place(&mut self, m: &Move, c: BoardChar, time: Option<Duration>) -> Result<MoveOutcome, GameError> {
+        self.check_move(m)?;
+        if c != self.turn() {
+            return Err(GameError::OutOfTurn);
+        }
+
+        let placement = Placement { at: *m, mark: c, time };
+        self.set(m.row, m.col, c);
+        self.history.record(placement);
+        for observer in &self.observers.0 {
+            observer.on_move(self, &placement);
+        }
+        if self.is_game_over() {
+            self.game_over();
+        }
+
+        Ok(MoveOutcome {
+            placement,
+            state: self.state(),
+        })
+    }
+    /// This function returns the symbol that completed a line, if any
+    pub fn winner(&self) -> Option<BoardChar> {
+        if let Some(c) = self.forfeited() {
+            Some(c.to_opposite())
+        } else if self.player_won() {
+            Some(self.player_char)
+        } else if self.machine_won() {
+            Some(self.machine_char)
+        } else {
+            None
+        }
+    }
+    pub fn player_char(&self) -> BoardChar {
+        self.player_char
+    }
+    pub fn machine_char(&self) -> BoardChar {
+        self.machine_char
+    }
+    /// This function returns true if player won, in the misère game when
+    /// the machine completed a line
+    pub fn player_won(&self) -> bool {
+        self.evaluate(if self.misere { self.machine_char } else { self.player_char }).is_some()
+    }
+    /// This function returns true if machine won, in the misère game when
+    /// the player completed a line
+    pub fn machine_won(&self) -> bool {
+        self.evaluate(if self.misere { self.player_char } else { self.machine_char }).is_some()
+    }
+    /// This function returns a random empty cell
+    fn random_move(&mut self) -> Option<Move> {
+        let cells: Vec<_> = self.empty_cells().collect();
+        if cells.is_empty() {
+            return None;
+        }
+
+        let (row, col) = cells[self.rng.below(cells.len())];
+        Some(Move { row, col })
+    }
+
+    /// This function searches depth 1, 2, ... until either the whole game
+    /// tree (or `max_depth`) is covered or the deadline passes, and returns
+    /// the best move of the side to move found by the deepest search that
+    /// finished, together with its depth
+    pub fn iterative_deepening(&mut self, deadline: Option<Instant>, max_depth: Option<usize>) -> Option<(Move, usize)> {
+        let empty = self.empty_cells().count();
+        let full_depth = max_depth.map_or(empty, |max| cmp::min(max, empty));
+        let mut best = None;
+
+        for depth in 1..=full_depth {
+            // The first iteration is cheap and always completes, so there is
+            // a move to play even when the budget is tiny
+            let limit = if depth == 1 { None } else { deadline };
+            let mut search = Search::new(Some(depth), limit);
+            if depth > 1 {
+                search.progress = self.progress.clone();
+            }
+            ai_debug!("iterative deepening to depth {}", depth);
+            let m = self.find_best_move_for(&mut search, self.turn()).map(|(m, _)| m);
+            self.searched.nodes += search.nodes;
+            self.searched.table_hits += search.table.hits;
+
+            // Out of time, keep the result of the previous depth
+            if search.aborted {
+                break;
+            }
+
+            match m {
+                Some(m) => {
+                    best = Some((m, depth));
+                    self.searched.principal_variation = self.principal_variation(&search.table, m);
+                    if let Some(progress) = &self.progress {
+                        progress.finished(depth, m);
+                    }
+                }
+                None => break,
+            }
+        }
+
+        best
+    }
+
+    /// This function returns `first`, the move of the side to move, and the
+    /// moves that follow it if both sides play into the positions `table`
+    /// knows to be worth exactly as much as the position after `first`
+    fn principal_variation(&mut self, table: &TranspositionTable, first: Move) -> Vec<Move> {
+        let mut side = self.turn();
+        let mut line = vec![first];
+        self.set(first.row, first.col, side);
+
+        if let Some(score) = self.remembered(table) {
+            loop {
+                side = side.to_opposite();
+                let cells: Vec<_> = self.empty_cells().collect();
+                let next = cells.into_iter().find(|&(i, j)| {
+                    self.set(i, j, side);
+                    let remembered = self.remembered(table);
+                    self.set(i, j, BoardChar::Empty);
+                    remembered == Some(score)
+                });
+
+                match next {
+                    Some((row, col)) => {
+                        self.set(row, col, side);
+                        line.push(Move { row, col });
+                    }
+                    None => break,
+                }
+            }
+        }
+
+        for m in &line {
+            self.set(m.row, m.col, BoardChar::Empty);
+        }
+
+        line
+    }
+
+    /// This function returns the exact score `table` holds for the board
+    fn remembered(&self, table: &TranspositionTable) -> Option<i16> {
+        let entry = TranspositionTable::key(&self.board).and_then(|key| table.peek(key))?;
+
+        Some(entry.score).filter(|_| entry.bound == Bound::Exact)
+    }
+
+    /// This function will return the best possible move for machine within
+    /// the limits of `search`. If the deadline passes the search is aborted.
+    /// Among equally good moves one is picked at random.
+    pub fn find_best_move(&mut self, search: &mut Search) -> Option<Move> {
+        let machine_char = self.machine_char;
+
+        self.find_best_move_for(search, machine_char).map(|(m, _)| m)
+    }
+
+    /// This function works like `find_best_move` but searches the best move
+    /// of `c`, which may be either side. It also returns the score of the
+    /// move from the point of view of `c`.
+    pub fn find_best_move_for(&mut self, search: &mut Search, c: BoardChar) -> Option<(Move, i16)> {
+        // The search always maximizes for the machine, so let `c` take the
+        // machine's seat for its duration
+        let swap = c != self.machine_char;
+        if swap {
+            std::mem::swap(&mut self.player_char, &mut self.machine_char);
+        }
+
+        let best = self.search_root(search);
+
+        if swap {
+            std::mem::swap(&mut self.player_char, &mut self.machine_char);
+        }
+
+        best
+    }
+
+    /// This function scores every legal move of `c` within the limits of
+    /// `search`, from the point of view of `c` like `find_best_move_for`.
+    /// Unlike the search for the best move it needs the exact score of each
+    /// move, so nothing is cut off at the root. It returns nothing if the
+    /// deadline passes.
+    pub fn score_moves(&mut self, search: &mut Search, c: BoardChar) -> Vec<(Move, i16)> {
+        let swap = c != self.machine_char;
+        if swap {
+            std::mem::swap(&mut self.player_char, &mut self.machine_char);
+        }
+
+        let mut scores = Vec::new();
+        let cells: Vec<_> = self.empty_cells().collect();
+        for (i, j) in cells {
+            self.set(i, j, self.machine_char);
+            let score = self.minimax(search, self.player_char, 1, i16::MIN, i16::MAX);
+            self.set(i, j, BoardChar::Empty);
+
+            if search.aborted {
+                scores.clear();
+                break;
+            }
+            scores.push((Move { row: i, col: j }, score));
+        }
+
+        if swap {
+            std::mem::swap(&mut self.player_char, &mut self.machine_char);
+        }
+
+        scores
+    }
+
+    /// This function scores `cells` one after the other for the machine.
+    /// Only moves at least as good as the best one so far get an exact
+    /// score, everything worse may be cut off by the pruning and scores
+    /// lower than the best one. It returns nothing if the search is aborted.
+    fn score_root(&mut self, search: &mut Search, cells: &[(usize, usize)]) -> Option<Vec<i16>> {
+        let mut best_val = i16::MIN;
+        let mut scores = Vec::with_capacity(cells.len());
+
+        for &(i, j) in cells {
+            self.set(i, j, self.machine_char);
+            let alpha = best_val.saturating_sub(1);
+            let move_val = self.minimax(search, self.player_char, 1, alpha, i16::MAX);
+            self.set(i, j, BoardChar::Empty);
+
+            if search.aborted {
+                return None;
+            }
+
+            ai_debug!("candidate {} scores {}", Move { row: i, col: j }, move_val);
+            best_val = best_val.max(move_val);
+            scores.push(move_val);
+        }
+
+        Some(scores)
+    }
+
+    /// This function scores `cells` like `score_root` with every thread
+    /// taking every `threads`-th of them, each with a copy of the game and
+    /// a search of its own. The nodes and table hits of all threads count for
+    /// `search`.
+    fn score_root_parallel(&self, search: &mut Search, cells: &[(usize, usize)], threads: usize) -> Option<Vec<i16>> {
+        let shares: Vec<Vec<(usize, usize)>> = (0..threads).map(|t| cells.iter().copied().skip(t).step_by(threads).collect()).collect();
+
+        let results: Vec<(Option<Vec<i16>>, u64, u64)> = thread::scope(|scope| {
+            let handles: Vec<_> = shares
+                .iter()
+                .map(|share| {
+                    let mut game = self.clone();
+                    let mut own = Search::new(search.max_depth, search.deadline);
+                    own.progress = search.progress.clone();
+                    scope.spawn(move || (game.score_root(&mut own, share), own.nodes, own.table.hits))
+                })
+                .collect();
+
+            handles.into_iter().map(|handle| handle.join().expect("a search thread panicked")).collect()
+        });
+
+        // The scores are put back in the order of `cells`, so that the
+        // result does not depend on which thread finished first
+        let mut scores = vec![0; cells.len()];
+        for (t, (share, nodes, hits)) in results.into_iter().enumerate() {
+            search.nodes += nodes;
+            search.table.hits += hits;
+            match share {
+                Some(share) => {
+                    for (k, score) in share.into_iter().enumerate() {
+                        scores[t + k * threads] = score;
+                    }
+                }
+                None => search.aborted = true,
+            }
+        }
+
+        if search.aborted {
+            None
+        } else {
+            Some(scores)
+        }
+    }
+
+    fn search_root(&mut self, search: &mut Search) -> Option<(Move, i16)> {
+        let cells = self.candidate_cells();
+        let threads = self.threads.min(cells.len());
+        let scores = if threads > 1 {
+            self.score_root_parallel(search, &cells, threads)
+        } else {
+            self.score_root(search, &cells)
+        };
+        let scores = match scores {
+            Some(scores) => scores,
+            None => {
+                ai_debug!("search aborted at the deadline after {} nodes", search.nodes);
+                return None;
+            }
+        };
+
+        // A score cut off by the pruning is always below the best one, so
+        // the moves as good as the best one are the same however the cells
+        // were shared out
+        let best_val = scores.iter().copied().max().unwrap_or(i16::MIN);
+        let mut best_moves: Vec<Move> = cells
+            .iter()
+            .zip(&scores)
+            .filter(|&(_, &score)| score == best_val)
+            .map(|(&(row, col), _)| Move { row, col })
+            .collect();
+
+        if best_moves.is_empty() {
+            return None;
+        }
+
+        let pick = self.rng.below(best_moves.len());
+        let best = best_moves.swap_remove(pick);
+        ai_debug!(
+            "selected {} with score {} out of {} equally good moves, {} nodes searched",
+            best,
+            best_val,
+            best_moves.len() + 1,
+            search.nodes
+        );
+
+        Some((best, best_val))
+    }
+
+    /// This function returns the run `c` completed, if any
+    pub fn evaluate(&self, c: BoardChar) -> Option<WinLine<'_>> {
+        let cells = match &self.bits {
+            Some(bits) => bits.winning_line(c).map(|i| &self.lines[i]),
+            None => self
+                .lines
+                .iter()
+                .find(|line| line.iter().all(|&(row, col)| self.board[row][col] == c)),
+        };
+
+        cells.map(|cells| WinLine { mark: c, cells })
+    }
+
+    /// This function returns the run that decided the game, in the misère
+    /// game the one the loser completed
+    pub fn completed_line(&self) -> Option<WinLine<'_>> {
+        self.evaluate(self.player_char)
+            .or_else(|| self.evaluate(self.machine_char))
+    }
+
+    /// This function returns the board as styled cells for a frontend to
+    /// draw: the glyphs and colors of the marks, the completed line and the
+    /// last move
+    pub fn render(&self) -> Grid {
+        let last = self.moves().last().map(|placement| placement.at);
+
+        render::render(&self.board, self.symbols, self.completed_line(), &self.players, last)
+    }
+
+    /// This function scores a position the search could not finish by the
+    /// runs of both sides, see `run_score`, or on boards won by long runs by
+    /// their threats, see `threat_score`. Positive values favour the
+    /// machine, negative ones the player. In the misère game open runs are
+    /// a danger to their owner, so the score changes its sign.
+    pub fn heuristic(&self) -> i16 {
+        let mut score = if self.win_len >= THREAT_WIN_LEN { self.threat_score() } else { self.run_score() };
+
+        if self.misere {
+            score = -score;
+        }
+
+        // A heuristic guess must never look as good as a real win
+        let bound = i32::from(HEURISTIC_BOUND);
+        score.clamp(-bound, bound) as i16
+    }
+
+    /// This function counts every run still open for only one side for
+    /// that side, the more marks it already holds the more it weighs (see
+    /// `line_weight`). A side with two or more cells that each win at once
+    /// has a fork, and every mark counts for every run it lies on.
+    fn run_score(&self) -> i32 {
+        let mut score: i32 = 0;
+        // The cells that complete a run, of the machine and of the player
+        let mut wins: [Vec<(usize, usize)>; 2] = [Vec::new(), Vec::new()];
+
+        for (i, line) in self.lines.iter().enumerate() {
+            let (machine, player) = match &self.bits {
+                Some(bits) => {
+                    let mask = bits.lines()[i];
+                    let count = |c| (bits.marks(c) & mask).count_ones() as usize;
+                    (count(self.machine_char), count(self.player_char))
+                }
+                None => {
+                    let count = |c| line.iter().filter(|&&(row, col)| self.board[row][col] == c).count();
+                    (count(self.machine_char), count(self.player_char))
+                }
+            };
+
+            if player == 0 && machine > 0 {
+                score += self.line_weight(machine);
+            } else if machine == 0 && player > 0 {
+                score -= self.line_weight(player);
+            }
+            score += REACH_WEIGHT * (machine as i32 - player as i32);
+
+            let side = match (machine, player) {
+                (m, 0) if m + 1 == self.win_len => Some(0),
+                (0, p) if p + 1 == self.win_len => Some(1),
+                _ => None,
+            };
+            let empty = line.iter().copied().find(|&(row, col)| self.board[row][col] == BoardChar::Empty);
+            if let (Some(side), Some(cell)) = (side, empty) {
+                if !wins[side].contains(&cell) {
+                    wins[side].push(cell);
+                }
+            }
+        }
+
+        if wins[0].len() >= 2 {
+            score += FORK_WEIGHT;
+        }
+        if wins[1].len() >= 2 {
+            score -= FORK_WEIGHT;
+        }
+
+        score
+    }
+
+    /// This function scores the unbroken runs of marks along the rows,
+    /// columns and diagonals by how far they are from a win and how many of
+    /// their ends are open: an open four cannot be stopped any more, a four
+    /// with one open end or an open three has to be answered at once.
+    fn threat_score(&self) -> i32 {
+        let size = self.board.len() as isize;
+        let mut score = 0;
+
+        for &(dr, dc) in &[(0, 1), (1, 0), (1, 1), (1, -1)] {
+            for (row, cells) in self.board.iter().enumerate() {
+                for (col, &c) in cells.iter().enumerate() {
+                    let at = |k: isize| {
+                        let (r, c) = (row as isize + dr * k, col as isize + dc * k);
+                        if r < 0 || c < 0 || r >= size || c >= size {
+                            None
+                        } else {
+                            Some(self.board[r as usize][c as usize])
+                        }
+                    };
+                    // Every run is counted once, from its first mark
+                    if c == BoardChar::Empty || at(-1) == Some(c) {
+                        continue;
+                    }
+
+                    let mut len = 1;
+                    while at(len) == Some(c) {
+                        len += 1;
+                    }
+                    let open = [at(-1), at(len)].iter().filter(|&&end| end == Some(BoardChar::Empty)).count();
+
+                    let short = self.win_len.saturating_sub(len as usize);
+                    let weight = match (short, open) {
+                        (1..=3, 2) => OPEN_RUN_WEIGHTS[short - 1],
+                        (1..=3, 1) => HALF_OPEN_RUN_WEIGHTS[short - 1],
+                        _ => 0,
+                    };
+                    if c == self.machine_char {
+                        score += weight;
+                    } else {
+                        score -= weight;
+                    }
+                }
+            }
+        }
+
+        score
+    }
+
+    /// This function returns how much an open run holding `marks` marks
+    /// weighs, a run a single mark short of winning counts twice as much
+    fn line_weight(&self, marks: usize) -> i32 {
+        let weight = 1 << (marks - 1);
+        if marks + 1 == self.win_len {
+            weight * 2
+        } else {
+            weight
+        }
+    }
+
+    // This is the minimax function with alpha-beta pruning. It considers
+    // all the possible ways the game can go and returns the value of the
+    // board. `alpha` is the score the machine is already sure of and `beta`
+    // the one the player is sure of, lines outside that window are not
+    // explored further. Once the deadline of the search passes it unwinds
+    // returning meaningless values.
+    fn minimax(&mut self, search: &mut Search, c: BoardChar, depth: usize, mut alpha: i16, mut beta: i16) -> i16 {
+        if search.visit() {
+            return 0;
+        }
+
+        // If Machine has won the game return his/her evaluated score,
+        // the sooner the win the better
+        if self.machine_won() {
+            return WIN_SCORE - depth as i16;
+        }
+
+        // If Player has won the game return his/her evaluated score,
+        // the later the loss the better
+        if self.player_won() {
+            return -WIN_SCORE + depth as i16;
+        }
+
+        if !self.has_moves() {
+            return 0;
+        }
+
+        // If the search is cut off, estimate the position instead
+        if search.max_depth.is_some_and(|max| depth >= max) {
+            return self.heuristic();
+        }
+
+        // A position seen before, maybe rotated or mirrored, may already be
+        // known well enough for this window
+        let key = TranspositionTable::key(&self.board);
+        let window = (alpha, beta);
+        if let Some(entry) = key.and_then(|key| search.table.get(key)) {
+            match entry.bound {
+                Bound::Exact => return entry.score,
+                Bound::Lower => alpha = cmp::max(alpha, entry.score),
+                Bound::Upper => beta = cmp::min(beta, entry.score),
+            }
+            if alpha >= beta {
+                return entry.score;
+            }
+        }
+
+        let mut best: i16 = if c == self.machine_char {
+            // If this maximizer's move
+            i16::MIN
+        } else {
+            // If this minimizer's move
+            i16::MAX
+        };
+
+        let cells = self.candidate_cells();
+        for (i, j) in cells {
+            // make the move
+            self.set(i, j, c);
+
+            // call minimax recursively
+            let next_best = self.minimax(search, c.to_opposite(), depth + 1, alpha, beta);
+
+            // undo the move
+            self.set(i, j, BoardChar::Empty);
+
+            if search.aborted {
+                return 0;
+            }
+
+            if c == self.machine_char {
+                // choose the maximum value
+                best = cmp::max(best, next_best);
+                alpha = cmp::max(alpha, best);
+            } else {
+                // choose the minimum value
+                best = cmp::min(best, next_best);
+                beta = cmp::min(beta, best);
+            }
+
+            // The other side already has a better alternative earlier in
+            // the tree, so it will never let the game get here
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if let Some(key) = key {
+            let bound = if best <= window.0 {
+                Bound::Upper
+            } else if best >= window.1 {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            search.table.insert(key, Entry { score: best, bound });
+        }
+
+        best
+    }
+}
+
+impl Game for TicTacToe {
+    type Move = Move;
+
+    fn legal_moves(&self) -> Vec<Move> {
+        if self.ending.is_some() || self.player_won() || self.machine_won() {
+            return Vec::new();
+        }
+
+        self.empty_cells().map(|(row, col)| Move { row, col }).collect()
+    }
+
+    fn play(&mut self, m: &Move) -> Result<(), GameError> {
+        self.play_move(m).map(|_| ())
+    }
+
+    fn turn(&self) -> BoardChar {
+        TicTacToe::turn(self)
+    }
+
+    fn winner(&self) -> Option<BoardChar> {
+        TicTacToe::winner(self)
+    }
+
+    fn is_game_over(&self) -> bool {
+        TicTacToe::is_game_over(self)
+    }
+
+    fn score(&self, me: BoardChar) -> i16 {
+        if me == self.machine_char {
+            self.heuristic()
+        } else {
+            -self.heuristic()
+        }
+    }
+}
+
+impl Table for TicTacToe {
+    fn player_char(&self) -> BoardChar {
+        TicTacToe::player_char(self)
+    }
+
+    fn machine_char(&self) -> BoardChar {
+        TicTacToe::machine_char(self)
+    }
+
+    fn glyph(&self, c: BoardChar) -> Option<char> {
+        Some(self.symbols.glyph(c))
+    }
+
+    fn set_first(&mut self, c: BoardChar) {
+        TicTacToe::set_first(self, c);
+    }
+
+    fn reset(&mut self) {
+        TicTacToe::reset(self);
+    }
+
+    fn machine_choice(&mut self, deadline: Option<Instant>) -> Option<(Move, SearchInfo)> {
+        self.choose_move_with_info(deadline)
+    }
+
+    fn should_swap(&mut self, deadline: Option<Instant>) -> bool {
+        TicTacToe::should_swap(self, deadline)
+    }
+}
+
+/// This function returns the best move for `to_move` on `board` and its
+/// score from the point of view of `to_move`: positive when it wins, 0 for
+/// a draw and negative when it loses against perfect play. The board is
+/// parsed by `parse_board`, with the `serde` feature a complete JSON game
+/// state is accepted too.
+pub fn suggest_move(board: &str, to_move: BoardChar) -> Result<(Move, i16), GameError> {
+    if to_move == BoardChar::Empty {
+        return Err(GameError::InvalidInput("The side to move must be X or O".to_string()));
+    }
+
+    let mut game = match board.trim().starts_with('{') {
+        #[cfg(feature = "serde")]
+        true => TicTacToe::from_json(board)?,
+        _ => {
+            let board = parse_board(board)?;
+            TicTacToe::from_snapshot(&GameSnapshot {
+                win_len: board.len(),
+                board,
+                player_char: to_move.to_opposite(),
+                machine_char: to_move,
+                symbols: Symbols::default(),
+                turn: to_move,
+                history: Vec::new(),
+                misere: false,
+                handicap: Vec::new(),
+            })?
+        }
+    };
+
+    if game.player_won() || game.machine_won() {
+        return Err(GameError::GameAlreadyOver);
+    }
+
+    // Equally good moves are picked the same way on every call
+    game.set_seed(0);
+
+    let max_depth = game.max_depth;
+    game.find_best_move_for(&mut Search::new(max_depth, None), to_move)
+        .ok_or(GameError::GameAlreadyOver)
+}
+
+impl fmt::Display for TicTacToe {
+    /// The board is drawn in the grid of its theme, columns are labeled
+    /// with letters and rows with numbers. Once a line is completed its
+    /// cells are highlighted. The marks of the players are drawn in their colors.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let board = draw_board_for(&self.board, self.symbols, self.theme, self.completed_line(), &self.players);
+        writeln!(f, "{}", board)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// This function plays `moves` in turn, the side that opens first
+    fn play(game: &mut TicTacToe, moves: &[(usize, usize)]) {
+        for &(row, col) in moves {
+            game.play_move(&Move { row, col }).expect("the moves of a test are legal");
+        }
+    }
+
+    #[test]
+    fn four_in_a_row_wins_on_five_by_five() {
+        let mut game = TicTacToe::with_size(BoardChar::X, 5, 4);
+        play(&mut game, &[(0, 0), (0, 1), (1, 1), (0, 2), (2, 2), (0, 3)]);
+        assert_eq!(game.winner(), None);
+
+        // Three in a row do not win when four are needed
+        assert!(!game.is_game_over());
+
+        play(&mut game, &[(3, 3)]);
+        assert_eq!(game.winner(), Some(BoardChar::X));
+        let line = game.completed_line().expect("X completed a line");
+        assert_eq!(line.cells, &[(0, 0), (1, 1), (2, 2), (3, 3)][..]);
+    }
+
+    #[test]
+    fn heuristic_prefers_the_center() {
+        // The machine plays X and opens
+        let score = |cell| {
+            let mut game = TicTacToe::new(BoardChar::O);
+            game.set_first(BoardChar::X);
+            play(&mut game, &[cell]);
+            game.heuristic()
+        };
+
+        assert!(score((1, 1)) > score((0, 0)));
+        assert!(score((1, 1)) > score((0, 1)));
+        assert!(score((1, 1)) > 0);
+    }
+
+    #[test]
+    fn cloned_game_is_independent() {
+        let mut game = TicTacToe::new(BoardChar::X);
+        play(&mut game, &[(1, 1)]);
+
+        let mut copy = game.clone();
+        play(&mut copy, &[(0, 0), (2, 2)]);
+        copy.undo();
+        assert_eq!(copy.board()[0][0], BoardChar::O);
+
+        assert_eq!(game.board()[0][0], BoardChar::Empty);
+        assert_eq!(game.moves().len(), 1);
+        assert_eq!(game.turn(), BoardChar::O);
+
+        copy.reset();
+        assert!(copy.is_empty());
+        assert_eq!(game.board()[1][1], BoardChar::X);
+        assert_eq!(copy.player_char(), BoardChar::X);
+    }
+
+    #[test]
+    fn counts_empty_cells() {
+        let mut game = TicTacToe::new(BoardChar::X);
+        assert_eq!(game.empty_cells().count(), 9);
+
+        play(&mut game, &[(1, 1), (0, 0), (0, 2)]);
+        let empty: Vec<(usize, usize)> = game.empty_cells().collect();
+        assert_eq!(empty, vec![(0, 1), (1, 0), (1, 2), (2, 0), (2, 1), (2, 2)]);
+
+        let game = TicTacToe::with_size(BoardChar::X, 5, 4);
+        assert_eq!(game.empty_cells().count(), 25);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn json_round_trip_keeps_the_best_move() {
+        let mut game = TicTacToe::new(BoardChar::O);
+        game.set_first(BoardChar::X);
+        game.set_symbols(Symbols { x: 'A', o: 'B' });
+        play(&mut game, &[(0, 0), (1, 1), (2, 2)]);
+
+        let mut restored = TicTacToe::from_json(&game.to_json()).expect("a game reads back its own JSON");
+        assert_eq!(restored.board(), game.board());
+        assert_eq!(restored.turn(), game.turn());
+        assert_eq!(restored.symbols(), game.symbols());
+        assert_eq!(restored.to_json(), game.to_json());
+
+        game.set_seed(3);
+        restored.set_seed(3);
+        assert_eq!(restored.choose_move(None), game.choose_move(None));
+    }
+
+    #[test]
+    fn a_handicap_cell_given_twice_is_refused() {
+        let mut game = TicTacToe::new(BoardChar::X);
+        game.set_handicap(&[Move { row: 0, col: 0 }]).expect("an empty board takes a handicap");
+
+        let mut state = game.snapshot();
+        state.handicap.push(Move { row: 0, col: 0 });
+        assert!(TicTacToe::from_snapshot(&state).is_err());
+
+        #[cfg(feature = "serde")]
+        {
+            let json = game.to_json().replace("\"handicap\":[\"1A\"]", "\"handicap\":[\"1A\",\"1A\"]");
+            assert!(json.contains("[\"1A\",\"1A\"]"));
+            assert!(TicTacToe::from_json(&json).is_err());
+        }
+    }
+
+    #[test]
+    fn custom_glyphs_win() {
+        let symbols = Symbols { x: '❌', o: '⭕' };
+        let mut game = TicTacToe::new(BoardChar::O);
+        game.set_symbols(symbols);
+        play(&mut game, &[(0, 0), (1, 1), (0, 1), (2, 2), (0, 2)]);
+
+        assert_eq!(game.winner(), Some(BoardChar::O));
+        assert!(game.player_won());
+        assert_eq!(symbols.glyph(game.winner().unwrap()), '⭕');
+        assert_eq!(symbols.mark('❌'), Some(BoardChar::X));
+
+        let drawn = game.to_string();
+        assert!(drawn.contains('⭕') && drawn.contains('❌'));
+        assert!(!drawn.contains('X') && !drawn.contains('O'));
+    }
+
+    #[test]
+    fn is_legal_checks_cells_without_changing_them() {
+        let mut game = TicTacToe::new(BoardChar::X);
+        play(&mut game, &[(1, 1)]);
+        let before = game.board().clone();
+
+        assert!(!game.is_legal(&Move { row: 1, col: 1 }));
+        assert!(game.is_legal(&Move { row: 0, col: 2 }));
+        assert!(!game.is_legal(&Move { row: 3, col: 0 }));
+        assert!(!game.is_legal(&Move { row: 0, col: 3 }));
+        assert_eq!(game.board(), &before);
+
+        assert_eq!(game.play_move(&Move { row: 1, col: 1 }), Err(GameError::CellOccupied));
+        assert_eq!(game.play_move(&Move { row: 0, col: 7 }), Err(GameError::OutOfBounds));
+        assert_eq!(game.board(), &before);
+    }
+
+    #[test]
+    fn seeds_vary_the_opening_move() {
+        let mut moves = Vec::new();
+        for seed in 0..16 {
+            let mut game = TicTacToe::new(BoardChar::O);
+            game.set_first(BoardChar::X);
+            game.set_book(false);
+            game.set_seed(seed);
+
+            let (m, _) = game.choose_move(None).expect("the empty board has moves");
+            if !moves.contains(&m) {
+                moves.push(m);
+            }
+        }
+
+        assert!(moves.len() > 1, "every seed opened with {:?}", moves);
+    }
+
+    #[test]
+    fn refuses_moves_once_won() {
+        let mut game = TicTacToe::new(BoardChar::X);
+        play(&mut game, &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)]);
+        assert_eq!(game.winner(), Some(BoardChar::X));
+        let before = game.board().clone();
+
+        assert_eq!(game.play_move(&Move { row: 2, col: 2 }), Err(GameError::GameAlreadyOver));
+        assert_eq!(game.do_move(&Move { row: 1, col: 2 }, BoardChar::O), Err(GameError::GameAlreadyOver));
+        assert_eq!(game.player_move(&Move { row: 2, col: 0 }), Err(GameError::GameAlreadyOver));
+        assert!(game.machine_move(Duration::from_millis(10)).is_err());
+
+        assert_eq!(game.board(), &before);
+        assert_eq!(game.moves().len(), 5);
+    }
+}
@@ -0,0 +1,80 @@
+//! The ways a request to the game can fail.
+
+use std::fmt;
+
+use crate::i18n::{Lang, Text};
+
+/// Reasons why input is rejected or a move is refused
+#[derive(Debug, PartialEq, Clone)]
+pub enum GameError {
+    /// Text that could not be read as a move, mark, board or saved game.
+    /// The message says what was wrong with it.
+    InvalidInput(String),
+    OutOfBounds,
+    CellOccupied,
+    GameAlreadyOver,
+    /// In Ultimate Tic-Tac-Toe the move is not on the small board the
+    /// opponent's last move sent it to, or that board is already decided
+    WrongBoard,
+    /// The move is not one of the side whose turn it is
+    OutOfTurn,
+    /// One side has more than one mark more than the other
+    UnbalancedMarks,
+    /// Both sides completed a line
+    BothSidesWon,
+    /// Marks were placed after a line was completed
+    MoveAfterWin,
+}
+
+impl GameError {
+    /// This function returns the key of the text of the error, none for
+    /// `InvalidInput`, whose message is its own
+    pub fn text(&self) -> Option<Text> {
+        match self {
+            GameError::InvalidInput(_) => None,
+            GameError::OutOfBounds => Some(Text::OutOfBounds),
+            GameError::CellOccupied => Some(Text::CellOccupied),
+            GameError::GameAlreadyOver => Some(Text::GameAlreadyOver),
+            GameError::WrongBoard => Some(Text::WrongBoard),
+            GameError::OutOfTurn => Some(Text::OutOfTurn),
+            GameError::UnbalancedMarks => Some(Text::UnbalancedMarks),
+            GameError::BothSidesWon => Some(Text::BothSidesWon),
+            GameError::MoveAfterWin => Some(Text::MoveAfterWin),
+        }
+    }
+}
+
+/// Errors are written in English whatever language `Lang::set` chose, so
+/// callers of the library, the HTTP API and the chat all get the same text.
+/// `i18n::localize` translates them for the terminal.
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self, self.text()) {
+            (_, Some(text)) => f.write_str(text.in_lang(Lang::English)),
+            (GameError::InvalidInput(message), None) => write!(f, "{}", message),
+            (_, None) => Ok(()),
+        }
+    }
+}
+
+impl std::error::Error for GameError {}
+
+/// Messages of the parsers below the public API become `InvalidInput`
+impl From<String> for GameError {
+    fn from(message: String) -> GameError {
+        GameError::InvalidInput(message)
+    }
+}
+
+impl From<&str> for GameError {
+    fn from(message: &str) -> GameError {
+        GameError::InvalidInput(message.to_string())
+    }
+}
+
+/// Lets code that reports errors as text use `?` on the game's results
+impl From<GameError> for String {
+    fn from(error: GameError) -> String {
+        error.to_string()
+    }
+}
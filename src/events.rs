@@ -0,0 +1,332 @@
+//! A frontend for programs rather than people: with `--json` every prompt,
+//! move and change of the game is written to stdout as one JSON object per
+//! line, and the answers are read from stdin the same way. A GUI wrapper or
+//! a bot can drive the game without reading the board art.
+//!
+//! ```text
+//! {"event":"start","size":3,"win":3,"player":"X","machine":"O","first":"X","misere":false}
+//! {"event":"state","position":"X../.O./... X","turn":"X","moves":2}
+//! {"event":"prompt","for":"move","turn":"X"}
+//! {"event":"move","mark":"X","at":"2B","by":"player"}
+//! {"event":"error","message":"the cell is already occupied"}
+//! {"event":"over","winner":"X","ending":"line"}
+//! {"event":"prompt","for":"again"}
+//! {"event":"bye"}
+//! ```
+//!
+//! `machine` is `null` when two people share the game, `winner` is `null`
+//! for a draw and `ending` is one of `line`, `full`, `resigned`, `time` or
+//! `agreed`. The answers are
+//!
+//! ```text
+//! {"action":"move","at":"2B"}     plays a move, at the move prompt
+//! {"action":"resign"}             gives up the game, at the move prompt
+//! {"action":"again","yes":true}   answers the question for another game
+//! {"action":"quit"}               leaves, as does the end of the input
+//! ```
+
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tic_tac_toe_rs::json::{self, Value};
+use tic_tac_toe_rs::{BoardChar, Ending, InputSource, Move, Observer, Placement, TicTacToe};
+
+use tic_tac_toe_rs::terminal::Mode;
+
+/// What is told to the frontend
+enum Event {
+    Start {
+        size: usize,
+        win: usize,
+        player: BoardChar,
+        machine: Option<BoardChar>,
+        first: BoardChar,
+        misere: bool,
+    },
+    State { position: String, turn: BoardChar, moves: usize },
+    PromptMove(BoardChar),
+    PromptAgain,
+    Move { mark: BoardChar, at: Move, machine: bool },
+    Error(String),
+    Over { winner: Option<BoardChar>, ending: &'static str },
+    Bye,
+}
+
+impl Event {
+    fn to_json(&self) -> Value {
+        let text = |s: &str| Value::String(s.to_string());
+        let mark = |c: BoardChar| Value::String(c.to_string());
+        let mut members = Vec::new();
+
+        let kind = match self {
+            Event::Start {
+                size,
+                win,
+                player,
+                machine,
+                first,
+                misere,
+            } => {
+                members.push(("size", Value::Number(*size as f64)));
+                members.push(("win", Value::Number(*win as f64)));
+                members.push(("player", mark(*player)));
+                members.push(("machine", machine.map_or(Value::Null, mark)));
+                members.push(("first", mark(*first)));
+                members.push(("misere", Value::Bool(*misere)));
+                "start"
+            }
+            Event::State { position, turn, moves } => {
+                members.push(("position", text(position)));
+                members.push(("turn", mark(*turn)));
+                members.push(("moves", Value::Number(*moves as f64)));
+                "state"
+            }
+            Event::PromptMove(turn) => {
+                members.push(("for", text("move")));
+                members.push(("turn", mark(*turn)));
+                "prompt"
+            }
+            Event::PromptAgain => {
+                members.push(("for", text("again")));
+                "prompt"
+            }
+            Event::Move { mark: c, at, machine } => {
+                members.push(("mark", mark(*c)));
+                members.push(("at", text(&at.to_string())));
+                members.push(("by", text(if *machine { "machine" } else { "player" })));
+                "move"
+            }
+            Event::Error(message) => {
+                members.push(("message", text(message)));
+                "error"
+            }
+            Event::Over { winner, ending } => {
+                members.push(("winner", winner.map_or(Value::Null, mark)));
+                members.push(("ending", text(ending)));
+                "over"
+            }
+            Event::Bye => "bye",
+        };
+
+        let mut object = vec![("event".to_string(), text(kind))];
+        object.extend(members.into_iter().map(|(key, value)| (key.to_string(), value)));
+        Value::Object(object)
+    }
+}
+
+/// What the frontend answers
+enum Action {
+    Move(Move),
+    Resign,
+    Again(bool),
+    Quit,
+}
+
+impl Action {
+    fn parse(line: &str) -> Result<Action, String> {
+        let value = json::parse(line)?;
+        let action = value.field("action")?.as_str().ok_or("Field 'action' must be a string")?;
+
+        match action {
+            "move" => {
+                let at = value.field("at")?.as_str().ok_or("Field 'at' must be a move like \"2B\"")?;
+                Ok(Action::Move(at.parse()?))
+            }
+            "resign" => Ok(Action::Resign),
+            "again" => Ok(Action::Again(value.field("yes")?.as_bool().ok_or("Field 'yes' must be true or false")?)),
+            "quit" => Ok(Action::Quit),
+            other => Err(format!("'{}' is not one of 'move', 'resign', 'again', 'quit'", other)),
+        }
+    }
+}
+
+/// The output the events are written to. It observes the game, so every
+/// move and the end of the game are told however they came about.
+struct Stream {
+    output: Mutex<Box<dyn Write + Send>>,
+    /// The machine plays a side
+    machine: bool,
+}
+
+impl Stream {
+    fn emit(&self, event: &Event) -> io::Result<()> {
+        let mut output = self.output.lock().unwrap();
+        writeln!(output, "{}", event.to_json())?;
+        output.flush()
+    }
+
+    fn emit_state(&self, game: &TicTacToe) -> io::Result<()> {
+        self.emit(&Event::State {
+            position: game.to_notation(),
+            turn: game.turn(),
+            moves: game.moves().len(),
+        })
+    }
+}
+
+/// An observer cannot fail, a write that does fails again with the next
+/// prompt, which ends the game
+impl Observer for Stream {
+    fn on_move(&self, game: &TicTacToe, placement: &Placement) {
+        let machine = self.machine && placement.mark == game.machine_char();
+        let _ = self.emit(&Event::Move {
+            mark: placement.mark,
+            at: placement.at,
+            machine,
+        });
+    }
+
+    fn on_game_over(&self, game: &TicTacToe) {
+        let _ = self.emit_state(game);
+        let _ = self.emit(&Event::Over {
+            winner: game.winner(),
+            ending: ending(game),
+        });
+    }
+}
+
+/// This function reads the next answer, telling the frontend about every
+/// line that is none. It returns `Quit` once the input ends.
+fn read_action(input: &mut dyn InputSource, output: &Stream) -> io::Result<Action> {
+    loop {
+        let line = match input.read_line()? {
+            Some(line) => line,
+            None => return Ok(Action::Quit),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match Action::parse(&line) {
+            Ok(action) => return Ok(action),
+            Err(e) => output.emit(&Event::Error(e))?,
+        }
+    }
+}
+
+/// This function returns how `game`, which is over, ended
+fn ending(game: &TicTacToe) -> &'static str {
+    match game.ending() {
+        Some(Ending::Resigned(_)) => "resigned",
+        Some(Ending::OutOfTime(_)) => "time",
+        Some(Ending::DrawAgreed) => "agreed",
+        None if game.winner().is_some() => "line",
+        None => "full",
+    }
+}
+
+/// This function plays games driven by JSON on `input` and `output` until
+/// the frontend quits. The machine thinks no longer than `budget` about a
+/// move. `new_game` sets up every game before its first move, including
+/// the first one if `game` is still empty, `finished` is told about every
+/// game that came to an end.
+pub fn run(
+    input: &mut dyn InputSource,
+    output: impl Write + Send + 'static,
+    game: &mut TicTacToe,
+    mode: Mode,
+    budget: Duration,
+    mut new_game: impl FnMut(&mut TicTacToe),
+    mut finished: impl FnMut(&TicTacToe),
+) -> io::Result<()> {
+    let machine = mode == Mode::Machine;
+    let output = Arc::new(Stream {
+        output: Mutex::new(Box::new(output)),
+        machine,
+    });
+    game.add_observer(Arc::clone(&output) as Arc<dyn Observer>);
+    if game.is_empty() {
+        new_game(game);
+    }
+
+    loop {
+        let start = Event::Start {
+            size: game.board().len(),
+            win: game.win_len(),
+            player: game.player_char(),
+            machine: Some(game.machine_char()).filter(|_| machine),
+            first: game.first(),
+            misere: game.is_misere(),
+        };
+        output.emit(&start)?;
+
+        // The moves and the end of the game are told by the observer
+        while !game.is_game_over() {
+            let turn = game.turn();
+            output.emit_state(game)?;
+
+            if machine && turn == game.machine_char() {
+                let _ = game.machine_move(budget);
+                continue;
+            }
+
+            output.emit(&Event::PromptMove(turn))?;
+            match read_action(input, &output)? {
+                Action::Move(m) => {
+                    if let Err(e) = game.do_move(&m, turn) {
+                        output.emit(&Event::Error(e.to_string()))?;
+                    }
+                }
+                Action::Resign => game.resign(turn),
+                Action::Again(_) => output.emit(&Event::Error("The game is not over yet".to_string()))?,
+                Action::Quit => return output.emit(&Event::Bye),
+            }
+        }
+        finished(game);
+
+        loop {
+            output.emit(&Event::PromptAgain)?;
+            match read_action(input, &output)? {
+                Action::Again(true) => break,
+                Action::Again(false) | Action::Quit => return output.emit(&Event::Bye),
+                _ => output.emit(&Event::Error("The game is over, another one?".to_string()))?,
+            }
+        }
+
+        game.reset();
+        new_game(game);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tic_tac_toe_rs::ScriptedInput;
+
+    /// Output the test reads back once the game is played
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn streams_the_moves_and_the_end_of_a_game() {
+        let mut input = ScriptedInput::new(
+            ["1A", "2A", "1B", "2B", "1C"]
+                .iter()
+                .map(|at| format!("{{\"action\":\"move\",\"at\":\"{}\"}}", at))
+                .chain(Some("{\"action\":\"again\",\"yes\":false}".to_string())),
+        );
+        let output = Shared::default();
+        let mut game = TicTacToe::new(BoardChar::X);
+        run(&mut input, output.clone(), &mut game, Mode::HotSeat, Duration::from_secs(1), |_| {}, |_| {}).unwrap();
+
+        let text = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        let events: Vec<&str> = text.lines().collect();
+        assert_eq!(events.iter().filter(|e| e.contains("\"event\":\"move\"")).count(), 5);
+        let over = events.iter().position(|e| e.contains("\"event\":\"over\"")).unwrap();
+        assert!(events[over].contains("\"winner\":\"X\""));
+        assert!(events[over - 1].contains("\"event\":\"state\""));
+        assert!(events[over - 2].contains("\"at\":\"1C\""));
+        assert!(events.last().unwrap().contains("\"event\":\"bye\""));
+    }
+}
@@ -0,0 +1,352 @@
+//! A C ABI over `TicTacToe`, so that the engine can be called from C,
+//! Python, C#, Swift and anything else that loads a shared library. Built
+//! only with the `ffi` feature, the declarations are in
+//! `include/tic_tac_toe.h`, which is written by hand and checked against
+//! this file by the tests below.
+//!
+//! A game is an opaque pointer made by `ttt_new` and released with
+//! `ttt_free`. Rows and columns count from 0, marks are `0` for an empty
+//! cell, `1` for X and `2` for O like in `web`. Every function that can fail
+//! returns `TTT_OK` or one of the negative `TTT_ERR_` codes.
+
+use std::os::raw::c_char;
+use std::time::{Duration, Instant};
+
+use crate::board::BoardChar;
+use crate::engine::{GameState, TicTacToe};
+use crate::error::GameError;
+use crate::r#move::{Move, MAX_SIZE};
+use crate::web::mark_code;
+
+pub const TTT_OK: i32 = 0;
+pub const TTT_ERR_NULL: i32 = -1;
+pub const TTT_ERR_OUT_OF_BOUNDS: i32 = -2;
+pub const TTT_ERR_OCCUPIED: i32 = -3;
+pub const TTT_ERR_GAME_OVER: i32 = -4;
+pub const TTT_ERR_INVALID: i32 = -5;
+/// The buffer passed to `ttt_state` cannot hold every cell
+pub const TTT_ERR_BUFFER: i32 = -6;
+
+/// What `ttt_state` returns for a game that goes on, X won, O won and a draw
+pub const TTT_IN_PROGRESS: i32 = 0;
+pub const TTT_X_WON: i32 = 1;
+pub const TTT_O_WON: i32 = 2;
+pub const TTT_DRAW: i32 = 3;
+
+/// A game as C sees it: only ever behind a pointer
+pub struct TttGame {
+    game: TicTacToe,
+}
+
+fn error_code(e: &GameError) -> i32 {
+    match e {
+        GameError::OutOfBounds => TTT_ERR_OUT_OF_BOUNDS,
+        GameError::CellOccupied => TTT_ERR_OCCUPIED,
+        GameError::GameAlreadyOver => TTT_ERR_GAME_OVER,
+        GameError::InvalidInput(_)
+        | GameError::WrongBoard
+        | GameError::OutOfTurn
+        | GameError::UnbalancedMarks
+        | GameError::BothSidesWon
+        | GameError::MoveAfterWin => TTT_ERR_INVALID,
+    }
+}
+
+/// This function starts a game on a `size` x `size` board where `win_len`
+/// marks in a row win. `player` is `'X'` or `'O'`, the machine plays the
+/// other mark and X opens. `seed` seeds the machine's random choices. It
+/// returns null if the arguments make no game.
+#[no_mangle]
+pub extern "C" fn ttt_new(size: u32, win_len: u32, player: c_char, seed: u64) -> *mut TttGame {
+    let (size, win_len) = (size as usize, win_len as usize);
+    let player = match player as u8 {
+        b'X' | b'x' => BoardChar::X,
+        b'O' | b'o' => BoardChar::O,
+        _ => return std::ptr::null_mut(),
+    };
+    if size == 0 || size > MAX_SIZE || win_len == 0 || win_len > size {
+        return std::ptr::null_mut();
+    }
+
+    let mut game = TicTacToe::with_size(player, size, win_len);
+    game.set_first(BoardChar::X);
+    game.set_seed(seed);
+
+    Box::into_raw(Box::new(TttGame { game }))
+}
+
+/// This function releases a game made by `ttt_new`, null is ignored
+///
+/// # Safety
+///
+/// `game` must be null or a pointer returned by `ttt_new` that has not
+/// been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_free(game: *mut TttGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+/// This function places the mark of the side to move at `row`, `col`
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `ttt_new`.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_player_move(game: *mut TttGame, row: u32, col: u32) -> i32 {
+    let game = match game.as_mut() {
+        Some(game) => &mut game.game,
+        None => return TTT_ERR_NULL,
+    };
+    let m = Move {
+        row: row as usize,
+        col: col as usize,
+    };
+
+    match game.play_move(&m) {
+        Ok(_) => TTT_OK,
+        Err(e) => error_code(&e),
+    }
+}
+
+/// This function writes the move the machine would play for the side to
+/// move to `row` and `col`, thinking no longer than `millis` milliseconds,
+/// 0 for no limit but the search depth. The board stays as it is.
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `ttt_new`, `row` and
+/// `col` null or valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_best_move(game: *mut TttGame, millis: u32, row: *mut u32, col: *mut u32) -> i32 {
+    let (game, row, col) = match (game.as_mut(), row.as_mut(), col.as_mut()) {
+        (Some(game), Some(row), Some(col)) => (&mut game.game, row, col),
+        _ => return TTT_ERR_NULL,
+    };
+    let deadline = match millis {
+        0 => None,
+        millis => Some(Instant::now() + Duration::from_millis(millis.into())),
+    };
+
+    match game.choose_move(deadline) {
+        Some((m, _)) => {
+            *row = m.row as u32;
+            *col = m.col as u32;
+            TTT_OK
+        }
+        None => TTT_ERR_GAME_OVER,
+    }
+}
+
+/// This function writes the marks of all cells into `cells`, row by row,
+/// and returns the state of the game or an error code. `len` is the number
+/// of bytes `cells` holds, at least the size squared.
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `ttt_new`, `cells`
+/// null or valid for writes of `len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_state(game: *const TttGame, cells: *mut u8, len: usize) -> i32 {
+    let game = match game.as_ref() {
+        Some(game) => &game.game,
+        None => return TTT_ERR_NULL,
+    };
+    if cells.is_null() {
+        return TTT_ERR_NULL;
+    }
+
+    let size = game.board().len();
+    if len < size * size {
+        return TTT_ERR_BUFFER;
+    }
+
+    let cells = std::slice::from_raw_parts_mut(cells, len);
+    for (cell, &c) in cells.iter_mut().zip(game.board().iter().flatten()) {
+        *cell = mark_code(c);
+    }
+
+    match game.state() {
+        GameState::InProgress { .. } => TTT_IN_PROGRESS,
+        GameState::Won { winner: BoardChar::X, .. } => TTT_X_WON,
+        GameState::Won { .. } => TTT_O_WON,
+        GameState::Draw => TTT_DRAW,
+    }
+}
+
+/// This function returns the size of the board, 0 for null
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `ttt_new`.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_size(game: *const TttGame) -> u32 {
+    game.as_ref().map_or(0, |game| game.game.board().len() as u32)
+}
+
+/// This function returns the mark of the side to move, 0 once the game is
+/// over or for null
+///
+/// # Safety
+///
+/// `game` must be null or a live pointer returned by `ttt_new`.
+#[no_mangle]
+pub unsafe extern "C" fn ttt_turn(game: *const TttGame) -> u8 {
+    match game.as_ref() {
+        Some(game) if !game.game.is_game_over() => mark_code(game.game.turn()),
+        _ => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = include_str!("../include/tic_tac_toe.h");
+    const SOURCE: &str = include_str!("ffi.rs");
+
+    /// This function returns the C spelling of the Rust type `ty`, pointers
+    /// end in `*` so the name follows without a space
+    fn c_type(ty: &str) -> String {
+        if let Some(target) = ty.strip_prefix("*mut ") {
+            return format!("{} *", c_type(target).trim_end());
+        }
+        if let Some(target) = ty.strip_prefix("*const ") {
+            return format!("const {} *", c_type(target).trim_end());
+        }
+
+        let c = match ty {
+            "" => "void",
+            "u8" => "uint8_t",
+            "u32" => "uint32_t",
+            "u64" => "uint64_t",
+            "i32" => "int32_t",
+            "usize" => "size_t",
+            "c_char" => "char",
+            "TttGame" => "TttGame",
+            other => panic!("{} has no C type", other),
+        };
+        format!("{} ", c)
+    }
+
+    /// This function returns the C declaration of the exported function
+    /// whose Rust signature starts at `rest`, the part after `fn `
+    fn c_declaration(rest: &str) -> String {
+        let (name, rest) = rest.split_once('(').unwrap();
+        let (params, rest) = rest.split_once(')').unwrap();
+        let ret = rest.trim().trim_end_matches('{').trim().trim_start_matches("->").trim();
+
+        let params: Vec<String> = params
+            .split(", ")
+            .map(|param| {
+                let (name, ty) = param.split_once(": ").unwrap();
+                format!("{}{}", c_type(ty), name)
+            })
+            .collect();
+        format!("{}{}({});", c_type(ret), name, params.join(", "))
+    }
+
+    #[test]
+    fn the_header_declares_every_exported_function() {
+        let declarations: Vec<String> = SOURCE
+            .lines()
+            .filter_map(|line| line.split(" extern \"C\" fn ").nth(1))
+            .map(c_declaration)
+            .collect();
+
+        assert_eq!(declarations.len(), 7);
+        for declaration in declarations {
+            assert!(HEADER.lines().any(|line| line == declaration), "{} is not declared", declaration);
+        }
+    }
+
+    #[test]
+    fn the_header_has_the_same_codes() {
+        let codes = [
+            ("TTT_OK", TTT_OK),
+            ("TTT_ERR_NULL", TTT_ERR_NULL),
+            ("TTT_ERR_OUT_OF_BOUNDS", TTT_ERR_OUT_OF_BOUNDS),
+            ("TTT_ERR_OCCUPIED", TTT_ERR_OCCUPIED),
+            ("TTT_ERR_GAME_OVER", TTT_ERR_GAME_OVER),
+            ("TTT_ERR_INVALID", TTT_ERR_INVALID),
+            ("TTT_ERR_BUFFER", TTT_ERR_BUFFER),
+            ("TTT_IN_PROGRESS", TTT_IN_PROGRESS),
+            ("TTT_X_WON", TTT_X_WON),
+            ("TTT_O_WON", TTT_O_WON),
+            ("TTT_DRAW", TTT_DRAW),
+        ];
+
+        for (name, value) in codes {
+            assert!(HEADER.contains(&format!("#define {} {}\n", name, value)), "{} is not {} in the header", name, value);
+        }
+    }
+
+    #[test]
+    fn a_null_game_is_refused() {
+        let (mut row, mut col) = (0, 0);
+        let mut cells = [0u8; 9];
+
+        unsafe {
+            assert_eq!(ttt_player_move(std::ptr::null_mut(), 0, 0), TTT_ERR_NULL);
+            assert_eq!(ttt_best_move(std::ptr::null_mut(), 0, &mut row, &mut col), TTT_ERR_NULL);
+            assert_eq!(ttt_state(std::ptr::null(), cells.as_mut_ptr(), cells.len()), TTT_ERR_NULL);
+            assert_eq!(ttt_size(std::ptr::null()), 0);
+            assert_eq!(ttt_turn(std::ptr::null()), 0);
+            ttt_free(std::ptr::null_mut());
+        }
+        assert!(ttt_new(3, 3, b'Z' as c_char, 1).is_null());
+        assert!(ttt_new(3, 4, b'X' as c_char, 1).is_null());
+    }
+
+    #[test]
+    fn a_buffer_too_short_for_the_board_is_refused() {
+        let game = ttt_new(3, 3, b'X' as c_char, 1);
+        let mut cells = [0u8; 8];
+
+        unsafe {
+            assert_eq!(ttt_size(game), 3);
+            assert_eq!(ttt_state(game, cells.as_mut_ptr(), cells.len()), TTT_ERR_BUFFER);
+            assert_eq!(ttt_state(game, std::ptr::null_mut(), 9), TTT_ERR_NULL);
+            ttt_free(game);
+        }
+    }
+
+    #[test]
+    fn an_occupied_cell_is_refused() {
+        let game = ttt_new(3, 3, b'X' as c_char, 1);
+
+        unsafe {
+            assert_eq!(ttt_player_move(game, 1, 1), TTT_OK);
+            assert_eq!(ttt_player_move(game, 1, 1), TTT_ERR_OCCUPIED);
+            assert_eq!(ttt_player_move(game, 3, 0), TTT_ERR_OUT_OF_BOUNDS);
+            assert_eq!(ttt_turn(game), 2);
+            ttt_free(game);
+        }
+    }
+
+    #[test]
+    fn a_game_is_played_to_the_end() {
+        let game = ttt_new(3, 3, b'X' as c_char, 1);
+        let (mut row, mut col) = (9, 9);
+        let mut cells = [9u8; 9];
+
+        unsafe {
+            assert_eq!(ttt_turn(game), 1);
+            assert_eq!(ttt_best_move(game, 0, &mut row, &mut col), TTT_OK);
+            assert!(row < 3 && col < 3);
+            assert_eq!(ttt_state(game, cells.as_mut_ptr(), cells.len()), TTT_IN_PROGRESS);
+            assert_eq!(cells, [0; 9]);
+
+            for (row, col) in [(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)] {
+                assert_eq!(ttt_player_move(game, row, col), TTT_OK);
+            }
+            assert_eq!(ttt_state(game, cells.as_mut_ptr(), cells.len()), TTT_X_WON);
+            assert_eq!(cells, [1, 1, 1, 2, 2, 0, 0, 0, 0]);
+            assert_eq!(ttt_turn(game), 0);
+            assert_eq!(ttt_player_move(game, 2, 2), TTT_ERR_GAME_OVER);
+            assert_eq!(ttt_best_move(game, 0, &mut row, &mut col), TTT_ERR_GAME_OVER);
+            ttt_free(game);
+        }
+    }
+}
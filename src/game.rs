@@ -0,0 +1,207 @@
+//! What the search engines need to know about a game to play it, and a
+//! minimax search that plays any game knowing only that. `evaluate_all` and
+//! `simulate` make engines easy to check against each other and against
+//! perfect play, see also `verify`.
+
+use std::cmp;
+use std::time::Instant;
+
+use crate::board::BoardChar;
+use crate::engine::{Engine, Search, WIN_SCORE};
+use crate::error::GameError;
+
+/// A two-player game in which X and O take turns. Any game implementing it
+/// can be played by every `Engine`.
+pub trait Game: Clone {
+    type Move: Copy + std::fmt::Display;
+
+    /// This function lists the moves the side to move may play, none once
+    /// the game is over
+    fn legal_moves(&self) -> Vec<Self::Move>;
+    /// This function plays `m` for the side to move
+    fn play(&mut self, m: &Self::Move) -> Result<(), GameError>;
+    /// This function returns the side to move
+    fn turn(&self) -> BoardChar;
+    /// This function returns the side that won, if any
+    fn winner(&self) -> Option<BoardChar>;
+    /// This function returns true if a side won or no move is left
+    fn is_game_over(&self) -> bool;
+    /// This function scores a position the search could not finish from the
+    /// point of view of `me`, positive values favour `me`. The score stays
+    /// within `HEURISTIC_BOUND`, well below any win.
+    fn score(&self, me: BoardChar) -> i16;
+}
+
+/// This function searches `game` depth 1, 2, ... until `full_depth`, a
+/// decided result or the deadline is reached, and returns the best move of
+/// the deepest search that finished with its depth. Games without a search
+/// of their own are played by `Minimax` with it.
+pub fn iterative_deepening<G: Game>(game: &G, deadline: Option<Instant>, full_depth: usize) -> Option<(G::Move, usize)> {
+    let mut best = None;
+
+    for depth in 1..=full_depth {
+        // The first iteration is cheap and always completes, so there is a
+        // move to play even when the deadline is tight
+        let limit = if depth == 1 { None } else { deadline };
+        let mut search = Search::new(Some(depth), limit);
+        let found = find_best_move(game, &mut search);
+
+        if search.aborted {
+            break;
+        }
+
+        match found {
+            Some((m, score)) => {
+                best = Some((m, depth));
+                // A forced win or loss stays what it is at any depth
+                if score.abs() > WIN_SCORE / 2 {
+                    break;
+                }
+            }
+            None => break,
+        }
+    }
+
+    best
+}
+
+/// This function returns the best move of the side to move in `game` within
+/// the limits of `search` and its score. Of equally good moves the first
+/// one listed by `legal_moves` is played.
+pub fn find_best_move<G: Game>(game: &G, search: &mut Search) -> Option<(G::Move, i16)> {
+    let me = game.turn();
+    let mut best: Option<(G::Move, i16)> = None;
+
+    for m in game.legal_moves() {
+        let mut next = game.clone();
+        if next.play(&m).is_err() {
+            continue;
+        }
+
+        let alpha = best.map_or(i16::MIN, |(_, score)| score);
+        let score = minimax(&next, search, me, 1, alpha, i16::MAX);
+        if search.aborted {
+            return None;
+        }
+
+        ai_debug!("candidate {} scores {}", m, score);
+
+        if best.is_none_or(|(_, best)| score > best) {
+            best = Some((m, score));
+        }
+    }
+
+    best
+}
+
+/// This function scores every legal move of the side to move in `game`
+/// within the limits of `search`, in the order `legal_moves` lists them.
+/// Unlike `find_best_move` no move is cut short by a better one found
+/// before, so every score is exact.
+pub fn evaluate_all<G: Game>(game: &G, search: &mut Search) -> Vec<(G::Move, i16)> {
+    let me = game.turn();
+    let mut scores = Vec::new();
+
+    for m in game.legal_moves() {
+        let mut next = game.clone();
+        if next.play(&m).is_ok() {
+            scores.push((m, minimax(&next, search, me, 1, i16::MIN, i16::MAX)));
+        }
+    }
+
+    scores
+}
+
+/// A game played to its end by two engines
+#[derive(Debug, Clone)]
+pub struct Simulation<G: Game> {
+    /// The game as it ended
+    pub end: G,
+    /// Every move played, oldest first
+    pub moves: Vec<G::Move>,
+}
+
+impl<G: Game> Simulation<G> {
+    pub fn winner(&self) -> Option<BoardChar> {
+        self.end.winner()
+    }
+}
+
+/// This function plays `game` to its end, `first` moving for the side to
+/// move and `second` for the other, without a deadline. An engine that
+/// finds no move or an illegal one ends the game where it stands.
+pub fn simulate<G: Game>(game: &G, first: &mut dyn Engine<G>, second: &mut dyn Engine<G>) -> Simulation<G> {
+    let opener = game.turn();
+    let mut simulation = Simulation {
+        end: game.clone(),
+        moves: Vec::new(),
+    };
+
+    while !simulation.end.is_game_over() {
+        let found = match simulation.end.turn() == opener {
+            true => first.best_move(&mut simulation.end, None),
+            false => second.best_move(&mut simulation.end, None),
+        };
+
+        match found {
+            Some((m, _)) if simulation.end.play(&m).is_ok() => simulation.moves.push(m),
+            _ => break,
+        }
+    }
+
+    simulation
+}
+
+/// This function returns the minimax score of `game` for `me`, who
+/// maximizes, with alpha-beta pruning. Once the deadline of the search
+/// passes it unwinds returning meaningless values.
+fn minimax<G: Game>(game: &G, search: &mut Search, me: BoardChar, depth: usize, mut alpha: i16, mut beta: i16) -> i16 {
+    if search.visit() {
+        return 0;
+    }
+
+    // The sooner the win the better
+    match game.winner() {
+        Some(c) if c == me => return WIN_SCORE - depth as i16,
+        Some(_) => return -WIN_SCORE + depth as i16,
+        None => {}
+    }
+
+    let moves = game.legal_moves();
+    if moves.is_empty() {
+        return 0;
+    }
+
+    if search.max_depth.is_some_and(|max| depth >= max) {
+        return game.score(me);
+    }
+
+    let maximizing = game.turn() == me;
+    let mut best = if maximizing { i16::MIN } else { i16::MAX };
+
+    for m in moves {
+        let mut next = game.clone();
+        if next.play(&m).is_err() {
+            continue;
+        }
+        let next_best = minimax(&next, search, me, depth + 1, alpha, beta);
+
+        if search.aborted {
+            return 0;
+        }
+
+        if maximizing {
+            best = cmp::max(best, next_best);
+            alpha = cmp::max(alpha, best);
+        } else {
+            best = cmp::min(best, next_best);
+            beta = cmp::min(beta, best);
+        }
+
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    best
+}
@@ -0,0 +1,99 @@
+//! Zobrist hashing of positions: every mark on every cell has a random
+//! 64-bit key and a position hashes to the XOR of the keys of its marks.
+//! Placing or taking back a mark changes the hash by a single XOR with
+//! `cell_key`, so a search can keep it up to date as it goes.
+//!
+//! The keys are made at compile time from a fixed seed and are the same in
+//! every build, so hashes may be kept on disk and compared between tools.
+//! Boards up to `MAX_SIZE` are covered, cell `(row, col)` has the same key
+//! whatever the size of the board. The side to move is not part of the
+//! hash, it follows from the marks on the board.
+
+use crate::board::{Board, BoardChar};
+use crate::r#move::MAX_SIZE;
+
+/// The seed of the keys, changing it changes every hash
+const SEED: u64 = 0x7A0B_2157_5EED_0001;
+
+/// The keys of X and O on every cell, row by row on a `MAX_SIZE` board
+const KEYS: [[u64; 2]; MAX_SIZE * MAX_SIZE] = keys();
+
+/// This function makes the keys with splitmix64
+const fn keys() -> [[u64; 2]; MAX_SIZE * MAX_SIZE] {
+    let mut keys = [[0; 2]; MAX_SIZE * MAX_SIZE];
+    let mut state = SEED;
+    let mut i = 0;
+
+    while i < keys.len() * 2 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i / 2][i % 2] = z ^ (z >> 31);
+        i += 1;
+    }
+
+    keys
+}
+
+/// Maps a cell `(row, col)` of a board whose last row and column is `last`
+/// to the cell it lands on in one of the images of the board
+type Image = fn(usize, usize, usize) -> (usize, usize);
+
+/// The four rotations of the board and the four reflections
+const IMAGES: [Image; 8] = [
+    |_, row, col| (row, col),
+    |last, row, col| (col, last - row),
+    |last, row, col| (last - row, last - col),
+    |last, row, col| (last - col, row),
+    |last, row, col| (row, last - col),
+    |last, row, col| (last - row, col),
+    |_, row, col| (col, row),
+    |last, row, col| (last - col, last - row),
+];
+
+/// This function returns the key of `mark` on `(row, col)`, 0 for an empty
+/// cell. The cell must lie on a `MAX_SIZE` board.
+pub fn cell_key(row: usize, col: usize, mark: BoardChar) -> u64 {
+    let keys = KEYS[row * MAX_SIZE + col];
+
+    match mark {
+        BoardChar::Empty => 0,
+        BoardChar::X => keys[0],
+        BoardChar::O => keys[1],
+    }
+}
+
+/// This function returns the hash of `board`
+pub fn hash(board: &Board) -> u64 {
+    marks(board).fold(0, |hash, (row, col, mark)| hash ^ cell_key(row, col, mark))
+}
+
+/// This function returns the hash shared by `board` and its rotations and
+/// reflections, the smallest hash among the eight of them
+pub fn canonical_hash(board: &Board) -> u64 {
+    let last = board.len().saturating_sub(1);
+    let marks: Vec<(usize, usize, BoardChar)> = marks(board).collect();
+
+    IMAGES
+        .iter()
+        .map(|image| {
+            marks.iter().fold(0, |hash, &(row, col, mark)| {
+                let (r, c) = image(last, row, col);
+                hash ^ cell_key(r, c, mark)
+            })
+        })
+        .min()
+        .unwrap_or(0)
+}
+
+/// The marks on `board` with their cells
+fn marks(board: &Board) -> impl Iterator<Item = (usize, usize, BoardChar)> + '_ {
+    board.iter().enumerate().flat_map(|(row, cells)| {
+        cells
+            .iter()
+            .enumerate()
+            .filter(|&(_, &mark)| mark != BoardChar::Empty)
+            .map(move |(col, &mark)| (row, col, mark))
+    })
+}
@@ -0,0 +1,62 @@
+//! The moves played so far, with undo and redo.
+
+use std::time::Duration;
+
+use crate::board::BoardChar;
+use crate::r#move::Move;
+
+/// A mark placed on the board
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Placement {
+    pub at: Move,
+    pub mark: BoardChar,
+    /// How long the side thought about the move, if it was timed
+    pub time: Option<Duration>,
+}
+
+/// Every move of a game in the order played, and the moves taken back that
+/// can still be redone
+#[derive(Debug, Default, Clone)]
+pub struct History {
+    played: Vec<Placement>,
+    undone: Vec<Placement>,
+}
+
+impl History {
+    pub fn new() -> History {
+        History::default()
+    }
+
+    /// This function records a new move. A new move starts a new line of
+    /// play, so the moves taken back can no longer be redone.
+    pub fn record(&mut self, placement: Placement) {
+        self.played.push(placement);
+        self.undone.clear();
+    }
+
+    /// This function takes back the last move and returns it
+    pub fn undo(&mut self) -> Option<Placement> {
+        let placement = self.played.pop()?;
+        self.undone.push(placement);
+
+        Some(placement)
+    }
+
+    /// This function plays the last move taken back again and returns it
+    pub fn redo(&mut self) -> Option<Placement> {
+        let placement = self.undone.pop()?;
+        self.played.push(placement);
+
+        Some(placement)
+    }
+
+    /// This function returns the moves played so far, oldest first
+    pub fn played(&self) -> &[Placement] {
+        &self.played
+    }
+
+    pub fn clear(&mut self) {
+        self.played.clear();
+        self.undone.clear();
+    }
+}
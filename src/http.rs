@@ -0,0 +1,470 @@
+//! A REST API over HTTP, so that web and mobile frontends can play against
+//! the engine without linking it. Built only with the `http` feature.
+//!
+//! ```text
+//! POST /game              starts a game, the body may set any of
+//!                         {"size": 3, "win": 3, "player": "X", "difficulty": "hard", "algorithm": "minimax"}
+//!                         -> 201 and the game
+//! POST /game/{id}/move    plays {"move": "2B"} for the player, the machine answers
+//!                         -> 200 and the game
+//! GET /game/{id}          -> 200 and the game
+//! ```
+//!
+//! A game is answered as
+//! `{"id": 1, "reply": "1A", "over": false, "winner": null, "state": {...}}`,
+//! where `reply` is the move the machine answered with, if any, and `state`
+//! the game as `TicTacToe::to_json` writes it. A request that fails is
+//! answered with a 4xx status and `{"error": "<text>"}`. Every connection
+//! carries one request and is closed after the answer.
+//!
+//! A game nobody asked about for `GAME_EXPIRY` is dropped, and at most
+//! `MAX_GAMES` are kept; a new game beyond that is answered with 503. At
+//! most `MAX_CONNECTIONS` connections are served at once, the next ones
+//! wait to be accepted, and a connection that stays silent for
+//! `IO_TIMEOUT` is closed.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::board::BoardChar;
+use crate::engine::TicTacToe;
+use crate::error::GameError;
+use crate::json::{self, ToJson, Value};
+use crate::r#move::{Move, MAX_SIZE};
+use crate::server::{read_line, SERVER_MOVE_BUDGET};
+
+/// The longest request body that is read
+const MAX_BODY: usize = 64 * 1024;
+/// The most header lines a request may have
+const MAX_HEADERS: usize = 64;
+/// The most games kept at once
+pub const MAX_GAMES: usize = 1024;
+/// How long a game is kept after it was last asked about
+pub const GAME_EXPIRY: Duration = Duration::from_secs(30 * 60);
+/// The most connections served at once
+pub const MAX_CONNECTIONS: usize = 64;
+/// How long a connection may stay silent while it is read or written
+pub const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The answer to a request
+#[derive(Debug, PartialEq, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub body: Value,
+}
+
+impl Response {
+    fn error(status: u16, text: &str) -> Response {
+        Response {
+            status,
+            body: Value::Object(vec![("error".to_string(), Value::String(text.to_string()))]),
+        }
+    }
+
+    /// This function answers a move that was refused with the status that
+    /// fits the reason
+    fn refused(e: GameError) -> Response {
+        let status = match e {
+            GameError::InvalidInput(_)
+            | GameError::OutOfBounds
+            | GameError::UnbalancedMarks
+            | GameError::BothSidesWon
+            | GameError::MoveAfterWin => 400,
+            GameError::CellOccupied | GameError::GameAlreadyOver | GameError::WrongBoard | GameError::OutOfTurn => 409,
+        };
+
+        Response::error(status, &e.to_string())
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            201 => "Created",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            409 => "Conflict",
+            413 => "Payload Too Large",
+            431 => "Request Header Fields Too Large",
+            503 => "Service Unavailable",
+            _ => "Error",
+        }
+    }
+}
+
+/// A game and when it was last asked about
+struct Kept {
+    game: Arc<Mutex<TicTacToe>>,
+    touched: Instant,
+}
+
+#[derive(Default)]
+struct Games {
+    next_id: u64,
+    games: HashMap<u64, Kept>,
+}
+
+impl Games {
+    /// This function drops the games nobody asked about for `GAME_EXPIRY`
+    /// before `now`
+    fn expire(&mut self, now: Instant) {
+        self.games.retain(|_, kept| now.saturating_duration_since(kept.touched) < GAME_EXPIRY);
+    }
+}
+
+/// Counts the connections being served, so that no more than
+/// `MAX_CONNECTIONS` are
+#[derive(Default)]
+struct Connections {
+    running: Mutex<usize>,
+    freed: Condvar,
+}
+
+impl Connections {
+    /// This function waits until fewer than `MAX_CONNECTIONS` connections
+    /// are served and counts one more
+    fn enter(&self) {
+        let mut running = self.running.lock().unwrap();
+        while *running >= MAX_CONNECTIONS {
+            running = self.freed.wait(running).unwrap();
+        }
+        *running += 1;
+    }
+}
+
+/// Counts a connection as closed when dropped, even if serving it panicked
+struct Left(Arc<Connections>);
+
+impl Drop for Left {
+    fn drop(&mut self) {
+        *self.0.running.lock().unwrap() -= 1;
+        self.0.freed.notify_one();
+    }
+}
+
+/// The games played over the API. Every game has its own lock, so the
+/// machine thinking in one game does not hold up the others.
+#[derive(Clone, Default)]
+pub struct Api {
+    games: Arc<Mutex<Games>>,
+    connections: Arc<Connections>,
+}
+
+impl Api {
+    pub fn new() -> Api {
+        Api::default()
+    }
+
+    /// This function returns how many games are kept
+    pub fn games(&self) -> usize {
+        self.games.lock().unwrap().games.len()
+    }
+
+    /// This function answers the request `method` `path` with `body`
+    pub fn handle(&self, method: &str, path: &str, body: &str) -> Response {
+        let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+        match (method, &segments[..]) {
+            ("POST", ["game"]) => self.create(body),
+            ("GET", ["game", id]) => self.with_game(id, |id, game| Ok(view(id, game, None))),
+            ("POST", ["game", id, "move"]) => self.with_game(id, |id, game| play(id, game, body)),
+            (_, ["game"]) | (_, ["game", _]) | (_, ["game", _, "move"]) => Response::error(405, "Method not allowed"),
+            _ => Response::error(404, "There is nothing at this path"),
+        }
+    }
+
+    /// This function starts a game set up by `body` and lets the machine
+    /// open it if the player chose O
+    fn create(&self, body: &str) -> Response {
+        let setup = match parse_body(body) {
+            Ok(setup) => setup,
+            Err(e) => return Response::error(400, &e),
+        };
+        let number = |key: &str, default: usize| match setup.get(key) {
+            None => Ok(default),
+            Some(value) => value.as_usize().ok_or(format!("Field '{}' must be a number", key)),
+        };
+        let text = |key: &str| match setup.get(key) {
+            None => Ok(None),
+            Some(value) => value.as_str().map(Some).ok_or(format!("Field '{}' must be a string", key)),
+        };
+
+        let game = (|| -> Result<TicTacToe, String> {
+            let size = number("size", 3)?;
+            let win_len = number("win", size)?;
+            if size == 0 || size > MAX_SIZE || win_len == 0 || win_len > size {
+                return Err(format!("There is no {}x{} board where {} in a row win", size, size, win_len));
+            }
+
+            let player: BoardChar = text("player")?.unwrap_or("X").parse()?;
+            let mut game = TicTacToe::with_size(player, size, win_len);
+            let mut ai = game.ai();
+            if let Some(difficulty) = text("difficulty")? {
+                ai.difficulty = difficulty.parse()?;
+            }
+            if let Some(algorithm) = text("algorithm")? {
+                ai.algorithm = algorithm.parse()?;
+            }
+            game.set_ai(ai);
+            game.set_first(BoardChar::X);
+
+            Ok(game)
+        })();
+
+        let mut game = match game {
+            Ok(game) => game,
+            Err(e) => return Response::error(400, &e),
+        };
+        let reply = machine_turn(&mut game);
+
+        let mut games = self.games.lock().unwrap();
+        let now = Instant::now();
+        games.expire(now);
+        if games.games.len() >= MAX_GAMES {
+            return Response::error(503, "There are too many games, please try again later");
+        }
+
+        games.next_id += 1;
+        let id = games.next_id;
+        let response = view(id, &game, reply);
+        let game = Arc::new(Mutex::new(game));
+        games.games.insert(id, Kept { game, touched: now });
+
+        Response { status: 201, ..response }
+    }
+
+    /// This function looks up the game `id` and answers with `f`
+    fn with_game<F>(&self, id: &str, f: F) -> Response
+    where
+        F: FnOnce(u64, &mut TicTacToe) -> Result<Response, GameError>,
+    {
+        let id = id.parse::<u64>().ok();
+        let game = id.and_then(|id| {
+            let mut games = self.games.lock().unwrap();
+            let kept = games.games.get_mut(&id)?;
+            kept.touched = Instant::now();
+            Some(Arc::clone(&kept.game))
+        });
+        let (id, game) = match (id, game) {
+            (Some(id), Some(game)) => (id, game),
+            _ => return Response::error(404, "There is no such game"),
+        };
+
+        let mut game = game.lock().unwrap();
+        f(id, &mut game).unwrap_or_else(Response::refused)
+    }
+
+    /// This function answers requests on `listener` until it fails, every
+    /// connection is served on a thread of its own, at most
+    /// `MAX_CONNECTIONS` at once
+    pub fn serve(&self, listener: &TcpListener) -> io::Result<()> {
+        loop {
+            self.connections.enter();
+            let left = Left(Arc::clone(&self.connections));
+            let (stream, _) = listener.accept()?;
+            let api = self.clone();
+
+            thread::spawn(move || {
+                let _left = left;
+                api.serve_connection(stream)
+            });
+        }
+    }
+
+    /// This function reads one request from `stream` and writes the answer
+    fn serve_connection(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+
+        let response = match read_request(&mut reader)? {
+            Ok((method, path, body)) => self.handle(&method, &path, &body),
+            Err(response) => response,
+        };
+
+        let body = response.body.to_string();
+        write!(
+            writer,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n{}",
+            response.status,
+            response.reason(),
+            body.len(),
+            body
+        )?;
+        writer.flush()
+    }
+}
+
+/// This function plays the player's move of `body` and the machine's answer
+fn play(id: u64, game: &mut TicTacToe, body: &str) -> Result<Response, GameError> {
+    let request = parse_body(body)?;
+    let m: Move = request
+        .field("move")?
+        .as_str()
+        .ok_or("Field 'move' must be a move like \"2B\"")?
+        .parse()?;
+
+    if game.turn() != game.player_char() {
+        return Err(GameError::InvalidInput("It is not the player's turn".to_string()));
+    }
+    game.play_move(&m)?;
+    let reply = machine_turn(game);
+
+    Ok(view(id, game, reply))
+}
+
+/// This function lets the machine move if it is its turn and returns the move
+fn machine_turn(game: &mut TicTacToe) -> Option<Move> {
+    if game.is_game_over() || game.turn() != game.machine_char() {
+        return None;
+    }
+
+    game.machine_move(SERVER_MOVE_BUDGET).ok().map(|(m, _)| m)
+}
+
+/// This function returns the answer describing `game`
+fn view(id: u64, game: &TicTacToe, reply: Option<Move>) -> Response {
+    let reply = reply.map_or(Value::Null, |m| Value::String(m.to_string()));
+    let winner = game.winner().map_or(Value::Null, |c| c.to_json());
+
+    Response {
+        status: 200,
+        body: Value::Object(vec![
+            ("id".to_string(), Value::Number(id as f64)),
+            ("reply".to_string(), reply),
+            ("over".to_string(), Value::Bool(game.is_game_over())),
+            ("winner".to_string(), winner),
+            ("state".to_string(), game.snapshot().to_json()),
+        ]),
+    }
+}
+
+/// This function reads a request body, an empty one as an empty object
+fn parse_body(body: &str) -> Result<Value, String> {
+    if body.trim().is_empty() {
+        return Ok(Value::Object(Vec::new()));
+    }
+
+    match json::parse(body)? {
+        value @ Value::Object(_) => Ok(value),
+        _ => Err("The body must be a JSON object".to_string()),
+    }
+}
+
+/// This function reads the method, path and body of a request. A request
+/// that is no proper HTTP is answered right away, like one with a line
+/// longer than `server::MAX_LINE` or more than `MAX_HEADERS` headers, so a
+/// client cannot fill the memory of the server.
+fn read_request(reader: &mut impl BufRead) -> io::Result<Result<(String, String, String), Response>> {
+    let line = match read_line(reader) {
+        Ok(line) => line.unwrap_or_default(),
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => return Ok(Err(Response::error(400, "The request line is too long or no text"))),
+        Err(e) => return Err(e),
+    };
+    let mut words = line.split_whitespace();
+    let (method, path) = match (words.next(), words.next()) {
+        (Some(method), Some(path)) => (method.to_string(), path.to_string()),
+        _ => return Ok(Err(Response::error(400, "Expected a request line like GET /game/1 HTTP/1.1"))),
+    };
+
+    let mut length = 0;
+    for count in 0.. {
+        let header = match read_line(reader) {
+            Ok(Some(header)) if !header.trim().is_empty() => header,
+            Ok(_) => break,
+            Err(e) if e.kind() == io::ErrorKind::InvalidData => return Ok(Err(Response::error(431, "A header is too long or no text"))),
+            Err(e) => return Err(e),
+        };
+        if count == MAX_HEADERS {
+            return Ok(Err(Response::error(431, "There are too many headers")));
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = match value.trim().parse() {
+                    Ok(length) => length,
+                    Err(_) => return Ok(Err(Response::error(400, "Content-Length must be a number"))),
+                };
+            }
+        }
+    }
+
+    if length > MAX_BODY {
+        return Ok(Err(Response::error(413, "The body is too large")));
+    }
+
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+    match String::from_utf8(body) {
+        Ok(body) => Ok(Ok((method, path, body))),
+        Err(_) => Ok(Err(Response::error(400, "The body must be UTF-8"))),
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::server::MAX_LINE;
+
+    fn request(text: &str) -> Result<(String, String, String), Response> {
+        read_request(&mut io::Cursor::new(text.as_bytes().to_vec())).expect("a cursor does not fail")
+    }
+
+    #[test]
+    fn a_request_is_read_with_its_body() {
+        let read = request("POST /game HTTP/1.1\r\nContent-Length: 2\r\n\r\n{}");
+        assert_eq!(read, Ok(("POST".to_string(), "/game".to_string(), "{}".to_string())));
+    }
+
+    #[test]
+    fn read_request_refuses_lines_without_end() {
+        let endless = "G".repeat(MAX_LINE + 1);
+        assert_eq!(request(&endless).unwrap_err().status, 400);
+
+        let header = format!("GET /game/1 HTTP/1.1\r\nX-Pad: {}", "a".repeat(MAX_LINE));
+        assert_eq!(request(&header).unwrap_err().status, 431);
+    }
+
+    #[test]
+    fn read_request_refuses_too_many_headers() {
+        let headers = "X-Pad: a\r\n".repeat(MAX_HEADERS + 1);
+        assert_eq!(request(&format!("GET /game/1 HTTP/1.1\r\n{}\r\n", headers)).unwrap_err().status, 431);
+
+        let headers = "X-Pad: a\r\n".repeat(MAX_HEADERS);
+        assert!(request(&format!("GET /game/1 HTTP/1.1\r\n{}\r\n", headers)).is_ok());
+    }
+
+    #[test]
+    fn no_more_than_max_games_are_kept() {
+        let api = Api::new();
+        for _ in 0..MAX_GAMES {
+            assert_eq!(api.handle("POST", "/game", "").status, 201);
+        }
+
+        assert_eq!(api.handle("POST", "/game", "").status, 503);
+        assert_eq!(api.games(), MAX_GAMES);
+    }
+
+    #[test]
+    fn idle_games_are_dropped() {
+        let api = Api::new();
+        assert_eq!(api.handle("POST", "/game", "").status, 201);
+        assert_eq!(api.handle("POST", "/game", "").status, 201);
+
+        // The first game was asked about just before the expiry
+        let later = Instant::now() + GAME_EXPIRY;
+        api.games.lock().unwrap().games.get_mut(&1).unwrap().touched = later - Duration::from_secs(1);
+        api.games.lock().unwrap().expire(later);
+
+        assert_eq!(api.games(), 1);
+        assert_eq!(api.handle("GET", "/game/1", "").status, 200);
+        assert_eq!(api.handle("GET", "/game/2", "").status, 404);
+    }
+}
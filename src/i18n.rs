@@ -0,0 +1,604 @@
+//! The texts the game shows the player, in English, German and Russian.
+//!
+//! The language is set once for the whole program with `Lang::set`, by
+//! default it is the one of the locale. Texts are looked up by their `Text`
+//! key, a `{}` in a text is filled in with `fill`. A `GameError` is
+//! written in English everywhere, `localize` translates it where the
+//! terminal shows it.
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use crate::error::GameError;
+
+/// The language set with `Lang::set`, as its index in `LANGS`
+static CURRENT: AtomicU8 = AtomicU8::new(0);
+
+const LANGS: [Lang; 3] = [Lang::English, Lang::German, Lang::Russian];
+
+#[derive(Debug, PartialEq, Default, Copy, Clone)]
+pub enum Lang {
+    #[default]
+    English,
+    German,
+    Russian,
+}
+
+impl Lang {
+    /// This function returns the language of the locale in `LANGUAGE`,
+    /// `LC_ALL`, `LC_MESSAGES` or `LANG`, English if there is no
+    /// translation for it
+    pub fn detect() -> Lang {
+        let locale = ["LANGUAGE", "LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        locale.parse().unwrap_or_default()
+    }
+
+    /// This function returns the language the texts are shown in
+    pub fn current() -> Lang {
+        LANGS[CURRENT.load(Ordering::Relaxed) as usize]
+    }
+
+    /// This function shows all texts in this language from now on
+    pub fn set(self) {
+        CURRENT.store(self as u8, Ordering::Relaxed);
+    }
+}
+
+impl FromStr for Lang {
+    type Err = GameError;
+
+    /// Reads a language code like `de` or a locale like `de_DE.UTF-8`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim().to_lowercase();
+        let code = code.split(|c: char| !c.is_ascii_alphabetic()).next().unwrap_or("");
+
+        match code {
+            "en" | "english" | "c" | "posix" => Ok(Lang::English),
+            "de" | "german" | "deutsch" => Ok(Lang::German),
+            "ru" | "russian" => Ok(Lang::Russian),
+            _ => Err(GameError::InvalidInput(format!("'{}' is not one of 'en', 'de', 'ru'", s.trim()))),
+        }
+    }
+}
+
+/// The keys of the texts
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Text {
+    ChooseSymbol,
+    /// `{}` is the opponent, e.g. `Text::TheMachine`
+    ChooseOtherSymbol,
+    /// `{}` is the opponent, e.g. `Text::TheMachine`
+    SymbolTaken,
+    HotSeatSymbols,
+    TheMachine,
+    Machine,
+    You,
+    Player1,
+    Player2,
+    YourTurn,
+    /// `{}` is the player whose turn it is
+    PlayerTurn,
+    /// `{}` is the time left
+    TimeLeft,
+    /// `{}` is the move
+    MachineMoved,
+    /// `{}` is the reason, e.g. a `GameError`
+    NotPossible,
+    MachineOpens,
+    YouWon,
+    YouLost,
+    /// `{}` is the winner
+    PlayerWon,
+    Draw,
+    DrawAgreed,
+    /// `{}` is the side that resigned
+    Resigned,
+    /// `{}` is the side whose time ran out
+    OutOfTime,
+    /// `{}` is the side that completed a line in misère
+    CompletedLine,
+    /// `{}` is the opponent of the side that offered the draw
+    AcceptDraw,
+    /// `{}` is the opponent of the side that offered the draw
+    DeclinesDraw,
+    /// `{}` are the side to move and the mark of the opener
+    OfferSwap,
+    /// `{}` are the mark of the opener and the side that took it over,
+    /// e.g. `Text::TheMachine`
+    Swapped,
+    PlayAgain,
+    /// `{}` is the move
+    BestMove,
+    Score,
+    /// `{}` are the games played and the length of the match
+    ScoreAfter,
+    /// `{}` are the player, their wins, the opponent, its wins and the draws
+    ScoreLine,
+    /// `{}` are the wins of both sides and the draws
+    MatchScore,
+    /// `{}` are the winner and the score
+    WonMatch,
+    /// `{}` is the score
+    MatchTied,
+    NoMoveLeft,
+    ConfirmQuit,
+    /// `{}` is the mark of the player, e.g. `X`
+    Restarted,
+    /// `{}` is the file
+    GameSaved,
+    /// `{}` is the file
+    GameLoaded,
+    /// `{}` are the number of the row and its cells
+    Row,
+    Empty,
+    /// `{}` are the empty cells
+    EmptySquares,
+    /// `{}` are the mark and the cells of the line
+    CompletedLineCells,
+    /// `{}` are the side, its mark, the row and the column
+    Placed,
+    ResumeInterrupted,
+    KeypadOnly,
+    /// `{}` are the file and the reason
+    CouldNotSave,
+    /// `{}` are the file and the reason
+    CouldNotLoad,
+    NothingToUndo,
+    /// `{}` are the moves
+    TookBack,
+    NothingToRedo,
+    /// `{}` are the moves
+    PlayedAgain,
+    MoveColumn,
+    SideColumn,
+    TimeColumn,
+    /// `{}` are the side, its time, its moves and the time per move
+    ThinkingTotal,
+    OnlineTurn,
+    WaitingForOpponent,
+    /// `{}` is the move
+    OpponentMoved,
+    /// `{}` is what the opponent said
+    OpponentSays,
+    /// `{}` is the reason
+    OpponentRefused,
+    OpponentLeft,
+    YouResigned,
+    OpponentResigned,
+    WaitingForRematch,
+    NoRematch,
+    ReplayStep,
+    ReplayEnd,
+    ReplayStart,
+    /// `{}` are the number of the move, the moves, the mark and the cell
+    ReplayMove,
+    /// `{}` are the moves
+    ReplayEmpty,
+    /// `{}` is the time
+    ThoughtFor,
+    PuzzleTurn,
+    Solved,
+    /// `{}` are the other solutions
+    SolvedToo,
+    NotQuite,
+    /// `{}` are the date and the outcome, e.g. `Text::Win`
+    DailyDone,
+    /// `{}` is the streak
+    DailyTomorrow,
+    /// `{}` is the challenge
+    DailyChallenge,
+    /// `{}` is the streak
+    DailyStreak,
+    DailySlipped,
+    Win,
+    Loss,
+    DrawOutcome,
+    NoGamesYet,
+    /// `{}` is the number of games
+    GamesPlayed,
+    Wins,
+    Losses,
+    Draws,
+    /// `{}` are the outcome, e.g. `Text::Win`, and the length
+    CurrentStreak,
+    /// `{}` is the length
+    LongestWinning,
+    /// `{}` is the length
+    LongestLosing,
+    /// `{}` is the number of moves
+    AverageLength,
+    /// `{}` is the rating
+    Rating,
+    /// `{}` are the opponent and its rating after one game
+    RatingAfterGame,
+    /// `{}` are the opponent, its rating and the games
+    RatingAfterGames,
+    /// `{}` is the difficulty
+    SuggestedDifficulty,
+    OutOfBounds,
+    CellOccupied,
+    GameAlreadyOver,
+    WrongBoard,
+    OutOfTurn,
+    UnbalancedMarks,
+    BothSidesWon,
+    MoveAfterWin,
+}
+
+impl Text {
+    /// This function returns the text in the current language
+    pub fn get(self) -> &'static str {
+        self.in_lang(Lang::current())
+    }
+
+    /// This function returns the text in `lang`
+    pub fn in_lang(self, lang: Lang) -> &'static str {
+        let [en, de, ru] = self.translations();
+
+        match lang {
+            Lang::English => en,
+            Lang::German => de,
+            Lang::Russian => ru,
+        }
+    }
+
+    fn translations(self) -> [&'static str; 3] {
+        match self {
+            Text::ChooseSymbol => [
+                "Please choose a symbol: X, O or any other character",
+                "Bitte wähle ein Symbol: X, O oder ein anderes Zeichen",
+                "Выберите символ: X, O или любой другой знак",
+            ],
+            Text::ChooseOtherSymbol => [
+                "Please choose the symbol of {}",
+                "Bitte wähle das Symbol des Gegners ({})",
+                "Выберите символ соперника ({})",
+            ],
+            Text::SymbolTaken => [
+                "{} needs a symbol different from yours",
+                "Der Gegner ({}) braucht ein anderes Symbol als du",
+                "Соперник ({}) должен играть другим символом",
+            ],
+            Text::HotSeatSymbols => [
+                "Player 1 chooses first, Player 2 plays the other symbol",
+                "Spieler 1 wählt zuerst, Spieler 2 spielt das andere Symbol",
+                "Игрок 1 выбирает первым, игрок 2 играет другим символом",
+            ],
+            Text::TheMachine => ["the machine", "Computer", "компьютер"],
+            Text::Machine => ["machine", "Computer", "компьютер"],
+            Text::You => ["you", "du", "вы"],
+            Text::Player1 => ["Player 1", "Spieler 1", "Игрок 1"],
+            Text::Player2 => ["Player 2", "Spieler 2", "Игрок 2"],
+            Text::YourTurn => ["your turn", "du bist am Zug", "ваш ход"],
+            Text::PlayerTurn => ["{}, your turn", "{}, du bist am Zug", "{}, ваш ход"],
+            Text::TimeLeft => ["{} left", "noch {}", "осталось {}"],
+            Text::MachineMoved => ["machine moved to: {}", "Der Computer zieht: {}", "ход компьютера: {}"],
+            Text::NotPossible => [
+                "This move is not possible, {}",
+                "Dieser Zug ist nicht möglich, {}",
+                "Этот ход невозможен: {}",
+            ],
+            Text::MachineOpens => [
+                "The machine opens the game",
+                "Der Computer beginnt",
+                "Компьютер ходит первым",
+            ],
+            Text::YouWon => ["Congratulations, you won!", "Glückwunsch, du hast gewonnen!", "Поздравляем, вы победили!"],
+            Text::YouLost => ["Sorry, but you lost", "Schade, du hast verloren", "К сожалению, вы проиграли"],
+            Text::PlayerWon => ["Congratulations, {} won!", "Glückwunsch, {} gewinnt!", "Поздравляем, победа: {}!"],
+            Text::Draw => ["Draw", "Unentschieden", "Ничья"],
+            Text::DrawAgreed => ["The draw was agreed", "Remis vereinbart", "Ничья по соглашению"],
+            Text::Resigned => ["{} resigned", "Aufgegeben: {}", "Сдача: {}"],
+            Text::OutOfTime => ["{} ran out of time", "Zeit abgelaufen: {}", "Время вышло: {}"],
+            Text::CompletedLine => ["{} completed a line", "Reihe vollendet: {}", "Линию собрал: {}"],
+            Text::AcceptDraw => [
+                "{}, do you accept a draw? (y/n)",
+                "{}, nimmst du das Remis an? (j/n)",
+                "{}, вы согласны на ничью? (д/н)",
+            ],
+            Text::DeclinesDraw => ["{} declines the draw", "Remis abgelehnt: {}", "Ничья отклонена: {}"],
+            Text::OfferSwap => [
+                "{}, swap sides and take over {}? (y/n)",
+                "{}, die Seiten tauschen und {} übernehmen? (j/n)",
+                "{}, поменяться сторонами и взять {}? (д/н)",
+            ],
+            Text::Swapped => [
+                "Sides swapped, {} now belongs to {}",
+                "Seiten getauscht, {} gehört jetzt: {}",
+                "Стороны поменялись, {} теперь играет: {}",
+            ],
+            Text::PlayAgain => ["Play again? (y/n)", "Noch einmal? (j/n)", "Сыграть ещё раз? (д/н)"],
+            Text::BestMove => ["Best move: {}", "Bester Zug: {}", "Лучший ход: {}"],
+            Text::Score => ["Score: ", "Stand: ", "Счёт: "],
+            Text::ScoreAfter => [
+                "Score after {} of at most {} games: ",
+                "Stand nach {} von höchstens {} Spielen: ",
+                "Счёт после {} из не более чем {} партий: ",
+            ],
+            Text::ScoreLine => [
+                "{} {}, {} {}, {} drawn",
+                "{} {}, {} {}, {} unentschieden",
+                "{} {}, {} {}, ничьих: {}",
+            ],
+            Text::MatchScore => ["{}-{}, {} drawn", "{}:{}, {} unentschieden", "{}:{}, ничьих: {}"],
+            Text::WonMatch => ["{} won the match {}", "Match gewonnen: {} mit {}", "Матч выиграл: {}, {}"],
+            Text::MatchTied => [
+                "The match ended in a tie, {}",
+                "Das Match endet unentschieden, {}",
+                "Матч закончился вничью, {}",
+            ],
+            Text::NoMoveLeft => ["There is no move left", "Es gibt keinen Zug mehr", "Ходов больше нет"],
+            Text::ConfirmQuit => [
+                "Quit the game? It is not finished (y/n)",
+                "Das Spiel beenden? Es ist nicht zu Ende gespielt (j/n)",
+                "Выйти из игры? Партия не закончена (д/н)",
+            ],
+            Text::Restarted => [
+                "The game starts over, you play {}",
+                "Das Spiel beginnt von vorn, du spielst {}",
+                "Партия начинается заново, вы играете {}",
+            ],
+            Text::GameSaved => ["Game saved to {}", "Spiel gespeichert in {}", "Партия сохранена в {}"],
+            Text::GameLoaded => ["Game loaded from {}", "Spiel geladen aus {}", "Партия загружена из {}"],
+            Text::Row => ["Row {}: {}", "Reihe {}: {}", "Ряд {}: {}"],
+            Text::Empty => ["empty", "leer", "пусто"],
+            Text::EmptySquares => ["Empty squares: {}", "Freie Felder: {}", "Свободные клетки: {}"],
+            Text::CompletedLineCells => [
+                "{} completed the line {}",
+                "Reihe von {} vollendet: {}",
+                "Линия {} собрана: {}",
+            ],
+            Text::Placed => [
+                "{} placed {} in row {}, column {}",
+                "{} setzt {} in Reihe {}, Spalte {}",
+                "{}: {} в ряд {}, столбец {}",
+            ],
+            Text::ResumeInterrupted => [
+                "This game was interrupted, resume it? (y/n)",
+                "Dieses Spiel wurde unterbrochen, fortsetzen? (j/n)",
+                "Эта партия была прервана, продолжить? (д/н)",
+            ],
+            Text::KeypadOnly => [
+                "Keypad digits only name the cells of a 3x3 board",
+                "Ziffern des Ziffernblocks benennen nur die Felder eines 3x3-Bretts",
+                "Цифры цифрового блока обозначают клетки только на доске 3x3",
+            ],
+            Text::CouldNotSave => [
+                "Could not save {}: {}",
+                "Konnte {} nicht speichern: {}",
+                "Не удалось сохранить {}: {}",
+            ],
+            Text::CouldNotLoad => ["Could not load {}: {}", "Konnte {} nicht laden: {}", "Не удалось загрузить {}: {}"],
+            Text::NothingToUndo => [
+                "There is no move to undo",
+                "Es gibt keinen Zug zum Zurücknehmen",
+                "Нет хода, который можно отменить",
+            ],
+            Text::TookBack => ["took back: {}", "zurückgenommen: {}", "отменено: {}"],
+            Text::NothingToRedo => [
+                "There is no move to redo",
+                "Es gibt keinen Zug zum Wiederholen",
+                "Нет хода, который можно повторить",
+            ],
+            Text::PlayedAgain => ["played again: {}", "erneut gespielt: {}", "сыграно снова: {}"],
+            Text::MoveColumn => ["move", "Zug", "ход"],
+            Text::SideColumn => ["side", "Seite", "сторона"],
+            Text::TimeColumn => ["time", "Zeit", "время"],
+            Text::ThinkingTotal => [
+                "{}: {} in {} moves, {} per move",
+                "{}: {} in {} Zügen, {} pro Zug",
+                "{}: {} за {} ходов, {} на ход",
+            ],
+            Text::OnlineTurn => [
+                "your turn (or resign, or /say TEXT): ",
+                "du bist am Zug (oder resign, oder /say TEXT): ",
+                "ваш ход (или resign, или /say ТЕКСТ): ",
+            ],
+            Text::WaitingForOpponent => [
+                "waiting for your opponent...",
+                "warte auf den Gegner...",
+                "ждём соперника...",
+            ],
+            Text::OpponentMoved => ["opponent moved to: {}", "Der Gegner zieht: {}", "ход соперника: {}"],
+            Text::OpponentSays => ["opponent: {}", "Gegner: {}", "соперник: {}"],
+            Text::OpponentRefused => ["Your opponent refused: {}", "Dein Gegner lehnt ab: {}", "Соперник отказал: {}"],
+            Text::OpponentLeft => [
+                "Your opponent left the game",
+                "Dein Gegner hat das Spiel verlassen",
+                "Соперник покинул игру",
+            ],
+            Text::YouResigned => ["You resigned", "Du hast aufgegeben", "Вы сдались"],
+            Text::OpponentResigned => [
+                "Your opponent resigned, you won!",
+                "Dein Gegner hat aufgegeben, du hast gewonnen!",
+                "Соперник сдался, вы победили!",
+            ],
+            Text::WaitingForRematch => [
+                "waiting for your opponent's answer...",
+                "warte auf die Antwort des Gegners...",
+                "ждём ответа соперника...",
+            ],
+            Text::NoRematch => [
+                "Your opponent does not want another game",
+                "Dein Gegner will kein weiteres Spiel",
+                "Соперник не хочет играть ещё",
+            ],
+            Text::ReplayStep => [
+                "n(ext, or just Enter), p(rev), a(uto) or q(uit): ",
+                "n (weiter, oder einfach Enter), p (zurück), a (automatisch) oder q (beenden): ",
+                "n (дальше, или просто Enter), p (назад), a (авто) или q (выход): ",
+            ],
+            Text::ReplayEnd => ["This is the end of the game", "Das ist das Ende des Spiels", "Это конец партии"],
+            Text::ReplayStart => [
+                "This is the start of the game",
+                "Das ist der Anfang des Spiels",
+                "Это начало партии",
+            ],
+            Text::ReplayMove => [
+                "move {} of {}: {} played {}",
+                "Zug {} von {}: {} spielt {}",
+                "ход {} из {}: {} на {}",
+            ],
+            Text::ReplayEmpty => ["move 0 of {}", "Zug 0 von {}", "ход 0 из {}"],
+            Text::ThoughtFor => ["thought for {}", "Bedenkzeit: {}", "обдумывание: {}"],
+            Text::PuzzleTurn => ["your move (or skip): ", "dein Zug (oder skip): ", "ваш ход (или skip): "],
+            Text::Solved => ["Solved!", "Gelöst!", "Решено!"],
+            Text::SolvedToo => ["Solved! {} works as well", "Gelöst! {} geht auch", "Решено! {} тоже подходит"],
+            Text::NotQuite => [
+                "Not quite, there is a better move. Try again",
+                "Nicht ganz, es gibt einen besseren Zug. Versuch es noch einmal",
+                "Не совсем, есть ход лучше. Попробуйте ещё раз",
+            ],
+            Text::DailyDone => [
+                "You played the challenge of {} already, it was a {}",
+                "Du hast die Aufgabe vom {} schon gespielt: {}",
+                "Вы уже сыграли задание за {}: {}",
+            ],
+            Text::DailyTomorrow => [
+                "Daily challenges won in a row: {}, come back tomorrow",
+                "Tägliche Aufgaben in Folge gewonnen: {}, komm morgen wieder",
+                "Ежедневных заданий выиграно подряд: {}, приходите завтра",
+            ],
+            Text::DailyChallenge => [
+                "{}, against the machine on hard",
+                "{}, gegen den Computer auf schwer",
+                "{}, против компьютера на уровне hard",
+            ],
+            Text::DailyStreak => [
+                "Daily challenges won in a row: {}",
+                "Tägliche Aufgaben in Folge gewonnen: {}",
+                "Ежедневных заданий выиграно подряд: {}",
+            ],
+            Text::DailySlipped => [
+                "The win slipped away, the streak starts over tomorrow",
+                "Der Sieg ist entwischt, die Serie beginnt morgen von vorn",
+                "Победа ускользнула, серия начнётся заново завтра",
+            ],
+            Text::Win => ["win", "Sieg", "победа"],
+            Text::Loss => ["loss", "Niederlage", "поражение"],
+            Text::DrawOutcome => ["draw", "Unentschieden", "ничья"],
+            Text::NoGamesYet => ["No games played yet", "Noch keine Spiele gespielt", "Сыгранных партий пока нет"],
+            Text::GamesPlayed => ["Games played: {}", "Gespielte Spiele: {}", "Сыграно партий: {}"],
+            Text::Wins => ["Wins:", "Siege:", "Победы:"],
+            Text::Losses => ["Losses:", "Niederlagen:", "Поражения:"],
+            Text::Draws => ["Draws:", "Remis:", "Ничьи:"],
+            Text::CurrentStreak => [
+                "Current streak: {}, {} in a row",
+                "Aktuelle Serie: {}, {} in Folge",
+                "Текущая серия: {}, {} подряд",
+            ],
+            Text::LongestWinning => [
+                "Longest winning streak: {}",
+                "Längste Siegesserie: {}",
+                "Самая длинная серия побед: {}",
+            ],
+            Text::LongestLosing => [
+                "Longest losing streak: {}",
+                "Längste Niederlagenserie: {}",
+                "Самая длинная серия поражений: {}",
+            ],
+            Text::AverageLength => [
+                "Average game length: {} moves",
+                "Durchschnittliche Spiellänge: {} Züge",
+                "Средняя длина партии: {} ходов",
+            ],
+            Text::Rating => ["Rating: {}", "Wertung: {}", "Рейтинг: {}"],
+            Text::RatingAfterGame => ["  {}: {} after 1 game", "  {}: {} nach 1 Spiel", "  {}: {} после 1 партии"],
+            Text::RatingAfterGames => [
+                "  {}: {} after {} games",
+                "  {}: {} nach {} Spielen",
+                "  {}: {} после {} партий",
+            ],
+            Text::SuggestedDifficulty => [
+                "Suggested difficulty: {}",
+                "Empfohlene Stufe: {}",
+                "Рекомендуемый уровень: {}",
+            ],
+            Text::OutOfBounds => [
+                "the cell is outside the board",
+                "das Feld liegt außerhalb des Bretts",
+                "клетка за пределами доски",
+            ],
+            Text::CellOccupied => [
+                "the cell is already occupied",
+                "das Feld ist schon besetzt",
+                "клетка уже занята",
+            ],
+            Text::GameAlreadyOver => [
+                "the game is already over",
+                "das Spiel ist schon vorbei",
+                "партия уже окончена",
+            ],
+            Text::WrongBoard => [
+                "the move must be played on another board",
+                "der Zug muss auf einem anderen Brett gespielt werden",
+                "ход нужно сделать на другой доске",
+            ],
+            Text::OutOfTurn => [
+                "it is the other side's turn",
+                "die andere Seite ist am Zug",
+                "сейчас ход другой стороны",
+            ],
+            Text::UnbalancedMarks => [
+                "one side has more than one mark more than the other",
+                "eine Seite hat mehr als ein Zeichen mehr als die andere",
+                "у одной стороны больше чем на одну отметку больше, чем у другой",
+            ],
+            Text::BothSidesWon => [
+                "both sides completed a line",
+                "beide Seiten haben eine Reihe vollendet",
+                "обе стороны собрали линию",
+            ],
+            Text::MoveAfterWin => [
+                "marks were placed after a line was completed",
+                "nach einer vollendeten Reihe wurden noch Zeichen gesetzt",
+                "отметки поставлены уже после того, как линия была собрана",
+            ],
+        }
+    }
+}
+
+impl fmt::Display for Text {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.get())
+    }
+}
+
+/// This function returns `text` in the current language with every `{}`
+/// filled in with the next of `args`
+pub fn fill(text: Text, args: &[&dyn fmt::Display]) -> String {
+    fill_in(text, Lang::current(), args)
+}
+
+/// This function returns `text` in `lang` with every `{}` filled in with
+/// the next of `args`
+pub fn fill_in(text: Text, lang: Lang, args: &[&dyn fmt::Display]) -> String {
+    let mut parts = text.in_lang(lang).split("{}");
+    let mut filled = parts.next().unwrap_or("").to_string();
+
+    for (i, part) in parts.enumerate() {
+        if let Some(arg) = args.get(i) {
+            filled.push_str(&arg.to_string());
+        }
+        filled.push_str(part);
+    }
+
+    filled
+}
+
+/// This function returns `error` in the current language. The message of
+/// `InvalidInput` is shown as it is.
+pub fn localize(error: &GameError) -> String {
+    match error.text() {
+        Some(text) => text.get().to_string(),
+        None => error.to_string(),
+    }
+}
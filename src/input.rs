@@ -0,0 +1,83 @@
+//! Where the lines typed by the players come from.
+//!
+//! The terminal frontend reads every answer and move through an
+//! `InputSource`, so a game can be driven from the keyboard as well as from
+//! a prepared script or a text held in memory.
+
+use std::collections::VecDeque;
+use std::io::{self, BufRead, Cursor};
+
+pub trait InputSource {
+    /// This function returns the next line without its line break, `None`
+    /// once the input has ended
+    fn read_line(&mut self) -> io::Result<Option<String>>;
+}
+
+/// This function reads a line from `reader` for an `InputSource`
+fn read_from(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if reader.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+
+    Ok(Some(line.trim_end_matches(['\r', '\n']).to_string()))
+}
+
+/// Lines typed on the terminal
+#[derive(Debug, Default, Copy, Clone)]
+pub struct StdinInput;
+
+impl InputSource for StdinInput {
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        read_from(&mut io::stdin().lock())
+    }
+}
+
+/// Lines given one by one up front, e.g. the moves of a scripted game
+#[derive(Debug, Default, Clone)]
+pub struct ScriptedInput {
+    lines: VecDeque<String>,
+}
+
+impl ScriptedInput {
+    pub fn new<I, S>(lines: I) -> ScriptedInput
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        ScriptedInput {
+            lines: lines.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// This function returns how many lines have not been read yet
+    pub fn remaining(&self) -> usize {
+        self.lines.len()
+    }
+}
+
+impl InputSource for ScriptedInput {
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        Ok(self.lines.pop_front())
+    }
+}
+
+/// Lines of a text held in memory, e.g. a file read at once
+#[derive(Debug, Default, Clone)]
+pub struct BufferInput {
+    reader: Cursor<String>,
+}
+
+impl BufferInput {
+    pub fn new(text: impl Into<String>) -> BufferInput {
+        BufferInput {
+            reader: Cursor::new(text.into()),
+        }
+    }
+}
+
+impl InputSource for BufferInput {
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        read_from(&mut self.reader)
+    }
+}
@@ -0,0 +1,114 @@
+//! Catching Ctrl-C while a game is played. The game is written to a
+//! recovery file after every move, so on Ctrl-C there is nothing left to do
+//! but to tell the player it is kept and to leave. Outside of Unix Ctrl-C
+//! ends the program as before, the recovery file is there all the same.
+//!
+//! While the terminal is switched into a mode of its own, e.g. while the
+//! machine thinks and keys are read one by one, leaving at once would leave
+//! the shell in that mode. Ctrl-C is then only noted, and whoever holds the
+//! `Defer` restores the terminal and calls `leave`.
+
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Set while a `Defer` lives, Ctrl-C is then noted instead of ending the program
+static DEFERRED: AtomicBool = AtomicBool::new(false);
+/// Set once Ctrl-C was pressed while it was deferred
+static PENDING: AtomicBool = AtomicBool::new(false);
+
+const NOTE: &str = "\nInterrupted, the game is kept and offered again at the next start\n";
+
+/// Catches Ctrl-C for as long as it lives and lets Ctrl-C end the program
+/// right away again when dropped
+pub struct Guard;
+
+impl Guard {
+    pub fn catch() -> Guard {
+        imp::catch();
+        Guard
+    }
+}
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        imp::release();
+    }
+}
+
+/// Keeps Ctrl-C from ending the program for as long as it lives, see
+/// `pending`
+pub struct Defer;
+
+impl Defer {
+    pub fn begin() -> Defer {
+        DEFERRED.store(true, Ordering::SeqCst);
+        Defer
+    }
+}
+
+impl Drop for Defer {
+    fn drop(&mut self) {
+        DEFERRED.store(false, Ordering::SeqCst);
+    }
+}
+
+/// This function returns true if Ctrl-C was pressed while it was deferred
+pub fn pending() -> bool {
+    PENDING.load(Ordering::SeqCst)
+}
+
+/// This function tells the player that the game is kept and ends the
+/// program the way Ctrl-C does
+pub fn leave() -> ! {
+    eprint!("{}", NOTE);
+    process::exit(130);
+}
+
+#[cfg(unix)]
+mod imp {
+    use std::os::raw::{c_int, c_void};
+    use std::sync::atomic::Ordering;
+
+    use super::{DEFERRED, NOTE, PENDING};
+
+    const SIGINT: c_int = 2;
+    const SIG_DFL: usize = 0;
+
+    extern "C" {
+        fn signal(signum: c_int, handler: usize) -> usize;
+        fn write(fd: c_int, buf: *const c_void, count: usize) -> isize;
+        fn _exit(status: c_int) -> !;
+    }
+
+    /// Only calls that are safe in a signal handler are made here
+    extern "C" fn on_interrupt(_: c_int) {
+        if DEFERRED.load(Ordering::SeqCst) {
+            PENDING.store(true, Ordering::SeqCst);
+            return;
+        }
+
+        unsafe {
+            write(2, NOTE.as_ptr().cast(), NOTE.len());
+            _exit(130);
+        }
+    }
+
+    pub fn catch() {
+        unsafe {
+            signal(SIGINT, on_interrupt as extern "C" fn(c_int) as usize);
+        }
+    }
+
+    pub fn release() {
+        unsafe {
+            signal(SIGINT, SIG_DFL);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+mod imp {
+    pub fn catch() {}
+
+    pub fn release() {}
+}
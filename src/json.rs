@@ -0,0 +1,655 @@
+//! A small JSON reader and writer, just enough to exchange game state with
+//! a web frontend without pulling in any dependencies.
+//!
+//! The game state itself (marks, moves, `GameSnapshot` and the rendered
+//! grid) is only written and read with the `serde` feature, the statistics
+//! and puzzle progress always are.
+
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::time::Duration;
+
+use crate::puzzle::Progress;
+#[cfg(feature = "serde")]
+use crate::render::{Grid, StyledCell};
+use crate::stats::{GameRecord, Stats};
+#[cfg(feature = "serde")]
+use crate::{BoardChar, GameSnapshot, Move, Placement, Symbols};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Value {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Value>),
+    /// Members keep the order they were written or parsed in
+    Object(Vec<(String, Value)>),
+}
+
+/// Types that can be written as JSON
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+/// Types that can be read back from JSON
+pub trait FromJson: Sized {
+    fn from_json(value: &Value) -> Result<Self, String>;
+}
+
+impl Value {
+    /// This function returns the member `key` of an object
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// This function returns the member `key` of an object or an error
+    /// naming the missing key
+    pub fn field(&self, key: &str) -> Result<&Value, String> {
+        self.get(key).ok_or_else(|| format!("Missing field '{}'", key))
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// This function returns the value as a non-negative integer
+    pub fn as_usize(&self) -> Option<usize> {
+        match *self {
+            Value::Number(n) if n >= 0.0 && n.fract() == 0.0 => Some(n as usize),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            Value::Bool(b) => Some(b),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => write!(f, "null"),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Number(n) => write!(f, "{}", n),
+            Value::String(s) => write_string(f, s),
+            Value::Array(items) => {
+                write!(f, "[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Value::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_string(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+fn write_string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"")?;
+    for c in s.chars() {
+        match c {
+            '"' => write!(f, "\\\"")?,
+            '\\' => write!(f, "\\\\")?,
+            '\n' => write!(f, "\\n")?,
+            '\r' => write!(f, "\\r")?,
+            '\t' => write!(f, "\\t")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    write!(f, "\"")
+}
+
+/// How deep arrays and objects may be nested, so that a document made up
+/// to overflow the stack of the recursive parser is refused instead
+pub const MAX_DEPTH: usize = 64;
+
+/// This function parses a complete JSON document
+pub fn parse(input: &str) -> Result<Value, String> {
+    let mut parser = Parser {
+        chars: input.chars().collect(),
+        pos: 0,
+        depth: 0,
+    };
+
+    let value = parser.value()?;
+    parser.skip_whitespace();
+    if parser.pos < parser.chars.len() {
+        return Err(format!("Unexpected trailing input at {}", parser.pos));
+    }
+
+    Ok(value)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    /// How many arrays and objects the value being parsed lies in
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek();
+        self.pos += 1;
+        c
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek() {
+            if !c.is_whitespace() {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("Expected '{}' but found '{}' at {}", expected, c, self.pos - 1)),
+            None => Err(format!("Expected '{}' but input ended", expected)),
+        }
+    }
+
+    fn keyword(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        for expected in word.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+
+        match self.peek() {
+            Some('n') => self.keyword("null", Value::Null),
+            Some('t') => self.keyword("true", Value::Bool(true)),
+            Some('f') => self.keyword("false", Value::Bool(false)),
+            Some('"') => self.string().map(Value::String),
+            Some('[') | Some('{') => self.nested(),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(format!("Unexpected '{}' at {}", c, self.pos)),
+            None => Err("Unexpected end of input".to_string()),
+        }
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_digit() || "+-.eE".contains(c) {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        let text: String = self.chars[start..self.pos].iter().collect();
+        text.parse()
+            .map(Value::Number)
+            .map_err(|_| format!("Invalid number '{}'", text))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut s = String::new();
+
+        loop {
+            match self.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match self.next() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('/') => s.push('/'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('b') => s.push('\u{8}'),
+                    Some('f') => s.push('\u{c}'),
+                    Some('u') => {
+                        let hex: String = (0..4).filter_map(|_| self.next()).collect();
+                        let code = u32::from_str_radix(&hex, 16)
+                            .map_err(|_| format!("Invalid escape '\\u{}'", hex))?;
+                        s.push(std::char::from_u32(code).unwrap_or('\u{FFFD}'));
+                    }
+                    Some(c) => return Err(format!("Invalid escape '\\{}'", c)),
+                    None => return Err("Unterminated string".to_string()),
+                },
+                Some(c) => s.push(c),
+                None => return Err("Unterminated string".to_string()),
+            }
+        }
+    }
+
+    /// This function parses an array or an object, one level deeper
+    fn nested(&mut self) -> Result<Value, String> {
+        if self.depth >= MAX_DEPTH {
+            return Err("nested too deeply".to_string());
+        }
+
+        self.depth += 1;
+        let value = if self.peek() == Some('[') { self.array() } else { self.object() };
+        self.depth -= 1;
+
+        value
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some(']') {
+            self.pos += 1;
+            return Ok(Value::Array(items));
+        }
+
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Value::Array(items)),
+                _ => return Err(format!("Expected ',' or ']' at {}", self.pos - 1)),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        let mut members = Vec::new();
+
+        self.skip_whitespace();
+        if self.peek() == Some('}') {
+            self.pos += 1;
+            return Ok(Value::Object(members));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.value()?;
+            members.push((key, value));
+
+            self.skip_whitespace();
+            match self.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Value::Object(members)),
+                _ => return Err(format!("Expected ',' or '}}' at {}", self.pos - 1)),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for BoardChar {
+    /// Marks are written as "X" and "O", an empty cell as null
+    fn to_json(&self) -> Value {
+        match *self {
+            BoardChar::Empty => Value::Null,
+            c => Value::String(c.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for BoardChar {
+    fn from_json(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Null => Ok(BoardChar::Empty),
+            Value::String(s) => Ok(s.parse()?),
+            _ => Err(format!("Expected \"X\", \"O\" or null but found {}", value)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for Move {
+    fn to_json(&self) -> Value {
+        Value::Object(vec![
+            ("row".to_string(), Value::Number(self.row as f64)),
+            ("col".to_string(), Value::Number(self.col as f64)),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for Move {
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let coordinate = |key| {
+            value
+                .field(key)?
+                .as_usize()
+                .ok_or_else(|| format!("Field '{}' must be a non-negative integer", key))
+        };
+
+        Ok(Move {
+            row: coordinate("row")?,
+            col: coordinate("col")?,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for StyledCell {
+    fn to_json(&self) -> Value {
+        Value::Object(vec![
+            ("mark".to_string(), self.mark.to_json()),
+            ("glyph".to_string(), Value::String(self.glyph.to_string())),
+            ("color".to_string(), self.color.map_or(Value::Null, |color| Value::String(color.to_string()))),
+            ("win_line".to_string(), Value::Bool(self.win_line)),
+            ("highlight".to_string(), Value::Bool(self.highlight)),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for Grid {
+    /// A grid is written as an array of rows, each an array of cells
+    fn to_json(&self) -> Value {
+        Value::Array(self.rows.iter().map(|row| Value::Array(row.iter().map(ToJson::to_json).collect())).collect())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for Placement {
+    fn to_json(&self) -> Value {
+        let mut value = Value::Object(vec![
+            ("row".to_string(), Value::Number(self.at.row as f64)),
+            ("col".to_string(), Value::Number(self.at.col as f64)),
+            ("mark".to_string(), self.mark.to_json()),
+        ]);
+        if let (Value::Object(fields), Some(time)) = (&mut value, self.time) {
+            fields.push(("ms".to_string(), Value::Number(time.as_millis() as f64)));
+        }
+
+        value
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for Placement {
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let mark = BoardChar::from_json(value.field("mark")?)?;
+        if mark == BoardChar::Empty {
+            return Err("A move in the history must place \"X\" or \"O\"".to_string());
+        }
+
+        let time = match value.get("ms") {
+            None => None,
+            Some(ms) => Some(Duration::from_millis(ms.as_usize().ok_or("\"ms\" must be a number of milliseconds")? as u64)),
+        };
+
+        Ok(Placement {
+            at: Move::from_json(value)?,
+            mark,
+            time,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for Symbols {
+    fn to_json(&self) -> Value {
+        Value::Object(vec![
+            ("X".to_string(), Value::String(self.x.to_string())),
+            ("O".to_string(), Value::String(self.o.to_string())),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for Symbols {
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let glyph = |key| {
+            let s = value.field(key)?.as_str().unwrap_or_default();
+            let mut chars = s.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(format!("Glyph of '{}' must be a single character", key)),
+            }
+        };
+
+        Ok(Symbols {
+            x: glyph("X")?,
+            o: glyph("O")?,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl ToJson for GameSnapshot {
+    fn to_json(&self) -> Value {
+        let board = self
+            .board
+            .iter()
+            .map(|row| Value::Array(row.iter().map(ToJson::to_json).collect()))
+            .collect();
+
+        Value::Object(vec![
+            ("board".to_string(), Value::Array(board)),
+            ("win_len".to_string(), Value::Number(self.win_len as f64)),
+            ("player".to_string(), self.player_char.to_json()),
+            ("machine".to_string(), self.machine_char.to_json()),
+            ("symbols".to_string(), self.symbols.to_json()),
+            ("turn".to_string(), self.turn.to_json()),
+            (
+                "history".to_string(),
+                Value::Array(self.history.iter().map(ToJson::to_json).collect()),
+            ),
+            ("misere".to_string(), Value::Bool(self.misere)),
+            (
+                "handicap".to_string(),
+                Value::Array(self.handicap.iter().map(|m| Value::String(m.to_string())).collect()),
+            ),
+        ])
+    }
+}
+
+#[cfg(feature = "serde")]
+impl FromJson for GameSnapshot {
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let rows = value
+            .field("board")?
+            .as_array()
+            .ok_or("Field 'board' must be an array of rows")?;
+
+        let mut board = Vec::new();
+        for row in rows {
+            let cells = row.as_array().ok_or("Every board row must be an array")?;
+            board.push(cells.iter().map(BoardChar::from_json).collect::<Result<Vec<_>, _>>()?);
+        }
+
+        Ok(GameSnapshot {
+            board,
+            win_len: value
+                .field("win_len")?
+                .as_usize()
+                .ok_or("Field 'win_len' must be a non-negative integer")?,
+            player_char: BoardChar::from_json(value.field("player")?)?,
+            machine_char: BoardChar::from_json(value.field("machine")?)?,
+            // Older states without glyphs use the classic X and O
+            symbols: match value.get("symbols") {
+                Some(symbols) => Symbols::from_json(symbols)?,
+                None => Symbols::default(),
+            },
+            turn: BoardChar::from_json(value.field("turn")?)?,
+            history: match value.get("history") {
+                Some(history) => history
+                    .as_array()
+                    .ok_or("Field 'history' must be an array of moves")?
+                    .iter()
+                    .map(Placement::from_json)
+                    .collect::<Result<_, _>>()?,
+                None => Vec::new(),
+            },
+            // Older states were always the classic game
+            misere: match value.get("misere") {
+                Some(misere) => misere.as_bool().ok_or("Field 'misere' must be true or false")?,
+                None => false,
+            },
+            handicap: match value.get("handicap") {
+                Some(handicap) => handicap
+                    .as_array()
+                    .ok_or("Field 'handicap' must be an array of moves")?
+                    .iter()
+                    .map(|m| m.as_str().ok_or("Every handicap mark must be a move like \"2B\"")?.parse().map_err(String::from))
+                    .collect::<Result<_, String>>()?,
+                None => Vec::new(),
+            },
+        })
+    }
+}
+
+impl ToJson for GameRecord {
+    fn to_json(&self) -> Value {
+        let mut fields = vec![
+            ("date".to_string(), Value::String(self.date.clone())),
+            ("result".to_string(), Value::String(self.outcome.to_string())),
+            (
+                "moves".to_string(),
+                Value::Array(self.moves.iter().map(|m| Value::String(m.to_string())).collect()),
+            ),
+            ("difficulty".to_string(), Value::String(self.difficulty.to_string())),
+        ];
+        if let Some(name) = &self.opponent {
+            fields.push(("opponent".to_string(), Value::String(name.clone())));
+        }
+        if let Some(date) = &self.daily {
+            fields.push(("daily".to_string(), Value::String(date.clone())));
+        }
+
+        Value::Object(fields)
+    }
+}
+
+impl FromJson for GameRecord {
+    fn from_json(value: &Value) -> Result<Self, String> {
+        let text = |key| value.field(key)?.as_str().ok_or_else(|| format!("Field '{}' must be a string", key));
+
+        Ok(GameRecord {
+            date: text("date")?.to_string(),
+            outcome: text("result")?.parse()?,
+            moves: value
+                .field("moves")?
+                .as_array()
+                .ok_or("Field 'moves' must be an array of moves")?
+                .iter()
+                .map(|m| Ok(m.as_str().ok_or("Every move must be a string like \"2B\"")?.parse()?))
+                .collect::<Result<_, String>>()?,
+            difficulty: text("difficulty")?.parse()?,
+            opponent: match value.get("opponent") {
+                Some(name) => Some(name.as_str().ok_or("Field 'opponent' must be a string")?.to_string()),
+                None => None,
+            },
+            daily: match value.get("daily") {
+                Some(date) => Some(date.as_str().ok_or("Field 'daily' must be a string")?.to_string()),
+                None => None,
+            },
+        })
+    }
+}
+
+impl ToJson for Stats {
+    fn to_json(&self) -> Value {
+        Value::Object(vec![(
+            "games".to_string(),
+            Value::Array(self.games.iter().map(ToJson::to_json).collect()),
+        )])
+    }
+}
+
+impl FromJson for Stats {
+    fn from_json(value: &Value) -> Result<Self, String> {
+        Ok(Stats {
+            games: value
+                .field("games")?
+                .as_array()
+                .ok_or("Field 'games' must be an array")?
+                .iter()
+                .map(GameRecord::from_json)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+impl ToJson for Progress {
+    fn to_json(&self) -> Value {
+        Value::Object(vec![(
+            "solved".to_string(),
+            Value::Array(self.solved.iter().map(|id| Value::String(id.clone())).collect()),
+        )])
+    }
+}
+
+impl FromJson for Progress {
+    fn from_json(value: &Value) -> Result<Self, String> {
+        Ok(Progress {
+            solved: value
+                .field("solved")?
+                .as_array()
+                .ok_or("Field 'solved' must be an array")?
+                .iter()
+                .map(|id| Ok(id.as_str().ok_or("Every solved puzzle must be an id")?.to_string()))
+                .collect::<Result<_, String>>()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_nesting_up_to_the_limit() {
+        let nested = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse(&nested).is_ok());
+    }
+
+    #[test]
+    fn refuses_deeper_nesting() {
+        let nested = format!("{}{}", "[".repeat(MAX_DEPTH + 1), "]".repeat(MAX_DEPTH + 1));
+        assert_eq!(parse(&nested), Err("nested too deeply".to_string()));
+
+        // A request body of opening brackets alone, as large as the HTTP API reads
+        assert_eq!(parse(&"[".repeat(60 * 1024)), Err("nested too deeply".to_string()));
+        assert_eq!(parse(&"{\"a\":".repeat(10 * 1024)), Err("nested too deeply".to_string()));
+    }
+}
@@ -0,0 +1,91 @@
+//! A Tic-Tac-Toe engine playing perfectly with the minimax algorithm.
+//!
+//! `TicTacToe` holds a game between a player and the machine; the binary
+//! in `main.rs` is a thin terminal frontend over it.
+
+/// This macro logs a decision of the search to stderr when the `debug-ai`
+/// feature is enabled and compiles to nothing otherwise. It stands in for
+/// `log::debug!`, so there is no level to filter by and no logger to set.
+macro_rules! ai_debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "debug-ai")]
+        eprintln!("[debug-ai] {}", format_args!($($arg)*));
+    };
+}
+
+pub mod analysis;
+pub mod bitboard;
+pub mod board;
+pub mod book;
+pub mod bot;
+#[cfg(feature = "chat")]
+pub mod chat;
+pub mod clock;
+pub mod config;
+pub mod connect4;
+pub mod cube;
+pub mod engine;
+pub mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod game;
+pub mod hash;
+pub mod history;
+pub mod i18n;
+#[cfg(feature = "http")]
+pub mod http;
+pub mod input;
+pub mod json;
+pub mod mcts;
+#[cfg(feature = "serde")]
+pub mod net;
+pub mod notakto;
+pub mod notify;
+pub mod observer;
+pub mod r#move;
+pub mod player;
+pub mod protocol;
+pub mod puzzle;
+pub mod record;
+pub mod render;
+pub mod replay;
+pub mod rng;
+pub mod selfplay;
+#[cfg(feature = "serde")]
+pub mod server;
+pub mod series;
+pub mod shortcut;
+pub mod stats;
+pub mod terminal;
+pub mod tournament;
+pub mod transposition;
+pub mod ultimate;
+pub mod verify;
+#[cfg(feature = "serde")]
+pub mod web;
+pub mod wild;
+
+pub use analysis::{Evaluation, Solution};
+pub use board::{parse_board, Board, BoardChar, Notation, WinLine};
+pub use connect4::{Column, ConnectFour};
+pub use cube::Cube;
+pub use engine::{suggest_move, AiConfig, Algorithm, Difficulty, Ending, Engine, GameSnapshot, GameState, Minimax, MoveOutcome, Personality, Progress, Search, SearchInfo, TicTacToe, DEFAULT_MAX_DEPTH, FULL_SEARCH_SIZE, WIN_SCORE};
+pub use error::GameError;
+pub use game::{evaluate_all, simulate, Game, Simulation};
+pub use history::{History, Placement};
+pub use input::{BufferInput, InputSource, ScriptedInput, StdinInput};
+pub use mcts::Mcts;
+pub use notakto::Notakto;
+pub use observer::Observer;
+pub use player::{Decision, Player, Symbols};
+pub use puzzle::{Goal, Puzzle};
+pub use render::{Color, PlayerInfo, Theme};
+pub use selfplay::{self_play, Contender, SelfPlayReport};
+pub use series::{Match, Score};
+pub use r#move::{CubeMove, Move, MoveFormat, NotaktoMove, UltimateMove, WildMove};
+pub use tournament::{tournament, Entrant, Standings};
+pub use ultimate::UltimateBoard;
+pub use verify::{verify, Counterexample, Flaw, Proof};
+#[cfg(feature = "serde")]
+pub use web::WebGame;
+pub use wild::Wild;
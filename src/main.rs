@@ -1,367 +1,986 @@
-use std::cmp;
+use std::cell::RefCell;
+use std::env;
 use std::fmt;
-use std::io;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::iter;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process;
+use std::rc::Rc;
 use std::str::FromStr;
-
-#[derive(PartialEq, Copy, Clone)]
-enum BoardChar {
-    O,
-    X,
-    Empty,
+use std::thread;
+use std::time::Duration;
+
+use tic_tac_toe_rs::bot::ExternalBot;
+use tic_tac_toe_rs::clock::format_split;
+use tic_tac_toe_rs::config::Config;
+use tic_tac_toe_rs::i18n::{fill, Lang, Text};
+use tic_tac_toe_rs::input::{InputSource, StdinInput};
+use tic_tac_toe_rs::net::{self, Audience, Connection, Link, Message, Role};
+use tic_tac_toe_rs::notify::Notifier;
+use tic_tac_toe_rs::player::{Machine, Player, Table};
+use tic_tac_toe_rs::protocol;
+use tic_tac_toe_rs::record::write_record;
+use tic_tac_toe_rs::render::{Color, PlayerInfo};
+use tic_tac_toe_rs::rng::Rng;
+use tic_tac_toe_rs::replay::Replay;
+use tic_tac_toe_rs::series::Match;
+use tic_tac_toe_rs::stats::{GameRecord, Stats};
+use tic_tac_toe_rs::terminal::{self, append_record, capitalize, load, name, read_input, Answer, Host, Mode};
+mod commands;
+mod events;
+mod interrupt;
+mod options;
+mod thinking;
+mod tui;
+
+use options::{First, GameKind, Glyph, Options, Variant};
+
+use tic_tac_toe_rs::{BoardChar, ConnectFour, Cube, Ending, GameSnapshot, Notakto, Symbols, Theme, TicTacToe, UltimateBoard, Wild};
+
+/// Pause between two moves when a replay plays on its own
+const REPLAY_DELAY: Duration = Duration::from_secs(1);
+
+/// What can be typed while a replay is shown
+enum Step {
+    Next,
+    Prev,
+    Auto,
+    Quit,
 }
 
-impl BoardChar {
-    fn to_opposite(&self) -> BoardChar {
-        match *self {
-            BoardChar::Empty => BoardChar::Empty,
-            BoardChar::O => BoardChar::X,
-            BoardChar::X => BoardChar::O,
+impl FromStr for Step {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "" | "n" | "next" => Ok(Step::Next),
+            "p" | "prev" => Ok(Step::Prev),
+            "a" | "auto" => Ok(Step::Auto),
+            "q" | "quit" => Ok(Step::Quit),
+            tr => Err(format!("'{}' is not one of 'n', 'p', 'a', 'q'", tr)),
         }
     }
 }
 
-impl fmt::Display for BoardChar {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match *self {
-            BoardChar::Empty => write!(f, " "),
-            BoardChar::O => write!(f, "O"),
-            BoardChar::X => write!(f, "X"),
+/// This function asks for the symbols to play with. X and O pick the classic
+/// marks, any other character becomes the player's glyph and the glyph of
+/// the `opponent` is asked for next.
+fn choose_symbols(input: &mut dyn InputSource, opponent: &str, preferred: Option<char>) -> Option<(BoardChar, Symbols)> {
+    let Glyph(player) = match preferred {
+        Some(c) => Glyph(c),
+        None => read_input(input, Text::ChooseSymbol.get())?,
+    };
+    if let Ok(bc) = player.to_string().parse() {
+        return Some((bc, Symbols::default()));
+    }
+
+    loop {
+        let Glyph(other) = read_input(input, &fill(Text::ChooseOtherSymbol, &[&opponent]))?;
+        if other != player {
+            return Some((BoardChar::X, Symbols { x: player, o: other }));
         }
+
+        println!("{}", capitalize(&fill(Text::SymbolTaken, &[&opponent])));
     }
 }
 
-impl FromStr for BoardChar {
-    type Err = String;
+fn main() {
+    Lang::detect().set();
+    let mut args = env::args().skip(1).peekable();
+    let command = args.peek().and_then(|name| commands::find(name));
+    if let Some((command, run)) = command.and_then(|command| Some((command, command.run?))) {
+        args.next();
+        let args: Vec<String> = args.collect();
+        match run(&mut StdinInput, args.into_iter()) {
+            Ok(true) => return,
+            Ok(false) => process::exit(1),
+            Err(e) => {
+                eprintln!("{}", e);
+                eprintln!("{}", command.hint());
+                process::exit(2);
+            }
+        }
+    }
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tr = s.trim();
-        if tr.len() > 1 {
-            return Err(format!("Input {} too lang", tr));
+    if let Some("help" | "--help" | "-h") = args.peek().map(String::as_str) {
+        for line in options::help() {
+            println!("{}", line);
         }
+        return;
+    }
 
-        if let Some(c) = tr.chars().next() {
-            match c {
-                'X' | 'x' => return Ok(BoardChar::X),
-                'O' | 'o' => return Ok(BoardChar::O),
-                _ => return Err(format!("'{}' is not one of 'X', 'x', 'O', 'o'", c)),
+    // `play`, `replay` and `stats` are the same as the flags they stand for,
+    // `setup` plays from the position it set up like `--position`
+    let args: Vec<String> = match args.peek().map(String::as_str) {
+        Some("setup") => match commands::run_setup(&mut StdinInput, args.skip(1)) {
+            Ok(Some(args)) => args,
+            Ok(None) => return,
+            Err(e) => {
+                eprintln!("{}", e);
+                if let Some(setup) = commands::find("setup") {
+                    eprintln!("{}", setup.hint());
+                }
+                process::exit(2);
+            }
+        },
+        Some("play") => args.skip(1).collect(),
+        Some("replay") => iter::once("--replay".to_string()).chain(args.skip(1)).collect(),
+        Some("stats") => iter::once("--stats".to_string()).chain(args.skip(1)).collect(),
+        _ => args.collect(),
+    };
+
+    let config = match Config::default_path() {
+        Some(path) => match Config::load(&path) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Could not read the configuration {}: {}", path.display(), e);
+                process::exit(2);
+            }
+        },
+        None => Config::default(),
+    };
+
+    let options = match Options::parse(args.into_iter(), &config) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{}", e);
+            for line in options::usage() {
+                eprintln!("{}", line);
             }
+            process::exit(2);
         }
-
-        Err(format!("Could not parse: {}", tr))
+    };
+    if let Some(lang) = options.lang {
+        lang.set();
     }
-}
 
-struct Move {
-    row: usize,
-    col: usize,
-}
-
-impl fmt::Display for Move {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let row = self.row + 1;
-        let col: char = match self.col {
-            0 => 'A',
-            1 => 'B',
-            2 => 'C',
-            _ => ' ',
-        };
-
-        write!(f, "{}{}", row, col)
+    if options.stats {
+        if let Err(e) = print_stats(&options) {
+            eprintln!("Could not read the statistics: {}", e);
+            process::exit(1);
+        }
+        return;
     }
-}
 
-impl FromStr for Move {
-    type Err = String;
+    let input = &mut StdinInput;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let tr = s.trim();
-        if tr.len() != 2 {
-            return Err(format!("Input {} too lang", tr));
-        }
-
-        let mut col: Option<usize> = None;
-        let mut row: Option<usize> = None;
-
-        for c in tr.chars() {
-            match c {
-                'A' | 'a' => col = Some(0),
-                'B' | 'b' => col = Some(1),
-                'C' | 'c' => col = Some(2),
-                '1' => row = Some(0),
-                '2' => row = Some(1),
-                '3' => row = Some(2),
-                _ => (),
-            }
+    if options.engine {
+        if let Err(e) = protocol::run(input, &mut io::stdout()) {
+            eprintln!("{}", e);
+            process::exit(1);
         }
+        return;
+    }
 
-        if row.is_some() && col.is_some() {
-            return Ok(Move {
-                row: row.unwrap(),
-                col: col.unwrap(),
-            });
+    if let Some(source) = &options.replay {
+        if let Err(e) = replay(input, source, &options) {
+            eprintln!("Could not replay {}: {}", source, e);
+            process::exit(1);
         }
-
-        Err(format!("Could not parse: {}", tr))
+        return;
     }
-}
 
-type Board = [[BoardChar; 3]; 3];
+    if options.variant == Variant::Ultimate {
+        play_ultimate(input, &options);
+        return;
+    }
 
-struct TicTacToe {
-    board: Board,
-    player_char: BoardChar,
-    machine_char: BoardChar,
-}
+    if options.variant == Variant::Cube {
+        play_cube(input, &options);
+        return;
+    }
 
-impl TicTacToe {
-    fn new(player_char: BoardChar) -> TicTacToe {
-        TicTacToe {
-            board: [[BoardChar::Empty; 3]; 3],
-            player_char,
-            machine_char: player_char.to_opposite(),
-        }
+    if options.variant == Variant::Wild {
+        play_wild(input, &options);
+        return;
     }
 
-    /// This function returns true if game is over
-    fn is_game_over(&self) -> bool {
-        !self.has_moves() || self.player_won() || self.machine_won()
+    if options.variant == Variant::Notakto {
+        play_notakto(input, &options);
+        return;
     }
-    /// This function returns true if there are moves remaining on the board.
-    /// It returns false if there are no moves left to play.    
-    fn has_moves(&self) -> bool {
-        for row in 0..3 {
-            for col in 0..3 {
-                if self.board[row][col] == BoardChar::Empty {
-                    return true;
-                }
-            }
-        }
 
-        false
+    if options.game == GameKind::ConnectFour {
+        play_connect_four(input, &options);
+        return;
     }
-    /// This function makes the player's move
-    fn player_move(&mut self, m: &Move) -> bool {
-        self.do_move(m, self.player_char)
+
+    if let Some(addr) = &options.watch {
+        if let Err(e) = watch(addr, options.theme()) {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+        return;
     }
-    /// This function makes the machin's move
-    fn machine_move(&mut self) -> Option<Move> {
-        if let Some(m) = self.find_best_move() {
-            self.do_move(&m, self.machine_char);
 
-            return Some(m)
+    if let Some(addr) = &options.connect {
+        if let Err(e) = join(input, addr, &options, options.notifier().as_mut()) {
+            eprintln!("{}", e);
+            process::exit(1);
         }
-        
-        None
+        return;
     }
 
-    fn do_move(&mut self, m: &Move, c: BoardChar) -> bool {
-        if self.board[m.row][m.col] == BoardChar::Empty {
-            self.board[m.row][m.col] = c;
+    let recovered = match (&options.resume, &options.position, options.host) {
+        (None, None, None) if !options.json => recover(input),
+        _ => None,
+    };
+    let mut game = match (&options.resume, recovered) {
+        (Some(path), _) => match load(path) {
+            Ok(game) => game,
+            Err(e) => {
+                eprintln!("Could not resume {}: {}", path, e);
+                process::exit(1);
+            }
+        },
+        (None, Some(game)) => game,
+        (None, None) => {
+            let chosen = if options.json {
+                // A program plays plain marks, X unless it asked for O
+                let bc = match options.symbol {
+                    Some('O' | 'o') => BoardChar::O,
+                    _ => BoardChar::X,
+                };
+                Some((bc, Symbols::default()))
+            } else if options.host.is_some() {
+                choose_symbols(input, "your opponent", options.symbol)
+            } else {
+                ask_symbols(input, &options)
+            };
+            let (bc, symbols) = match chosen {
+                Some(chosen) => chosen,
+                None => return,
+            };
+
+            let mut game = match &options.position {
+                Some((board, turn)) => {
+                    let state = GameSnapshot {
+                        board: board.clone(),
+                        win_len: options.win_len(),
+                        player_char: bc,
+                        machine_char: bc.to_opposite(),
+                        symbols,
+                        turn: *turn,
+                        history: Vec::new(),
+                        misere: options.variant == Variant::Misere,
+                        handicap: Vec::new(),
+                    };
+                    match TicTacToe::from_snapshot(&state) {
+                        Ok(game) => game,
+                        Err(e) => {
+                            eprintln!("Could not set up the position: {}", e);
+                            process::exit(1);
+                        }
+                    }
+                }
+                None => TicTacToe::with_size(bc, options.size, options.win_len()),
+            };
+            game.set_symbols(symbols);
+            game.set_misere(options.variant == Variant::Misere);
+            game
+        }
+    };
+    configure(&mut game, &options);
 
-            return true
+    if let Some(port) = options.host {
+        if let Err(e) = host(input, game, port, &options, options.notifier().as_mut()) {
+            eprintln!("{}", e);
+            process::exit(1);
         }
-        
-        false
+        return;
     }
-    /// This function returns true if player won
-    fn player_won(&self) -> bool {
-        self.evaluate(self.player_char)
-    }
-    /// This function returns true if machine won
-    fn machine_won(&self) -> bool {
-        self.evaluate(self.machine_char)
+
+    let mut rng = options.rng();
+
+    if options.tui {
+        let mode = options.mode;
+        let first = options.first;
+        let started = tui::run(
+            &mut game,
+            mode,
+            options.think,
+            |game| {
+                if mode == Mode::Machine {
+                    let c = if machine_first(first, &mut rng) {
+                        game.machine_char()
+                    } else {
+                        game.player_char()
+                    };
+                    game.set_first(c);
+                }
+            },
+            |game| record(game, &options),
+        );
+
+        if let Err(e) = started {
+            eprintln!("Could not start the full-screen frontend: {}", e);
+            process::exit(1);
+        }
+        return;
     }
-    /// This function will return the best possible move for machine
-    fn find_best_move(&mut self) -> Option<Move> {
-        let mut best_val = -10;
-        let mut best_move = None;
 
-        // Traverse all cells, evaluate minimax function for all empty cells.
-        // And return the cell with optimal value.
-        for i in 0..3 {
-            for j in 0..3 {
-                // Check if cell is empty
-                if self.board[i][j] == BoardChar::Empty {
-                    // Make the move
-                    self.board[i][j] = self.machine_char;
-                    // compute evaluation function for this move.
-                    let move_val = self.minimax(self.player_char);
-                    // If the move_value is more than the best_val, then update best_val
-                    if move_val > best_val {
-                        best_move = Some(Move { row: i, col: j });
-                        best_val = move_val;
+    if options.json {
+        let mode = options.mode;
+        let first = options.first;
+        let played = events::run(
+            input,
+            io::stdout(),
+            &mut game,
+            mode,
+            options.think,
+            |game| {
+                if mode == Mode::Machine {
+                    let c = if machine_first(first, &mut rng) {
+                        game.machine_char()
+                    } else {
+                        game.player_char()
+                    };
+                    game.set_first(c);
+                }
+            },
+            |game| {
+                record(game, &options);
+                if let Some(path) = &options.export {
+                    let record = write_record(game, &name(game, mode, BoardChar::X), &name(game, mode, BoardChar::O), &[]);
+                    if let Err(e) = append_record(&record, path) {
+                        eprintln!("Could not add the game to {}: {}", path.display(), e);
                     }
-
-                    // undo the move
-                    self.board[i][j] = BoardChar::Empty;
                 }
-            }
+            },
+        );
+
+        if let Err(e) = played {
+            eprintln!("{}", e);
+            process::exit(1);
         }
+        return;
+    }
 
-        best_move
+    if options.best_of.is_some() {
+        game.set_players(players(&game, options.mode));
+    }
+    let mut series = Match::new(game, options.best_of);
+    if let Some(seed) = options.seed {
+        series.set_seed(seed);
+    }
+    series.set_swap_symbols(options.swap_symbols);
+    if let (Some(personality), Mode::Machine) = (options.personality, options.mode) {
+        println!("You play against {}", personality);
     }
 
-    fn evaluate(&self, c: BoardChar) -> bool {
-        // Checking for Rows for X or O victory.
-        for row in 0..3 {
-            if self.board[row][0] == c && self.board[row][0] == self.board[row][1] && self.board[row][1] == self.board[row][2] {
-                return true;
+    loop {
+        // A resumed game already knows who opened it, and within a best-of
+        // match the opener alternates
+        let alternates = options.best_of.is_some() && series.played() > 0;
+        let game = series.game_mut();
+        if options.mode == Mode::Machine && game.is_empty() && !alternates {
+            if machine_first(options.first, &mut rng) {
+                game.set_first(game.machine_char());
+            } else {
+                game.set_first(game.player_char());
             }
         }
-    
-        // Checking for Columns for X or O victory.
-        for col in 0..3 {
-            if self.board[0][col] == c && self.board[0][col] == self.board[1][col] && self.board[1][col] == self.board[2][col] {
-                return true;
+        if let (Some(handicap), true) = (&options.handicap, game.is_empty()) {
+            let cells = handicap.cells(game, &mut rng);
+            if let Err(e) = game.set_handicap(&cells) {
+                eprintln!("Could not give the handicap: {}", e);
+                process::exit(2);
             }
+            let cells: Vec<String> = cells.iter().map(|m| m.to_string()).collect();
+            println!("You start with a mark on {}, the machine moves next", cells.join(", "));
         }
-    
-        // Checking for Diagonals for X or O victory.
-        if self.board[0][0] == c && self.board[0][0] == self.board[1][1] && self.board[1][1] == self.board[2][2] {
-            return true;
+        if let [one, other] = game.players() {
+            println!("{} vs {}", one.describe(game.symbols(), game.theme()), other.describe(game.symbols(), game.theme()));
         }
-    
-        if self.board[0][2] == c && self.board[0][2] == self.board[1][1] && self.board[1][1] == self.board[2][0] {
-            return true;
+        if options.mode == Mode::Machine && game.is_empty() && game.first() == game.machine_char() {
+            println!("{}", Text::MachineOpens);
         }
-    
-        // Else if none of them have won
-        false
-    }
 
-    // This is the minimax function. It considers all the possible ways
-    // the game can go and returns the value of the board
-    fn minimax(&mut self, c: BoardChar) -> i16 {
-        // If Machine has won the game return his/her evaluated score
-        if self.machine_won() {
-            return 1;
+        play(input, game, &options);
+        if !game.is_game_over() {
+            // The input ended in the middle of the game
+            break;
         }
+        record(game, &options);
+        print_score(&series, options.mode);
 
-        // If Player has won the game return his/her evaluated score
-        if self.player_won() {
-            return -1;
+        if series.is_over() {
+            break;
         }
 
-        if !self.has_moves() {
-            return 0;
+        if read_input(input, Text::PlayAgain.get()) != Some(Answer::Yes) {
+            break;
         }
 
-        let mut best: i16 = if c == self.machine_char {
-            // If this maximizer's move
-            -10
-        } else {
-            // If this minimizer's move
-            10
-        };
+        series.next_game();
+    }
+}
 
-        for i in 0..3 {
-            for j in 0..3 {
-                // check if cell is empty
-                if self.board[i][j] == BoardChar::Empty {
-                    // make the move
-                    self.board[i][j] = c;
+/// This function adds a finished game against the machine to the statistics
+fn record(game: &TicTacToe, options: &Options) {
+    if options.mode != Mode::Machine {
+        return;
+    }
 
-                    // call minimax recursively
-                    let next_best = self.minimax(c.to_opposite());
+    if let (Some(path), Some(record)) = (options.stats_path(), GameRecord::new(game)) {
+        if let Err(e) = Stats::append(&path, record) {
+            eprintln!("Could not record the game in {}: {}", path.display(), e);
+        }
+    }
+}
 
-                    if c == self.machine_char {
-                        // choose the maximum value
-                        best = cmp::max(best, next_best);
-                    } else {
-                        // choose the minimum value
-                        best = cmp::min(best, next_best);
-                    }
+/// This function adds a finished network game to the statistics, under the
+/// name given with `--opponent` or else the address of the opponent
+fn record_remote(game: &TicTacToe, link: &mut Link, options: &Options) {
+    let opponent = options.opponent.clone().or_else(|| link.connection().peer().ok().map(|addr| addr.ip().to_string()));
 
-                    // undo the move
-                    self.board[i][j] = BoardChar::Empty;
-                }
-            }
+    if let (Some(path), Some(mut record)) = (options.stats_path(), GameRecord::new(game)) {
+        record.opponent = Some(opponent.unwrap_or_else(|| "remote".to_string()));
+        if let Err(e) = Stats::append(&path, record) {
+            eprintln!("Could not record the game in {}: {}", path.display(), e);
         }
+    }
+}
+
+/// This function prints what the statistics say about the games played so far
+fn print_stats(options: &Options) -> Result<(), String> {
+    let path = options.stats_path().ok_or("There is no home directory to keep statistics in")?;
+    print!("{}", Stats::load(&path)?.summary(Lang::current()));
+
+    Ok(())
+}
 
-        best
+/// This function returns who plays the sides of `game`, each in the color
+/// of the side it has, which it keeps when the symbols are swapped
+fn players(game: &TicTacToe, mode: Mode) -> Vec<PlayerInfo> {
+    let (player, other) = match mode {
+        Mode::Machine => (Text::You, Text::TheMachine),
+        Mode::HotSeat => (Text::Player1, Text::Player2),
+    };
+
+    [(player, game.player_char()), (other, game.machine_char())]
+        .iter()
+        .filter_map(|&(name, side)| {
+            Some(PlayerInfo {
+                name: capitalize(name.get()),
+                side,
+                color: Color::of(side)?,
+            })
+        })
+        .collect()
+}
+
+/// This function prints the running score of the match and its result once it is over
+fn print_score(series: &Match, mode: Mode) {
+    let score = series.score();
+    let (player, machine) = match mode {
+        Mode::Machine => (Text::You, Text::TheMachine),
+        Mode::HotSeat => (Text::Player1, Text::Player2),
+    };
+    let (player, machine) = (player.get(), machine.get());
+
+    match series.best_of() {
+        Some(n) => print!("{}", fill(Text::ScoreAfter, &[&series.played(), &n])),
+        None => print!("{}", Text::Score),
+    }
+    println!(
+        "{}",
+        fill(Text::ScoreLine, &[&player, &score.player, &machine, &score.machine, &score.draws])
+    );
+
+    if series.is_over() {
+        let game = series.game();
+        match series.leader() {
+            Some(c) if c == game.player_char() => println!("{}", fill(Text::WonMatch, &[&capitalize(player), &score])),
+            Some(_) => {
+                let score = fill(Text::MatchScore, &[&score.machine, &score.player, &score.draws]);
+                println!("{}", fill(Text::WonMatch, &[&capitalize(machine), &score]));
+            }
+            None => println!("{}", fill(Text::MatchTied, &[&score])),
+        }
     }
 }
 
-impl fmt::Display for TicTacToe {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut board_txt = format!(
-            "  A B C\n \u{250C}\u{2500}\u{252C}\u{2500}\u{252C}\u{2500}\u{2510}\n{}\u{2502}",
-            1
-        );
+/// This function decides whether the machine opens the next game
+fn machine_first(first: First, rng: &mut Rng) -> bool {
+    match first {
+        First::Player => false,
+        First::Machine => true,
+        First::Random => rng.below(2) == 0,
+    }
+}
 
-        for row in &self.board[0] {
-            board_txt.push_str(&format!("{}\u{2502}", row));
+/// This function asks who plays which symbol, for either mode
+fn ask_symbols(input: &mut dyn InputSource, options: &Options) -> Option<(BoardChar, Symbols)> {
+    match options.mode {
+        Mode::Machine => choose_symbols(input, Text::TheMachine.get(), options.symbol),
+        Mode::HotSeat => {
+            println!("{}", Text::HotSeatSymbols);
+            choose_symbols(input, Text::Player2.get(), options.symbol)
         }
+    }
+}
 
-        board_txt.push_str(&format!(
-            "\n \u{251C}\u{2500}\u{253C}\u{2500}\u{253C}\u{2500}\u{2524}\n{}\u{2502}",
-            2
-        ));
+/// This function applies the machine settings of the command line
+fn configure(game: &mut TicTacToe, options: &Options) {
+    if options.depth.is_some() {
+        game.set_max_depth(options.depth);
+    }
+    game.set_ai(options.ai());
+    game.set_book(!options.no_book);
+    game.set_threads(options.threads);
+    if let Some(seed) = options.seed {
+        game.set_seed(seed);
+    }
+    game.set_theme(options.theme());
+}
+
+/// This function returns where the game being played is kept in case it is
+/// interrupted, `~/.tictactoe/recovery.json`
+fn recovery_path() -> Option<PathBuf> {
+    Stats::default_path().map(|path| path.with_file_name("recovery.json"))
+}
 
-        for row in &self.board[1] {
-            board_txt.push_str(&format!("{}\u{2502}", row));
+/// This function keeps `game` in the recovery file. It is written next to it
+/// first, so that Ctrl-C never leaves half a game behind.
+fn autosave(game: &TicTacToe) {
+    let path = match recovery_path() {
+        Some(path) => path,
+        None => return,
+    };
+    let written = (|| -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
         }
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, game.to_json())?;
+
+        fs::rename(&partial, &path)
+    })();
+
+    if let Err(e) = written {
+        eprintln!("Could not keep the game in {}: {}", path.display(), e);
+    }
+}
 
-        board_txt.push_str(&format!(
-            "\n \u{251C}\u{2500}\u{253C}\u{2500}\u{253C}\u{2500}\u{2524}\n{}\u{2502}",
-            3
-        ));
+/// This function removes the recovery file once the game it holds is over
+fn discard_recovery() {
+    if let Some(path) = recovery_path() {
+        let _ = fs::remove_file(path);
+    }
+}
 
-        for row in &self.board[2] {
-            board_txt.push_str(&format!("{}\u{2502}", row));
+/// This function offers to go on with a game that was interrupted. The
+/// recovery file is removed whatever the answer.
+fn recover(input: &mut dyn InputSource) -> Option<TicTacToe> {
+    let path = recovery_path()?;
+    let text = fs::read_to_string(&path).ok()?;
+    discard_recovery();
+
+    let game = match TicTacToe::from_json(&text) {
+        Ok(game) => game,
+        Err(e) => {
+            eprintln!("Could not read the interrupted game in {}: {}", path.display(), e);
+            return None;
         }
+    };
+    if game.is_empty() {
+        return None;
+    }
+    println!("{}", game);
 
-        writeln!(
-            f,
-            "{}\n \u{2514}\u{2500}\u{2534}\u{2500}\u{2534}\u{2500}\u{2518}\n",
-            board_txt
-        )
+    match read_input(input, Text::ResumeInterrupted.get()) {
+        Some(Answer::Yes) => Some(game),
+        _ => None,
     }
 }
 
-/// This is a generic function to convert terminal input in some type
-fn read_input<T: FromStr<Err = String>>(ask: &str) -> T {
-    println!("{}", ask);
+/// The program around a game at the terminal
+struct Cli<'a> {
+    options: &'a Options,
+}
+
+impl Host for Cli<'_> {
+    fn turn_started(&mut self, game: &TicTacToe) {
+        autosave(game);
+    }
+
+    fn ended(&mut self, _game: &TicTacToe) {
+        discard_recovery();
+    }
+
+    fn restarted(&mut self, game: &mut TicTacToe) {
+        configure(game, self.options);
+    }
+}
 
-    let mut input = String::new();
-    match io::stdin().read_line(&mut input) {
-        Ok(_) => match T::from_str(&input) {
-            Ok(bc) => return bc,
+/// This function runs a single game until it is over and announces the
+/// result. It returns early, with the game unfinished, if the input ends.
+fn play(input: &mut dyn InputSource, game: &mut TicTacToe, options: &Options) {
+    // At a terminal the machine shows how far its search got, screen
+    // readers would read out every tick of the spinner
+    let shown = io::stdin().is_terminal() && io::stdout().is_terminal() && options.theme() != Theme::Accessible;
+    let mut bot = match &options.bot {
+        Some(command) => match ExternalBot::spawn(command, options.bot_time) {
+            Ok(bot) => {
+                println!("You play against {}", bot.name());
+                Some(bot)
+            }
             Err(e) => {
-                println!("{}", e);
-                return read_input::<T>(ask);
+                eprintln!("Could not start the bot '{}': {}", command, e);
+                process::exit(1);
             }
         },
+        None => None,
+    };
+    let mut machine: Box<dyn Player> = match shown {
+        true => Box::new(thinking::Thinking::new(options.think, io::stdin())),
+        false => Box::new(Machine::new(options.think)),
+    };
+    let machine: &mut dyn Player = match &mut bot {
+        Some(bot) => bot,
+        None => machine.as_mut(),
+    };
+    let mut notifier = options.notifier();
+    let _interrupt = interrupt::Guard::catch();
+
+    terminal::play(input, game, &options.settings(), machine, &mut Cli { options }, notifier.as_mut());
+
+    if let (true, Some(failure)) = (game.is_game_over(), bot.as_ref().and_then(|bot| bot.failure())) {
+        println!("The bot lost, {}", failure);
+    }
+}
 
-        Err(error) => panic!(error),
+/// This function plays games of a variant with the machine settings of the
+/// command line until the players had enough
+fn play_variant<G: Table>(input: &mut dyn InputSource, game: &mut G, options: &Options)
+where
+    G::Move: FromStr,
+    <G::Move as FromStr>::Err: fmt::Display,
+{
+    let mut notifier = options.notifier();
+    let mut machine = Machine::new(options.think);
+    let mut rng = options.rng();
+
+    terminal::play_games(input, game, options.mode, &mut machine, notifier.as_mut(), &mut || machine_first(options.first, &mut rng));
+}
+
+/// This function runs Ultimate Tic-Tac-Toe games until the players had enough
+fn play_ultimate(input: &mut dyn InputSource, options: &Options) {
+    let (bc, symbols) = match ask_symbols(input, options) {
+        Some(chosen) => chosen,
+        None => return,
+    };
+    let mut game = UltimateBoard::new(bc);
+    game.set_symbols(symbols);
+    game.set_theme(options.theme());
+    game.set_max_depth(options.depth);
+    game.set_ai(options.ai());
+    if let Some(seed) = options.seed {
+        game.set_seed(seed);
     }
+
+    println!("Moves name the board and then the cell, e.g. B2/A1");
+    play_variant(input, &mut game, options);
 }
 
-fn main() {
-    let bc = read_input("Please choose a symbol: X or O");
-    let mut game = TicTacToe::new(bc);
-    
-    while !game.is_game_over() {
+/// This function runs 3D Tic-Tac-Toe games until the players had enough
+fn play_cube(input: &mut dyn InputSource, options: &Options) {
+    let (bc, symbols) = match ask_symbols(input, options) {
+        Some(chosen) => chosen,
+        None => return,
+    };
+    let mut game = Cube::new(bc);
+    game.set_symbols(symbols);
+    game.set_theme(options.theme());
+    if let Some(depth) = options.depth {
+        game.set_max_depth(Some(depth));
+    }
+    game.set_ai(options.ai());
+    if let Some(seed) = options.seed {
+        game.set_seed(seed);
+    }
+
+    println!("Moves name the cell and then the layer, e.g. 2B-L3");
+    play_variant(input, &mut game, options);
+}
+
+/// This function runs Wild Tic-Tac-Toe games until the players had enough.
+/// The marks belong to neither side, so no symbols are asked for and the
+/// player, or Player 1, is the side X.
+fn play_wild(input: &mut dyn InputSource, options: &Options) {
+    let mut game = Wild::new(BoardChar::X);
+    game.set_theme(options.theme());
+    if let Some(depth) = options.depth {
+        game.set_max_depth(Some(depth));
+    }
+    game.set_ai(options.ai());
+    if let Some(seed) = options.seed {
+        game.set_seed(seed);
+    }
+
+    println!("Moves name the cell and then the mark, X or O, e.g. 2B X");
+    play_variant(input, &mut game, options);
+}
+
+/// This function runs Notakto games until the players had enough. Both
+/// sides place X's, so no symbols are asked for and the player, or Player
+/// 1, is the side X.
+fn play_notakto(input: &mut dyn InputSource, options: &Options) {
+    let mut game = Notakto::new(BoardChar::X, options.boards.unwrap_or(1));
+    game.set_theme(options.theme());
+    if let Some(depth) = options.depth {
+        game.set_max_depth(Some(depth));
+    }
+    game.set_ai(options.ai());
+    if let Some(seed) = options.seed {
+        game.set_seed(seed);
+    }
+
+    if game.board_count() > 1 {
+        println!("Moves name the cell and then the board, e.g. 2B-2");
+    }
+    play_variant(input, &mut game, options);
+}
+
+/// This function runs Connect Four games until the players had enough
+fn play_connect_four(input: &mut dyn InputSource, options: &Options) {
+    let (bc, symbols) = match ask_symbols(input, options) {
+        Some(chosen) => chosen,
+        None => return,
+    };
+    let mut game = ConnectFour::new(bc);
+    game.set_symbols(symbols);
+    game.set_theme(options.theme());
+    if let Some(depth) = options.depth {
+        game.set_max_depth(Some(depth));
+    }
+    game.set_ai(options.ai());
+    if let Some(seed) = options.seed {
+        game.set_seed(seed);
+    }
+
+    println!("Moves name the column a disc is dropped into, e.g. D");
+    play_variant(input, &mut game, options);
+}
+
+/// This function hosts `game` on `port` for a remote opponent
+fn host(input: &mut dyn InputSource, mut game: TicTacToe, port: u16, options: &Options, notifier: &mut dyn Notifier) -> Result<(), String> {
+    let listener = TcpListener::bind(("0.0.0.0", port)).map_err(|e| format!("Could not listen on port {}: {}", port, e))?;
+    println!("Waiting for an opponent on port {}, spectators can watch there as well", port);
+
+    let audience = Audience::default();
+    audience.show(game.to_json());
+    let players = net::admit(listener, audience.clone());
+
+    let mut connection = players.recv().map_err(|_| "Nobody can join any more".to_string())?;
+    net::welcome(&mut connection, &game)?;
+    println!("Your opponent joined and plays {}", game.symbols().glyph(game.machine_char()));
+
+    let link = Rc::new(RefCell::new(Link::host(players, connection, audience.clone())));
+    let result = play_remote(input, &mut game, &link, options, notifier);
+    audience.broadcast(&Message::Bye);
+    result
+}
+
+/// This function joins the game hosted at `addr`
+fn join(input: &mut dyn InputSource, addr: &str, options: &Options, notifier: &mut dyn Notifier) -> Result<(), String> {
+    let mut connection = Connection::connect(addr, Role::Player).map_err(|e| format!("Could not connect to {}: {}", addr, e))?;
+    let mut game = net::welcomed(&mut connection)?;
+    game.set_theme(options.theme());
+    println!("You joined the game and play {}", game.symbols().glyph(game.player_char()));
+
+    let link = Rc::new(RefCell::new(Link::guest(addr, connection)));
+    play_remote(input, &mut game, &link, options, notifier)
+}
+
+/// This function plays games against the remote opponent until one side
+/// does not want a rematch. Every rematch is opened by the other side. If
+/// the input ends this side leaves. Finished games are added to the
+/// statistics and to `--export` with their chat.
+fn play_remote(
+    input: &mut dyn InputSource,
+    game: &mut TicTacToe,
+    link: &Rc<RefCell<Link>>,
+    options: &Options,
+    notifier: &mut dyn Notifier,
+) -> Result<(), String> {
+    loop {
+        let over = terminal::play_online(input, game, link, notifier);
+        let mut link = link.borrow_mut();
+        if let Some(failure) = link.failure() {
+            return Err(failure.to_string());
+        }
+        let chat = link.take_chat();
+        if !over {
+            return Ok(());
+        }
+
         println!("{}", game);
 
-        let m = read_input("your turn: ");
-        if !game.player_move(&m) {
-            println!("This move is not possible, the cell is already occupied");
-            continue;
+        let result = match (game.ending(), game.winner()) {
+            (Some(Ending::Resigned(c)), _) if c == game.player_char() => Text::YouResigned.get(),
+            (Some(Ending::Resigned(_)), _) => Text::OpponentResigned.get(),
+            (_, Some(c)) if c == game.player_char() => Text::YouWon.get(),
+            (_, Some(_)) => Text::YouLost.get(),
+            (_, None) => Text::Draw.get(),
+        };
+        println!("{}", result);
+        notifier.game_over(result);
+
+        record_remote(game, &mut link, options);
+        if let Some(path) = &options.export {
+            let (x, o) = link.names(game);
+            if let Err(e) = append_record(&write_record(game, x, o, &chat), path) {
+                eprintln!("Could not add the game to {}: {}", path.display(), e);
+            }
+        }
+
+        // A finished game is not worth reconnecting for, a lost
+        // connection here simply ends the match
+        let again = read_input(input, Text::PlayAgain.get()) == Some(Answer::Yes);
+        let sent = link.connection().send(&Message::Rematch(again));
+        if !again {
+            let _ = link.connection().send(&Message::Bye);
+            return Ok(());
+        }
+
+        if sent.is_err() {
+            println!("{}", Text::OpponentLeft);
+            return Ok(());
         }
 
-        if let Some(m) = game.machine_move() {
-            println!("machine moved to: {}", m);
+        println!("{}", Text::WaitingForRematch);
+        loop {
+            match link.connection().receive() {
+                Ok(Message::Rematch(true)) => break,
+                Ok(Message::Chat(text)) => println!("{}", fill(Text::OpponentSays, &[&text])),
+                Ok(Message::Rematch(false)) | Ok(Message::Bye) => {
+                    println!("{}", Text::NoRematch);
+                    return Ok(());
+                }
+                Ok(_) => {}
+                Err(_) => {
+                    println!("{}", Text::OpponentLeft);
+                    return Ok(());
+                }
+            }
         }
+
+        let first = game.first().to_opposite();
+        game.reset();
+        game.set_first(first);
+        link.show(game);
     }
+}
 
+/// This function watches the game hosted at `addr` until the host leaves.
+/// Nothing can be played from here.
+fn watch(addr: &str, theme: Theme) -> Result<(), String> {
+    let mut connection = Connection::connect(addr, Role::Spectator).map_err(|e| format!("Could not connect to {}: {}", addr, e))?;
+    let watched = |state: &str| -> Result<TicTacToe, String> {
+        let mut game = TicTacToe::from_json(state)?;
+        game.set_theme(theme);
+        Ok(game)
+    };
+
+    let mut game = match connection.receive().map_err(|e| e.to_string())? {
+        Message::Game(state) => watched(&state)?,
+        Message::Error(text) => return Err(format!("The host refused: {}", text)),
+        other => return Err(format!("Expected GAME from the host, not {}", other)),
+    };
+    println!("You watch the game at {}, the moves are shown as they are played", addr);
+
+    loop {
+        println!("{}", game);
+        if game.is_game_over() {
+            let result = match (game.ending(), game.winner()) {
+                (Some(Ending::Resigned(c)), _) => fill(Text::Resigned, &[&game.symbols().glyph(c)]),
+                (_, Some(c)) => fill(Text::PlayerWon, &[&game.symbols().glyph(c)]),
+                (_, None) => Text::Draw.get().to_string(),
+            };
+            println!("{}", result);
+        }
+
+        // Whatever the host says, the board is shown again
+        loop {
+            match connection.receive() {
+                Ok(Message::Move(m)) => {
+                    let mark = game.turn();
+                    match game.do_move(&m, mark) {
+                        Ok(_) => println!("{} moved to: {}", game.symbols().glyph(mark), m),
+                        Err(e) => println!("The host sent {}, which is not possible: {}", m, e),
+                    }
+                }
+                // Only the side to move can resign
+                Ok(Message::Resign) => game.resign(game.turn()),
+                Ok(Message::Chat(text)) => {
+                    println!("{}", text);
+                    continue;
+                }
+                Ok(Message::Game(state)) => {
+                    game = watched(&state)?;
+                    println!("A new game starts");
+                }
+                Ok(Message::Bye) | Err(_) => {
+                    println!("The host left, the game is over");
+                    return Ok(());
+                }
+                Ok(_) => continue,
+            }
+            break;
+        }
+    }
+}
+
+/// This function steps through the game recorded in `source`, a saved game
+/// file or a move list, until the viewer quits
+fn replay(input: &mut dyn InputSource, source: &str, options: &Options) -> Result<(), String> {
+    let record = fs::read_to_string(source).unwrap_or_else(|_| source.to_string());
+    let mut replay = Replay::parse(&record, options.size, options.win_len())?;
+    replay.set_theme(options.theme());
+
+    loop {
+        show(&replay);
+
+        let step = read_input(input, Text::ReplayStep.get());
+        match step.unwrap_or(Step::Quit) {
+            Step::Next if replay.at_end() => println!("{}", Text::ReplayEnd),
+            Step::Next => {
+                replay.forward();
+            }
+            Step::Prev => {
+                if replay.back().is_none() {
+                    println!("{}", Text::ReplayStart);
+                }
+            }
+            Step::Auto => {
+                // The last position is shown by the loop like any other
+                while replay.forward().is_some() && !replay.at_end() {
+                    show(&replay);
+                    thread::sleep(REPLAY_DELAY);
+                }
+            }
+            Step::Quit => return Ok(()),
+        }
+    }
+}
+
+/// This function prints the board of `replay` and the move that led to it
+fn show(replay: &Replay) {
+    let game = replay.game();
     println!("{}", game);
-    
-    if game.player_won() {
-        println!("Congratulations, you won!");
-    } else if game.machine_won() {
-        println!("Sorry, but you lost");
-    } else {
-        println!("Draw");
+
+    match game.moves().last() {
+        Some(p) => println!(
+            "{}",
+            fill(Text::ReplayMove, &[&replay.position(), &replay.len(), &game.symbols().glyph(p.mark), &p.at])
+        ),
+        None => println!("{}", fill(Text::ReplayEmpty, &[&replay.len()])),
+    }
+    if let Some(time) = game.moves().last().and_then(|p| p.time) {
+        println!("{}", fill(Text::ThoughtFor, &[&format_split(time)]));
     }
 }
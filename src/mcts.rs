@@ -0,0 +1,174 @@
+//! Monte Carlo Tree Search: instead of looking at every reply the engine
+//! plays many random games to the end and grows a tree towards the moves
+//! that won most of them. It needs no heuristic and scales to boards where
+//! a full minimax is out of reach.
+
+use std::time::Instant;
+
+use crate::board::BoardChar;
+use crate::engine::Engine;
+use crate::game::Game;
+use crate::rng::Rng;
+
+/// How much the search explores moves it knows little about (the UCT constant, √2)
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+/// Playouts per move when neither a deadline nor `max_playouts` limits the search
+pub const DEFAULT_PLAYOUTS: usize = 10_000;
+/// How often (in playouts) the search looks at the clock
+const DEADLINE_CHECK_INTERVAL: usize = 64;
+
+/// A position in the search tree, reached by `m` from its parent
+struct Node<M> {
+    m: Option<M>,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Moves not yet expanded into children
+    untried: Vec<M>,
+    /// The side that played `m`, whose wins this node counts
+    mover: BoardChar,
+    depth: usize,
+    visits: u32,
+    /// Won playouts through this node, draws count half
+    wins: f64,
+}
+
+impl<M> Node<M> {
+    /// This function returns the UCT value of the node seen from its parent,
+    /// which has been visited `parent_visits` times
+    fn uct(&self, parent_visits: u32) -> f64 {
+        let visits = self.visits as f64;
+        self.wins / visits + EXPLORATION * ((parent_visits as f64).ln() / visits).sqrt()
+    }
+}
+
+pub struct Mcts {
+    rng: Rng,
+    /// Upper limit of playouts per move, `None` plays until the deadline
+    /// (or `DEFAULT_PLAYOUTS` times without one)
+    pub max_playouts: Option<usize>,
+}
+
+impl Mcts {
+    pub fn new(rng: Rng, max_playouts: Option<usize>) -> Mcts {
+        Mcts { rng, max_playouts }
+    }
+
+    /// This function picks a random element of `items`
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        items[self.rng.below(items.len())]
+    }
+}
+
+impl<G: Game> Engine<G> for Mcts {
+    /// The chosen move is the most visited one at the root. The depth
+    /// returned is the deepest the tree grew.
+    fn best_move(&mut self, game: &mut G, deadline: Option<Instant>) -> Option<(G::Move, usize)> {
+        let moves = game.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        let mut tree = vec![Node {
+            m: None,
+            parent: None,
+            children: Vec::new(),
+            untried: moves,
+            mover: game.turn().to_opposite(),
+            depth: 0,
+            visits: 0,
+            wins: 0.0,
+        }];
+        let mut deepest = 0;
+        let mut playouts = 0;
+        let max_playouts = match (self.max_playouts, deadline) {
+            (None, None) => Some(DEFAULT_PLAYOUTS),
+            (max, _) => max,
+        };
+
+        loop {
+            if max_playouts.is_some_and(|max| playouts >= max) {
+                break;
+            }
+            // At least one playout, so there is a move to play even when the budget is tiny
+            let checks_clock = playouts > 0 && playouts.is_multiple_of(DEADLINE_CHECK_INTERVAL);
+            if checks_clock && deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                break;
+            }
+            playouts += 1;
+
+            let mut state = game.clone();
+            let mut node = 0;
+
+            // Selection: follow the best UCT child down to a node with unexpanded moves
+            while tree[node].untried.is_empty() && !tree[node].children.is_empty() {
+                let parent_visits = tree[node].visits;
+                node = *tree[node]
+                    .children
+                    .iter()
+                    .max_by(|&&a, &&b| tree[a].uct(parent_visits).total_cmp(&tree[b].uct(parent_visits)))
+                    .expect("the node has children");
+                if let Some(m) = tree[node].m {
+                    let _ = state.play(&m);
+                }
+            }
+
+            // Expansion: add one of the moves not tried yet
+            if !tree[node].untried.is_empty() {
+                let i = self.rng.below(tree[node].untried.len());
+                let m = tree[node].untried.swap_remove(i);
+                let mover = state.turn();
+                let _ = state.play(&m);
+
+                let child = tree.len();
+                tree.push(Node {
+                    m: Some(m),
+                    parent: Some(node),
+                    children: Vec::new(),
+                    untried: state.legal_moves(),
+                    mover,
+                    depth: tree[node].depth + 1,
+                    visits: 0,
+                    wins: 0.0,
+                });
+                tree[node].children.push(child);
+                deepest = deepest.max(tree[child].depth);
+                node = child;
+            }
+
+            // Playout: random moves until the game is over
+            loop {
+                let moves = state.legal_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                let m = self.pick(&moves);
+                let _ = state.play(&m);
+            }
+
+            // Backpropagation: credit the side that won on the whole path
+            let winner = state.winner();
+            let mut at = Some(node);
+            while let Some(i) = at {
+                tree[i].visits += 1;
+                tree[i].wins += match winner {
+                    Some(c) if c == tree[i].mover => 1.0,
+                    Some(_) => 0.0,
+                    None => 0.5,
+                };
+                at = tree[i].parent;
+            }
+        }
+
+        ai_debug!("{} playouts, tree of {} nodes", playouts, tree.len());
+
+        let best = *tree[0].children.iter().max_by_key(|&&i| tree[i].visits)?;
+        #[cfg(feature = "debug-ai")]
+        for &i in &tree[0].children {
+            if let Some(m) = tree[i].m {
+                ai_debug!("candidate {} won {} of {} playouts", m, tree[i].wins, tree[i].visits);
+            }
+        }
+
+        tree[best].m.map(|m| (m, deepest))
+    }
+}
@@ -0,0 +1,312 @@
+//! Moves and how they are written.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board::BoardChar;
+use crate::error::GameError;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Move {
+    pub row: usize,
+    pub col: usize,
+}
+
+/// Largest board side that can still be written with one column letter
+pub const MAX_SIZE: usize = 26;
+
+/// This function returns the letter naming column `col`
+pub fn column_name(col: usize) -> char {
+    if col < MAX_SIZE {
+        (b'A' + col as u8) as char
+    } else {
+        ' '
+    }
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.row + 1, column_name(self.col))
+    }
+}
+
+/// How a move is written
+#[derive(Debug, PartialEq, Default, Copy, Clone)]
+pub enum MoveFormat {
+    /// Row number, then column letter: `2B`
+    #[default]
+    RowColumn,
+    /// Column letter, then row number: `B2`
+    ColumnRow,
+    /// A single digit laid out like the keys of a numeric keypad, for 3x3
+    /// boards only: `7` is the top left corner, `5` the center
+    Numpad,
+    /// Row and column number separated by a comma: `2,2`
+    Pair,
+}
+
+impl fmt::Display for MoveFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            MoveFormat::RowColumn => write!(f, "rowcol"),
+            MoveFormat::ColumnRow => write!(f, "colrow"),
+            MoveFormat::Numpad => write!(f, "numpad"),
+            MoveFormat::Pair => write!(f, "pair"),
+        }
+    }
+}
+
+impl FromStr for MoveFormat {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "rowcol" => Ok(MoveFormat::RowColumn),
+            "colrow" => Ok(MoveFormat::ColumnRow),
+            "numpad" => Ok(MoveFormat::Numpad),
+            "pair" => Ok(MoveFormat::Pair),
+            tr => Err(GameError::InvalidInput(format!(
+                "'{}' is not one of 'rowcol', 'colrow', 'numpad', 'pair'",
+                tr
+            ))),
+        }
+    }
+}
+
+impl MoveFormat {
+    /// This function returns the format a move is written in, `None` if
+    /// `s` is no move
+    pub fn of(s: &str) -> Option<MoveFormat> {
+        let tr = s.trim();
+        tr.parse::<Move>().ok()?;
+
+        if tr.contains(',') {
+            Some(MoveFormat::Pair)
+        } else if tr.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            Some(MoveFormat::ColumnRow)
+        } else if tr.contains(|c: char| c.is_ascii_alphabetic()) {
+            Some(MoveFormat::RowColumn)
+        } else {
+            Some(MoveFormat::Numpad)
+        }
+    }
+}
+
+impl Move {
+    /// This function writes the move in `format`. Moves off a 3x3 board
+    /// have no key on the numeric keypad and are written as `2B` instead.
+    pub fn format(&self, format: MoveFormat, size: usize) -> String {
+        match format {
+            MoveFormat::RowColumn => self.to_string(),
+            MoveFormat::ColumnRow => format!("{}{}", column_name(self.col), self.row + 1),
+            MoveFormat::Numpad if size == 3 => ((2 - self.row) * 3 + self.col + 1).to_string(),
+            MoveFormat::Numpad => self.to_string(),
+            MoveFormat::Pair => format!("{},{}", self.row + 1, self.col + 1),
+        }
+    }
+}
+
+impl FromStr for Move {
+    type Err = GameError;
+
+    /// Moves are written as row number and column letter in either order,
+    /// e.g. `2B`, `b2` or `10C` on large boards, as row and column number
+    /// separated by a comma, e.g. `2,3`, or on 3x3 boards as a digit of the
+    /// numeric keypad, e.g. `5` for the center
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tr = s.trim();
+        let invalid = || GameError::InvalidInput(format!("Could not parse: {}", tr));
+
+        if let Some((row, col)) = tr.split_once(',') {
+            let number = |n: &str| match n.trim().parse::<usize>() {
+                Ok(n) if n > 0 => Ok(n - 1),
+                _ => Err(invalid()),
+            };
+
+            return Ok(Move {
+                row: number(row)?,
+                col: number(col)?,
+            });
+        }
+
+        if let [key @ b'1'..=b'9'] = tr.as_bytes() {
+            let key = (key - b'1') as usize;
+
+            return Ok(Move {
+                row: 2 - key / 3,
+                col: key % 3,
+            });
+        }
+
+        let mut col: Option<usize> = None;
+        let mut digits = String::new();
+
+        for c in tr.chars() {
+            match c {
+                'A'..='Z' | 'a'..='z' if col.is_none() => {
+                    col = Some((c.to_ascii_uppercase() as u8 - b'A') as usize);
+                }
+                '0'..='9' => digits.push(c),
+                _ => return Err(invalid()),
+            }
+        }
+
+        let row = match digits.parse::<usize>() {
+            Ok(row) if row > 0 => Some(row - 1),
+            _ => None,
+        };
+
+        if let (Some(row), Some(col)) = (row, col) {
+            return Ok(Move { row, col });
+        }
+
+        Err(invalid())
+    }
+}
+
+/// A move of Ultimate Tic-Tac-Toe: the small board to play on and the cell
+/// within it, both counted on a 3x3 grid
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct UltimateMove {
+    pub board: Move,
+    pub cell: Move,
+}
+
+impl fmt::Display for UltimateMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.board, self.cell)
+    }
+}
+
+impl FromStr for UltimateMove {
+    type Err = GameError;
+
+    /// Ultimate moves are written as the small board and the cell separated
+    /// by a slash, each like a plain move, e.g. `B2/A1` or `2b/1a`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tr = s.trim();
+
+        match tr.split_once('/') {
+            Some((board, cell)) => Ok(UltimateMove {
+                board: board.parse()?,
+                cell: cell.parse()?,
+            }),
+            None => Err(GameError::InvalidInput(format!("Please write the board and the cell, e.g. B2/A1, not: {}", tr))),
+        }
+    }
+}
+
+/// A move of 3D Tic-Tac-Toe: the cell within a layer and the layer of the
+/// cube, counted from 1 at the top
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct CubeMove {
+    pub cell: Move,
+    pub layer: usize,
+}
+
+impl fmt::Display for CubeMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-L{}", self.cell, self.layer + 1)
+    }
+}
+
+impl FromStr for CubeMove {
+    type Err = GameError;
+
+    /// Cube moves are written as the cell like a plain move and the layer
+    /// after a dash, e.g. `2B-L3` or `b2-l1`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tr = s.trim();
+        let parts = tr.rsplit_once('-').map(|(cell, layer)| (cell, layer.trim().trim_start_matches(['L', 'l'])));
+
+        match parts {
+            Some((cell, layer)) => {
+                let layer: usize = layer
+                    .parse()
+                    .map_err(|_| GameError::InvalidInput(format!("'{}' names no layer, please write L1, L2 or L3", layer)))?;
+                if layer == 0 {
+                    return Err(GameError::OutOfBounds);
+                }
+
+                Ok(CubeMove {
+                    cell: cell.parse()?,
+                    layer: layer - 1,
+                })
+            }
+            None => Err(GameError::InvalidInput(format!("Please write the cell and the layer, e.g. 2B-L3, not: {}", tr))),
+        }
+    }
+}
+
+/// A move of Wild Tic-Tac-Toe: the cell and the mark placed on it, which
+/// either side may choose
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct WildMove {
+    pub cell: Move,
+    pub mark: BoardChar,
+}
+
+impl fmt::Display for WildMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.cell, self.mark)
+    }
+}
+
+impl FromStr for WildMove {
+    type Err = GameError;
+
+    /// Wild moves are written as the cell like a plain move and the mark
+    /// after a space, e.g. `2B X` or `1a o`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tr = s.trim();
+
+        match tr.rsplit_once(char::is_whitespace) {
+            Some((cell, mark)) => Ok(WildMove {
+                cell: cell.parse()?,
+                mark: mark.parse()?,
+            }),
+            None => Err(GameError::InvalidInput(format!("Please write the cell and the mark, e.g. 2B X, not: {}", tr))),
+        }
+    }
+}
+
+/// A move of Notakto: the cell and the board it is on, counted from 1
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct NotaktoMove {
+    pub cell: Move,
+    pub board: usize,
+}
+
+impl fmt::Display for NotaktoMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.cell, self.board + 1)
+    }
+}
+
+impl FromStr for NotaktoMove {
+    type Err = GameError;
+
+    /// Notakto moves are written as the cell like a plain move and the board
+    /// after a dash, e.g. `2B-3`. A cell alone is on the first board.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tr = s.trim();
+
+        match tr.rsplit_once('-') {
+            Some((cell, board)) => {
+                let board: usize = board
+                    .trim()
+                    .parse()
+                    .map_err(|_| GameError::InvalidInput(format!("'{}' names no board, please write its number", board.trim())))?;
+                if board == 0 {
+                    return Err(GameError::OutOfBounds);
+                }
+
+                Ok(NotaktoMove {
+                    cell: cell.parse()?,
+                    board: board - 1,
+                })
+            }
+            None => Ok(NotaktoMove { cell: tr.parse()?, board: 0 }),
+        }
+    }
+}
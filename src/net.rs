@@ -0,0 +1,495 @@
+//! A small line based protocol for two people playing over TCP.
+//!
+//! One side hosts the game and owns it, the other connects. Every message
+//! is a single line of text:
+//!
+//! ```text
+//! HELLO <version> [role]   the connecting side introduces itself, as a
+//!                          player or, with `spectator`, to watch
+//! WELCOME <mark> <state>   the host answers the player with the mark of
+//!                          the guest and the game as one line of JSON (see
+//!                          `GameSnapshot`)
+//! GAME <state>             the game as it stands, sent to a spectator when
+//!                          it comes and whenever a new game starts
+//! MOVE <move>              the sender played <move>, e.g. MOVE 2B
+//! RESIGN                   the sender gives up the game
+//! REMATCH <yes|no>         the sender wants another game or not
+//! CHAT <text>              the sender says <text>, e.g. CHAT gg
+//! BYE                      the sender leaves
+//! ERROR <text>             the last message was refused
+//! ```
+//!
+//! A guest that lost the connection connects again and says HELLO, the
+//! host then sends the current game again with WELCOME, so both sides
+//! continue from the same position.
+//!
+//! Spectators only listen: the host sends them every MOVE and RESIGN of
+//! both sides and a BYE at the end, and reads nothing from them. The chat
+//! of the players is passed on to them as `CHAT <mark>: <text>`.
+//!
+//! A `Link` is the connection of a player to the other one, which it
+//! restores when it is lost.
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::board::BoardChar;
+use crate::engine::{GameSnapshot, TicTacToe};
+use crate::json::{self, FromJson};
+use crate::r#move::Move;
+use crate::record::Comment;
+
+/// Version of the protocol spoken by this build
+pub const PROTOCOL_VERSION: u32 = 1;
+/// How often the guest tries to reach the host before giving up
+pub const CONNECT_ATTEMPTS: usize = 10;
+/// Pause between two attempts to reach the host
+pub const CONNECT_RETRY: Duration = Duration::from_secs(1);
+
+/// What the connecting side comes for
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Role {
+    /// Plays the other side of the game
+    Player,
+    /// Watches the game and cannot move
+    Spectator,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Player => write!(f, "player"),
+            Role::Spectator => write!(f, "spectator"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "player" => Ok(Role::Player),
+            "spectator" => Ok(Role::Spectator),
+            tr => Err(format!("'{}' is not one of 'player', 'spectator'", tr)),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Message {
+    Hello(u32, Role),
+    Welcome { mark: BoardChar, state: String },
+    Game(String),
+    Move(Move),
+    Resign,
+    Rematch(bool),
+    Chat(String),
+    Bye,
+    Error(String),
+}
+
+impl fmt::Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Message::Hello(version, Role::Player) => write!(f, "HELLO {}", version),
+            Message::Hello(version, role) => write!(f, "HELLO {} {}", version, role),
+            Message::Welcome { mark, state } => write!(f, "WELCOME {} {}", mark, state),
+            Message::Game(state) => write!(f, "GAME {}", state),
+            Message::Move(m) => write!(f, "MOVE {}", m),
+            Message::Resign => write!(f, "RESIGN"),
+            Message::Rematch(yes) => write!(f, "REMATCH {}", if *yes { "yes" } else { "no" }),
+            Message::Chat(text) => write!(f, "CHAT {}", text),
+            Message::Bye => write!(f, "BYE"),
+            Message::Error(text) => write!(f, "ERROR {}", text),
+        }
+    }
+}
+
+impl FromStr for Message {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tr = s.trim();
+        let (word, rest) = match tr.split_once(' ') {
+            Some((word, rest)) => (word, rest.trim()),
+            None => (tr, ""),
+        };
+
+        match word {
+            "HELLO" => {
+                // A HELLO without a role comes from a player, as it did
+                // before there were spectators
+                let (version, role) = rest.split_once(' ').unwrap_or((rest, "player"));
+                let version = version
+                    .parse()
+                    .map_err(|_| format!("Could not parse the protocol version: {}", version))?;
+
+                Ok(Message::Hello(version, role.parse()?))
+            }
+            "WELCOME" => match rest.split_once(' ') {
+                Some((mark, state)) => Ok(Message::Welcome {
+                    mark: mark.parse()?,
+                    state: state.trim().to_string(),
+                }),
+                None => Err(format!("WELCOME needs a mark and a game: {}", tr)),
+            },
+            "GAME" if !rest.is_empty() => Ok(Message::Game(rest.to_string())),
+            "GAME" => Err(format!("GAME needs a game: {}", tr)),
+            "MOVE" => rest.parse().map(Message::Move).map_err(String::from),
+            "RESIGN" => Ok(Message::Resign),
+            "REMATCH" => match rest {
+                "yes" => Ok(Message::Rematch(true)),
+                "no" => Ok(Message::Rematch(false)),
+                _ => Err(format!("REMATCH needs yes or no: {}", tr)),
+            },
+            "CHAT" => Ok(Message::Chat(rest.to_string())),
+            "BYE" => Ok(Message::Bye),
+            "ERROR" => Ok(Message::Error(rest.to_string())),
+            _ => Err(format!("Unknown message: {}", tr)),
+        }
+    }
+}
+
+/// One end of a connection between the two players
+pub struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Connection> {
+        Ok(Connection {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: stream,
+        })
+    }
+
+    /// This function waits on `listener` for the next guest and reads its
+    /// HELLO, which tells the role it comes for. Guests speaking another
+    /// protocol version are turned away.
+    pub fn accept(listener: &TcpListener) -> io::Result<(Connection, Role)> {
+        loop {
+            let (stream, _) = listener.accept()?;
+            let mut connection = Connection::new(stream)?;
+
+            // A guest that hangs up before its HELLO is not worth more
+            match connection.receive() {
+                Ok(Message::Hello(PROTOCOL_VERSION, role)) => return Ok((connection, role)),
+                Ok(Message::Hello(version, _)) => {
+                    let text = format!("protocol version {} is not supported, this host speaks {}", version, PROTOCOL_VERSION);
+                    let _ = connection.send(&Message::Error(text));
+                }
+                Ok(other) => {
+                    let _ = connection.send(&Message::Error(format!("expected HELLO, not {}", other)));
+                }
+                Err(_) => {}
+            }
+        }
+    }
+
+    /// This function connects to the host at `addr` and says HELLO as
+    /// `role`. It tries `CONNECT_ATTEMPTS` times before it gives up.
+    pub fn connect<A: ToSocketAddrs>(addr: A, role: Role) -> io::Result<Connection> {
+        let mut attempt = 1;
+
+        loop {
+            match TcpStream::connect(&addr) {
+                Ok(stream) => {
+                    let mut connection = Connection::new(stream)?;
+                    connection.send(&Message::Hello(PROTOCOL_VERSION, role))?;
+                    return Ok(connection);
+                }
+                Err(e) if attempt >= CONNECT_ATTEMPTS => return Err(e),
+                Err(_) => {
+                    attempt += 1;
+                    thread::sleep(CONNECT_RETRY);
+                }
+            }
+        }
+    }
+
+    /// This function returns the address of the other end
+    pub fn peer(&self) -> io::Result<SocketAddr> {
+        self.writer.peer_addr()
+    }
+
+    pub fn send(&mut self, message: &Message) -> io::Result<()> {
+        writeln!(self.writer, "{}", message)?;
+        self.writer.flush()
+    }
+
+    /// This function waits for the next message. A closed connection is
+    /// reported as `UnexpectedEof`, a line that is no message as `InvalidData`.
+    pub fn receive(&mut self) -> io::Result<Message> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the connection was closed"));
+        }
+
+        line.parse().map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// The spectators of a hosted game and the game they are shown. It is
+/// shared between the host and the thread letting guests in with `admit`.
+#[derive(Clone, Default)]
+pub struct Audience {
+    seats: Arc<Mutex<Seats>>,
+}
+
+#[derive(Default)]
+struct Seats {
+    spectators: Vec<Connection>,
+    state: String,
+}
+
+impl Audience {
+    /// This function lets `connection` watch, it is sent the game first
+    pub fn seat(&self, mut connection: Connection) {
+        let mut seats = self.seats.lock().unwrap();
+
+        if connection.send(&Message::Game(seats.state.clone())).is_ok() {
+            seats.spectators.push(connection);
+        }
+    }
+
+    /// This function sends `message` to every spectator. Spectators that
+    /// cannot be reached any more are dropped.
+    pub fn broadcast(&self, message: &Message) {
+        self.seats.lock().unwrap().spectators.retain_mut(|connection| connection.send(message).is_ok());
+    }
+
+    /// This function shows the spectators `state`, the game as one line of
+    /// JSON, and every spectator coming from now on
+    pub fn show(&self, state: String) {
+        self.broadcast(&Message::Game(state.clone()));
+        self.seats.lock().unwrap().state = state;
+    }
+
+    /// This function returns how many spectators watch
+    pub fn len(&self) -> usize {
+        self.seats.lock().unwrap().spectators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// This function lets guests in on `listener` in the background until it
+/// fails. Spectators are seated in `audience`, players are handed out by
+/// the returned receiver in the order they come.
+pub fn admit(listener: TcpListener, audience: Audience) -> Receiver<Connection> {
+    let (players, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        while let Ok((connection, role)) = Connection::accept(&listener) {
+            match role {
+                Role::Player => {
+                    if players.send(connection).is_err() {
+                        return;
+                    }
+                }
+                Role::Spectator => audience.seat(connection),
+            }
+        }
+    });
+
+    receiver
+}
+
+/// This function tells the guest its mark and the position of the game
+pub fn welcome(connection: &mut Connection, game: &TicTacToe) -> Result<(), String> {
+    let message = Message::Welcome {
+        mark: game.machine_char(),
+        state: game.to_json(),
+    };
+
+    connection.send(&message).map_err(|e| e.to_string())
+}
+
+/// This function reads the WELCOME of the host and returns the game seen
+/// from the guest's side of the board
+pub fn welcomed(connection: &mut Connection) -> Result<TicTacToe, String> {
+    match connection.receive().map_err(|e| e.to_string())? {
+        Message::Welcome { mark, state } => {
+            let mut state = GameSnapshot::from_json(&json::parse(&state)?)?;
+            if mark != state.machine_char {
+                return Err(format!("The host offered {} but the game says otherwise", mark));
+            }
+
+            std::mem::swap(&mut state.player_char, &mut state.machine_char);
+            Ok(TicTacToe::from_snapshot(&state)?)
+        }
+        Message::Error(text) => Err(format!("The host refused: {}", text)),
+        other => Err(format!("Expected WELCOME from the host, not {}", other)),
+    }
+}
+
+/// Which end of the game a `Link` is
+enum End {
+    /// This side owns the game, the guests come in through `players` and
+    /// the spectators watch in `audience`
+    Host { players: Receiver<Connection>, audience: Audience },
+    /// This side joined the game at `addr`
+    Guest { addr: String },
+}
+
+/// The connection to the remote player, how to get it back once it is lost
+/// and the chat of the game being played
+pub struct Link {
+    end: End,
+    connection: Connection,
+    /// What the two players said during the game, for its record
+    chat: Vec<Comment>,
+    /// Why the connection could not be restored
+    failure: Option<String>,
+}
+
+impl Link {
+    /// This function returns the link of the host to the guest on
+    /// `connection`, who comes back through `players` when it is lost
+    pub fn host(players: Receiver<Connection>, connection: Connection, audience: Audience) -> Link {
+        Link::new(End::Host { players, audience }, connection)
+    }
+
+    /// This function returns the link of a guest to the host at `addr`
+    pub fn guest(addr: &str, connection: Connection) -> Link {
+        Link::new(End::Guest { addr: addr.to_string() }, connection)
+    }
+
+    fn new(end: End, connection: Connection) -> Link {
+        Link {
+            end,
+            connection,
+            chat: Vec::new(),
+            failure: None,
+        }
+    }
+
+    pub fn connection(&mut self) -> &mut Connection {
+        &mut self.connection
+    }
+
+    /// This function returns why the connection could not be restored, if
+    /// it could not
+    pub fn failure(&self) -> Option<&str> {
+        self.failure.as_deref()
+    }
+
+    /// This function returns the chat of the game so far and starts the
+    /// chat of the next one
+    pub fn take_chat(&mut self) -> Vec<Comment> {
+        std::mem::take(&mut self.chat)
+    }
+
+    /// This function shows `message`, a move or the end of the game, to the
+    /// spectators. Only the host has any.
+    pub fn broadcast(&self, message: &Message) {
+        if let End::Host { audience, .. } = &self.end {
+            audience.broadcast(message);
+        }
+    }
+
+    /// This function returns the names of the host and the guest in a record
+    /// as the names of X and O in `game`
+    pub fn names(&self, game: &TicTacToe) -> (&'static str, &'static str) {
+        let (me, other) = match self.end {
+            End::Host { .. } => ("host", "guest"),
+            End::Guest { .. } => ("guest", "host"),
+        };
+
+        match game.player_char() {
+            BoardChar::X => (me, other),
+            _ => (other, me),
+        }
+    }
+
+    /// This function shows the spectators `game` as it stands
+    pub fn show(&self, game: &TicTacToe) {
+        if let End::Host { audience, .. } = &self.end {
+            audience.show(game.to_json());
+        }
+    }
+
+    /// This function keeps `text`, said by side `mark`, in the chat of the
+    /// game and passes it on to the spectators
+    pub fn log(&mut self, mark: BoardChar, text: &str, game: &TicTacToe) {
+        let said = format!("{}: {}", mark, text);
+        self.broadcast(&Message::Chat(said.clone()));
+        self.chat.push(Comment {
+            after: game.moves().len(),
+            text: said,
+        });
+    }
+
+    /// This function sends `message`. If the connection is lost it is
+    /// restored instead, which brings `game` in sync with the host again.
+    pub fn send(&mut self, message: &Message, game: &mut TicTacToe) -> Result<(), String> {
+        match self.connection.send(message) {
+            Ok(()) => Ok(()),
+            Err(_) => self.reconnect(game),
+        }
+    }
+
+    /// This function waits for the next message. It returns `None` if the
+    /// connection had to be restored in the meantime, `game` then holds the
+    /// position of the host and the caller should look at it anew.
+    pub fn receive(&mut self, game: &mut TicTacToe) -> Result<Option<Message>, String> {
+        loop {
+            match self.connection.receive() {
+                Ok(message) => return Ok(Some(message)),
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    let _ = self.connection.send(&Message::Error(e.to_string()));
+                }
+                Err(_) => {
+                    self.reconnect(game)?;
+                    return Ok(None);
+                }
+            }
+        }
+    }
+
+    /// This function restores a lost connection: the host waits for the
+    /// guest to come back and the guest connects again
+    pub fn reconnect(&mut self, game: &mut TicTacToe) -> Result<(), String> {
+        let restored = match &self.end {
+            End::Host { players, .. } => {
+                println!("The connection was lost, waiting for your opponent to come back");
+                players
+                    .recv()
+                    .map_err(|_| "Nobody can join any more".to_string())
+                    .and_then(|mut connection| welcome(&mut connection, game).map(|()| connection))
+            }
+            End::Guest { addr } => {
+                println!("The connection was lost, connecting again");
+                Connection::connect(addr.as_str(), Role::Player)
+                    .map_err(|e| format!("Could not reconnect: {}", e))
+                    .and_then(|mut connection| {
+                        let theme = game.theme();
+                        *game = welcomed(&mut connection)?;
+                        game.set_theme(theme);
+                        Ok(connection)
+                    })
+            }
+        };
+
+        match restored {
+            Ok(connection) => {
+                self.connection = connection;
+                Ok(())
+            }
+            Err(e) => {
+                self.failure = Some(e.clone());
+                Err(e)
+            }
+        }
+    }
+}
@@ -0,0 +1,476 @@
+//! Notakto: both sides place X's on one or more 3x3 boards. A board with
+//! three in a row is dead and takes no more marks, and the side that kills
+//! the last live board loses.
+//!
+//! Boards are kept as bit masks, bit `row * 3 + col` set for a mark. The
+//! sides keep the names X and O only to tell them apart, every mark on the
+//! boards is an X. `Solver` plays the game perfectly: it knows that boards
+//! which are rotations or reflections of each other play the same, and
+//! remembers every position it has searched, so a few boards are solved
+//! in a moment.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::board::{BoardChar, WinLine};
+use crate::engine::{AiConfig, Difficulty, Engine, Minimax, SearchInfo, MEDIUM_RANDOM_PERCENT};
+use crate::error::GameError;
+use crate::game::{self, Game};
+use crate::player::{Symbols, Table};
+use crate::r#move::{Move, NotaktoMove};
+use crate::render::{draw_board, side_by_side, Theme};
+use crate::rng::Rng;
+
+const SIZE: usize = 3;
+const CELLS: usize = SIZE * SIZE;
+/// The center of a board, which lies on more lines than any other cell
+const CENTER: usize = CELLS / 2;
+/// The most boards a game is played on
+pub const MAX_BOARDS: usize = 5;
+/// The rows, columns and diagonals of a board as masks
+const LINES: [u16; 8] = [0o007, 0o070, 0o700, 0o111, 0o222, 0o444, 0o421, 0o124];
+/// Where every cell lands in the four rotations and four reflections of a board
+const IMAGES: [[usize; CELLS]; 8] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8],
+    [2, 5, 8, 1, 4, 7, 0, 3, 6],
+    [8, 7, 6, 5, 4, 3, 2, 1, 0],
+    [6, 3, 0, 7, 4, 1, 8, 5, 2],
+    [2, 1, 0, 5, 4, 3, 8, 7, 6],
+    [6, 7, 8, 3, 4, 5, 0, 1, 2],
+    [0, 3, 6, 1, 4, 7, 2, 5, 8],
+    [8, 5, 2, 7, 4, 1, 6, 3, 0],
+];
+
+/// This function returns the line completed on `board`, if any
+fn dead_line(board: u16) -> Option<u16> {
+    LINES.iter().copied().find(|&line| board & line == line)
+}
+
+/// Every board mapped to the smallest mask among its rotations and
+/// reflections, which is shared by all of them
+const CANONICAL: [u16; 1 << CELLS] = canonical_boards();
+
+/// This function makes `CANONICAL`
+const fn canonical_boards() -> [u16; 1 << CELLS] {
+    let mut table = [0; 1 << CELLS];
+    let mut board = 0;
+
+    while board < table.len() {
+        let mut smallest = u16::MAX;
+        let mut k = 0;
+        while k < IMAGES.len() {
+            let mut mask = 0;
+            let mut i = 0;
+            while i < CELLS {
+                if board & 1 << i != 0 {
+                    mask |= 1 << IMAGES[k][i];
+                }
+                i += 1;
+            }
+            if mask < smallest {
+                smallest = mask;
+            }
+            k += 1;
+        }
+        table[board] = smallest;
+        board += 1;
+    }
+
+    table
+}
+
+#[derive(Clone)]
+pub struct Notakto {
+    /// Every board, the dead ones as well
+    boards: Vec<u16>,
+    /// The side to move
+    turn: BoardChar,
+    /// The side that opens every game
+    first: BoardChar,
+    /// How many moves ahead the machine looks, `None` for the `Solver`
+    max_depth: Option<usize>,
+    player_char: BoardChar,
+    machine_char: BoardChar,
+    /// How the boards are drawn by `Display`
+    theme: Theme,
+    rng: Rng,
+    ai: AiConfig,
+}
+
+impl Notakto {
+    /// This function starts an empty game on `boards` boards, at least one
+    /// and at most `MAX_BOARDS`, in which the player is the side
+    /// `player_char`. X opens unless `set_first` says otherwise.
+    pub fn new(player_char: BoardChar, boards: usize) -> Notakto {
+        Notakto {
+            boards: vec![0; boards.clamp(1, MAX_BOARDS)],
+            turn: BoardChar::X,
+            first: BoardChar::X,
+            max_depth: None,
+            player_char,
+            machine_char: player_char.to_opposite(),
+            theme: Theme::default(),
+            rng: Rng::from_time(),
+            ai: AiConfig::default(),
+        }
+    }
+
+    /// This function limits how many moves ahead the machine looks. `None`,
+    /// the default, lets the `Solver` play.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn set_ai(&mut self, ai: AiConfig) {
+        self.ai = ai;
+    }
+
+    /// This function reseeds the machine's random choices
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    /// This function changes how the boards are drawn
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn player_char(&self) -> BoardChar {
+        self.player_char
+    }
+
+    pub fn machine_char(&self) -> BoardChar {
+        self.machine_char
+    }
+
+    /// This function returns the side to move next
+    pub fn turn(&self) -> BoardChar {
+        self.turn
+    }
+
+    /// This function lets the side `c` open the game. It has no effect once
+    /// a move has been played.
+    pub fn set_first(&mut self, c: BoardChar) {
+        if self.is_empty() && c != BoardChar::Empty {
+            self.turn = c;
+            self.first = c;
+        }
+    }
+
+    /// This function returns true if no mark has been placed yet
+    pub fn is_empty(&self) -> bool {
+        self.boards.iter().all(|&board| board == 0)
+    }
+
+    /// This function clears the boards for a new game, the opener stays the same
+    pub fn reset(&mut self) {
+        self.boards.iter_mut().for_each(|board| *board = 0);
+        self.turn = self.first;
+    }
+
+    /// This function returns how many boards the game is played on
+    pub fn board_count(&self) -> usize {
+        self.boards.len()
+    }
+
+    /// This function returns true if the board numbered `board` from 0 has
+    /// three in a row
+    pub fn is_dead(&self, board: usize) -> bool {
+        self.boards.get(board).is_some_and(|&board| dead_line(board).is_some())
+    }
+
+    /// The boards still alive
+    fn live(&self) -> Vec<u16> {
+        self.boards.iter().copied().filter(|&board| dead_line(board).is_none()).collect()
+    }
+
+    /// This function returns the side that won, the one that did not kill
+    /// the last board, once the game is over
+    pub fn winner(&self) -> Option<BoardChar> {
+        Some(self.turn).filter(|_| self.is_game_over())
+    }
+
+    /// This function returns true once every board is dead
+    pub fn is_game_over(&self) -> bool {
+        (0..self.boards.len()).all(|board| self.is_dead(board))
+    }
+
+    /// This function lists the empty cells of the live boards, the center of
+    /// each board first
+    pub fn legal_moves(&self) -> Vec<NotaktoMove> {
+        let mut moves = Vec::new();
+
+        for (board, &mask) in self.boards.iter().enumerate() {
+            if dead_line(mask).is_some() {
+                continue;
+            }
+
+            let mut cells: Vec<usize> = (0..CELLS).filter(|&i| mask & 1 << i == 0).collect();
+            if let Some(i) = cells.iter().position(|&i| i == CENTER) {
+                cells.swap(0, i);
+            }
+            moves.extend(cells.into_iter().map(|i| NotaktoMove {
+                cell: Move { row: i / SIZE, col: i % SIZE },
+                board,
+            }));
+        }
+
+        moves
+    }
+
+    /// This function places an X on the cell of `m` for the side to move
+    pub fn play_move(&mut self, m: &NotaktoMove) -> Result<(), GameError> {
+        if self.is_game_over() {
+            return Err(GameError::GameAlreadyOver);
+        }
+        if m.board >= self.boards.len() || m.cell.row >= SIZE || m.cell.col >= SIZE {
+            return Err(GameError::OutOfBounds);
+        }
+        if self.is_dead(m.board) {
+            return Err(GameError::WrongBoard);
+        }
+        let bit = 1 << (m.cell.row * SIZE + m.cell.col);
+        if self.boards[m.board] & bit != 0 {
+            return Err(GameError::CellOccupied);
+        }
+
+        self.boards[m.board] |= bit;
+        self.turn = self.turn.to_opposite();
+
+        Ok(())
+    }
+
+    /// This function makes the machine's move, thinking no longer than `budget`.
+    /// It returns the chosen move and the search depth that was completed.
+    pub fn machine_move(&mut self, budget: Duration) -> Result<(NotaktoMove, usize), GameError> {
+        let (m, depth) = self.best_move(Some(Instant::now() + budget)).ok_or(GameError::GameAlreadyOver)?;
+        self.play_move(&m)?;
+
+        Ok((m, depth))
+    }
+
+    /// This function returns the move the machine would play, thinking
+    /// until `deadline`, and the search depth that was completed. It
+    /// returns `None` once the game is over.
+    pub fn best_move(&mut self, deadline: Option<Instant>) -> Option<(NotaktoMove, usize)> {
+        if self.is_game_over() {
+            return None;
+        }
+
+        match self.ai.difficulty {
+            Difficulty::Easy => self.random_move().map(|m| (m, 0)),
+            Difficulty::Medium if self.rng.below(100) < MEDIUM_RANDOM_PERCENT => {
+                self.random_move().map(|m| (m, 0))
+            }
+            Difficulty::Medium | Difficulty::Hard => {
+                let mut engine = self.ai.engine(&mut self.rng, self.max_depth);
+                engine.best_move(self, deadline)
+            }
+        }
+    }
+
+    /// This function returns a random legal move
+    fn random_move(&mut self) -> Option<NotaktoMove> {
+        let moves = self.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        Some(moves[self.rng.below(moves.len())])
+    }
+}
+
+/// The perfect player of Notakto. It keeps what it learned about positions
+/// between moves, so the same solver should play a whole game.
+#[derive(Default)]
+pub struct Solver {
+    /// Whether the side to move wins, by the sorted canonical live boards
+    /// packed ten bits each
+    memo: HashMap<u64, bool>,
+}
+
+impl Solver {
+    pub fn new() -> Solver {
+        Solver::default()
+    }
+
+    /// This function returns true if the side to move in `game` wins with
+    /// perfect play
+    pub fn wins(&mut self, game: &Notakto) -> bool {
+        self.search(&game.live())
+    }
+
+    /// This function returns a winning move of the side to move in `game`.
+    /// In a lost position it plays on without killing the last board as long
+    /// as it can, hoping the opponent goes wrong.
+    pub fn best_move(&mut self, game: &Notakto) -> Option<NotaktoMove> {
+        let moves = game.legal_moves();
+        let after = |m: &NotaktoMove| {
+            let mut next = game.clone();
+            next.play_move(m).ok().map(|_| next)
+        };
+
+        moves
+            .iter()
+            .copied()
+            .find(|m| after(m).is_some_and(|next| !self.wins(&next)))
+            .or_else(|| moves.iter().copied().find(|m| after(m).is_some_and(|next| !next.is_game_over())))
+            .or_else(|| moves.first().copied())
+    }
+
+    /// This function returns true if the side to move wins on the live
+    /// boards `boards`. With none left the opponent killed the last one.
+    fn search(&mut self, boards: &[u16]) -> bool {
+        if boards.is_empty() {
+            return true;
+        }
+
+        let live = boards.len();
+        let mut key = [0; MAX_BOARDS];
+        for (canonical, &board) in key.iter_mut().zip(boards) {
+            *canonical = CANONICAL[usize::from(board)];
+        }
+        key[..live].sort_unstable();
+        // A mask is below 512, so one more than it fits ten bits and no
+        // board packs to 0
+        let packed = key[..live].iter().fold(0, |packed, &board| packed << 10 | (u64::from(board) + 1));
+        if let Some(&wins) = self.memo.get(&packed) {
+            return wins;
+        }
+
+        let mut wins = false;
+        'boards: for i in 0..live {
+            // Equal boards offer the same moves
+            if i > 0 && key[i - 1] == key[i] {
+                continue;
+            }
+
+            for cell in (0..CELLS).filter(|&cell| key[i] & 1 << cell == 0) {
+                let next = key[i] | 1 << cell;
+                let mut rest = key;
+                let left = if dead_line(next).is_some() {
+                    rest[i] = rest[live - 1];
+                    live - 1
+                } else {
+                    rest[i] = next;
+                    live
+                };
+
+                if !self.search(&rest[..left]) {
+                    wins = true;
+                    break 'boards;
+                }
+            }
+        }
+
+        self.memo.insert(packed, wins);
+        wins
+    }
+}
+
+/// Without a depth limit the `Solver` plays, which needs no deadline, and
+/// with one the search of `game::iterative_deepening`
+impl Engine<Notakto> for Minimax {
+    fn best_move(&mut self, game: &mut Notakto, deadline: Option<Instant>) -> Option<(NotaktoMove, usize)> {
+        let left = game.live().iter().map(|board| CELLS - board.count_ones() as usize).sum();
+
+        match self.max_depth {
+            None => Solver::new().best_move(game).map(|m| (m, left)),
+            Some(max) => game::iterative_deepening(game, deadline, max.min(left)),
+        }
+    }
+}
+
+impl Game for Notakto {
+    type Move = NotaktoMove;
+
+    fn legal_moves(&self) -> Vec<NotaktoMove> {
+        Notakto::legal_moves(self)
+    }
+
+    fn play(&mut self, m: &NotaktoMove) -> Result<(), GameError> {
+        self.play_move(m)
+    }
+
+    fn turn(&self) -> BoardChar {
+        Notakto::turn(self)
+    }
+
+    fn winner(&self) -> Option<BoardChar> {
+        Notakto::winner(self)
+    }
+
+    fn is_game_over(&self) -> bool {
+        Notakto::is_game_over(self)
+    }
+
+    /// A position the search could not finish is too hard to judge by its
+    /// marks, every one of them counts as even
+    fn score(&self, _me: BoardChar) -> i16 {
+        0
+    }
+}
+
+impl Table for Notakto {
+    fn player_char(&self) -> BoardChar {
+        Notakto::player_char(self)
+    }
+
+    fn machine_char(&self) -> BoardChar {
+        Notakto::machine_char(self)
+    }
+
+    fn glyph(&self, _c: BoardChar) -> Option<char> {
+        None
+    }
+
+    fn set_first(&mut self, c: BoardChar) {
+        Notakto::set_first(self, c);
+    }
+
+    fn reset(&mut self) {
+        Notakto::reset(self);
+    }
+
+    fn machine_choice(&mut self, deadline: Option<Instant>) -> Option<(NotaktoMove, SearchInfo)> {
+        let (m, depth) = self.best_move(deadline)?;
+        Some((m, SearchInfo { depth, ..SearchInfo::default() }))
+    }
+}
+
+impl fmt::Display for Notakto {
+    /// The boards are drawn side by side, the line that killed a board
+    /// highlighted
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let boards: Vec<Vec<String>> = self
+            .boards
+            .iter()
+            .enumerate()
+            .map(|(n, &mask)| {
+                let board: Vec<Vec<BoardChar>> = (0..SIZE)
+                    .map(|row| {
+                        (0..SIZE)
+                            .map(|col| if mask & 1 << (row * SIZE + col) != 0 { BoardChar::X } else { BoardChar::Empty })
+                            .collect()
+                    })
+                    .collect();
+                let cells: Vec<(usize, usize)> = dead_line(mask)
+                    .map(|line| (0..CELLS).filter(|&i| line & 1 << i != 0).map(|i| (i / SIZE, i % SIZE)).collect())
+                    .unwrap_or_default();
+                let line = Some(WinLine {
+                    mark: BoardChar::X,
+                    cells: &cells,
+                })
+                .filter(|_| !cells.is_empty());
+
+                let mut lines: Vec<String> = draw_board(&board, Symbols::default(), self.theme, line).lines().map(String::from).collect();
+                let dead = if cells.is_empty() { "" } else { " (dead)" };
+                lines.insert(0, format!("board {}{}", n + 1, dead));
+                lines
+            })
+            .collect();
+
+        writeln!(f, "{}", side_by_side(&boards))
+    }
+}
@@ -0,0 +1,124 @@
+//! Hooks telling the player that the opponent moved or the game is over,
+//! so that a slow opponent, e.g. a peer on the network, can be waited for
+//! in another window. A frontend calls its `Notifier` and does not care how
+//! the player is told.
+
+use std::io::{self, Write};
+#[cfg(feature = "desktop-notify")]
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+
+use crate::error::GameError;
+
+/// Something that tells the player about the game
+pub trait Notifier {
+    /// This function is told that the opponent played `m`, written the way
+    /// the frontend announces moves, and that it is the player's turn
+    fn your_turn(&mut self, _m: &str) {}
+
+    /// This function is told that the game is over, `result` is the line
+    /// the frontend announces it with
+    fn game_over(&mut self, _result: &str) {}
+}
+
+/// Tells nothing
+pub struct Silent;
+
+impl Notifier for Silent {}
+
+/// Rings the bell of the terminal
+pub struct Bell;
+
+impl Bell {
+    fn ring(&self) {
+        let mut out = io::stdout();
+        let _ = out.write_all(b"\x07");
+        let _ = out.flush();
+    }
+}
+
+impl Notifier for Bell {
+    fn your_turn(&mut self, _m: &str) {
+        self.ring();
+    }
+
+    fn game_over(&mut self, _result: &str) {
+        self.ring();
+    }
+}
+
+/// Shows a notification on the desktop with `notify-send`, or
+/// `osascript` on macOS. Built only with the `desktop-notify` feature.
+#[cfg(feature = "desktop-notify")]
+pub struct Desktop;
+
+#[cfg(feature = "desktop-notify")]
+impl Desktop {
+    /// This function shows `body`. A desktop that cannot show it is not
+    /// worth interrupting the game for.
+    fn show(&self, body: &str) {
+        let mut command = if cfg!(target_os = "macos") {
+            let script = format!("display notification {:?} with title \"Tic-Tac-Toe\"", body);
+            let mut command = Command::new("osascript");
+            command.args(["-e", &script]);
+            command
+        } else {
+            let mut command = Command::new("notify-send");
+            command.args(["Tic-Tac-Toe", body]);
+            command
+        };
+
+        let _ = command.stdout(Stdio::null()).stderr(Stdio::null()).status();
+    }
+}
+
+#[cfg(feature = "desktop-notify")]
+impl Notifier for Desktop {
+    fn your_turn(&mut self, m: &str) {
+        self.show(&format!("Your opponent played {}, it is your turn", m));
+    }
+
+    fn game_over(&mut self, result: &str) {
+        self.show(result);
+    }
+}
+
+/// The notifiers to choose from on the command line
+#[derive(Debug, PartialEq, Default, Copy, Clone)]
+pub enum Notification {
+    #[default]
+    None,
+    Bell,
+    #[cfg(feature = "desktop-notify")]
+    Desktop,
+}
+
+impl Notification {
+    /// This function returns the notifier that tells the player this way
+    pub fn notifier(self) -> Box<dyn Notifier> {
+        match self {
+            Notification::None => Box::new(Silent),
+            Notification::Bell => Box::new(Bell),
+            #[cfg(feature = "desktop-notify")]
+            Notification::Desktop => Box::new(Desktop),
+        }
+    }
+}
+
+impl FromStr for Notification {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "none" => Ok(Notification::None),
+            "bell" => Ok(Notification::Bell),
+            #[cfg(feature = "desktop-notify")]
+            "desktop" => Ok(Notification::Desktop),
+            #[cfg(not(feature = "desktop-notify"))]
+            "desktop" => Err(GameError::InvalidInput(
+                "Desktop notifications need the desktop-notify feature".to_string(),
+            )),
+            tr => Err(GameError::InvalidInput(format!("'{}' is not one of 'none', 'bell', 'desktop'", tr))),
+        }
+    }
+}
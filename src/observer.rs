@@ -0,0 +1,85 @@
+//! Hooks on the events of a game. A logger, a frontend, a broadcaster or a
+//! statistics recorder registers an `Observer` on a `TicTacToe` with
+//! `add_observer` and is told about every move, the end of the game and
+//! every search of the machine, however the game is played.
+
+use std::sync::Arc;
+
+use crate::engine::{SearchInfo, TicTacToe};
+use crate::history::Placement;
+
+/// Is told about what happens in a game. Every callback does nothing unless
+/// it is implemented. The searches of the machine may run on a thread of
+/// their own, so the callbacks take `&self` and keep their state behind a
+/// lock or in atomics.
+pub trait Observer: Send + Sync {
+    /// This function is told about every move once it is played
+    fn on_move(&self, _game: &TicTacToe, _placement: &Placement) {}
+
+    /// This function is told once the game is over, by a move or because a
+    /// side resigned, ran out of time or agreed to a draw
+    fn on_game_over(&self, _game: &TicTacToe) {}
+
+    /// This function is told when the machine starts looking for a move
+    fn on_search_start(&self, _game: &TicTacToe) {}
+
+    /// This function is told when the machine found its move, before it is
+    /// played, and what the search did
+    fn on_search_stop(&self, _game: &TicTacToe, _info: &SearchInfo) {}
+}
+
+/// The observers of a game. A copy of the game, like the ones the searches
+/// play their moves on, has none, so only the game itself is observed.
+#[derive(Default)]
+pub(crate) struct Observers(pub(crate) Vec<Arc<dyn Observer>>);
+
+impl Clone for Observers {
+    fn clone(&self) -> Observers {
+        Observers::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::BoardChar;
+    use crate::r#move::Move;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct Counter {
+        moves: AtomicUsize,
+        over: AtomicUsize,
+    }
+
+    impl Observer for Counter {
+        fn on_move(&self, _game: &TicTacToe, _placement: &Placement) {
+            self.moves.fetch_add(1, Ordering::SeqCst);
+        }
+
+        fn on_game_over(&self, _game: &TicTacToe) {
+            self.over.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn is_told_the_moves_and_the_end_but_not_by_clones() {
+        let counter = Arc::new(Counter::default());
+        let mut game = TicTacToe::new(BoardChar::X);
+        game.add_observer(Arc::clone(&counter) as Arc<dyn Observer>);
+
+        let mut copy = game.clone();
+        copy.play_move(&Move { row: 0, col: 0 }).unwrap();
+        assert_eq!(counter.moves.load(Ordering::SeqCst), 0);
+
+        for &(row, col) in &[(0, 0), (1, 0), (0, 1), (1, 1), (0, 2)] {
+            game.play_move(&Move { row, col }).unwrap();
+        }
+        assert_eq!(counter.moves.load(Ordering::SeqCst), 5);
+        assert_eq!(counter.over.load(Ordering::SeqCst), 1);
+
+        game.reset();
+        game.play_move(&Move { row: 1, col: 1 }).unwrap();
+        assert_eq!(counter.moves.load(Ordering::SeqCst), 6);
+    }
+}
@@ -0,0 +1,925 @@
+//! The command line of a game: the flags it takes, which of them go
+//! together and the usage printed for `help`. The flags are listed once, in
+//! `FLAGS`, and the usage and the errors for a missing value are made from
+//! that list; which flags exclude which is the list `RULES`.
+//!
+//! The tables take the place of clap, so a value is always the next
+//! argument after its flag and there are no short flags.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::time::Duration;
+
+use tic_tac_toe_rs::clock::parse_duration;
+use tic_tac_toe_rs::config::Config;
+use tic_tac_toe_rs::i18n::Lang;
+use tic_tac_toe_rs::notakto;
+use tic_tac_toe_rs::notify::{Notification, Notifier};
+use tic_tac_toe_rs::r#move::MAX_SIZE;
+use tic_tac_toe_rs::rng::Rng;
+use tic_tac_toe_rs::stats::Stats;
+use tic_tac_toe_rs::terminal::{Mode, Settings};
+use tic_tac_toe_rs::{AiConfig, Algorithm, Board, BoardChar, Difficulty, Move, MoveFormat, Notation, Personality, Theme, TicTacToe};
+
+use crate::commands::COMMANDS;
+
+use self::Part::*;
+
+/// Time the machine may think about a single move in the CLI unless
+/// `--think` says otherwise
+pub const MOVE_BUDGET: Duration = Duration::from_secs(2);
+
+/// A flag of the command line of a game
+pub struct Flag {
+    pub name: &'static str,
+    /// What follows the flag in the usage, empty for a switch. Values made
+    /// of words separated by `|` are the only ones the flag takes.
+    pub value: &'static str,
+    /// What the flag needs if its value is missing, empty for a flag that
+    /// takes one of the words of `value`
+    needs: &'static str,
+    /// What the flag does, for `help`
+    pub help: &'static str,
+    /// The flag is an alternative to the one before it in the usage
+    or: bool,
+}
+
+impl Flag {
+    /// This function returns the error for the flag given without its value
+    fn missing(&self) -> String {
+        match self.needs {
+            "" => format!("{} needs a value: {}", self.name, self.value.replace('|', ", ")),
+            needs => format!("{} needs {}", self.name, needs),
+        }
+    }
+}
+
+/// This function returns a flag of the table
+const fn flag(name: &'static str, value: &'static str, needs: &'static str, help: &'static str) -> Flag {
+    Flag {
+        name,
+        value,
+        needs,
+        help,
+        or: false,
+    }
+}
+
+/// This function returns a flag of the table that is an alternative to the
+/// one before it
+const fn or(name: &'static str, value: &'static str, needs: &'static str, help: &'static str) -> Flag {
+    Flag {
+        or: true,
+        ..flag(name, value, needs, help)
+    }
+}
+
+/// Every flag of a game, in the order of the usage
+pub const FLAGS: &[Flag] = &[
+    flag("--mode", "pve|pvp", "", "play against the machine or against a second person at this terminal"),
+    flag("--game", "tictactoe|connect4", "", "the board game"),
+    flag("--variant", "classic|ultimate|misere|gomoku|3d|wild|notakto", "", "the variant of Tic-Tac-Toe"),
+    flag("--boards", "N", "a number", "the number of boards Notakto is played on"),
+    flag("--difficulty", "easy|medium|hard", "", "how well the machine plays"),
+    flag("--ai", "minimax|mcts", "", "how the machine searches"),
+    flag("--no-book", "", "", "search the opening instead of answering it from the book"),
+    flag("--seed", "N", "a number", "seed of all random choices, so a game can be played again"),
+    flag("--symbol", "C", "a character, e.g. X", "the symbol of the player, asked for otherwise"),
+    flag("--size", "N", "a number", "the size of the board"),
+    flag("--win", "K", "a number", "the number of marks in a row that win, a full line by default"),
+    flag("--depth", "D", "a number", "how many moves ahead the machine looks at most"),
+    flag("--think", "TIME", "a time, e.g. 500ms or 5s", "how long the machine thinks about a move at most"),
+    flag("--threads", "N", "a number", "how many threads the machine searches with"),
+    flag("--first", "player|machine|random", "", "who opens a game against the machine"),
+    flag("--resume", "FILE", "a saved game file", "carry on with a saved game"),
+    or("--position", "POSITION", "a position, e.g. \"XO./.X./..O X\"", "start from a position and the side to move in it"),
+    flag("--host", "PORT", "a port", "wait for a remote opponent on a port"),
+    or("--connect", "ADDR", "an address, e.g. 192.168.1.2:4000", "play the game hosted at an address"),
+    or("--watch", "ADDR", "an address, e.g. 192.168.1.2:4000", "watch the game hosted at an address"),
+    flag("--opponent", "NAME", "the name of the remote opponent", "the name of the remote opponent in the statistics"),
+    flag("--tui", "", "", "play in the full-screen frontend"),
+    or("--json", "", "", "talk to a program in JSON lines instead of drawing the board"),
+    or("--engine", "", "", "speak the engine protocol on stdin and stdout"),
+    flag("--pie", "", "", "let the second side take over the first move instead of answering it"),
+    flag("--best-of", "N", "a number", "play a match of at most this many games"),
+    flag("--swap-symbols", "", "", "the sides of a match trade their symbols from game to game"),
+    flag("--clock", "TIME", "a time, e.g. 30s or 5m", "thinking time of each side for the whole game"),
+    flag("--move-time", "TIME", "a time, e.g. 10s", "thinking time of each side for a single move"),
+    flag("--speedrun", "", "", "time every move and print the times once the game is over"),
+    flag("--notation", "rowcol|colrow|numpad|pair", "", "how moves are announced, by default the way the player writes them"),
+    flag("--theme", "unicode|ascii|color|large|accessible", "", "how boards are drawn, by default what the terminal can show"),
+    flag("--accessible", "", "", "the accessible theme, for screen readers"),
+    flag("--teach", "", "", "explain every move of the player once it is played"),
+    flag("--verbose", "", "", "tell what the search of the machine did after each of its moves"),
+    flag("--handicap", "N|CELLS", "a number of marks or their cells, e.g. 1 or 2B", "extra marks the player starts every game with"),
+    flag("--blunder", "P", "a chance between 0 and 1, e.g. 0.15", "the chance that the machine plays a worse move than the best one"),
+    flag("--personality", "randy|sam|carla|pat", "", "the opponent the machine plays as, instead of the difficulty"),
+    flag("--bot", "COMMAND", "the command that runs the bot", "a bot that plays instead of the machine"),
+    flag("--bot-time", "TIME", "a time, e.g. 500ms or 5s", "thinking time of the bot for a single move"),
+    flag("--export", "FILE", "the file the records are added to", "a file every finished game is added to as a record"),
+    flag("--notify", "none|bell|desktop", "", "how the player is told that the opponent moved or the game is over"),
+    flag("--lang", "en|de|ru", "", "the language of the texts, by default the one of the locale"),
+    flag("--replay", "FILE|MOVES", "a saved game file or a move list", "step through a saved game or a move list instead of playing"),
+    or("--stats", "", "", "print the statistics instead of playing"),
+];
+
+/// This function returns the usage of the program, the flags of a game on
+/// the first line and every command after it
+pub fn usage() -> Vec<String> {
+    let mut game = "Usage: tic_tac-toe_rs [play]".to_string();
+    for f in FLAGS {
+        let item = match f.value {
+            "" => f.name.to_string(),
+            value => format!("{} {}", f.name, value),
+        };
+        match f.or {
+            true => {
+                game.pop();
+                game.push_str(&format!(" | {}]", item));
+            }
+            false => game.push_str(&format!(" [{}]", item)),
+        }
+    }
+
+    let mut lines = vec![game];
+    lines.extend(COMMANDS.iter().map(|command| format!("       tic_tac-toe_rs {}", command.usage())));
+    lines
+}
+
+/// This function returns the help of the program: the usage and what every
+/// flag of a game does
+pub fn help() -> Vec<String> {
+    let mut lines = usage();
+    lines.push(String::new());
+    lines.push("Flags of a game:".to_string());
+
+    let items: Vec<String> = FLAGS
+        .iter()
+        .map(|f| match f.value {
+            "" => f.name.to_string(),
+            value => format!("{} {}", f.name, value),
+        })
+        .collect();
+    let width = items.iter().map(String::len).max().unwrap_or_default();
+    for (item, f) in items.iter().zip(FLAGS) {
+        lines.push(format!("  {:<width$}  {}", item, f.help, width = width));
+    }
+
+    lines
+}
+
+/// This function returns the value that follows `name` on the command line
+fn value(args: &mut impl Iterator<Item = String>, name: &str) -> Result<String, String> {
+    args.next().ok_or_else(|| match FLAGS.iter().find(|f| f.name == name) {
+        Some(f) => f.missing(),
+        None => format!("{} needs a value", name),
+    })
+}
+
+/// A part of a command line that some flags do not go with
+#[derive(Debug, Copy, Clone)]
+enum Part {
+    ConnectFour,
+    /// Any variant but the classic one
+    NotClassic,
+    /// A variant with a board of its own, see `Variant::own_board`
+    OwnBoard,
+    /// Any variant but Notakto
+    NoNotakto,
+    Size,
+    Win,
+    Boards,
+    Resume,
+    FromPosition,
+    Replay,
+    Engine,
+    Tui,
+    Json,
+    Host,
+    Connect,
+    Watch,
+    /// Neither `--host` nor `--connect`
+    Offline,
+    Opponent,
+    /// `--clock` or `--move-time`
+    Clock,
+    Speedrun,
+    Teach,
+    Verbose,
+    Pie,
+    BestOf,
+    /// No `--best-of`
+    Single,
+    SwapSymbols,
+    Handicapped,
+    Blunder,
+    Persona,
+    Bot,
+    Export,
+    Accessible,
+    /// Two people at the terminal
+    HotSeat,
+}
+
+/// A part of a command line and the parts it does not go with, the message
+/// tells where it does go
+struct Rule {
+    part: Part,
+    excludes: &'static [Part],
+    message: &'static str,
+}
+
+/// This function returns a rule of the table
+const fn rule(part: Part, excludes: &'static [Part], message: &'static str) -> Rule {
+    Rule { part, excludes, message }
+}
+
+/// Which flags go together, the first rule broken is reported
+const RULES: &[Rule] = &[
+    rule(
+        ConnectFour,
+        &[NotClassic, Size, Win, Resume, FromPosition, Replay, Engine, Tui, Host, Connect, Clock, Speedrun],
+        "--game connect4 is played on its own 7x6 grid at this terminal, with none of the options of Tic-Tac-Toe boards",
+    ),
+    rule(Resume, &[OwnBoard], "--resume is only available for the classic variant"),
+    rule(FromPosition, &[OwnBoard, Resume, Connect], "--position starts a new classic game"),
+    rule(Boards, &[NoNotakto], "--boards is only available for --variant notakto"),
+    rule(
+        SwapSymbols,
+        &[Single, Tui, Json, Engine, OwnBoard, ConnectFour, Host, Connect, Watch, Replay],
+        "--swap-symbols is only available for --best-of matches of classic games at this terminal",
+    ),
+    rule(Host, &[Connect, Watch], "Please either --host a game, --connect to one or --watch one"),
+    rule(Connect, &[Watch], "Please either --host a game, --connect to one or --watch one"),
+    rule(Opponent, &[Offline], "--opponent names the remote opponent of --host or --connect"),
+    rule(Watch, &[OwnBoard, ConnectFour], "Network games are only available for the classic variant"),
+    rule(Host, &[OwnBoard], "Network games are only available for the classic variant"),
+    rule(Connect, &[OwnBoard], "Network games are only available for the classic variant"),
+    rule(Clock, &[Tui, OwnBoard, Host, Connect], "--clock and --move-time are only available for classic games at this terminal"),
+    rule(Speedrun, &[Tui, OwnBoard, Host, Connect], "--speedrun is only available for classic games at this terminal"),
+    rule(
+        Handicapped,
+        &[HotSeat, Tui, OwnBoard, ConnectFour, Host, Connect, Resume, FromPosition],
+        "--handicap is only available for new classic games against the machine at this terminal",
+    ),
+    rule(Blunder, &[OwnBoard, ConnectFour], "--blunder and --personality are only available for classic games"),
+    rule(Persona, &[OwnBoard, ConnectFour], "--blunder and --personality are only available for classic games"),
+    rule(Bot, &[HotSeat, Tui, OwnBoard, ConnectFour, Host, Connect], "--bot plays classic games against the machine's side at this terminal"),
+    rule(Export, &[OwnBoard, ConnectFour, Watch], "--export is only available for classic games at this terminal"),
+    rule(Teach, &[Tui, OwnBoard, ConnectFour, Host, Connect], "--teach is only available for classic games at this terminal"),
+    rule(
+        Verbose,
+        &[Tui, Json, Engine, OwnBoard, ConnectFour, Host, Connect, Watch, Replay, Bot],
+        "--verbose is only available for classic games against the machine at this terminal",
+    ),
+    rule(Accessible, &[Tui, OwnBoard, ConnectFour], "--accessible is only available for classic games without --tui"),
+    rule(Tui, &[OwnBoard, Host, Connect], "--tui is only available for classic games at this terminal"),
+    rule(
+        Pie,
+        &[Tui, Json, Engine, OwnBoard, ConnectFour, Host, Connect, Watch, Replay, Resume, FromPosition, Handicapped, Bot],
+        "--pie is only available for new classic games at this terminal",
+    ),
+    rule(
+        Json,
+        &[Tui, Engine, OwnBoard, ConnectFour, Host, Connect, Watch, Replay, BestOf, Clock, Speedrun, Teach, Handicapped, Bot],
+        "--json plays single classic games, without the options of the frontends for people",
+    ),
+];
+
+/// Which game is played
+#[derive(PartialEq, Copy, Clone)]
+pub enum Variant {
+    /// A single board of `--size` cells a side
+    Classic,
+    /// Nine small boards on a big one, see `UltimateBoard`
+    Ultimate,
+    /// The classic board, but completing a line loses
+    Misere,
+    /// Five in a row on a 15x15 board
+    Gomoku,
+    /// Three layers stacked into a cube, see `Cube`
+    Cube,
+    /// The classic board on which both sides place either mark, see `Wild`
+    Wild,
+    /// Only X's on one or more boards, killing the last one loses, see `Notakto`
+    Notakto,
+}
+
+impl Variant {
+    /// This function returns true for the variants with a board of their
+    /// own, which the options of the classic board do not apply to
+    pub fn own_board(self) -> bool {
+        matches!(self, Variant::Ultimate | Variant::Cube | Variant::Wild | Variant::Notakto)
+    }
+
+    /// The size of the board of the variant unless `--size` says
+    /// otherwise, `None` for the variants played on any size
+    pub fn size(self) -> Option<usize> {
+        match self {
+            Variant::Gomoku => Some(15),
+            _ => None,
+        }
+    }
+
+    /// The number of marks in a row that win unless `--win` says
+    /// otherwise, `None` for a full line
+    pub fn win_len(self) -> Option<usize> {
+        match self {
+            Variant::Gomoku => Some(5),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Variant {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "classic" => Ok(Variant::Classic),
+            "ultimate" => Ok(Variant::Ultimate),
+            "misere" | "misère" => Ok(Variant::Misere),
+            "gomoku" => Ok(Variant::Gomoku),
+            "3d" => Ok(Variant::Cube),
+            "wild" => Ok(Variant::Wild),
+            "notakto" => Ok(Variant::Notakto),
+            tr => Err(format!("'{}' is not one of 'classic', 'ultimate', 'misere', 'gomoku', '3d', 'wild', 'notakto'", tr)),
+        }
+    }
+}
+
+/// Which board game is played
+#[derive(PartialEq, Copy, Clone)]
+pub enum GameKind {
+    TicTacToe,
+    /// Discs dropped into a 7x6 grid, see `ConnectFour`
+    ConnectFour,
+}
+
+impl FromStr for GameKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "tictactoe" | "tic-tac-toe" => Ok(GameKind::TicTacToe),
+            "connect4" | "connect-four" => Ok(GameKind::ConnectFour),
+            tr => Err(format!("'{}' is not one of 'tictactoe', 'connect4'", tr)),
+        }
+    }
+}
+
+/// Who opens the game against the machine
+#[derive(PartialEq, Copy, Clone)]
+pub enum First {
+    Player,
+    Machine,
+    /// Decided anew for every game
+    Random,
+}
+
+impl FromStr for First {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "player" => Ok(First::Player),
+            "machine" => Ok(First::Machine),
+            "random" => Ok(First::Random),
+            tr => Err(format!("'{}' is not one of 'player', 'machine', 'random'", tr)),
+        }
+    }
+}
+
+/// The extra marks the player starts with against the machine
+#[derive(PartialEq, Clone)]
+pub enum Handicap {
+    /// This many marks on cells picked at random for every game
+    Random(usize),
+    /// Marks on these cells
+    Cells(Vec<Move>),
+}
+
+impl FromStr for Handicap {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Ok(n) = s.trim().parse() {
+            return Ok(Handicap::Random(n));
+        }
+
+        let cells = s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|m| !m.is_empty())
+            .map(|m| m.parse::<Move>().map_err(|e| format!("'{}' is no cell, {}", m, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Handicap::Cells(cells))
+    }
+}
+
+impl Handicap {
+    /// This function returns the cells of the marks for the next game
+    pub fn cells(&self, game: &TicTacToe, rng: &mut Rng) -> Vec<Move> {
+        match self {
+            Handicap::Cells(cells) => cells.clone(),
+            Handicap::Random(n) => {
+                let mut empty: Vec<Move> = game.empty_cells().map(|(row, col)| Move { row, col }).collect();
+                let mut cells = Vec::new();
+                while cells.len() < *n && !empty.is_empty() {
+                    let pick = rng.below(empty.len());
+                    cells.push(empty.swap_remove(pick));
+                }
+
+                cells
+            }
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Handicap::Random(n) => *n,
+            Handicap::Cells(cells) => cells.len(),
+        }
+    }
+}
+
+/// Settings given on the command line
+pub struct Options {
+    pub mode: Mode,
+    pub game: GameKind,
+    pub variant: Variant,
+    pub size: usize,
+    pub win_len: Option<usize>,
+    pub difficulty: Difficulty,
+    pub algorithm: Algorithm,
+    pub depth: Option<usize>,
+    /// How many boards Notakto is played on
+    pub boards: Option<usize>,
+    pub resume: Option<String>,
+    pub first: First,
+    /// Port to wait on for a remote opponent
+    pub host: Option<u16>,
+    /// Address of a host to play against
+    pub connect: Option<String>,
+    /// Address of a host whose game is watched
+    pub watch: Option<String>,
+    /// Name of the remote opponent in the statistics, by default its address
+    pub opponent: Option<String>,
+    /// Let the second side take over the first move instead of answering it
+    pub pie: bool,
+    /// Play in the full-screen frontend
+    pub tui: bool,
+    /// Talk to a program in JSON lines instead of drawing the board
+    pub json: bool,
+    /// Play a match of at most this many games
+    pub best_of: Option<usize>,
+    /// The sides of the match trade their symbols from game to game
+    pub swap_symbols: bool,
+    /// Print the statistics instead of playing
+    pub stats: bool,
+    /// A saved game or a move list to step through instead of playing
+    pub replay: Option<String>,
+    /// Thinking time of each side for the whole game
+    pub clock: Option<Duration>,
+    /// Thinking time of each side for a single move
+    pub move_time: Option<Duration>,
+    /// How moves are announced, by default the way the player writes them
+    pub notation: Option<MoveFormat>,
+    /// How boards are drawn, by default what the terminal can show
+    pub theme: Option<Theme>,
+    /// Speak the engine protocol on stdin and stdout instead of playing
+    pub engine: bool,
+    /// A position to start from and the side to move in it
+    pub position: Option<(Board, BoardChar)>,
+    /// Search the opening instead of answering it from the book
+    pub no_book: bool,
+    /// Seed of all random choices, so a game can be played again
+    pub seed: Option<u64>,
+    /// Time every move and print the times once the game is over
+    pub speedrun: bool,
+    /// The symbol of the player, asked for before every game if not set
+    pub symbol: Option<char>,
+    /// Where the statistics are kept, by default `Stats::default_path`
+    pub stats_path: Option<PathBuf>,
+    /// How the player is told that the opponent moved or the game is over
+    pub notify: Notification,
+    /// The language of the texts, by default the one of the locale
+    pub lang: Option<Lang>,
+    /// Explain every move of the player once it is played
+    pub teach: bool,
+    /// Tell what the search of the machine did after each of its moves
+    pub verbose: bool,
+    /// Extra marks the player starts every game with
+    pub handicap: Option<Handicap>,
+    /// The chance that the machine plays a worse move than the best one
+    pub blunder: Option<f64>,
+    /// The opponent the machine plays as, instead of the difficulty
+    pub personality: Option<Personality>,
+    /// The command of a bot that plays instead of the machine
+    pub bot: Option<String>,
+    /// Thinking time of the bot for a single move
+    pub bot_time: Duration,
+    /// A file every finished game is added to as a record
+    pub export: Option<PathBuf>,
+    /// How many threads the machine searches with
+    pub threads: usize,
+    /// How long the machine thinks about a move at most
+    pub think: Duration,
+}
+
+impl Options {
+    /// This function reads the command line arguments. Settings they leave
+    /// out are taken from `config`, then from the defaults.
+    pub fn parse(mut args: impl Iterator<Item = String>, config: &Config) -> Result<Options, String> {
+        let first = match &config.first {
+            Some(first) => first.parse().map_err(|e| format!("first in the configuration: {}", e))?,
+            None => First::Player,
+        };
+        let mut options = Options {
+            mode: Mode::Machine,
+            game: GameKind::TicTacToe,
+            variant: Variant::Classic,
+            size: 3,
+            win_len: None,
+            difficulty: config.difficulty.unwrap_or(Difficulty::Hard),
+            algorithm: Algorithm::Minimax,
+            depth: None,
+            boards: None,
+            resume: None,
+            first,
+            host: None,
+            connect: None,
+            watch: None,
+            opponent: None,
+            pie: false,
+            tui: false,
+            json: false,
+            best_of: None,
+            swap_symbols: false,
+            stats: false,
+            replay: None,
+            clock: None,
+            move_time: None,
+            notation: None,
+            theme: config.theme,
+            position: None,
+            engine: false,
+            no_book: false,
+            seed: None,
+            speedrun: false,
+            notify: Notification::None,
+            lang: config.lang,
+            teach: false,
+            verbose: false,
+            handicap: None,
+            blunder: None,
+            personality: None,
+            bot: None,
+            bot_time: MOVE_BUDGET,
+            export: None,
+            threads: 1,
+            think: MOVE_BUDGET,
+            symbol: config.symbol,
+            stats_path: config.stats_path.clone(),
+        };
+        let mut size = None;
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--mode" => {
+                    let value = value(&mut args, &arg)?;
+                    options.mode = value.parse()?;
+                }
+                "--game" => {
+                    let value = value(&mut args, &arg)?;
+                    options.game = value.parse()?;
+                }
+                "--variant" => {
+                    let value = value(&mut args, &arg)?;
+                    options.variant = value.parse()?;
+                }
+                "--size" => size = Some(number(&arg, args.next())?),
+                "--win" => options.win_len = Some(number(&arg, args.next())?),
+                "--depth" => options.depth = Some(number(&arg, args.next())?),
+                "--boards" => options.boards = Some(number(&arg, args.next())?),
+                "--first" => {
+                    let value = value(&mut args, &arg)?;
+                    options.first = value.parse()?;
+                }
+                "--position" => {
+                    let value = value(&mut args, &arg)?;
+                    options.position = Some(Board::from_notation(&value)?);
+                }
+                "--resume" => options.resume = Some(value(&mut args, &arg)?),
+                "--difficulty" => {
+                    let value = value(&mut args, &arg)?;
+                    options.difficulty = value.parse()?;
+                }
+                "--host" => {
+                    let value = value(&mut args, &arg)?;
+                    options.host = Some(value.parse().map_err(|_| format!("--host needs a port, not '{}'", value))?);
+                }
+                "--connect" => options.connect = Some(value(&mut args, &arg)?),
+                "--opponent" => options.opponent = Some(value(&mut args, &arg)?),
+                "--watch" => options.watch = Some(value(&mut args, &arg)?),
+                "--pie" => options.pie = true,
+                "--tui" => options.tui = true,
+                "--json" => options.json = true,
+                "--engine" => options.engine = true,
+                "--no-book" => options.no_book = true,
+                "--seed" => options.seed = Some(seed(args.next())?),
+                "--speedrun" => options.speedrun = true,
+                "--lang" => {
+                    let value = value(&mut args, &arg)?;
+                    options.lang = Some(value.parse()?);
+                }
+                "--accessible" => options.theme = Some(Theme::Accessible),
+                "--teach" => options.teach = true,
+                "--verbose" => options.verbose = true,
+                "--blunder" => {
+                    let value = value(&mut args, &arg)?;
+                    match value.parse::<f64>() {
+                        Ok(p) if (0.0..=1.0).contains(&p) => options.blunder = Some(p),
+                        _ => return Err(format!("--blunder must be a chance between 0 and 1, not '{}'", value)),
+                    }
+                }
+                "--think" => options.think = parse_duration(&value(&mut args, &arg)?)?,
+                "--threads" => {
+                    options.threads = number(&arg, args.next())?;
+                    if options.threads == 0 {
+                        return Err("--threads needs at least one thread".to_string());
+                    }
+                }
+                "--export" => options.export = Some(PathBuf::from(value(&mut args, &arg)?)),
+                "--bot" => options.bot = Some(value(&mut args, &arg)?),
+                "--bot-time" => options.bot_time = parse_duration(&value(&mut args, &arg)?)?,
+                "--personality" => {
+                    let value = value(&mut args, &arg)?;
+                    options.personality = Some(value.parse()?);
+                }
+                "--handicap" => {
+                    let value = value(&mut args, &arg)?;
+                    options.handicap = Some(value.parse()?);
+                }
+                "--notify" => {
+                    let value = value(&mut args, &arg)?;
+                    options.notify = value.parse()?;
+                }
+                "--symbol" => {
+                    let Glyph(c) = value(&mut args, &arg)?.parse()?;
+                    options.symbol = Some(c);
+                }
+                "--stats" => options.stats = true,
+                "--replay" => options.replay = Some(value(&mut args, &arg)?),
+                "--best-of" => options.best_of = Some(number(&arg, args.next())?),
+                "--swap-symbols" => options.swap_symbols = true,
+                "--notation" => {
+                    let value = value(&mut args, &arg)?;
+                    options.notation = Some(value.parse()?);
+                }
+                "--theme" => {
+                    let value = value(&mut args, &arg)?;
+                    options.theme = Some(value.parse()?);
+                }
+                "--clock" => options.clock = Some(parse_duration(&value(&mut args, &arg)?)?),
+                "--move-time" => options.move_time = Some(parse_duration(&value(&mut args, &arg)?)?),
+                "--ai" => {
+                    let value = value(&mut args, &arg)?;
+                    options.algorithm = value.parse()?;
+                }
+                _ => return Err(format!("Unknown argument '{}'", arg)),
+            }
+        }
+
+        options.check(size)?;
+
+        options.size = size.or_else(|| options.variant.size()).or(config.size).unwrap_or(3);
+        options.win_len = options.win_len.or_else(|| options.variant.win_len());
+
+        // A position brings its own board
+        if let Some((board, _)) = &options.position {
+            options.size = board.len();
+        }
+
+        if options.size == 0 || options.size > MAX_SIZE {
+            return Err(format!("--size must be between 1 and {}", MAX_SIZE));
+        }
+
+        if options.win_len.is_some_and(|k| k == 0 || k > options.size) {
+            return Err(format!("--win must be between 1 and the board size {}", options.size));
+        }
+
+        if options.boards.is_some_and(|n| n == 0 || n > notakto::MAX_BOARDS) {
+            return Err(format!("--boards must be between 1 and {}", notakto::MAX_BOARDS));
+        }
+
+        if options.best_of == Some(0) {
+            return Err("--best-of needs at least one game".to_string());
+        }
+
+        if let Some(handicap) = &options.handicap {
+            if handicap.len() == 0 || handicap.len() >= options.win_len() {
+                return Err(format!("--handicap must be between 1 and {} marks", options.win_len() - 1));
+            }
+        }
+
+        Ok(options)
+    }
+
+    /// This function returns true if the command line has `part`, `size`
+    /// is the size given with `--size`
+    fn has(&self, part: Part, size: Option<usize>) -> bool {
+        match part {
+            ConnectFour => self.game == GameKind::ConnectFour,
+            NotClassic => self.variant != Variant::Classic,
+            OwnBoard => self.variant.own_board(),
+            NoNotakto => self.variant != Variant::Notakto,
+            Size => size.is_some(),
+            Win => self.win_len.is_some(),
+            Boards => self.boards.is_some(),
+            Resume => self.resume.is_some(),
+            FromPosition => self.position.is_some(),
+            Replay => self.replay.is_some(),
+            Engine => self.engine,
+            Tui => self.tui,
+            Json => self.json,
+            Host => self.host.is_some(),
+            Connect => self.connect.is_some(),
+            Watch => self.watch.is_some(),
+            Offline => self.host.is_none() && self.connect.is_none(),
+            Opponent => self.opponent.is_some(),
+            Clock => self.clock.is_some() || self.move_time.is_some(),
+            Speedrun => self.speedrun,
+            Teach => self.teach,
+            Verbose => self.verbose,
+            Pie => self.pie,
+            BestOf => self.best_of.is_some(),
+            Single => self.best_of.is_none(),
+            SwapSymbols => self.swap_symbols,
+            Handicapped => self.handicap.is_some(),
+            Blunder => self.blunder.is_some(),
+            Persona => self.personality.is_some(),
+            Bot => self.bot.is_some(),
+            Export => self.export.is_some(),
+            Accessible => self.theme == Some(Theme::Accessible),
+            HotSeat => self.mode != Mode::Machine,
+        }
+    }
+
+    /// This function returns the message of the first rule the command line
+    /// breaks, if any
+    fn check(&self, size: Option<usize>) -> Result<(), String> {
+        for rule in RULES {
+            if self.has(rule.part, size) && rule.excludes.iter().any(|&part| self.has(part, size)) {
+                return Err(rule.message.to_string());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The machine settings chosen
+    pub fn ai(&self) -> AiConfig {
+        let ai = match self.personality {
+            Some(personality) => personality.ai(self.algorithm),
+            None => AiConfig {
+                difficulty: self.difficulty,
+                algorithm: self.algorithm,
+                blunder: None,
+            },
+        };
+
+        AiConfig {
+            blunder: self.blunder.or(ai.blunder),
+            ..ai
+        }
+    }
+
+    /// Where the statistics are kept
+    pub fn stats_path(&self) -> Option<PathBuf> {
+        self.stats_path.clone().or_else(Stats::default_path)
+    }
+
+    /// The theme boards are drawn in
+    pub fn theme(&self) -> Theme {
+        self.theme.unwrap_or_else(Theme::detect)
+    }
+
+    /// How a game at the terminal is played
+    pub fn settings(&self) -> Settings {
+        Settings {
+            mode: self.mode,
+            notation: self.notation,
+            think: self.think,
+            clock: self.clock,
+            move_time: self.move_time,
+            seed: self.seed,
+            pie: self.pie,
+            teach: self.teach,
+            verbose: self.verbose,
+            speedrun: self.speedrun,
+            export: self.export.clone(),
+        }
+    }
+
+    /// The notifier that tells the player about the game
+    pub fn notifier(&self) -> Box<dyn Notifier> {
+        self.notify.notifier()
+    }
+
+    /// The generator of the choices made outside a game, seeded like the games
+    pub fn rng(&self) -> Rng {
+        self.seed.map_or_else(Rng::from_time, Rng::new)
+    }
+
+    /// The number of marks in a row that win, a full line by default
+    pub fn win_len(&self) -> usize {
+        self.win_len.unwrap_or(self.size)
+    }
+}
+
+/// This function parses the numeric value of the command line flag `flag`
+pub fn number(flag: &str, value: Option<String>) -> Result<usize, String> {
+    let value = value.ok_or_else(|| format!("{} needs a number", flag))?;
+
+    value
+        .parse()
+        .map_err(|_| format!("{} needs a number, not '{}'", flag, value))
+}
+
+/// This function parses the value of `--seed`
+pub fn seed(value: Option<String>) -> Result<u64, String> {
+    let value = value.ok_or("--seed needs a number")?;
+
+    value.parse().map_err(|_| format!("--seed needs a number, not '{}'", value))
+}
+
+/// A single character typed on the terminal
+pub struct Glyph(pub char);
+
+impl FromStr for Glyph {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tr = s.trim();
+        let mut chars = tr.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(Glyph(c)),
+            (None, _) => Err("Please type a character".to_string()),
+            _ => Err(format!("'{}' is more than one character", tr)),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()), &Config::default())
+    }
+
+    #[test]
+    fn the_usage_shows_every_flag() {
+        let usage = usage();
+
+        for f in FLAGS {
+            assert!(usage[0].contains(&format!("[{}", f.name)) || usage[0].contains(&format!("| {}", f.name)), "{}", f.name);
+        }
+        assert!(usage[0].contains("[--host PORT | --connect ADDR | --watch ADDR]"));
+        assert_eq!(usage.len(), 1 + COMMANDS.len());
+    }
+
+    #[test]
+    fn every_flag_of_the_table_is_read() {
+        for f in FLAGS {
+            let args: Vec<&str> = match f.value {
+                "" => vec![f.name],
+                _ => vec![f.name, "?"],
+            };
+            // A value that does not fit may be refused, but no flag is unknown
+            if let Err(e) = parse(&args) {
+                assert!(!e.starts_with("Unknown argument"), "{}", f.name);
+            }
+        }
+    }
+
+    #[test]
+    fn a_missing_value_is_told_from_the_table() {
+        assert_eq!(parse(&["--mode"]).err().as_deref(), Some("--mode needs a value: pve, pvp"));
+        assert_eq!(parse(&["--think"]).err().as_deref(), Some("--think needs a time, e.g. 500ms or 5s"));
+    }
+
+    #[test]
+    fn flags_that_do_not_go_together_are_refused() {
+        assert_eq!(parse(&["--pie", "--tui"]).err().as_deref(), Some("--pie is only available for new classic games at this terminal"));
+        assert!(parse(&["--game", "connect4", "--size", "4"]).is_err());
+        assert!(parse(&["--host", "4000", "--watch", "localhost:4000"]).is_err());
+        assert!(parse(&["--swap-symbols"]).is_err());
+
+        assert!(parse(&["--swap-symbols", "--best-of", "3"]).is_ok());
+        assert!(parse(&["--game", "connect4", "--mode", "pvp"]).is_ok());
+    }
+}
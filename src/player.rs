@@ -0,0 +1,274 @@
+//! The players of a game and what they choose for themselves.
+
+#[cfg(feature = "serde")]
+use std::cell::RefCell;
+use std::cmp;
+use std::collections::VecDeque;
+use std::fmt;
+#[cfg(feature = "serde")]
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::board::BoardChar;
+use crate::engine::{SearchInfo, TicTacToe};
+use crate::game::Game;
+#[cfg(feature = "serde")]
+use crate::i18n::{fill, Text};
+#[cfg(feature = "serde")]
+use crate::net::{Link, Message};
+use crate::r#move::Move;
+
+/// The characters the two marks are drawn with. Win detection only ever
+/// looks at `BoardChar`, so any pair of distinct glyphs can be used.
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Symbols {
+    pub x: char,
+    pub o: char,
+}
+
+impl Default for Symbols {
+    fn default() -> Symbols {
+        Symbols { x: 'X', o: 'O' }
+    }
+}
+
+impl Symbols {
+    /// This function returns the glyph a cell is drawn with
+    pub fn glyph(&self, c: BoardChar) -> char {
+        match c {
+            BoardChar::Empty => ' ',
+            BoardChar::O => self.o,
+            BoardChar::X => self.x,
+        }
+    }
+
+    /// This function returns the mark drawn with `glyph`, if any
+    pub fn mark(&self, glyph: char) -> Option<BoardChar> {
+        if glyph == self.x {
+            Some(BoardChar::X)
+        } else if glyph == self.o {
+            Some(BoardChar::O)
+        } else {
+            None
+        }
+    }
+}
+
+/// What a player decided on its turn
+#[derive(Debug, PartialEq, Clone)]
+pub enum Decision<M = Move> {
+    Play(M),
+    /// The position changed without a move, e.g. one was taken back, so
+    /// the turn starts over
+    Changed,
+    /// The game started over, from the empty board or from a saved game, so
+    /// its settings and the clocks start over as well
+    Restarted,
+    /// The player left the game, e.g. its input or connection was closed
+    Leave,
+}
+
+/// One side of a game: a person at the terminal, the machine, a peer on the
+/// network or a list of moves. A game loop only has to ask the player
+/// whose turn it is and play what it decided, see `terminal::play_turns`.
+pub trait Player<G: Game = TicTacToe> {
+    /// This function decides on the move of the side to move in `game`,
+    /// whose time runs out at `deadline` if there is one. Moves it plays
+    /// are legal.
+    fn choose_move(&mut self, game: &mut G, deadline: Option<Instant>) -> Decision<G::Move>;
+
+    /// This function is told about every move once it is played, by either
+    /// side, `mark` is the side that played it
+    fn observe(&mut self, _game: &G, _mark: BoardChar, _m: &G::Move) {}
+
+    /// This function decides, right after the first move and under the pie
+    /// rule, whether the side to move takes that move over instead of
+    /// answering it
+    fn swap(&mut self, _game: &mut G) -> bool {
+        false
+    }
+
+    /// This function returns what the search behind the last move this
+    /// player chose did, for players that search
+    fn search_info(&self) -> Option<&SearchInfo> {
+        None
+    }
+}
+
+/// A game between the side of the people at the terminal and the other
+/// side, played by the machine, a bot, a peer on the network or a second
+/// person. It tells the players and the game loop who sits where and how
+/// the machine finds its move.
+pub trait Table: Game + fmt::Display {
+    /// This function returns the side of the player, or of Player 1
+    fn player_char(&self) -> BoardChar;
+
+    /// This function returns the other side
+    fn machine_char(&self) -> BoardChar;
+
+    /// This function returns the glyph side `c` is drawn with, `None` in
+    /// games whose marks belong to neither side
+    fn glyph(&self, c: BoardChar) -> Option<char>;
+
+    /// This function lets side `c` open the game, on an empty board
+    fn set_first(&mut self, c: BoardChar);
+
+    /// This function clears the board for the next game
+    fn reset(&mut self);
+
+    /// This function returns the move the machine plays with the settings
+    /// of the game, thinking until `deadline`, and what its search did
+    fn machine_choice(&mut self, deadline: Option<Instant>) -> Option<(Self::Move, SearchInfo)>;
+
+    /// This function returns true if the side to move does better by
+    /// swapping sides under the pie rule, in games that have it
+    fn should_swap(&mut self, _deadline: Option<Instant>) -> bool {
+        false
+    }
+}
+
+/// Time the machine keeps in hand on a clock, so that its move arrives
+/// before its time is up
+pub const CLOCK_MARGIN: Duration = Duration::from_millis(100);
+
+/// The machine, playing with the settings of the game
+pub struct Machine {
+    budget: Duration,
+    /// What the search of the last move did
+    searched: Option<SearchInfo>,
+}
+
+impl Machine {
+    /// This function returns the machine thinking no longer than `budget`
+    /// about a move
+    pub fn new(budget: Duration) -> Machine {
+        Machine { budget, searched: None }
+    }
+}
+
+impl<G: Table> Player<G> for Machine {
+    fn choose_move(&mut self, game: &mut G, deadline: Option<Instant>) -> Decision<G::Move> {
+        let mut until = Instant::now() + self.budget;
+        if let Some(deadline) = deadline {
+            until = cmp::min(until, deadline.checked_sub(CLOCK_MARGIN).unwrap_or(deadline));
+        }
+
+        match game.machine_choice(Some(until)) {
+            Some((m, info)) => {
+                self.searched = Some(info);
+                Decision::Play(m)
+            }
+            None => Decision::Leave,
+        }
+    }
+
+    fn swap(&mut self, game: &mut G) -> bool {
+        game.should_swap(Some(Instant::now() + self.budget))
+    }
+
+    fn search_info(&self) -> Option<&SearchInfo> {
+        self.searched.as_ref()
+    }
+}
+
+/// A player that plays a list of moves and leaves once it ran out of them,
+/// or at the first one that is not possible
+pub struct Scripted<M = Move> {
+    moves: VecDeque<M>,
+}
+
+impl<M> Scripted<M> {
+    pub fn new(moves: Vec<M>) -> Scripted<M> {
+        Scripted { moves: moves.into() }
+    }
+}
+
+impl<G: Game> Player<G> for Scripted<G::Move> {
+    fn choose_move(&mut self, game: &mut G, _deadline: Option<Instant>) -> Decision<G::Move> {
+        match self.moves.pop_front() {
+            Some(m) if game.clone().play(&m).is_ok() => Decision::Play(m),
+            _ => Decision::Leave,
+        }
+    }
+}
+
+/// A peer playing `mark` over a link shared with the player at this end:
+/// its moves and its chat are read from the link, and every move of this
+/// end is sent to it and shown to the spectators. A link that cannot be
+/// restored leaves the game, `Link::failure` tells why.
+#[cfg(feature = "serde")]
+pub struct Remote {
+    link: Rc<RefCell<Link>>,
+    mark: BoardChar,
+    /// A move could not be sent, the connection is restored before the
+    /// next message is waited for
+    lost: bool,
+}
+
+#[cfg(feature = "serde")]
+impl Remote {
+    pub fn new(link: Rc<RefCell<Link>>, mark: BoardChar) -> Remote {
+        Remote { link, mark, lost: false }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Player for Remote {
+    fn choose_move(&mut self, game: &mut TicTacToe, _deadline: Option<Instant>) -> Decision {
+        let mut link = self.link.borrow_mut();
+        if std::mem::take(&mut self.lost) {
+            return match link.reconnect(game) {
+                Ok(()) => Decision::Changed,
+                Err(_) => Decision::Leave,
+            };
+        }
+
+        println!("{}", Text::WaitingForOpponent);
+        loop {
+            match link.receive(game) {
+                Err(_) => return Decision::Leave,
+                // The connection was restored, the position may have changed
+                Ok(None) => return Decision::Changed,
+                Ok(Some(Message::Chat(text))) => {
+                    println!("{}", fill(Text::OpponentSays, &[&text]));
+                    link.log(self.mark, &text, game);
+                }
+                Ok(Some(Message::Move(m))) => match game.check_move(&m) {
+                    Ok(()) => {
+                        println!("{}", fill(Text::OpponentMoved, &[&m]));
+                        return Decision::Play(m);
+                    }
+                    Err(e) => {
+                        if link.send(&Message::Error(format!("{} is not possible, {}", m, e)), game).is_err() {
+                            return Decision::Leave;
+                        }
+                    }
+                },
+                Ok(Some(Message::Resign)) => {
+                    link.broadcast(&Message::Resign);
+                    game.resign(self.mark);
+                    return Decision::Changed;
+                }
+                Ok(Some(Message::Bye)) => {
+                    println!("{}", Text::OpponentLeft);
+                    return Decision::Leave;
+                }
+                Ok(Some(Message::Error(text))) => println!("{}", fill(Text::OpponentRefused, &[&text])),
+                Ok(Some(other)) => {
+                    if link.send(&Message::Error(format!("unexpected {}", other)), game).is_err() {
+                        return Decision::Leave;
+                    }
+                }
+            }
+        }
+    }
+
+    fn observe(&mut self, _game: &TicTacToe, mark: BoardChar, m: &Move) {
+        let mut link = self.link.borrow_mut();
+        link.broadcast(&Message::Move(*m));
+        // A peer that cannot be reached is reconnected on its next turn
+        if mark != self.mark && link.connection().send(&Message::Move(*m)).is_err() {
+            self.lost = true;
+        }
+    }
+}
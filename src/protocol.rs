@@ -0,0 +1,323 @@
+//! A text protocol in the spirit of UCI, so that GUIs and bots can use the
+//! engine without its prompts.
+//!
+//! The controller writes one command per line to the engine, the engine
+//! answers with zero or more lines:
+//!
+//! ```text
+//! engine                      -> id name tic_tac-toe_rs <version>
+//!                                id protocol <version>
+//!                                engineok
+//! isready                     -> readyok
+//! newgame [<size> [<win>]]    starts an empty board, 3x3 and a full line by default
+//! position startpos|<position> [moves <move>...]
+//!                             sets up the empty board or a position like
+//!                             `XO./.X./..O X` (see `Notation`), then plays the moves
+//! setoption depth <n>|none    limits the search, `none` searches to the end
+//! setoption misere <yes|no>   makes completing a line lose
+//! go [depth <n>] [movetime <ms>]
+//!                             -> info depth <n> score <score> nodes <nodes>, one per
+//!                                finished depth, then bestmove <move>, or
+//!                                bestmove none if the game is over
+//! eval                        -> info move <move> score <score>, for every legal move
+//! show                        -> position <position>
+//! quit                        ends the session
+//! ```
+//!
+//! Scores are seen from the side to move: above 1000 it wins, the closer to
+//! `WIN_SCORE` the sooner, below -1000 it loses, anything in between is a
+//! draw or an estimate of the heuristic. A command that cannot be carried
+//! out is answered with `error <text>`.
+
+use std::fmt;
+use std::io::{self, Write};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::board::{Board, BoardChar, Notation};
+use crate::engine::{GameSnapshot, Search, TicTacToe};
+use crate::error::GameError;
+use crate::input::InputSource;
+use crate::player::Symbols;
+use crate::r#move::{Move, MAX_SIZE};
+
+/// Version of the protocol spoken by this build
+pub const ENGINE_PROTOCOL_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Command {
+    Engine,
+    IsReady,
+    /// An empty board of this size where this many in a row win
+    NewGame(usize, Option<usize>),
+    /// A position, `None` for the empty board, and moves played from it
+    Position(Option<(Board, BoardChar)>, Vec<Move>),
+    Depth(Option<usize>),
+    Misere(bool),
+    Go { depth: Option<usize>, movetime: Option<Duration> },
+    Eval,
+    Show,
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut words = s.split_whitespace();
+        let word = words.next().unwrap_or("");
+        let rest: Vec<&str> = words.collect();
+        let number = |text: &str| {
+            text.parse::<usize>()
+                .map_err(|_| GameError::InvalidInput(format!("'{}' is no number", text)))
+        };
+
+        match (word, &rest[..]) {
+            ("engine", []) => Ok(Command::Engine),
+            ("isready", []) => Ok(Command::IsReady),
+            ("newgame", []) => Ok(Command::NewGame(3, None)),
+            ("newgame", [size]) => Ok(Command::NewGame(number(size)?, None)),
+            ("newgame", [size, win]) => Ok(Command::NewGame(number(size)?, Some(number(win)?))),
+            ("position", [..]) => {
+                let (setup, moves) = match rest.iter().position(|&w| w == "moves") {
+                    Some(i) => (&rest[..i], &rest[i + 1..]),
+                    None => (&rest[..], &rest[rest.len()..]),
+                };
+                let position = match setup {
+                    ["startpos"] => None,
+                    [] => return Err("position needs startpos or a position".into()),
+                    _ => Some(Board::from_notation(&setup.join(" "))?),
+                };
+                let moves = moves.iter().map(|m| m.parse()).collect::<Result<_, _>>()?;
+
+                Ok(Command::Position(position, moves))
+            }
+            ("setoption", ["depth", "none"]) => Ok(Command::Depth(None)),
+            ("setoption", ["depth", depth]) => Ok(Command::Depth(Some(number(depth)?))),
+            ("setoption", ["misere", "yes"]) => Ok(Command::Misere(true)),
+            ("setoption", ["misere", "no"]) => Ok(Command::Misere(false)),
+            ("setoption", _) => Err("setoption takes depth <n>|none or misere <yes|no>".into()),
+            ("go", [..]) => {
+                let (mut depth, mut movetime) = (None, None);
+                for pair in rest.chunks(2) {
+                    match pair {
+                        ["depth", n] => depth = Some(number(n)?),
+                        ["movetime", ms] => movetime = Some(Duration::from_millis(number(ms)? as u64)),
+                        _ => return Err(GameError::InvalidInput(format!("go takes depth <n> and movetime <ms>, not '{}'", pair.join(" ")))),
+                    }
+                }
+
+                Ok(Command::Go { depth, movetime })
+            }
+            ("eval", []) => Ok(Command::Eval),
+            ("show", []) => Ok(Command::Show),
+            ("quit", []) => Ok(Command::Quit),
+            _ => Err(GameError::InvalidInput(format!("Unknown command: {}", s.trim()))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Reply {
+    Id(String, String),
+    EngineOk,
+    ReadyOk,
+    /// The result of one finished depth of the search
+    Info { depth: usize, score: i16, nodes: u64 },
+    MoveScore(Move, i16),
+    BestMove(Option<Move>),
+    Position(String),
+    Error(String),
+}
+
+impl fmt::Display for Reply {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reply::Id(key, value) => write!(f, "id {} {}", key, value),
+            Reply::EngineOk => write!(f, "engineok"),
+            Reply::ReadyOk => write!(f, "readyok"),
+            Reply::Info { depth, score, nodes } => write!(f, "info depth {} score {} nodes {}", depth, score, nodes),
+            Reply::MoveScore(m, score) => write!(f, "info move {} score {}", m, score),
+            Reply::BestMove(Some(m)) => write!(f, "bestmove {}", m),
+            Reply::BestMove(None) => write!(f, "bestmove none"),
+            Reply::Position(position) => write!(f, "position {}", position),
+            Reply::Error(text) => write!(f, "error {}", text),
+        }
+    }
+}
+
+/// The state of the engine between two commands
+pub struct Session {
+    game: TicTacToe,
+    win_len: usize,
+    /// The depth limit set with `setoption`, `None` keeps the one of the board
+    depth: Option<Option<usize>>,
+    misere: bool,
+}
+
+impl Default for Session {
+    fn default() -> Session {
+        Session::new()
+    }
+}
+
+impl Session {
+    /// This function starts a session on an empty 3x3 board
+    pub fn new() -> Session {
+        Session {
+            game: TicTacToe::new(BoardChar::O),
+            win_len: 3,
+            depth: None,
+            misere: false,
+        }
+    }
+
+    pub fn game(&self) -> &TicTacToe {
+        &self.game
+    }
+
+    /// This function carries out `command` and returns the replies
+    pub fn handle(&mut self, command: Command) -> Result<Vec<Reply>, GameError> {
+        match command {
+            Command::Engine => Ok(vec![
+                Reply::Id("name".to_string(), format!("tic_tac-toe_rs {}", env!("CARGO_PKG_VERSION"))),
+                Reply::Id("protocol".to_string(), ENGINE_PROTOCOL_VERSION.to_string()),
+                Reply::EngineOk,
+            ]),
+            Command::IsReady => Ok(vec![Reply::ReadyOk]),
+            Command::NewGame(size, win_len) => {
+                let win_len = win_len.unwrap_or(size);
+                if size == 0 || size > MAX_SIZE || win_len == 0 || win_len > size {
+                    return Err(GameError::InvalidInput(format!("There is no {}x{} board where {} in a row win", size, size, win_len)));
+                }
+
+                self.win_len = win_len;
+                self.game = self.setup(vec![vec![BoardChar::Empty; size]; size], BoardChar::X)?;
+                Ok(Vec::new())
+            }
+            Command::Position(position, moves) => {
+                let size = self.game.board().len();
+                let (board, turn) = position.unwrap_or_else(|| (vec![vec![BoardChar::Empty; size]; size], BoardChar::X));
+                if self.win_len > board.len() {
+                    self.win_len = board.len();
+                }
+
+                let mut game = self.setup(board, turn)?;
+                for m in &moves {
+                    game.play_move(m)?;
+                }
+                self.game = game;
+                Ok(Vec::new())
+            }
+            Command::Depth(depth) => {
+                self.depth = Some(depth);
+                self.game.set_max_depth(depth);
+                Ok(Vec::new())
+            }
+            Command::Misere(misere) => {
+                self.misere = misere;
+                self.game.set_misere(misere);
+                Ok(Vec::new())
+            }
+            Command::Go { depth, movetime } => Ok(self.go(depth, movetime)),
+            Command::Eval => {
+                let turn = self.game.turn();
+                if self.game.is_game_over() {
+                    return Ok(Vec::new());
+                }
+
+                let mut search = Search::new(self.game.max_depth(), None);
+                let scores = self.game.score_moves(&mut search, turn);
+                Ok(scores.into_iter().map(|(m, score)| Reply::MoveScore(m, score)).collect())
+            }
+            Command::Show => Ok(vec![Reply::Position(self.game.to_notation())]),
+            Command::Quit => Ok(Vec::new()),
+        }
+    }
+
+    /// This function sets up `board` with `turn` to move under the options
+    /// of the session
+    fn setup(&self, board: Board, turn: BoardChar) -> Result<TicTacToe, GameError> {
+        let mut game = TicTacToe::from_snapshot(&GameSnapshot {
+            win_len: self.win_len,
+            board,
+            player_char: turn.to_opposite(),
+            machine_char: turn,
+            symbols: Symbols::default(),
+            turn,
+            history: Vec::new(),
+            misere: self.misere,
+            handicap: Vec::new(),
+        })?;
+        if let Some(depth) = self.depth {
+            game.set_max_depth(depth);
+        }
+
+        Ok(game)
+    }
+
+    /// This function searches the side to move one depth after the other
+    /// until `depth`, the end of the game or `movetime` is reached, and
+    /// reports every depth it finished
+    fn go(&mut self, depth: Option<usize>, movetime: Option<Duration>) -> Vec<Reply> {
+        if self.game.is_game_over() {
+            return vec![Reply::BestMove(None)];
+        }
+
+        let deadline = movetime.map(|movetime| Instant::now() + movetime);
+        let turn = self.game.turn();
+        let empty = self.game.empty_cells().count();
+        let full_depth = depth.or(self.game.max_depth()).map_or(empty, |max| max.min(empty));
+        let mut replies = Vec::new();
+        let mut best = None;
+
+        for depth in 1..=full_depth {
+            // The first depth always finishes, so there is a move to report
+            let limit = if depth == 1 { None } else { deadline };
+            let mut search = Search::new(Some(depth), limit);
+            let found = self.game.find_best_move_for(&mut search, turn);
+            if search.aborted {
+                break;
+            }
+
+            if let Some((m, score)) = found {
+                replies.push(Reply::Info { depth, score, nodes: search.nodes });
+                best = Some(m);
+            }
+        }
+
+        replies.push(Reply::BestMove(best));
+        replies
+    }
+}
+
+/// This function answers the commands read from `input` on `output` until
+/// the input ends or says quit
+pub fn run(input: &mut dyn InputSource, output: &mut dyn Write) -> io::Result<()> {
+    let mut session = Session::new();
+
+    while let Some(line) = input.read_line()? {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let command = line.parse::<Command>();
+        let quit = matches!(command, Ok(Command::Quit));
+        let replies = command.and_then(|command| session.handle(command));
+        match replies {
+            Ok(replies) => {
+                for reply in replies {
+                    writeln!(output, "{}", reply)?;
+                }
+            }
+            Err(e) => writeln!(output, "{}", Reply::Error(e.to_string()))?,
+        }
+        output.flush()?;
+
+        if quit {
+            break;
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,292 @@
+//! Tactics exercises: find the move that wins or the one that saves the draw.
+//!
+//! The puzzles shipped with the game are kept in `puzzles.toml` and built
+//! into the binary. Each one is a `[[puzzle]]` table with
+//!
+//! ```toml
+//! [[puzzle]]
+//! id = "finish-the-row"
+//! title = "Two in a row"
+//! position = "XX./OO./... X"
+//! goal = "win"
+//! ```
+//!
+//! `position` is written in the notation of `Notation`, `goal` is `win` or
+//! `draw` and an optional `win = K` sets how many marks in a row win, a full
+//! line by default. Only this much of TOML is understood. An answer is right
+//! if the solver rates it as well as the best move: any winning move solves
+//! a `win` puzzle, not only the fastest one.
+//!
+//! `daily` makes up a puzzle of its own for every day, the same for
+//! everyone, from a generator seeded with the date.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::board::{Board, BoardChar, Notation};
+use crate::engine::{GameSnapshot, Search, TicTacToe};
+use crate::error::GameError;
+use crate::json::{self, FromJson, ToJson};
+use crate::player::Symbols;
+use crate::r#move::Move;
+use crate::rng::Rng;
+use crate::stats::day_number;
+
+/// The puzzles built into the game
+pub const PUZZLES: &str = include_str!("puzzles.toml");
+
+/// What the side to move has to achieve
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Goal {
+    Win,
+    /// Avoid losing a position that looks harmless or lost
+    Draw,
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Goal::Win => write!(f, "win"),
+            Goal::Draw => write!(f, "draw"),
+        }
+    }
+}
+
+impl FromStr for Goal {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "win" => Ok(Goal::Win),
+            "draw" => Ok(Goal::Draw),
+            tr => Err(GameError::InvalidInput(format!("'{}' is not one of 'win', 'draw'", tr))),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Puzzle {
+    pub id: String,
+    pub title: String,
+    pub board: Board,
+    pub to_move: BoardChar,
+    pub win_len: usize,
+    pub goal: Goal,
+}
+
+impl Puzzle {
+    /// This function returns the puzzle as a game in which the player is the
+    /// side to move
+    pub fn game(&self) -> TicTacToe {
+        self.setup().expect("puzzles are checked when they are read")
+    }
+
+    fn setup(&self) -> Result<TicTacToe, GameError> {
+        TicTacToe::from_snapshot(&GameSnapshot {
+            board: self.board.clone(),
+            win_len: self.win_len,
+            player_char: self.to_move,
+            machine_char: self.to_move.to_opposite(),
+            symbols: Symbols::default(),
+            turn: self.to_move,
+            history: Vec::new(),
+            misere: false,
+            handicap: Vec::new(),
+        })
+    }
+
+    /// This function returns the moves that solve the puzzle
+    pub fn solutions(&self) -> Vec<Move> {
+        let mut game = self.game();
+        let scores = game.score_moves(&mut Search::new(None, None), self.to_move);
+        let best = scores.iter().map(|&(_, score)| score.signum()).max();
+
+        scores
+            .into_iter()
+            .filter(|&(_, score)| Some(score.signum()) == best)
+            .map(|(m, _)| m)
+            .collect()
+    }
+
+    /// This function returns true if `m` solves the puzzle
+    pub fn check(&self, m: &Move) -> Result<bool, GameError> {
+        self.game().play_move(m)?;
+
+        Ok(self.solutions().contains(m))
+    }
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.goal {
+            Goal::Win => write!(f, "{}: {} to move and win", self.title, self.to_move),
+            Goal::Draw => write!(f, "{}: {} to move and hold the draw", self.title, self.to_move),
+        }
+    }
+}
+
+/// This function reads the puzzles of a TOML file written like `PUZZLES`
+pub fn parse_puzzles(text: &str) -> Result<Vec<Puzzle>, GameError> {
+    let mut tables: Vec<Vec<(String, String, usize)>> = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let number = i + 1;
+        let tr = line.trim();
+        if tr.is_empty() || tr.starts_with('#') {
+            continue;
+        }
+
+        if tr == "[[puzzle]]" {
+            tables.push(Vec::new());
+            continue;
+        }
+
+        let (key, value) = tr
+            .split_once('=')
+            .ok_or_else(|| GameError::InvalidInput(format!("Line {}: expected key = value or [[puzzle]]", number)))?;
+        let table = tables
+            .last_mut()
+            .ok_or_else(|| GameError::InvalidInput(format!("Line {}: keys belong into a [[puzzle]] table", number)))?;
+        let value = value.trim();
+        let value = match value.strip_prefix('"').and_then(|v| v.strip_suffix('"')) {
+            Some(text) => text.to_string(),
+            None if value.parse::<usize>().is_ok() => value.to_string(),
+            None => return Err(GameError::InvalidInput(format!("Line {}: '{}' is no string or number", number, value))),
+        };
+        table.push((key.trim().to_string(), value, number));
+    }
+
+    tables.iter().map(|table| puzzle_from(table)).collect()
+}
+
+/// This function builds a puzzle from the keys of its table
+fn puzzle_from(table: &[(String, String, usize)]) -> Result<Puzzle, GameError> {
+    let get = |key: &str| table.iter().find(|(k, _, _)| k == key).map(|(_, v, _)| v.as_str());
+    let id = get("id").ok_or("Every puzzle needs an id")?;
+    let field = |key: &str| get(key).ok_or_else(|| GameError::InvalidInput(format!("Puzzle '{}' has no {}", id, key)));
+    let invalid = |e: GameError| GameError::InvalidInput(format!("Puzzle '{}': {}", id, e));
+
+    if let Some((key, _, number)) = table
+        .iter()
+        .find(|(k, _, _)| !["id", "title", "position", "goal", "win"].contains(&k.as_str()))
+    {
+        return Err(GameError::InvalidInput(format!("Line {}: unknown key '{}'", number, key)));
+    }
+
+    let (board, to_move) = Board::from_notation(field("position")?).map_err(invalid)?;
+    let win_len = match get("win") {
+        Some(k) => k.parse().map_err(|_| invalid(GameError::InvalidInput(format!("'{}' is no number", k))))?,
+        None => board.len(),
+    };
+    if win_len == 0 || win_len > board.len() {
+        return Err(invalid(GameError::InvalidInput(format!("{} in a row do not fit on the board", win_len))));
+    }
+
+    let puzzle = Puzzle {
+        id: id.to_string(),
+        title: field("title")?.to_string(),
+        board,
+        to_move,
+        win_len,
+        goal: field("goal")?.parse().map_err(invalid)?,
+    };
+
+    if puzzle.setup().map_err(invalid)?.is_game_over() {
+        return Err(invalid(GameError::GameAlreadyOver));
+    }
+
+    Ok(puzzle)
+}
+
+/// This function returns the puzzles built into the game
+pub fn builtin() -> Vec<Puzzle> {
+    parse_puzzles(PUZZLES).expect("the built-in puzzles are valid")
+}
+
+/// This function returns the challenge of `date`, written `YYYY-MM-DD`: a
+/// position on the classic board in which the side to move can force a
+/// win, but not with every move
+pub fn daily(date: &str) -> Puzzle {
+    let mut rng = Rng::new(day_number(date).unwrap_or_default() as u64);
+
+    loop {
+        let mut game = TicTacToe::with_size(BoardChar::X, 3, 3);
+        for _ in 0..2 + rng.below(4) {
+            let cells: Vec<(usize, usize)> = game.empty_cells().collect();
+            let (row, col) = cells[rng.below(cells.len())];
+            if game.play_move(&Move { row, col }).is_err() || game.is_game_over() {
+                break;
+            }
+        }
+        if game.is_game_over() {
+            continue;
+        }
+
+        let to_move = game.turn();
+        let scores = game.score_moves(&mut Search::new(None, None), to_move);
+        let wins = scores.iter().filter(|&&(_, score)| score > 0).count();
+        if wins == 0 || wins == scores.len() {
+            continue;
+        }
+
+        return Puzzle {
+            id: format!("daily-{}", date),
+            title: format!("Challenge of {}", date),
+            board: game.board().clone(),
+            to_move,
+            win_len: 3,
+            goal: Goal::Win,
+        };
+    }
+}
+
+/// The puzzles solved so far, by id in the order they were solved
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct Progress {
+    pub solved: Vec<String>,
+}
+
+impl Progress {
+    /// This function returns where the progress is kept by default,
+    /// `~/.tictactoe/puzzles.json`
+    pub fn default_path() -> Option<PathBuf> {
+        let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+
+        Some(PathBuf::from(home).join(".tictactoe").join("puzzles.json"))
+    }
+
+    /// This function reads the progress kept at `path`. A file that does not
+    /// exist yet holds no solved puzzles.
+    pub fn load(path: &Path) -> Result<Progress, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Progress::default()),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        Progress::from_json(&json::parse(&text)?)
+    }
+
+    /// This function writes the progress to `path`, creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        fs::write(path, self.to_json().to_string()).map_err(|e| e.to_string())
+    }
+
+    pub fn is_solved(&self, id: &str) -> bool {
+        self.solved.iter().any(|solved| solved == id)
+    }
+
+    /// This function remembers that the puzzle `id` has been solved
+    pub fn solve(&mut self, id: &str) {
+        if !self.is_solved(id) {
+            self.solved.push(id.to_string());
+        }
+    }
+}
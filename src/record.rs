@@ -0,0 +1,231 @@
+//! Game records in a portable text format, to share finished games, attach
+//! them to bug reports and replay them.
+//!
+//! A record starts with tags in square brackets, one per line, followed by
+//! the moves numbered in pairs and the result, in the spirit of PGN:
+//!
+//! ```text
+//! [Event "Tic-Tac-Toe"]
+//! [Date "2026-10-14"]
+//! [X "you"]
+//! [O "machine"]
+//! [Size "3"]
+//! [Win "3"]
+//! [Result "1-0"]
+//!
+//! 1. 2B 1A 2. 3C 1C 3. 1B 3B 4. 3A 1-0
+//! ```
+//!
+//! The result is `1-0` if X won, `0-1` if O won, `1/2-1/2` for a draw and
+//! `*` for a game that is not over. Optional tags are `First` with the side
+//! that opened if it was O, `Variant "misere"`, `Handicap` with the cells of
+//! the handicap marks and `Termination` with `resigned`, `time` or `agreed`
+//! for games that no line or full board ended. A file may hold several
+//! records one after the other.
+//!
+//! Comments in braces may follow any move, e.g. what was said in the chat
+//! of a network game: `1. 2B {X: good luck} 1A`. They are kept for the
+//! reader and skipped when the record is read.
+
+use crate::board::BoardChar;
+use crate::engine::{Ending, TicTacToe};
+use crate::error::GameError;
+use crate::r#move::{Move, MAX_SIZE};
+use crate::stats::utc_now;
+
+/// This function returns the result of `game` as it is written in a record
+fn result(game: &TicTacToe) -> &'static str {
+    match (game.is_game_over(), game.winner()) {
+        (false, _) => "*",
+        (true, Some(BoardChar::X)) => "1-0",
+        (true, Some(BoardChar::O)) => "0-1",
+        (true, _) => "1/2-1/2",
+    }
+}
+
+/// A comment in the moves of a record
+#[derive(Debug, PartialEq, Clone)]
+pub struct Comment {
+    /// How many moves were played when it was made
+    pub after: usize,
+    pub text: String,
+}
+
+/// This function writes `game` as a record, with `x` and `o` as the names
+/// of the sides and `comments` between the moves
+pub fn write_record(game: &TicTacToe, x: &str, o: &str, comments: &[Comment]) -> String {
+    let date = utc_now();
+    let mut tags = vec![
+        ("Event", "Tic-Tac-Toe".to_string()),
+        ("Date", date[..10].to_string()),
+        ("X", x.to_string()),
+        ("O", o.to_string()),
+        ("Size", game.board().len().to_string()),
+        ("Win", game.win_len().to_string()),
+    ];
+    if game.first() == BoardChar::O {
+        tags.push(("First", "O".to_string()));
+    }
+    if game.is_misere() {
+        tags.push(("Variant", "misere".to_string()));
+    }
+    if !game.handicap().is_empty() {
+        let cells: Vec<String> = game.handicap().iter().map(Move::to_string).collect();
+        tags.push(("Handicap", cells.join(" ")));
+    }
+    match game.ending() {
+        Some(Ending::Resigned(_)) => tags.push(("Termination", "resigned".to_string())),
+        Some(Ending::OutOfTime(_)) => tags.push(("Termination", "time".to_string())),
+        Some(Ending::DrawAgreed) => tags.push(("Termination", "agreed".to_string())),
+        None => {}
+    }
+    tags.push(("Result", result(game).to_string()));
+
+    let mut record = String::new();
+    for (key, value) in tags {
+        record.push_str(&format!("[{} \"{}\"]\n", key, value.replace('"', "'")));
+    }
+    record.push('\n');
+
+    let mut words = Vec::new();
+    let comment = |words: &mut Vec<String>, after: usize| {
+        for c in comments.iter().filter(|c| c.after == after) {
+            words.push(format!("{{{}}}", c.text.replace(['{', '}'], "")));
+        }
+    };
+    comment(&mut words, 0);
+    for (i, p) in game.moves().iter().enumerate() {
+        if i % 2 == 0 {
+            words.push(format!("{}.", i / 2 + 1));
+        }
+        words.push(p.at.to_string());
+        comment(&mut words, i + 1);
+    }
+    words.push(result(game).to_string());
+    record.push_str(&words.join(" "));
+    record.push('\n');
+
+    record
+}
+
+/// This function returns true if `text` looks like a record rather than a
+/// saved game or a move list
+pub fn is_record(text: &str) -> bool {
+    text.trim_start().starts_with('[')
+}
+
+/// This function reads every record in `text`, oldest first, and returns
+/// the games with all their moves played
+pub fn read_records(text: &str) -> Result<Vec<TicTacToe>, GameError> {
+    let mut games = Vec::new();
+    let mut tags = Vec::new();
+    let mut moves = String::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('[') {
+            // A tag after moves starts the next record
+            if !moves.trim().is_empty() {
+                games.push(read_game(&tags, &moves)?);
+                tags.clear();
+                moves.clear();
+            }
+            tags.push(read_tag(line)?);
+        } else {
+            moves.push_str(line);
+            moves.push(' ');
+        }
+    }
+    if !tags.is_empty() || !moves.trim().is_empty() {
+        games.push(read_game(&tags, &moves)?);
+    }
+
+    Ok(games)
+}
+
+/// This function reads a single record, the first one if `text` holds more
+pub fn read_record(text: &str) -> Result<TicTacToe, GameError> {
+    read_records(text)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| GameError::InvalidInput("There is no game in the record".to_string()))
+}
+
+/// This function reads a tag like `[Size "3"]` into its key and value
+fn read_tag(line: &str) -> Result<(String, String), GameError> {
+    let invalid = || GameError::InvalidInput(format!("'{}' is no tag like [Size \"3\"]", line));
+
+    let inner = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')).ok_or_else(invalid)?;
+    let (key, value) = inner.split_once(' ').ok_or_else(invalid)?;
+    let value = value.trim().strip_prefix('"').and_then(|rest| rest.strip_suffix('"')).ok_or_else(invalid)?;
+
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// This function sets up the game the `tags` describe and plays `moves` on it
+fn read_game(tags: &[(String, String)], moves: &str) -> Result<TicTacToe, GameError> {
+    let tag = |key: &str| tags.iter().find(|(k, _)| k == key).map(|(_, value)| value.as_str());
+    let number = |key: &str, default: usize| match tag(key) {
+        Some(value) => value
+            .parse::<usize>()
+            .map_err(|_| GameError::InvalidInput(format!("{} must be a number, not '{}'", key, value))),
+        None => Ok(default),
+    };
+
+    let size = number("Size", 3)?;
+    let win_len = number("Win", size)?;
+    if size == 0 || size > MAX_SIZE || win_len == 0 || win_len > size {
+        return Err(GameError::InvalidInput(format!("There is no {}x{} board where {} in a row win", size, size, win_len)));
+    }
+    let first = match tag("First") {
+        Some("O") => BoardChar::O,
+        Some("X") | None => BoardChar::X,
+        Some(other) => return Err(GameError::InvalidInput(format!("First must be X or O, not '{}'", other))),
+    };
+
+    // Handicap marks belong to the side that did not open
+    let mut game = TicTacToe::with_size(first.to_opposite(), size, win_len);
+    game.set_misere(tag("Variant") == Some("misere"));
+    game.set_first(first);
+    if let Some(cells) = tag("Handicap") {
+        let cells = cells.split_whitespace().map(str::parse).collect::<Result<Vec<Move>, _>>()?;
+        game.set_handicap(&cells)?;
+    }
+
+    // Comments are for the reader only
+    let mut uncommented = String::new();
+    let mut depth = 0;
+    for c in moves.chars() {
+        match c {
+            '{' => depth += 1,
+            '}' if depth > 0 => depth -= 1,
+            _ if depth == 0 => uncommented.push(c),
+            _ => {}
+        }
+    }
+
+    let mut number = 0;
+    for word in uncommented.split_whitespace() {
+        if word.ends_with('.') || ["1-0", "0-1", "1/2-1/2", "*"].contains(&word) {
+            continue;
+        }
+
+        number += 1;
+        let m: Move = word.parse()?;
+        game.play_move(&m).map_err(|e| GameError::InvalidInput(format!("Move {} ({}) is not possible, {}", number, m, e)))?;
+    }
+
+    // The result tells which side resigned or ran out of time
+    let loser = match tag("Result") {
+        Some("1-0") => BoardChar::O,
+        Some("0-1") => BoardChar::X,
+        _ => game.turn(),
+    };
+    match tag("Termination") {
+        Some("resigned") => game.resign(loser),
+        Some("time") => game.forfeit(loser),
+        Some("agreed") => game.end(Ending::DrawAgreed),
+        _ => {}
+    }
+
+    Ok(game)
+}
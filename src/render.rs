@@ -0,0 +1,489 @@
+//! How boards are drawn on the terminal.
+//!
+//! A theme decides which characters the grid is drawn with, whether the
+//! marks are colored with ANSI escape codes and how wide a cell is. A
+//! terminal that does not speak UTF-8 cannot show box-drawing characters,
+//! `Theme::detect` picks the ASCII theme for it. The accessible theme
+//! draws no grid but describes the board in words for screen readers.
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::board::{Board, BoardChar, WinLine};
+use crate::error::GameError;
+use crate::i18n::{fill, Text};
+use crate::player::Symbols;
+use crate::r#move::{column_name, Move};
+
+/// ANSI escape codes the `Color` theme paints the marks with
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const RESET: &str = "\x1b[0m";
+/// Escape code swapping the colors of the text and the background
+const REVERSE: &str = "\x1b[7m";
+/// Gap between boards drawn side by side
+const BOARD_GAP: &str = "   ";
+
+#[derive(Debug, PartialEq, Default, Copy, Clone)]
+pub enum Theme {
+    /// Box-drawing characters and plain marks
+    #[default]
+    Unicode,
+    /// Only `+`, `-` and `|`, for terminals without UTF-8
+    Ascii,
+    /// Box-drawing characters, X in red and O in blue
+    Color,
+    /// Box-drawing characters and cells three characters wide
+    Large,
+    /// The board described row by row in words, for screen readers
+    Accessible,
+}
+
+/// The characters a grid is drawn with
+#[derive(Debug, PartialEq, Clone)]
+pub struct Frame {
+    pub horizontal: char,
+    pub vertical: char,
+    /// The corners and crossings of the top, inner and bottom borders, each
+    /// from left to right
+    pub corners: [[char; 3]; 3],
+}
+
+const BOX_DRAWING: Frame = Frame {
+    horizontal: '\u{2500}',
+    vertical: '\u{2502}',
+    corners: [
+        ['\u{250C}', '\u{252C}', '\u{2510}'],
+        ['\u{251C}', '\u{253C}', '\u{2524}'],
+        ['\u{2514}', '\u{2534}', '\u{2518}'],
+    ],
+};
+
+const ASCII: Frame = Frame {
+    horizontal: '-',
+    vertical: '|',
+    corners: [['+'; 3]; 3],
+};
+
+impl Theme {
+    /// This function returns the theme a terminal can show: ASCII unless
+    /// the locale in `LC_ALL`, `LC_CTYPE` or `LANG` uses UTF-8
+    pub fn detect() -> Theme {
+        if cfg!(windows) {
+            return Theme::Unicode;
+        }
+
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        if locale.contains("utf-8") || locale.contains("utf8") {
+            Theme::Unicode
+        } else {
+            Theme::Ascii
+        }
+    }
+
+    pub fn frame(self) -> &'static Frame {
+        match self {
+            Theme::Ascii => &ASCII,
+            _ => &BOX_DRAWING,
+        }
+    }
+
+    /// This function returns how many characters wide a cell is drawn
+    pub fn cell_width(self) -> usize {
+        match self {
+            Theme::Large => 3,
+            _ => 1,
+        }
+    }
+
+    /// This function returns `text` as it is shown in a cell holding `c`
+    pub fn paint(self, text: &str, c: BoardChar) -> String {
+        self.paint_in(text, Color::of(c))
+    }
+
+    /// This function returns `text` in `color` if the theme shows colors
+    pub fn paint_in(self, text: &str, color: Option<Color>) -> String {
+        match (self, color) {
+            (Theme::Color, Some(color)) => format!("{}{}{}", color.code(), text, RESET),
+            _ => text.to_string(),
+        }
+    }
+
+    /// This function returns `text` as it is shown in a cell of a completed
+    /// line. The color theme swaps the colors of the cell and large cells
+    /// put the mark in brackets. The other themes leave the text alone,
+    /// `draw_row` puts brackets around the line instead, so that no escape
+    /// codes end up where no terminal shows them.
+    pub fn highlight(self, text: &str, c: BoardChar) -> String {
+        self.highlight_in(text, Color::of(c))
+    }
+
+    /// This function works like `highlight` for a mark drawn in `color`
+    pub fn highlight_in(self, text: &str, color: Option<Color>) -> String {
+        match self {
+            Theme::Color => format!("{}{}{}", REVERSE, self.paint_in(text, color), RESET),
+            Theme::Large => format!("[{}]", text.trim()),
+            _ => text.to_string(),
+        }
+    }
+
+    /// This function returns true if the theme marks a completed line with
+    /// brackets in place of the borders around its cells
+    fn brackets(self) -> bool {
+        !matches!(self, Theme::Color | Theme::Large)
+    }
+}
+
+/// This function draws a row of cells between the borders of `theme`, every
+/// cell as its text and whether it lies on the completed line. Themes that
+/// show no colors replace the borders around the cells of the line with
+/// brackets, e.g. `|[X|X|X]` or `[X]| | |`.
+pub fn draw_row(theme: Theme, cells: &[(String, bool)]) -> String {
+    let vertical = theme.frame().vertical;
+    let on_line = |i: usize| theme.brackets() && cells.get(i).is_some_and(|&(_, on_line)| on_line);
+    let mut row = String::new();
+
+    for i in 0..=cells.len() {
+        let before = i > 0 && on_line(i - 1);
+        row.push(match (before, on_line(i)) {
+            (false, true) => '[',
+            (true, false) => ']',
+            _ => vertical,
+        });
+        if let Some((text, _)) = cells.get(i) {
+            row.push_str(text);
+        }
+    }
+
+    row
+}
+
+/// The colors the `Color` theme paints marks in
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Color {
+    Red,
+    Blue,
+}
+
+impl Color {
+    /// This function returns the color of `c` when nobody chose one, X in
+    /// red and O in blue
+    pub fn of(c: BoardChar) -> Option<Color> {
+        match c {
+            BoardChar::X => Some(Color::Red),
+            BoardChar::O => Some(Color::Blue),
+            BoardChar::Empty => None,
+        }
+    }
+
+    fn code(self) -> &'static str {
+        match self {
+            Color::Red => RED,
+            Color::Blue => BLUE,
+        }
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Red => write!(f, "red"),
+            Color::Blue => write!(f, "blue"),
+        }
+    }
+}
+
+/// Who plays a side of a game and the color its marks are drawn in, which
+/// stays with the player when the symbols of the sides are swapped
+#[derive(Debug, PartialEq, Clone)]
+pub struct PlayerInfo {
+    pub name: String,
+    pub side: BoardChar,
+    pub color: Color,
+}
+
+impl PlayerInfo {
+    /// This function describes the player with the glyph of its side and,
+    /// if `theme` shows colors, its color, e.g. `Alice (X, red)`
+    pub fn describe(&self, symbols: Symbols, theme: Theme) -> String {
+        let glyph = symbols.glyph(self.side);
+
+        match theme {
+            Theme::Color => format!("{} ({}, {})", self.name, theme.paint_in(&glyph.to_string(), Some(self.color)), self.color),
+            _ => format!("{} ({})", self.name, glyph),
+        }
+    }
+}
+
+/// This function returns the color `c` is drawn in, the one of the player
+/// of its side if there is one among `players`
+fn color(players: &[PlayerInfo], c: BoardChar) -> Option<Color> {
+    match players.iter().find(|player| player.side == c) {
+        Some(player) if c != BoardChar::Empty => Some(player.color),
+        _ => Color::of(c),
+    }
+}
+
+impl fmt::Display for Theme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Theme::Unicode => write!(f, "unicode"),
+            Theme::Ascii => write!(f, "ascii"),
+            Theme::Color => write!(f, "color"),
+            Theme::Large => write!(f, "large"),
+            Theme::Accessible => write!(f, "accessible"),
+        }
+    }
+}
+
+impl FromStr for Theme {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "unicode" | "plain" => Ok(Theme::Unicode),
+            "ascii" => Ok(Theme::Ascii),
+            "color" | "colour" => Ok(Theme::Color),
+            "large" => Ok(Theme::Large),
+            "accessible" => Ok(Theme::Accessible),
+            tr => Err(GameError::InvalidInput(format!(
+                "'{}' is not one of 'unicode', 'ascii', 'color', 'large', 'accessible'",
+                tr
+            ))),
+        }
+    }
+}
+
+/// This function draws `board` in a grid of `theme`, columns are labeled
+/// with letters and rows with numbers. The cells of `line` are highlighted.
+pub fn draw_board(board: &Board, symbols: Symbols, theme: Theme, line: Option<WinLine<'_>>) -> String {
+    draw_board_for(board, symbols, theme, line, &[])
+}
+
+/// This function works like `draw_board` but paints the marks of each of
+/// `players` in its color
+pub fn draw_board_for(board: &Board, symbols: Symbols, theme: Theme, line: Option<WinLine<'_>>, players: &[PlayerInfo]) -> String {
+    if theme == Theme::Accessible {
+        return describe_board(board, symbols, line);
+    }
+
+    draw_grid(&render(board, symbols, line, players, None), theme)
+}
+
+/// One cell of a board as any frontend shows it
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct StyledCell {
+    pub mark: BoardChar,
+    /// The glyph of the mark, a space for an empty cell
+    pub glyph: char,
+    /// The color of the mark, `None` for an empty cell
+    pub color: Option<Color>,
+    /// The cell lies on the completed line
+    pub win_line: bool,
+    /// The last move was played on the cell
+    pub highlight: bool,
+}
+
+/// A board as styled cells, row by row from the top. It tells what every
+/// cell shows and leaves to the frontend how to draw it.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Grid {
+    pub rows: Vec<Vec<StyledCell>>,
+}
+
+impl Grid {
+    /// This function returns the number of rows and columns
+    pub fn size(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// This function returns the cell `m` names, if it is on the board
+    pub fn cell(&self, m: &Move) -> Option<&StyledCell> {
+        self.rows.get(m.row).and_then(|row| row.get(m.col))
+    }
+}
+
+/// This function styles the cells of `board`: the marks in the glyphs of
+/// `symbols` and the colors of `players`, the cells of `line` and of the
+/// `last` move flagged
+pub fn render(board: &Board, symbols: Symbols, line: Option<WinLine<'_>>, players: &[PlayerInfo], last: Option<Move>) -> Grid {
+    let rows = board
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            row.iter()
+                .enumerate()
+                .map(|(j, &mark)| StyledCell {
+                    mark,
+                    glyph: symbols.glyph(mark),
+                    color: color(players, mark),
+                    win_line: line.is_some_and(|line| line.contains(i, j)),
+                    highlight: last == Some(Move { row: i, col: j }),
+                })
+                .collect()
+        })
+        .collect();
+
+    Grid { rows }
+}
+
+/// This function draws `grid` in a grid of `theme` like `draw_board`. The
+/// last move is not marked.
+pub fn draw_grid(grid: &Grid, theme: Theme) -> String {
+    let size = grid.size();
+    let frame = theme.frame();
+    let width = theme.cell_width();
+    // Row numbers are right aligned in a margin as wide as the largest one
+    let margin = size.to_string().len();
+    let border = |corners: [char; 3]| {
+        let cell = frame.horizontal.to_string().repeat(width);
+        let cells = vec![cell; size].join(&corners[1].to_string());
+        format!("{:margin$}{}{}{}\n", "", corners[0], cells, corners[2], margin = margin)
+    };
+
+    let mut board_txt = format!("{:margin$}", "", margin = margin);
+    for col in 0..size {
+        board_txt.push_str(&format!(" {:^width$}", column_name(col), width = width));
+    }
+    board_txt.push('\n');
+    board_txt.push_str(&border(frame.corners[0]));
+
+    for (i, row) in grid.rows.iter().enumerate() {
+        if i > 0 {
+            board_txt.push_str(&border(frame.corners[1]));
+        }
+
+        let cells: Vec<(String, bool)> = row
+            .iter()
+            .map(|cell| {
+                let text = format!("{:^width$}", cell.glyph, width = width);
+                match cell.win_line {
+                    true => (theme.highlight_in(&text, cell.color), true),
+                    false => (theme.paint_in(&text, cell.color), false),
+                }
+            })
+            .collect();
+        board_txt.push_str(&format!("{:>margin$}{}\n", i + 1, draw_row(theme, &cells), margin = margin));
+    }
+
+    board_txt.push_str(&border(frame.corners[2]));
+
+    board_txt
+}
+
+/// This function describes `board` in words: every row from the top, then
+/// the empty cells left and the cells of `line`, if a line is completed
+pub fn describe_board(board: &Board, symbols: Symbols, line: Option<WinLine<'_>>) -> String {
+    let mut text = String::new();
+    let mut empty = Vec::new();
+
+    for (i, row) in board.iter().enumerate() {
+        let cells: Vec<String> = row
+            .iter()
+            .map(|&cell| match cell {
+                BoardChar::Empty => Text::Empty.to_string(),
+                _ => symbols.glyph(cell).to_string(),
+            })
+            .collect();
+        text.push_str(&fill(Text::Row, &[&(i + 1), &cells.join(", ")]));
+        text.push('\n');
+
+        for (j, &cell) in row.iter().enumerate() {
+            if cell == BoardChar::Empty {
+                empty.push(Move { row: i, col: j }.to_string());
+            }
+        }
+    }
+
+    if let Some(line) = line {
+        let cells: Vec<String> = line.cells.iter().map(|&(row, col)| Move { row, col }.to_string()).collect();
+        text.push_str(&fill(Text::CompletedLineCells, &[&symbols.glyph(line.mark), &cells.join(", ")]));
+        text.push('\n');
+    } else if !empty.is_empty() {
+        text.push_str(&fill(Text::EmptySquares, &[&empty.join(", ")]));
+        text.push('\n');
+    }
+
+    text
+}
+
+/// This function returns how many columns `text` takes on the terminal,
+/// leaving out the escape codes that color it
+fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut escaped = false;
+
+    for c in text.chars() {
+        match c {
+            '\x1b' => escaped = true,
+            'm' if escaped => escaped = false,
+            _ if escaped => {}
+            _ => width += 1,
+        }
+    }
+
+    width
+}
+
+/// This function lays out boards drawn line by line next to each other,
+/// like the layers of the cube. Every board must have as many lines.
+pub(crate) fn side_by_side(boards: &[Vec<String>]) -> String {
+    let width = boards.iter().flatten().map(|line| visible_width(line)).max().unwrap_or(0);
+    let mut text = String::new();
+
+    for row in 0..boards.first().map_or(0, Vec::len) {
+        let parts: Vec<String> = boards
+            .iter()
+            .map(|board| format!("{}{}", board[row], " ".repeat(width - visible_width(&board[row]))))
+            .collect();
+        text.push_str(parts.join(BOARD_GAP).trim_end());
+        text.push('\n');
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::parse_board;
+
+    /// This function draws `board` with its line of X highlighted
+    fn drawn(board: &str, cells: &[(usize, usize)], theme: Theme) -> String {
+        let board = parse_board(board).unwrap();
+        let line = WinLine { mark: BoardChar::X, cells };
+
+        draw_board(&board, Symbols::default(), theme, Some(line))
+    }
+
+    #[test]
+    fn text_themes_bracket_the_line_without_escapes() {
+        let row = drawn("XXX/OO./...", &[(0, 0), (0, 1), (0, 2)], Theme::Ascii);
+        assert!(row.contains("1[X|X|X]\n"), "{}", row);
+        assert!(row.contains("2|O|O| |\n"), "{}", row);
+        assert!(!row.contains('\x1b'));
+
+        let diagonal = drawn("XO./OX./..X", &[(0, 0), (1, 1), (2, 2)], Theme::Unicode);
+        assert!(diagonal.contains("1[X]O\u{2502} \u{2502}\n"), "{}", diagonal);
+        assert!(diagonal.contains("2\u{2502}O[X] \u{2502}\n"), "{}", diagonal);
+        assert!(!diagonal.contains('\x1b'));
+    }
+
+    #[test]
+    fn color_theme_reverses_the_line() {
+        let row = drawn("XXX/OO./...", &[(0, 0), (0, 1), (0, 2)], Theme::Color);
+        assert!(row.contains(REVERSE));
+        assert!(!row.contains("[X"));
+
+        let large = drawn("XXX/OO./...", &[(0, 0), (0, 1), (0, 2)], Theme::Large);
+        assert!(large.contains("[X]"));
+        assert!(!large.contains('\x1b'));
+    }
+}
@@ -0,0 +1,116 @@
+//! Stepping through a recorded game move by move.
+//!
+//! Games are recorded either as a game saved with `TicTacToe::to_json`,
+//! which carries its moves in the history, as a record of `record.rs`, or
+//! as a plain move list like `2B 1A 3C` (commas work as separators too)
+//! that X opens.
+
+use crate::board::BoardChar;
+use crate::engine::TicTacToe;
+use crate::error::GameError;
+use crate::history::Placement;
+use crate::r#move::Move;
+use crate::record;
+use crate::render::Theme;
+
+/// A recorded game and the move it is currently shown at
+pub struct Replay {
+    /// The game at the current move. Moves after it wait on the redo
+    /// stack of its history.
+    game: TicTacToe,
+    position: usize,
+    len: usize,
+}
+
+impl Replay {
+    /// This function prepares a replay of every move `game` has been played
+    /// with, starting from the empty board
+    pub fn new(mut game: TicTacToe) -> Replay {
+        let len = game.moves().len();
+        while game.undo().is_some() {}
+
+        Replay { game, position: 0, len }
+    }
+
+    /// This function reads a move list like `2B 1A 3C` played on a `size` x
+    /// `size` board where `win_len` marks in a row win
+    pub fn from_moves(moves: &str, size: usize, win_len: usize) -> Result<Replay, GameError> {
+        let mut game = TicTacToe::with_size(BoardChar::X, size, win_len);
+
+        for (i, m) in moves.split([' ', ',', '\n']).filter(|m| !m.trim().is_empty()).enumerate() {
+            let m: Move = m.parse()?;
+            game.play_move(&m).map_err(|e| GameError::InvalidInput(format!("Move {} ({}) is not possible, {}", i + 1, m, e)))?;
+        }
+
+        Ok(Replay::new(game))
+    }
+
+    /// This function restores a replay from a game saved as JSON (with the
+    /// `serde` feature), from the last game of a file of records, or from a
+    /// move list if `record` is neither
+    pub fn parse(record: &str, size: usize, win_len: usize) -> Result<Replay, GameError> {
+        #[cfg(feature = "serde")]
+        if record.trim_start().starts_with('{') {
+            return Ok(Replay::new(TicTacToe::from_json(record)?));
+        }
+        if record::is_record(record) {
+            let game = record::read_records(record)?.pop();
+            return game
+                .map(Replay::new)
+                .ok_or_else(|| GameError::InvalidInput("There is no game in the record".to_string()));
+        }
+
+        Replay::from_moves(record, size, win_len)
+    }
+
+    /// This function returns the game as it stands after the current move
+    pub fn game(&self) -> &TicTacToe {
+        &self.game
+    }
+
+    /// This function changes how the board of the game is drawn
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.game.set_theme(theme);
+    }
+
+    /// This function returns how many moves have been replayed
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// This function returns the number of moves of the whole game
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// This function returns true once every move has been replayed
+    pub fn at_end(&self) -> bool {
+        self.position == self.len
+    }
+
+    /// This function plays the next move and returns it
+    pub fn forward(&mut self) -> Option<Placement> {
+        let placement = self.game.redo()?;
+        self.position += 1;
+
+        Some(placement)
+    }
+
+    /// This function takes back the current move and returns it
+    pub fn back(&mut self) -> Option<Placement> {
+        let placement = self.game.undo()?;
+        self.position -= 1;
+
+        Some(placement)
+    }
+
+    /// This function goes to the position after `position` moves
+    pub fn seek(&mut self, position: usize) {
+        while self.position > position && self.back().is_some() {}
+        while self.position < position && self.forward().is_some() {}
+    }
+}
@@ -0,0 +1,59 @@
+//! A tiny seedable pseudo random number generator (xorshift64*), good enough
+//! to vary the machine's play while keeping games reproducible from a seed.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// This function creates a generator that always yields the same
+    /// sequence for the same seed
+    pub fn new(seed: u64) -> Rng {
+        // Spread the seed with splitmix64 so that small seeds like 0, 1, 2
+        // still start from very different states. The state must not be 0.
+        let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+
+        Rng {
+            state: if z == 0 { 1 } else { z },
+        }
+    }
+
+    /// This function creates a generator seeded from the system clock. On
+    /// wasm32 without WASI there is no clock, the seed is then always 0 and
+    /// callers should use `set_seed` with a seed of their own.
+    pub fn from_time() -> Rng {
+        if cfg!(all(target_arch = "wasm32", not(target_os = "wasi"))) {
+            return Rng::new(0);
+        }
+
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or_default();
+
+        Rng::new(nanos)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    /// This function returns true with probability `p`
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+    }
+
+    /// This function returns a number in `0..n`, `n` must not be 0
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+}
@@ -0,0 +1,176 @@
+//! Games between two machine settings, to compare searches and to check
+//! that a new one never loses where perfect play draws.
+//!
+//! A contender is written as its parts separated by colons, in any order:
+//! the algorithm (`minimax` or `mcts`), the difficulty (`easy`, `medium` or
+//! `hard`) and a depth limit for minimax, e.g. `minimax:3`, `mcts` or
+//! `minimax:medium`. Missing parts default to hard minimax searching as
+//! deep as the board allows.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::board::BoardChar;
+use crate::engine::{AiConfig, Difficulty, TicTacToe};
+use crate::error::GameError;
+use crate::rng::Rng;
+
+/// One side of a self-play match
+#[derive(Debug, PartialEq, Default, Copy, Clone)]
+pub struct Contender {
+    pub ai: AiConfig,
+    /// Depth limit of the minimax search, `None` keeps the one of the board
+    pub max_depth: Option<usize>,
+}
+
+impl fmt::Display for Contender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.ai.algorithm)?;
+
+        if self.ai.difficulty != Difficulty::Hard {
+            write!(f, ":{}", self.ai.difficulty)?;
+        }
+        if let Some(depth) = self.max_depth {
+            write!(f, ":{}", depth)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for Contender {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut contender = Contender::default();
+
+        for part in s.trim().split(':') {
+            if let Ok(depth) = part.parse() {
+                contender.max_depth = Some(depth);
+            } else if let Ok(algorithm) = part.parse() {
+                contender.ai.algorithm = algorithm;
+            } else if let Ok(difficulty) = part.parse() {
+                contender.ai.difficulty = difficulty;
+            } else {
+                return Err(GameError::InvalidInput(format!(
+                    "'{}' is no algorithm, difficulty or depth, e.g. minimax:3 or mcts",
+                    part
+                )));
+            }
+        }
+
+        Ok(contender)
+    }
+}
+
+/// What happened in a self-play match, counted for each contender
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct SelfPlayReport {
+    pub contenders: [Contender; 2],
+    pub games: usize,
+    pub wins: [usize; 2],
+    pub draws: usize,
+    /// Moves each contender made and the time it thought about them
+    pub moves: [usize; 2],
+    pub thinking: [Duration; 2],
+}
+
+impl SelfPlayReport {
+    /// This function returns the share of games contender `side` won, in percent
+    pub fn win_rate(&self, side: usize) -> f64 {
+        percent(self.wins[side], self.games)
+    }
+
+    /// This function returns the share of drawn games, in percent
+    pub fn draw_rate(&self) -> f64 {
+        percent(self.draws, self.games)
+    }
+
+    /// This function returns how long contender `side` thought about a move on average
+    pub fn average_move_time(&self, side: usize) -> Duration {
+        match self.moves[side] {
+            0 => Duration::default(),
+            moves => self.thinking[side] / moves as u32,
+        }
+    }
+}
+
+fn percent(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+
+    100.0 * count as f64 / total as f64
+}
+
+impl fmt::Display for SelfPlayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} vs {}, {} games", self.contenders[0], self.contenders[1], self.games)?;
+
+        let width = self.contenders.iter().map(|c| c.to_string().len()).max().unwrap_or(0);
+        for side in 0..2 {
+            writeln!(
+                f,
+                "{:<width$}  {:>4} wins ({:.1}%), {:.2} ms per move",
+                self.contenders[side].to_string(),
+                self.wins[side],
+                self.win_rate(side),
+                self.average_move_time(side).as_secs_f64() * 1000.0,
+                width = width
+            )?;
+        }
+
+        writeln!(f, "{:<width$}  {:>4} ({:.1}%)", "draws", self.draws, self.draw_rate(), width = width)
+    }
+}
+
+/// This function plays `games` games between the two `contenders` on boards
+/// set up like `board`. The contenders take turns opening, the first one
+/// opens the first game. `rng` seeds the random choices of every game.
+/// Moves are searched without a deadline, only the depth limits and the
+/// playouts of the difficulty end the search.
+pub fn self_play(board: &TicTacToe, contenders: [Contender; 2], games: usize, rng: &mut Rng) -> SelfPlayReport {
+    let mut report = SelfPlayReport {
+        contenders,
+        games,
+        ..SelfPlayReport::default()
+    };
+
+    for number in 0..games {
+        let mut game = board.clone();
+        game.reset();
+        game.set_first(BoardChar::X);
+        game.set_seed(rng.next_u64());
+        let default_depth = game.max_depth();
+
+        // The contender opening this game plays X
+        let side_of = |c: BoardChar| if (c == BoardChar::X) == number.is_multiple_of(2) { 0 } else { 1 };
+
+        while !game.is_game_over() {
+            let side = side_of(game.turn());
+            let contender = contenders[side];
+            game.set_ai(contender.ai);
+            game.set_max_depth(contender.max_depth.or(default_depth));
+
+            let started = Instant::now();
+            let best = game.choose_move(None);
+            report.thinking[side] += started.elapsed();
+            report.moves[side] += 1;
+
+            match best {
+                Some((m, _)) => {
+                    game.play_move(&m).expect("the engine picks a legal move");
+                }
+                None => break,
+            }
+        }
+
+        match game.winner() {
+            Some(c) => report.wins[side_of(c)] += 1,
+            None => report.draws += 1,
+        }
+    }
+
+    report
+}
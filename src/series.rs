@@ -0,0 +1,150 @@
+//! A match of several games between the same two sides.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::board::BoardChar;
+use crate::engine::TicTacToe;
+use crate::i18n::{fill, Text};
+use crate::player::Symbols;
+use crate::rng::Rng;
+
+/// Games won by each side of a match, and draws
+#[derive(Debug, PartialEq, Default, Copy, Clone)]
+pub struct Score {
+    pub player: usize,
+    pub machine: usize,
+    pub draws: usize,
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", fill(Text::MatchScore, &[&self.player, &self.machine, &self.draws]))
+    }
+}
+
+/// A series of games, either open ended or best of a number of games. The
+/// side that opens alternates from game to game, and so may the symbols of
+/// the sides.
+pub struct Match {
+    /// Every game of the match so far, the last one is being played
+    games: Vec<TicTacToe>,
+    best_of: Option<usize>,
+    /// The sides trade their symbols from game to game
+    swap_symbols: bool,
+    rng: Rng,
+}
+
+impl Match {
+    /// This function starts a match with `first_game`. With `best_of` set
+    /// the match is over as soon as one side won more than half of that
+    /// many games, or all of them have been played.
+    pub fn new(first_game: TicTacToe, best_of: Option<usize>) -> Match {
+        Match {
+            games: vec![first_game],
+            best_of,
+            swap_symbols: false,
+            rng: Rng::from_time(),
+        }
+    }
+
+    /// This function seeds the random choices of the games that follow, so
+    /// a match is played the same way again for the same seed
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    /// This function lets the sides trade their symbols in every game that
+    /// follows, the glyph that opened the last game opens the next one too
+    pub fn set_swap_symbols(&mut self, swap: bool) {
+        self.swap_symbols = swap;
+    }
+
+    /// This function returns the game being played
+    pub fn game(&self) -> &TicTacToe {
+        self.games.last().expect("a match always has a game")
+    }
+
+    pub fn game_mut(&mut self) -> &mut TicTacToe {
+        self.games.last_mut().expect("a match always has a game")
+    }
+
+    /// This function returns all games of the match, the one being played last
+    pub fn games(&self) -> &[TicTacToe] {
+        &self.games
+    }
+
+    pub fn best_of(&self) -> Option<usize> {
+        self.best_of
+    }
+
+    /// This function returns the number of finished games
+    pub fn played(&self) -> usize {
+        self.games.iter().filter(|game| game.is_game_over()).count()
+    }
+
+    /// This function tallies the finished games
+    pub fn score(&self) -> Score {
+        let mut score = Score::default();
+
+        for game in self.games.iter().filter(|game| game.is_game_over()) {
+            match game.winner() {
+                Some(c) if c == game.player_char() => score.player += 1,
+                Some(_) => score.machine += 1,
+                None => score.draws += 1,
+            }
+        }
+
+        score
+    }
+
+    /// This function returns true once a best-of match is decided
+    pub fn is_over(&self) -> bool {
+        let best_of = match self.best_of {
+            Some(best_of) => best_of,
+            None => return false,
+        };
+
+        let score = self.score();
+        let needed = best_of / 2 + 1;
+
+        score.player >= needed || score.machine >= needed || self.played() >= best_of
+    }
+
+    /// This function returns the side that won more games, `None` while
+    /// both won equally many
+    pub fn leader(&self) -> Option<BoardChar> {
+        let score = self.score();
+        let game = self.game();
+
+        match score.player.cmp(&score.machine) {
+            std::cmp::Ordering::Greater => Some(game.player_char()),
+            std::cmp::Ordering::Less => Some(game.machine_char()),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    /// This function starts the next game with the same settings, opened by
+    /// the side that did not open the last one, which also takes over the
+    /// symbol of the other side if they are swapped. The last game is kept
+    /// as it ended.
+    pub fn next_game(&mut self) -> &mut TicTacToe {
+        let mut game = self.game().clone();
+        let first = game.first().to_opposite();
+
+        game.reset();
+        game.set_first(first);
+        if self.swap_symbols {
+            let symbols = game.symbols();
+            game.set_symbols(Symbols { x: symbols.o, o: symbols.x });
+        }
+        // Every game gets its own random choices
+        game.set_seed(self.rng.next_u64());
+        for observer in self.game().observers() {
+            game.add_observer(Arc::clone(observer));
+        }
+        self.games.push(game);
+
+        self.game_mut()
+    }
+}
@@ -0,0 +1,418 @@
+//! A server hosting many games at once, between two clients or a client
+//! and the machine.
+//!
+//! Every client gets its own thread, every game its own lock, so one game
+//! waiting for the machine to think does not hold up the others. Clients
+//! speak a line based protocol:
+//!
+//! ```text
+//! client                      server
+//! PLAY machine [<size> [<win>]]
+//!                             GAME <id> X
+//! PLAY human                  WAITING until a second client asks for a human,
+//!                             then GAME <id> <mark> to both of them
+//! MOVE <move>                 MOVE <move> to the opponent; the machine answers
+//!                             with its own MOVE <move>
+//! STATE                       STATE <state>, the game as one line of JSON
+//! LEAVE                       LEFT to the opponent, the game is closed
+//!                             OVER <X|O|draw> to both sides once the game ends
+//!                             ERROR <text> if a line was refused
+//! ```
+//!
+//! X opens every game, against the machine the client plays X. Once a game
+//! is over the client may PLAY again right away. A line longer than
+//! `MAX_LINE` closes the connection.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use crate::board::BoardChar;
+use crate::engine::TicTacToe;
+use crate::r#move::{Move, MAX_SIZE};
+
+/// Time the machine may think about a move on the server
+pub const SERVER_MOVE_BUDGET: Duration = Duration::from_secs(1);
+/// The longest line a client may send, a longer one ends its connection
+pub const MAX_LINE: usize = 4096;
+
+/// What a client asks for
+#[derive(Debug, PartialEq, Clone)]
+pub enum Request {
+    /// A game against the machine on a board of this size where this many
+    /// in a row win
+    PlayMachine(usize, usize),
+    PlayHuman,
+    Move(Move),
+    State,
+    Leave,
+}
+
+impl FromStr for Request {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let number = |text: &str| text.parse::<usize>().map_err(|_| format!("'{}' is no number", text));
+
+        let (size, win_len) = match words[..] {
+            ["PLAY", "machine"] => (3, 3),
+            ["PLAY", "machine", size] => (number(size)?, number(size)?),
+            ["PLAY", "machine", size, win] => (number(size)?, number(win)?),
+            ["PLAY", "human"] => return Ok(Request::PlayHuman),
+            ["MOVE", m] => return m.parse().map(Request::Move).map_err(String::from),
+            ["STATE"] => return Ok(Request::State),
+            ["LEAVE"] => return Ok(Request::Leave),
+            _ => return Err(format!("Unknown request: {}", s.trim())),
+        };
+
+        if size == 0 || size > MAX_SIZE || win_len == 0 || win_len > size {
+            return Err(format!("There is no {}x{} board where {} in a row win", size, size, win_len));
+        }
+
+        Ok(Request::PlayMachine(size, win_len))
+    }
+}
+
+/// What the server tells a client
+#[derive(Debug, PartialEq, Clone)]
+pub enum Event {
+    Game { id: u64, mark: BoardChar },
+    Waiting,
+    Move(Move),
+    State(String),
+    Left,
+    Over(Option<BoardChar>),
+    Error(String),
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Game { id, mark } => write!(f, "GAME {} {}", id, mark),
+            Event::Waiting => write!(f, "WAITING"),
+            Event::Move(m) => write!(f, "MOVE {}", m),
+            Event::State(state) => write!(f, "STATE {}", state),
+            Event::Left => write!(f, "LEFT"),
+            Event::Over(Some(c)) => write!(f, "OVER {}", c),
+            Event::Over(None) => write!(f, "OVER draw"),
+            Event::Error(text) => write!(f, "ERROR {}", text),
+        }
+    }
+}
+
+/// A game and the clients playing it, X first and O second. A seat
+/// without a client is the machine's.
+struct Table {
+    game: TicTacToe,
+    seats: [Option<TcpStream>; 2],
+}
+
+fn seat(c: BoardChar) -> usize {
+    match c {
+        BoardChar::O => 1,
+        _ => 0,
+    }
+}
+
+impl Table {
+    /// This function sends `event` to the client sitting at `c`, if any. A
+    /// client that cannot be reached any more finds out when it reads.
+    fn tell(&mut self, c: BoardChar, event: &Event) {
+        if let Some(stream) = &mut self.seats[seat(c)] {
+            let _ = writeln!(stream, "{}", event);
+        }
+    }
+
+    /// This function tells both sides the result once the game is over
+    fn announce_end(&mut self) {
+        if self.game.is_game_over() {
+            let over = Event::Over(self.game.winner());
+            self.tell(BoardChar::X, &over);
+            self.tell(BoardChar::O, &over);
+        }
+    }
+
+    /// This function plays the machine's move if it is the machine's turn
+    fn machine_turn(&mut self) {
+        let turn = self.game.turn();
+        if self.game.is_game_over() || self.seats[seat(turn)].is_some() {
+            return;
+        }
+
+        if let Ok((m, _)) = self.game.machine_move(SERVER_MOVE_BUDGET) {
+            self.tell(turn.to_opposite(), &Event::Move(m));
+        }
+        self.announce_end();
+    }
+}
+
+/// The games being played and who plays them
+#[derive(Default)]
+struct Lobby {
+    next_id: u64,
+    games: HashMap<u64, Arc<Mutex<Table>>>,
+    /// The game and mark of every client seated at a table, by client
+    seated: HashMap<u64, (u64, BoardChar)>,
+    /// A client waiting for a human opponent
+    waiting: Option<(u64, TcpStream)>,
+}
+
+impl Lobby {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    /// This function opens a table for `game`, seats the clients and returns its id
+    fn open(&mut self, game: TicTacToe, x: Option<(u64, TcpStream)>, o: Option<(u64, TcpStream)>) -> (u64, Arc<Mutex<Table>>) {
+        let id = self.next_id();
+        let mut seats = [None, None];
+
+        for (mark, client) in [(BoardChar::X, x), (BoardChar::O, o)] {
+            if let Some((client, stream)) = client {
+                self.seated.insert(client, (id, mark));
+                seats[seat(mark)] = Some(stream);
+            }
+        }
+
+        let table = Arc::new(Mutex::new(Table { game, seats }));
+        self.games.insert(id, Arc::clone(&table));
+        (id, table)
+    }
+
+    /// This function takes the client off its table and closes the table
+    fn close(&mut self, client: u64) -> Option<(Arc<Mutex<Table>>, BoardChar)> {
+        let (id, mark) = self.seated.remove(&client)?;
+        let table = self.games.remove(&id)?;
+        self.seated.retain(|_, &mut (game, _)| game != id);
+
+        Some((table, mark))
+    }
+}
+
+/// This function reads the next line of `reader` without its line break,
+/// `None` once the client closed the connection. A line longer than
+/// `MAX_LINE` is refused as invalid data, so a client that never sends a
+/// line break cannot fill the memory of the server.
+pub fn read_line(reader: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if Read::take(&mut *reader, MAX_LINE as u64 + 1).read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    if line.len() > MAX_LINE {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "The line is too long"));
+    }
+
+    let end = line.trim_end_matches(['\r', '\n']).len();
+    line.truncate(end);
+    Ok(Some(line))
+}
+
+/// A server hosting many games at once
+#[derive(Clone, Default)]
+pub struct Server {
+    lobby: Arc<Mutex<Lobby>>,
+}
+
+impl Server {
+    pub fn new() -> Server {
+        Server::default()
+    }
+
+    /// This function returns how many games are being played
+    pub fn games(&self) -> usize {
+        self.lobby.lock().unwrap().games.len()
+    }
+
+    /// This function accepts clients on `listener` until it fails, every
+    /// client is served on a thread of its own
+    pub fn serve(&self, listener: &TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, _) = listener.accept()?;
+            let server = self.clone();
+            let client = server.lobby.lock().unwrap().next_id();
+
+            thread::spawn(move || {
+                let _ = server.serve_client(client, stream);
+                server.leave(client);
+            });
+        }
+    }
+
+    /// This function answers the requests of one client until it leaves
+    fn serve_client(&self, client: u64, stream: TcpStream) -> io::Result<()> {
+        let mut writer = stream.try_clone()?;
+        let mut reader = BufReader::new(stream);
+
+        while let Some(line) = read_line(&mut reader)? {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let reply = match line.parse::<Request>() {
+                Ok(request) => self.handle(client, &writer, request)?,
+                Err(e) => Some(Event::Error(e)),
+            };
+            if let Some(reply) = reply {
+                writeln!(writer, "{}", reply)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// This function carries out a request of `client`, who is reached on
+    /// `stream`, and returns the answer meant for the client alone
+    fn handle(&self, client: u64, stream: &TcpStream, request: Request) -> io::Result<Option<Event>> {
+        let mut seated = self.lobby.lock().unwrap().seated.get(&client).copied();
+
+        let playing = matches!(request, Request::PlayMachine(..) | Request::PlayHuman);
+        if let (true, Some((id, _))) = (playing, seated) {
+            let table = self.lobby.lock().unwrap().games.get(&id).cloned();
+            if table.is_none_or(|table| table.lock().unwrap().game.is_game_over()) {
+                self.leave(client);
+                seated = None;
+            }
+        }
+
+        match (request, seated) {
+            (Request::PlayMachine(..), Some(_)) | (Request::PlayHuman, Some(_)) => {
+                Ok(Some(Event::Error("Please LEAVE your game first".to_string())))
+            }
+            (Request::PlayMachine(size, win_len), None) => {
+                let game = TicTacToe::with_size(BoardChar::X, size, win_len);
+                let mut lobby = self.lobby.lock().unwrap();
+                // A client waiting for a human plays the machine instead
+                if lobby.waiting.as_ref().is_some_and(|(waiting, _)| *waiting == client) {
+                    lobby.waiting = None;
+                }
+                let (id, _) = lobby.open(game, Some((client, stream.try_clone()?)), None);
+
+                Ok(Some(Event::Game { id, mark: BoardChar::X }))
+            }
+            (Request::PlayHuman, None) => {
+                let mut lobby = self.lobby.lock().unwrap();
+                match lobby.waiting.take() {
+                    Some((other, other_stream)) if other != client => {
+                        let game = TicTacToe::new(BoardChar::X);
+                        let (id, table) = lobby.open(game, Some((other, other_stream)), Some((client, stream.try_clone()?)));
+                        table.lock().unwrap().tell(BoardChar::X, &Event::Game { id, mark: BoardChar::X });
+
+                        Ok(Some(Event::Game { id, mark: BoardChar::O }))
+                    }
+                    _ => {
+                        lobby.waiting = Some((client, stream.try_clone()?));
+                        Ok(Some(Event::Waiting))
+                    }
+                }
+            }
+            (Request::Move(_), None) | (Request::State, None) => Ok(Some(Event::Error("You are not in a game, PLAY first".to_string()))),
+            (Request::Move(m), Some((id, mark))) => {
+                let table = match self.lobby.lock().unwrap().games.get(&id) {
+                    Some(table) => Arc::clone(table),
+                    None => return Ok(Some(Event::Error("The game is closed".to_string()))),
+                };
+                let mut table = table.lock().unwrap();
+
+                if table.game.is_game_over() {
+                    return Ok(Some(Event::Error("The game is over".to_string())));
+                }
+                if table.game.turn() != mark {
+                    return Ok(Some(Event::Error("It is not your turn".to_string())));
+                }
+                if let Err(e) = table.game.play_move(&m) {
+                    return Ok(Some(Event::Error(format!("This move is not possible, {}", e))));
+                }
+
+                table.tell(mark.to_opposite(), &Event::Move(m));
+                table.announce_end();
+                table.machine_turn();
+                Ok(None)
+            }
+            (Request::State, Some((id, _))) => {
+                let table = match self.lobby.lock().unwrap().games.get(&id) {
+                    Some(table) => Arc::clone(table),
+                    None => return Ok(Some(Event::Error("The game is closed".to_string()))),
+                };
+                let state = table.lock().unwrap().game.to_json();
+
+                Ok(Some(Event::State(state)))
+            }
+            (Request::Leave, _) => {
+                self.leave(client);
+                Ok(None)
+            }
+        }
+    }
+
+    /// This function takes `client` out of the lobby and out of its game,
+    /// whose other side is told
+    fn leave(&self, client: u64) {
+        let mut lobby = self.lobby.lock().unwrap();
+        if lobby.waiting.as_ref().is_some_and(|(waiting, _)| *waiting == client) {
+            lobby.waiting = None;
+        }
+
+        if let Some((table, mark)) = lobby.close(client) {
+            drop(lobby);
+            let mut table = table.lock().unwrap();
+            if !table.game.is_game_over() {
+                table.tell(mark.to_opposite(), &Event::Left);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// This function connects a client to `addr` and returns its reader
+    /// and writer
+    fn client(addr: std::net::SocketAddr) -> (BufReader<TcpStream>, TcpStream) {
+        let stream = TcpStream::connect(addr).unwrap();
+        (BufReader::new(stream.try_clone().unwrap()), stream)
+    }
+
+    fn send(client: &mut (BufReader<TcpStream>, TcpStream), line: &str) -> String {
+        writeln!(client.1, "{}", line).unwrap();
+        read_line(&mut client.0).unwrap().unwrap()
+    }
+
+    #[test]
+    fn read_line_refuses_lines_without_end() {
+        let mut reader = io::Cursor::new(b"MOVE 2B\r\nSTATE".to_vec());
+        assert_eq!(read_line(&mut reader).unwrap(), Some("MOVE 2B".to_string()));
+        assert_eq!(read_line(&mut reader).unwrap(), Some("STATE".to_string()));
+        assert_eq!(read_line(&mut reader).unwrap(), None);
+
+        let mut reader = io::Cursor::new(vec![b'x'; MAX_LINE * 2]);
+        assert_eq!(read_line(&mut reader).unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn waiting_client_playing_the_machine_stops_waiting() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = Server::new();
+        let serving = server.clone();
+        thread::spawn(move || serving.serve(&listener));
+
+        let mut ann = client(addr);
+        assert_eq!(send(&mut ann, "PLAY human"), "WAITING");
+        let game = send(&mut ann, "PLAY machine");
+        assert!(game.starts_with("GAME ") && game.ends_with(" X"), "{}", game);
+
+        // The next client looking for a human waits instead of joining ann
+        let mut bob = client(addr);
+        assert_eq!(send(&mut bob, "PLAY human"), "WAITING");
+        assert_eq!(server.games(), 1);
+        assert!(send(&mut ann, "STATE").starts_with("STATE "));
+    }
+}
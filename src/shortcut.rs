@@ -0,0 +1,86 @@
+//! Shorthands for moves at the prompt, turned into the cell they name on
+//! the board at hand: `c` (or `center`) for the center, `corner` for a free
+//! corner picked at random and `same` for the cell opposite the last move
+//! of the other side, its mirror image through the center.
+
+use std::str::FromStr;
+
+use crate::board::BoardChar;
+use crate::engine::TicTacToe;
+use crate::error::GameError;
+use crate::r#move::Move;
+use crate::rng::Rng;
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Shortcut {
+    /// The center, on a board of even size a free one of its four cells
+    Center,
+    /// A free corner, picked at random
+    Corner,
+    /// The last move of the other side mirrored through the center
+    Same,
+}
+
+impl Shortcut {
+    /// This function returns the move the shortcut names for the side to
+    /// move in `game`. It fails if that cell is taken.
+    pub fn expand(self, game: &TicTacToe, rng: &mut Rng) -> Result<Move, GameError> {
+        let last = game.board().len() - 1;
+        let free = |m: &Move| game.board()[m.row][m.col] == BoardChar::Empty;
+
+        let cells: Vec<Move> = match self {
+            Shortcut::Center => {
+                let (low, high) = (last / 2, last.div_ceil(2));
+                let cells = [(low, low), (low, high), (high, low), (high, high)];
+                let mut cells: Vec<Move> = cells.iter().map(|&(row, col)| Move { row, col }).collect();
+                cells.dedup();
+                cells
+            }
+            Shortcut::Corner => [(0, 0), (0, last), (last, 0), (last, last)]
+                .iter()
+                .map(|&(row, col)| Move { row, col })
+                .collect(),
+            Shortcut::Same => {
+                let other = game.turn().to_opposite();
+                let placement = game
+                    .moves()
+                    .iter()
+                    .rev()
+                    .find(|placement| placement.mark == other)
+                    .ok_or_else(|| GameError::InvalidInput("There is no move of the other side to mirror yet".to_string()))?;
+                let mirror = Move {
+                    row: last - placement.at.row,
+                    col: last - placement.at.col,
+                };
+                if !free(&mirror) {
+                    return Err(GameError::InvalidInput(format!("The mirror image of {}, {}, is taken", placement.at, mirror)));
+                }
+                return Ok(mirror);
+            }
+        };
+
+        let cells: Vec<Move> = cells.into_iter().filter(free).collect();
+        if cells.is_empty() {
+            let taken = match self {
+                Shortcut::Center => "The center is taken",
+                _ => "Every corner is taken",
+            };
+            return Err(GameError::InvalidInput(taken.to_string()));
+        }
+
+        Ok(cells[rng.below(cells.len())])
+    }
+}
+
+impl FromStr for Shortcut {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "c" | "center" | "centre" => Ok(Shortcut::Center),
+            "corner" => Ok(Shortcut::Corner),
+            "same" => Ok(Shortcut::Same),
+            tr => Err(GameError::InvalidInput(format!("'{}' is not one of 'c', 'corner', 'same'", tr))),
+        }
+    }
+}
@@ -0,0 +1,420 @@
+//! A log of the games played against the machine and remote opponents and
+//! what can be learned from it: how often the player wins, streaks, the
+//! length of games and an Elo rating.
+//!
+//! The rating is worked out from the whole log, oldest game first, so it
+//! needs no file of its own. Each difficulty of the machine has a fixed
+//! rating, remote opponents start at `START_RATING` and are rated along
+//! with the player.
+
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::engine::{Difficulty, TicTacToe};
+use crate::i18n::{fill_in, Lang, Text};
+use crate::json::{self, FromJson, ToJson};
+use crate::r#move::Move;
+
+/// How a game ended for the player
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Outcome {
+    Win,
+    Loss,
+    Draw,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Outcome::Win => write!(f, "win"),
+            Outcome::Loss => write!(f, "loss"),
+            Outcome::Draw => write!(f, "draw"),
+        }
+    }
+}
+
+impl Outcome {
+    /// This function returns the key of the outcome shown to the player
+    pub fn text(self) -> Text {
+        match self {
+            Outcome::Win => Text::Win,
+            Outcome::Loss => Text::Loss,
+            Outcome::Draw => Text::DrawOutcome,
+        }
+    }
+}
+
+impl FromStr for Outcome {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "win" => Ok(Outcome::Win),
+            "loss" => Ok(Outcome::Loss),
+            "draw" => Ok(Outcome::Draw),
+            tr => Err(format!("'{}' is not one of 'win', 'loss', 'draw'", tr)),
+        }
+    }
+}
+
+/// The rating the player and every remote opponent start with
+pub const START_RATING: f64 = 1200.0;
+/// How many points a single game can move a rating at most
+pub const RATING_K: f64 = 32.0;
+
+/// This function returns the fixed rating of the machine at `difficulty`
+pub fn machine_rating(difficulty: Difficulty) -> f64 {
+    match difficulty {
+        Difficulty::Easy => 800.0,
+        Difficulty::Medium => 1200.0,
+        Difficulty::Hard => 1600.0,
+    }
+}
+
+/// A finished game against the machine or a remote opponent
+#[derive(Debug, PartialEq, Clone)]
+pub struct GameRecord {
+    /// When the game ended, in UTC, e.g. `2021-03-14T15:09:26Z`
+    pub date: String,
+    pub outcome: Outcome,
+    /// Every move of the game, oldest first
+    pub moves: Vec<Move>,
+    pub difficulty: Difficulty,
+    /// The name of the remote opponent, `None` for the machine at `difficulty`
+    pub opponent: Option<String>,
+    /// The date of the daily challenge the game was, `YYYY-MM-DD`
+    pub daily: Option<String>,
+}
+
+impl GameRecord {
+    /// This function records `game` as it ended just now. It returns `None`
+    /// while the game is still going on.
+    pub fn new(game: &TicTacToe) -> Option<GameRecord> {
+        if !game.is_game_over() {
+            return None;
+        }
+
+        let outcome = match game.winner() {
+            Some(c) if c == game.player_char() => Outcome::Win,
+            Some(_) => Outcome::Loss,
+            None => Outcome::Draw,
+        };
+
+        Some(GameRecord {
+            date: utc_now(),
+            outcome,
+            moves: game.moves().iter().map(|p| p.at).collect(),
+            difficulty: game.ai().difficulty,
+            opponent: None,
+            daily: None,
+        })
+    }
+
+    /// This function returns how many points of a game this record is
+    /// worth for the player
+    fn points(&self) -> f64 {
+        match self.outcome {
+            Outcome::Win => 1.0,
+            Outcome::Draw => 0.5,
+            Outcome::Loss => 0.0,
+        }
+    }
+}
+
+/// The ratings after all games of the log
+#[derive(Debug, PartialEq, Clone)]
+pub struct Ratings {
+    pub player: f64,
+    /// Every remote opponent with its rating and the games played against
+    /// it, in the order they were first met
+    pub opponents: Vec<(String, f64, usize)>,
+}
+
+/// This function returns how many points a side rated `rating` is expected
+/// to score against one rated `other`
+fn expected(rating: f64, other: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((other - rating) / 400.0))
+}
+
+/// This function returns the current time in UTC as `YYYY-MM-DDTHH:MM:SSZ`
+pub(crate) fn utc_now() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+
+    let (days, rest) = (secs / 86_400, secs % 86_400);
+    let (year, month, day) = civil_from_days(days as i64);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        rest / 3600,
+        rest % 3600 / 60,
+        rest % 60
+    )
+}
+
+/// This function returns the current date in UTC as `YYYY-MM-DD`, the day
+/// of the daily challenge
+pub fn today() -> String {
+    utc_now()[..10].to_string()
+}
+
+/// This function turns a date written `YYYY-MM-DD` into days since
+/// 1970-01-01, the inverse of `civil_from_days`
+pub(crate) fn day_number(date: &str) -> Option<i64> {
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let mp = if month > 2 { month - 3 } else { month + 9 };
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+
+    Some(era * 146_097 + doe - 719_468)
+}
+
+/// This function turns days since 1970-01-01 into year, month and day of
+/// the proleptic Gregorian calendar (Howard Hinnant's algorithm)
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    (year, month, day)
+}
+
+/// The games recorded so far, oldest first
+#[derive(Debug, PartialEq, Default, Clone)]
+pub struct Stats {
+    pub games: Vec<GameRecord>,
+}
+
+impl Stats {
+    /// This function returns where the statistics are kept by default,
+    /// `~/.tictactoe/stats.json`
+    pub fn default_path() -> Option<PathBuf> {
+        let home = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE"))?;
+
+        Some(PathBuf::from(home).join(".tictactoe").join("stats.json"))
+    }
+
+    /// This function reads the statistics kept at `path`. A file that does
+    /// not exist yet holds no games.
+    pub fn load(path: &Path) -> Result<Stats, String> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Stats::default()),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        Stats::from_json(&json::parse(&text)?)
+    }
+
+    /// This function writes the statistics to `path`, creating its directory if needed
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+        }
+
+        fs::write(path, self.to_json().to_string()).map_err(|e| e.to_string())
+    }
+
+    /// This function adds `record` to the statistics kept at `path`
+    pub fn append(path: &Path, record: GameRecord) -> Result<(), String> {
+        let mut stats = Stats::load(path)?;
+        stats.games.push(record);
+
+        stats.save(path)
+    }
+
+    fn count(&self, outcome: Outcome) -> usize {
+        self.games.iter().filter(|game| game.outcome == outcome).count()
+    }
+
+    /// This function returns the share of games that ended with `outcome`,
+    /// in percent
+    pub fn rate(&self, outcome: Outcome) -> f64 {
+        if self.games.is_empty() {
+            return 0.0;
+        }
+
+        100.0 * self.count(outcome) as f64 / self.games.len() as f64
+    }
+
+    /// This function returns the outcome of the latest games and how many
+    /// games in a row ended that way
+    pub fn current_streak(&self) -> Option<(Outcome, usize)> {
+        let last = self.games.last()?.outcome;
+        let length = self.games.iter().rev().take_while(|game| game.outcome == last).count();
+
+        Some((last, length))
+    }
+
+    /// This function returns the most games in a row that ended with `outcome`
+    pub fn longest_streak(&self, outcome: Outcome) -> usize {
+        let mut longest = 0;
+        let mut current = 0;
+
+        for game in &self.games {
+            if game.outcome == outcome {
+                current += 1;
+                longest = longest.max(current);
+            } else {
+                current = 0;
+            }
+        }
+
+        longest
+    }
+
+    /// This function rates the player and the remote opponents by replaying
+    /// the log
+    pub fn ratings(&self) -> Ratings {
+        let mut ratings = Ratings {
+            player: START_RATING,
+            opponents: Vec::new(),
+        };
+
+        for game in &self.games {
+            let change = |other: f64, player: f64| RATING_K * (game.points() - expected(player, other));
+
+            match &game.opponent {
+                None => ratings.player += change(machine_rating(game.difficulty), ratings.player),
+                Some(name) => {
+                    let at = match ratings.opponents.iter().position(|(known, _, _)| known == name) {
+                        Some(at) => at,
+                        None => {
+                            ratings.opponents.push((name.clone(), START_RATING, 0));
+                            ratings.opponents.len() - 1
+                        }
+                    };
+
+                    let (_, rating, games) = &mut ratings.opponents[at];
+                    let delta = change(*rating, ratings.player);
+                    ratings.player += delta;
+                    *rating -= delta;
+                    *games += 1;
+                }
+            }
+        }
+
+        ratings
+    }
+
+    /// This function returns the daily challenge played on `date`, if any
+    pub fn daily(&self, date: &str) -> Option<&GameRecord> {
+        self.games.iter().find(|game| game.daily.as_deref() == Some(date))
+    }
+
+    /// This function returns how many daily challenges in a row were won up
+    /// to `today`. A challenge not played yet today does not break it.
+    pub fn daily_streak(&self, today: &str) -> usize {
+        let won: Vec<i64> = self
+            .games
+            .iter()
+            .filter(|game| game.outcome == Outcome::Win)
+            .filter_map(|game| game.daily.as_deref().and_then(day_number))
+            .collect();
+
+        let mut day = match day_number(today) {
+            Some(day) if won.contains(&day) => day,
+            Some(day) => day - 1,
+            None => return 0,
+        };
+        let mut streak = 0;
+        while won.contains(&day) {
+            streak += 1;
+            day -= 1;
+        }
+
+        streak
+    }
+
+    /// This function returns the difficulty of the machine rated closest
+    /// to the player, the one that makes for the best games
+    pub fn suggested_difficulty(&self) -> Difficulty {
+        let player = self.ratings().player;
+        let distance = |d: &Difficulty| (machine_rating(*d) - player).abs();
+
+        [Difficulty::Easy, Difficulty::Medium, Difficulty::Hard]
+            .iter()
+            .copied()
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .unwrap_or(Difficulty::Medium)
+    }
+
+    /// This function returns the average number of moves per game
+    pub fn average_length(&self) -> f64 {
+        if self.games.is_empty() {
+            return 0.0;
+        }
+
+        let moves: usize = self.games.iter().map(|game| game.moves.len()).sum();
+        moves as f64 / self.games.len() as f64
+    }
+
+    /// This function returns the summary printed by `--stats` in `lang`
+    pub fn summary(&self, lang: Lang) -> String {
+        if self.games.is_empty() {
+            return format!("{}\n", Text::NoGamesYet.in_lang(lang));
+        }
+
+        let mut lines = vec![fill_in(Text::GamesPlayed, lang, &[&self.games.len()])];
+        let labels = [(Outcome::Win, Text::Wins), (Outcome::Loss, Text::Losses), (Outcome::Draw, Text::Draws)];
+        let width = labels.iter().map(|(_, label)| label.in_lang(lang).chars().count()).max().unwrap_or(0) + 1;
+        for &(outcome, label) in &labels {
+            let label = label.in_lang(lang);
+            lines.push(format!("{:<width$}{:>4} ({:.1}%)", label, self.count(outcome), self.rate(outcome), width = width));
+        }
+
+        if let Some((outcome, length)) = self.current_streak() {
+            lines.push(fill_in(Text::CurrentStreak, lang, &[&outcome.text().in_lang(lang), &length]));
+        }
+        lines.push(fill_in(Text::LongestWinning, lang, &[&self.longest_streak(Outcome::Win)]));
+        lines.push(fill_in(Text::LongestLosing, lang, &[&self.longest_streak(Outcome::Loss)]));
+        lines.push(fill_in(Text::AverageLength, lang, &[&format!("{:.1}", self.average_length())]));
+
+        let ratings = self.ratings();
+        lines.push(fill_in(Text::Rating, lang, &[&format!("{:.0}", ratings.player)]));
+        for (name, rating, games) in &ratings.opponents {
+            let rating = format!("{:.0}", rating);
+            lines.push(match games {
+                1 => fill_in(Text::RatingAfterGame, lang, &[name, &rating]),
+                _ => fill_in(Text::RatingAfterGames, lang, &[name, &rating, games]),
+            });
+        }
+        lines.push(fill_in(Text::SuggestedDifficulty, lang, &[&self.suggested_difficulty()]));
+
+        if self.games.iter().any(|game| game.daily.is_some()) {
+            lines.push(fill_in(Text::DailyStreak, lang, &[&self.daily_streak(&today())]));
+        }
+
+        lines.iter().map(|line| format!("{}\n", line)).collect()
+    }
+}
+
+impl fmt::Display for Stats {
+    /// The summary in English, `--stats` prints it with `summary`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.summary(Lang::English))
+    }
+}
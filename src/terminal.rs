@@ -0,0 +1,1007 @@
+//! A game of tic-tac-toe played at a terminal. What the people type is read
+//! from an `InputSource`, the board, the prompts and the result are printed
+//! to stdout, and the machine is any `Player`, so a test can play a whole
+//! game from a list of lines. What is left to the program around the game,
+//! like keeping it in case the program is interrupted, is done by a `Host`.
+//!
+//! The variants and the games over the network are played here too, and
+//! every game runs through the turns of `play_turns`.
+
+#[cfg(feature = "serde")]
+use std::cell::RefCell;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+#[cfg(feature = "serde")]
+use std::rc::Rc;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use crate::analysis::Lesson;
+use crate::board::BoardChar;
+use crate::clock::{format_split, format_time, Clock};
+use crate::engine::{Ending, GameState, TicTacToe};
+use crate::game::Game;
+use crate::i18n::{fill, localize, Text};
+use crate::input::InputSource;
+#[cfg(feature = "serde")]
+use crate::net::{Link, Message};
+use crate::notify::{Notifier, Silent};
+#[cfg(feature = "serde")]
+use crate::player::Remote;
+use crate::player::{Decision, Player, Symbols, Table};
+use crate::r#move::{column_name, Move, MoveFormat};
+use crate::record::write_record;
+use crate::render::Theme;
+use crate::rng::Rng;
+use crate::shortcut::Shortcut;
+
+/// Who sits at the board
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Mode {
+    /// A human against the machine
+    Machine,
+    /// Two humans taking turns at the same terminal
+    HotSeat,
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "pve" | "machine" => Ok(Mode::Machine),
+            "pvp" => Ok(Mode::HotSeat),
+            tr => Err(format!("'{}' is not one of 'pve', 'pvp'", tr)),
+        }
+    }
+}
+
+/// What can be typed at the move prompt
+#[derive(Debug, PartialEq, Clone)]
+pub enum Command {
+    /// A move and the way it was written
+    Move(Move, Option<MoveFormat>),
+    /// A move named by a shorthand, which cell it is depends on the board
+    Shortcut(Shortcut),
+    Undo,
+    Redo,
+    Hint,
+    /// Print the value of every legal move
+    Eval,
+    /// Print the position in the notation of `--position`
+    Position,
+    Save(String),
+    Load(String),
+    Resign,
+    /// Offer the opponent a draw
+    Draw,
+    /// Start the game over, with the symbols swapped if true
+    Restart(bool),
+    /// Leave the game and the program
+    Quit,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tr = s.trim();
+        let (word, rest) = match tr.split_once(char::is_whitespace) {
+            Some((word, rest)) => (word, rest.trim()),
+            None => (tr, ""),
+        };
+
+        match (word.to_lowercase().as_str(), rest) {
+            ("undo", "") => Ok(Command::Undo),
+            ("redo", "") => Ok(Command::Redo),
+            ("hint", "") => Ok(Command::Hint),
+            ("eval", "") => Ok(Command::Eval),
+            ("position", "") => Ok(Command::Position),
+            ("resign", "") => Ok(Command::Resign),
+            ("draw", "") => Ok(Command::Draw),
+            ("restart", "") => Ok(Command::Restart(false)),
+            ("restart", "swap") => Ok(Command::Restart(true)),
+            ("restart", _) => Err("Please write restart, or restart swap to swap the symbols".to_string()),
+            ("quit", "") | ("exit", "") => Ok(Command::Quit),
+            ("save", "") | ("load", "") => Err(format!("Please give a file: {} <path>", word)),
+            ("save", path) => Ok(Command::Save(path.to_string())),
+            ("load", path) => Ok(Command::Load(path.to_string())),
+            _ => match tr.parse() {
+                Ok(shortcut) => Ok(Command::Shortcut(shortcut)),
+                Err(_) => tr.parse().map(|m| Command::Move(m, MoveFormat::of(tr))).map_err(String::from),
+            },
+        }
+    }
+}
+
+/// Answer to a yes/no question asked on the terminal
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Answer {
+    Yes,
+    No,
+}
+
+impl FromStr for Answer {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "y" | "yes" | "j" | "ja" | "д" | "да" => Ok(Answer::Yes),
+            "n" | "no" | "nein" | "н" | "нет" => Ok(Answer::No),
+            tr => Err(format!("'{}' is not one of 'y', 'n'", tr)),
+        }
+    }
+}
+
+/// This is a generic function to convert input in some type. It asks
+/// again until a line can be converted and returns `None` once the input
+/// has ended.
+pub fn read_input<T: FromStr>(input: &mut dyn InputSource, ask: &str) -> Option<T>
+where
+    T::Err: fmt::Display,
+{
+    loop {
+        println!("{}", ask);
+
+        match input.read_line() {
+            Ok(Some(line)) => match T::from_str(&line) {
+                Ok(value) => return Some(value),
+                Err(e) => println!("{}", e),
+            },
+            Ok(None) => return None,
+            Err(e) => {
+                eprintln!("Could not read the input: {}", e);
+                return None;
+            }
+        }
+    }
+}
+
+/// This function returns `s` with its first letter in upper case
+pub fn capitalize(s: &str) -> String {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) => c.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// This function returns how a side is addressed on the terminal
+pub fn name(game: &TicTacToe, mode: Mode, c: BoardChar) -> String {
+    let glyph = game.symbols().glyph(c);
+
+    match mode {
+        Mode::Machine if c == game.machine_char() => Text::Machine.to_string(),
+        Mode::Machine => Text::You.to_string(),
+        Mode::HotSeat if c == game.player_char() => format!("{} ({})", Text::Player1, glyph),
+        Mode::HotSeat => format!("{} ({})", Text::Player2, glyph),
+    }
+}
+
+/// Watches over the turns of a game for `play_turns`: when the time of the
+/// side to move runs out, whether its move counts and what is told once it
+/// is played. Every hook does nothing unless it is implemented.
+pub trait Referee<G: Game> {
+    /// This function is told before every turn and returns when the time of
+    /// the side to move runs out, if it plays on a clock
+    fn turn(&mut self, _game: &mut G) -> Option<Instant> {
+        None
+    }
+
+    /// This function plays `m`, which side `mark` chose, and returns false
+    /// if the move does not count, e.g. because the time of the side ran out
+    /// and the game ended instead
+    fn play(&mut self, game: &mut G, _mark: BoardChar, m: &G::Move) -> bool {
+        game.play(m).is_ok()
+    }
+
+    /// This function is told about every move, which side `mark` played,
+    /// once the players were, `by` is the player whose move it was
+    fn played(&mut self, _game: &mut G, _mark: BoardChar, _m: &G::Move, _by: &dyn Player<G>) {}
+
+    /// This function is told that a player started the game over
+    fn restarted(&mut self, _game: &mut G) {}
+
+    /// This function returns true if the side to move may take the first
+    /// move over under the pie rule
+    fn offers_swap(&self, _game: &G) -> bool {
+        false
+    }
+
+    /// This function lets the side to move take the first move over
+    fn swap(&mut self, _game: &mut G) {}
+}
+
+/// Referees a casual game: every legal move counts and there is no clock
+pub struct Casual;
+
+impl<G: Game> Referee<G> for Casual {}
+
+/// This function returns the player whose turn it is, `opponent` playing
+/// the machine's side if there is one and `people` every other side
+fn seat<'a, G: Table>(
+    game: &G,
+    people: &'a mut dyn Player<G>,
+    opponent: &'a mut Option<&mut dyn Player<G>>,
+) -> &'a mut dyn Player<G> {
+    match opponent {
+        Some(opponent) if game.turn() == game.machine_char() => &mut **opponent,
+        _ => people,
+    }
+}
+
+/// This function plays `game` until it is over. `opponent` plays the
+/// machine's side, if there is one, and `people` every other side, so a
+/// game between two people at the same terminal has no opponent. It
+/// returns false if a player left before the game was over.
+pub fn play_turns<G: Table>(
+    game: &mut G,
+    people: &mut dyn Player<G>,
+    mut opponent: Option<&mut dyn Player<G>>,
+    referee: &mut dyn Referee<G>,
+) -> bool {
+    while !game.is_game_over() {
+        let deadline = referee.turn(game);
+        let turn = game.turn();
+
+        let m = match seat(game, people, &mut opponent).choose_move(game, deadline) {
+            Decision::Play(m) => m,
+            Decision::Changed => continue,
+            Decision::Restarted => {
+                referee.restarted(game);
+                continue;
+            }
+            Decision::Leave => return false,
+        };
+        if !referee.play(game, turn, &m) {
+            continue;
+        }
+
+        people.observe(game, turn, &m);
+        if let Some(opponent) = &mut opponent {
+            opponent.observe(game, turn, &m);
+        }
+        // The side to move changed with the move
+        let by = match &opponent {
+            Some(opponent) if turn == game.machine_char() => &**opponent,
+            _ => &*people,
+        };
+        referee.played(game, turn, &m, by);
+
+        if referee.offers_swap(game) && seat(game, people, &mut opponent).swap(game) {
+            referee.swap(game);
+        }
+    }
+
+    true
+}
+
+/// How a game at the terminal is played
+#[derive(Debug, Clone)]
+pub struct Settings {
+    pub mode: Mode,
+    /// How moves are announced, by default the way the player writes them
+    pub notation: Option<MoveFormat>,
+    /// How long the machine thinks about a hint or whether to swap sides
+    pub think: Duration,
+    /// Thinking time of each side for the whole game
+    pub clock: Option<Duration>,
+    /// Thinking time of each side for a single move
+    pub move_time: Option<Duration>,
+    /// Seed of the cells a shortcut picks among
+    pub seed: Option<u64>,
+    /// Let the second side take over the first move instead of answering it
+    pub pie: bool,
+    /// Explain every move of the player once it is played
+    pub teach: bool,
+    /// Tell what the search of the machine did after each of its moves
+    pub verbose: bool,
+    /// Time every move and print the times once the game is over
+    pub speedrun: bool,
+    /// A file every finished game is added to as a record
+    pub export: Option<PathBuf>,
+}
+
+impl Default for Settings {
+    fn default() -> Settings {
+        Settings {
+            mode: Mode::Machine,
+            notation: None,
+            think: Duration::from_secs(2),
+            clock: None,
+            move_time: None,
+            seed: None,
+            pie: false,
+            teach: false,
+            verbose: false,
+            speedrun: false,
+            export: None,
+        }
+    }
+}
+
+/// What the game loop leaves to the program it runs in. Every hook does
+/// nothing unless it is implemented.
+pub trait Host {
+    /// This function is told before every turn, e.g. to keep the game in
+    /// case the program is interrupted
+    fn turn_started(&mut self, _game: &TicTacToe) {}
+
+    /// This function is told once the game is over or was left
+    fn ended(&mut self, _game: &TicTacToe) {}
+
+    /// This function is given the game once it started over, from the empty
+    /// board or from a saved game, to apply the settings of the program to it
+    fn restarted(&mut self, _game: &mut TicTacToe) {}
+}
+
+impl Host for Silent {}
+
+/// This function writes the game to `path`
+#[cfg(feature = "serde")]
+pub fn save(game: &TicTacToe, path: &str) -> Result<(), String> {
+    fs::write(path, game.to_json()).map_err(|e| e.to_string())
+}
+
+/// This function reads a game written by `save`
+#[cfg(feature = "serde")]
+pub fn load(path: &str) -> Result<TicTacToe, String> {
+    let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    Ok(TicTacToe::from_json(&text)?)
+}
+
+/// Games are saved as JSON, which is left out without the serde feature
+#[cfg(not(feature = "serde"))]
+pub fn save(_game: &TicTacToe, _path: &str) -> Result<(), String> {
+    Err(NO_SERDE.to_string())
+}
+
+#[cfg(not(feature = "serde"))]
+pub fn load(_path: &str) -> Result<TicTacToe, String> {
+    Err(NO_SERDE.to_string())
+}
+
+#[cfg(not(feature = "serde"))]
+const NO_SERDE: &str = "saving games needs the serde feature";
+
+/// This function adds `record` to the end of the file at `path`
+pub fn append_record(record: &str, path: &Path) -> io::Result<()> {
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() > 0 {
+        writeln!(file)?;
+    }
+
+    file.write_all(record.as_bytes())
+}
+
+/// This function prints what every legal move of the side to move leads to
+pub fn print_analysis(game: &mut TicTacToe, notation: MoveFormat) {
+    let size = game.board().len();
+
+    for (m, evaluation) in game.analyze() {
+        println!("{:>5}  {}", m.format(notation, size), evaluation);
+    }
+}
+
+/// The person at the terminal, playing every side that is not the machine's
+struct Human<'a> {
+    input: &'a mut dyn InputSource,
+    settings: &'a Settings,
+    notifier: &'a mut dyn Notifier,
+    /// How the moves are announced, by default the way the player writes them
+    notation: MoveFormat,
+    /// Picks among the cells a shortcut may name
+    rng: Rng,
+}
+
+impl<'a> Human<'a> {
+    fn new(input: &'a mut dyn InputSource, settings: &'a Settings, notifier: &'a mut dyn Notifier) -> Human<'a> {
+        Human {
+            input,
+            settings,
+            notifier,
+            notation: settings.notation.unwrap_or_default(),
+            rng: settings.seed.map_or_else(Rng::from_time, Rng::new),
+        }
+    }
+}
+
+impl Human<'_> {
+    /// This function offers the opponent of `c` a draw. The machine accepts
+    /// unless it can force a win, a person is asked.
+    fn offer_draw(&mut self, game: &mut TicTacToe, c: BoardChar) {
+        let mode = self.settings.mode;
+        let opponent = name(game, mode, c.to_opposite());
+
+        let accepted = match mode {
+            Mode::Machine => game.offer_draw(c),
+            Mode::HotSeat => {
+                let ask = fill(Text::AcceptDraw, &[&capitalize(&opponent)]);
+                read_input(self.input, &ask) == Some(Answer::Yes) && game.offer_draw(c)
+            }
+        };
+
+        if !accepted {
+            println!("{}", capitalize(&fill(Text::DeclinesDraw, &[&capitalize(&opponent)])));
+        }
+    }
+
+    /// This function clears the board, handicap marks stay where they are
+    /// and the same side opens. `swap` swaps the symbols of the two sides.
+    fn restart(&mut self, game: &mut TicTacToe, swap: bool) {
+        let handicap = game.handicap().to_vec();
+        let first = game.first();
+        game.reset();
+        game.set_first(first);
+        if !handicap.is_empty() {
+            // The cells were possible on the empty board before
+            let _ = game.set_handicap(&handicap);
+        }
+        if swap {
+            let symbols = game.symbols();
+            game.set_symbols(Symbols { x: symbols.o, o: symbols.x });
+        }
+
+        println!("{}", fill(Text::Restarted, &[&game.symbols().glyph(game.player_char())]));
+        if self.settings.mode == Mode::Machine && game.first() == game.machine_char() && handicap.is_empty() {
+            println!("{}", Text::MachineOpens);
+        }
+    }
+}
+
+impl Player for Human<'_> {
+    fn choose_move(&mut self, game: &mut TicTacToe, deadline: Option<Instant>) -> Decision {
+        let mode = self.settings.mode;
+        let size = game.board().len();
+        let turn = game.turn();
+        println!("{}", game);
+
+        let ask = match mode {
+            Mode::Machine => Text::YourTurn.to_string(),
+            Mode::HotSeat => fill(Text::PlayerTurn, &[&name(game, mode, turn)]),
+        };
+        let ask = match deadline {
+            Some(deadline) => {
+                let left = format_time(deadline.saturating_duration_since(Instant::now()));
+                format!("{} ({}): ", ask, fill(Text::TimeLeft, &[&left]))
+            }
+            None => format!("{}: ", ask),
+        };
+        let command = match read_input(self.input, &ask) {
+            Some(command) => command,
+            None => return Decision::Leave,
+        };
+
+        match command {
+            Command::Move(_, Some(MoveFormat::Numpad)) if size != 3 => {
+                println!("{}", Text::KeypadOnly);
+            }
+            Command::Move(m, format) => match game.check_move(&m) {
+                Ok(()) => {
+                    // Moves are announced the way the player writes them
+                    if self.settings.notation.is_none() {
+                        self.notation = format.unwrap_or(self.notation);
+                    }
+                    return Decision::Play(m);
+                }
+                Err(e) => println!("{}", fill(Text::NotPossible, &[&localize(&e)])),
+            },
+            Command::Shortcut(shortcut) => match shortcut.expand(game, &mut self.rng) {
+                Ok(m) => return Decision::Play(m),
+                Err(e) => println!("{}", e),
+            },
+            Command::Undo => undo(game, mode),
+            Command::Redo => redo(game, mode),
+            Command::Hint => match game.hint(self.settings.think) {
+                Some(m) => println!("{}", fill(Text::BestMove, &[&m.format(self.notation, size)])),
+                None => println!("{}", Text::NoMoveLeft),
+            },
+            Command::Eval => print_analysis(game, self.notation),
+            Command::Position => println!("{}", game.to_notation()),
+            Command::Save(path) => match save(game, &path) {
+                Ok(()) => println!("{}", fill(Text::GameSaved, &[&path])),
+                Err(e) => println!("{}", fill(Text::CouldNotSave, &[&path, &e])),
+            },
+            Command::Load(path) => match load(&path) {
+                Ok(loaded) => {
+                    *game = loaded;
+                    println!("{}", fill(Text::GameLoaded, &[&path]));
+                    return Decision::Restarted;
+                }
+                Err(e) => println!("{}", fill(Text::CouldNotLoad, &[&path, &e])),
+            },
+            Command::Resign => game.resign(turn),
+            Command::Draw => self.offer_draw(game, turn),
+            Command::Restart(swap) => {
+                self.restart(game, swap);
+                return Decision::Restarted;
+            }
+            Command::Quit => {
+                if read_input(self.input, Text::ConfirmQuit.get()) == Some(Answer::Yes) {
+                    return Decision::Leave;
+                }
+            }
+        }
+
+        Decision::Changed
+    }
+
+    fn observe(&mut self, game: &TicTacToe, mark: BoardChar, m: &Move) {
+        let machine = self.settings.mode == Mode::Machine && mark == game.machine_char();
+
+        // Screen readers hear every move in full, the side's own too
+        if game.theme() == Theme::Accessible {
+            let side = capitalize(&name(game, self.settings.mode, mark));
+            let glyph = game.symbols().glyph(mark);
+            let (row, col) = (m.row + 1, column_name(m.col));
+            println!("{}", fill(Text::Placed, &[&side, &glyph, &row, &col]));
+        } else if machine {
+            println!("{}", fill(Text::MachineMoved, &[&m.format(self.notation, game.board().len())]));
+        }
+        if machine {
+            self.notifier.your_turn(&m.format(self.notation, game.board().len()));
+        }
+    }
+
+    fn swap(&mut self, game: &mut TicTacToe) -> bool {
+        let side = name(game, self.settings.mode, game.turn());
+        let opener = game.turn().to_opposite();
+        println!("{}", game);
+
+        let ask = fill(Text::OfferSwap, &[&capitalize(&side), &game.symbols().glyph(opener)]);
+        read_input(self.input, &ask) == Some(Answer::Yes)
+    }
+}
+
+/// Referees a game at the terminal with the settings of the program: the
+/// clock, timing every move, the lessons and the pie rule
+struct Local<'a> {
+    settings: &'a Settings,
+    host: &'a mut dyn Host,
+    clock: Clock,
+    /// When the side to move started thinking, commands at the prompt count too
+    started: Instant,
+    /// What the move being played teaches, found before it changed the board
+    lesson: Option<Lesson>,
+}
+
+impl Local<'_> {
+    /// This function returns true if side `c` is the machine's
+    fn machine(&self, game: &TicTacToe, c: BoardChar) -> bool {
+        self.settings.mode == Mode::Machine && c == game.machine_char()
+    }
+}
+
+impl Referee<TicTacToe> for Local<'_> {
+    fn turn(&mut self, game: &mut TicTacToe) -> Option<Instant> {
+        self.host.turn_started(game);
+
+        let turn = game.turn();
+        if self.clock.running() != Some(turn) {
+            self.clock.start(turn);
+        }
+
+        self.clock.deadline()
+    }
+
+    fn play(&mut self, game: &mut TicTacToe, mark: BoardChar, m: &Move) -> bool {
+        // A move that comes after the time is up does not count
+        if !self.clock.stop() {
+            game.forfeit(mark);
+            return false;
+        }
+
+        self.lesson = match self.settings.teach && !self.machine(game, mark) {
+            true => game.lesson(m),
+            false => None,
+        };
+        let played = match self.settings.speedrun {
+            true => game.play_timed(m, self.started.elapsed()),
+            false => game.play_move(m),
+        };
+
+        played.is_ok()
+    }
+
+    fn played(&mut self, game: &mut TicTacToe, mark: BoardChar, _m: &Move, by: &dyn Player) {
+        let mode = self.settings.mode;
+
+        if let (true, true, Some(info)) = (self.settings.verbose, self.machine(game, mark), by.search_info()) {
+            println!("  {}", info);
+        }
+        if let Some(lesson) = self.lesson.take() {
+            let opponent = match mode {
+                Mode::Machine => Text::TheMachine.to_string(),
+                Mode::HotSeat => name(game, mode, mark.to_opposite()),
+            };
+            for sentence in lesson.explain(&opponent) {
+                println!("  {}", sentence);
+            }
+        }
+        self.started = Instant::now();
+    }
+
+    fn restarted(&mut self, game: &mut TicTacToe) {
+        self.host.restarted(game);
+        // Both sides get their full time again
+        self.clock = Clock::new(self.settings.clock, self.settings.move_time);
+        self.started = Instant::now();
+    }
+
+    fn offers_swap(&self, game: &TicTacToe) -> bool {
+        self.settings.pie && game.moves().len() == 1 && !game.is_game_over()
+    }
+
+    fn swap(&mut self, game: &mut TicTacToe) {
+        let mode = self.settings.mode;
+        let turn = game.turn();
+        let opener = turn.to_opposite();
+        let owner = match mode {
+            Mode::Machine if turn == game.machine_char() => Text::TheMachine,
+            Mode::Machine => Text::You,
+            Mode::HotSeat if turn == game.player_char() => Text::Player1,
+            Mode::HotSeat => Text::Player2,
+        };
+
+        if game.swap_sides().is_ok() {
+            println!("{}", fill(Text::Swapped, &[&game.symbols().glyph(opener), &owner]));
+        }
+    }
+}
+
+/// This function runs a single game until it is over and announces the
+/// result. The people at the terminal type their moves on `input`, the
+/// moves of the machine's side are chosen by `machine`. It returns early,
+/// with the game unfinished, if the input ends.
+pub fn play(
+    input: &mut dyn InputSource,
+    game: &mut TicTacToe,
+    settings: &Settings,
+    machine: &mut dyn Player,
+    host: &mut dyn Host,
+    notifier: &mut dyn Notifier,
+) {
+    let mode = settings.mode;
+    let mut human = Human::new(input, settings, notifier);
+    let mut referee = Local {
+        settings,
+        host,
+        clock: Clock::new(settings.clock, settings.move_time),
+        started: Instant::now(),
+        lesson: None,
+    };
+    let opponent = match mode {
+        Mode::Machine => Some(machine),
+        Mode::HotSeat => None,
+    };
+
+    let over = play_turns(game, &mut human, opponent, &mut referee);
+    referee.host.ended(game);
+    if !over {
+        return;
+    }
+
+    println!("{}", game);
+
+    match game.ending() {
+        Some(Ending::OutOfTime(c)) => println!("{}", fill(Text::OutOfTime, &[&capitalize(&name(game, mode, c))])),
+        Some(Ending::Resigned(c)) => println!("{}", fill(Text::Resigned, &[&capitalize(&name(game, mode, c))])),
+        Some(Ending::DrawAgreed) => println!("{}", Text::DrawAgreed),
+        None => {}
+    }
+    if let (true, None, Some(c)) = (game.is_misere(), game.forfeited(), game.winner()) {
+        println!("{}", fill(Text::CompletedLine, &[&capitalize(&name(game, mode, c.to_opposite()))]));
+    }
+
+    let result = match (mode, game.state()) {
+        (Mode::Machine, GameState::Won { winner, .. }) if winner == game.player_char() => Text::YouWon.to_string(),
+        (Mode::Machine, GameState::Won { .. }) => Text::YouLost.to_string(),
+        (Mode::HotSeat, GameState::Won { winner, .. }) => fill(Text::PlayerWon, &[&name(game, mode, winner)]),
+        (_, _) => Text::Draw.to_string(),
+    };
+    println!("{}", result);
+    human.notifier.game_over(&result);
+
+    if let Some(path) = &settings.export {
+        let record = write_record(game, &name(game, mode, BoardChar::X), &name(game, mode, BoardChar::O), &[]);
+        if let Err(e) = append_record(&record, path) {
+            eprintln!("Could not add the game to {}: {}", path.display(), e);
+        }
+    }
+
+    if settings.speedrun {
+        print_timings(game, mode, human.notation);
+    }
+}
+
+/// This function returns the prompt for the move of the player, or in a game
+/// between two people of Player 1 if `first` and of Player 2 otherwise.
+/// Sides are shown with their glyph in games that have one.
+fn turn_prompt(mode: Mode, first: bool, glyph: Option<char>) -> String {
+    let player = match first {
+        true => Text::Player1,
+        false => Text::Player2,
+    };
+    let ask = match (mode, glyph) {
+        (Mode::Machine, _) => Text::YourTurn.to_string(),
+        (Mode::HotSeat, Some(glyph)) => fill(Text::PlayerTurn, &[&format!("{} ({})", player, glyph)]),
+        (Mode::HotSeat, None) => fill(Text::PlayerTurn, &[&player]),
+    };
+
+    format!("{}: ", ask)
+}
+
+/// This function returns the line announcing the result of a game, `won`
+/// says whether the player, or Player 1, won and is `None` for a draw
+fn result_line(mode: Mode, won: Option<bool>) -> String {
+    match (mode, won) {
+        (Mode::Machine, Some(true)) => Text::YouWon.to_string(),
+        (Mode::Machine, Some(false)) => Text::YouLost.to_string(),
+        (Mode::HotSeat, Some(true)) => fill(Text::PlayerWon, &[&Text::Player1]),
+        (Mode::HotSeat, Some(false)) => fill(Text::PlayerWon, &[&Text::Player2]),
+        (_, None) => Text::Draw.to_string(),
+    }
+}
+
+/// The people at the terminal in a variant, typing moves and nothing else
+struct Prompt<'a> {
+    input: &'a mut dyn InputSource,
+    mode: Mode,
+    notifier: &'a mut dyn Notifier,
+}
+
+impl<G: Table> Player<G> for Prompt<'_>
+where
+    G::Move: FromStr,
+    <G::Move as FromStr>::Err: fmt::Display,
+{
+    fn choose_move(&mut self, game: &mut G, _deadline: Option<Instant>) -> Decision<G::Move> {
+        println!("{}", game);
+
+        let turn = game.turn();
+        let ask = turn_prompt(self.mode, turn == game.player_char(), game.glyph(turn));
+        let m: G::Move = match read_input(self.input, &ask) {
+            Some(m) => m,
+            None => return Decision::Leave,
+        };
+
+        // The move is tried on a copy, the turn loop plays it
+        match game.clone().play(&m) {
+            Ok(()) => Decision::Play(m),
+            Err(e) => {
+                println!("{}", fill(Text::NotPossible, &[&localize(&e)]));
+                Decision::Changed
+            }
+        }
+    }
+
+    fn observe(&mut self, game: &G, mark: BoardChar, m: &G::Move) {
+        if self.mode == Mode::Machine && mark == game.machine_char() {
+            println!("{}", fill(Text::MachineMoved, &[m]));
+            self.notifier.your_turn(&m.to_string());
+        }
+    }
+}
+
+/// This function runs games of a variant until the players had enough. The
+/// people at the terminal type their moves on `input`, the moves of the
+/// machine's side are chosen by `machine`, and before every game against
+/// the machine `first` tells whether the machine opens it.
+pub fn play_games<G: Table>(
+    input: &mut dyn InputSource,
+    game: &mut G,
+    mode: Mode,
+    machine: &mut dyn Player<G>,
+    notifier: &mut dyn Notifier,
+    first: &mut dyn FnMut() -> bool,
+) where
+    G::Move: FromStr,
+    <G::Move as FromStr>::Err: fmt::Display,
+{
+    loop {
+        if mode == Mode::Machine {
+            if first() {
+                game.set_first(game.machine_char());
+                println!("{}", Text::MachineOpens);
+            } else {
+                game.set_first(game.player_char());
+            }
+        }
+
+        let mut people = Prompt {
+            input: &mut *input,
+            mode,
+            notifier: &mut *notifier,
+        };
+        let opponent = match mode {
+            Mode::Machine => Some(&mut *machine as &mut dyn Player<G>),
+            Mode::HotSeat => None,
+        };
+        if !play_turns(game, &mut people, opponent, &mut Casual) {
+            return;
+        }
+
+        println!("{}", game);
+
+        let result = result_line(mode, game.winner().map(|c| c == game.player_char()));
+        println!("{}", result);
+        notifier.game_over(&result);
+
+        if read_input(input, Text::PlayAgain.get()) != Some(Answer::Yes) {
+            break;
+        }
+
+        game.reset();
+    }
+}
+
+/// What can be typed at the move prompt of a network game
+#[cfg(feature = "serde")]
+enum RemoteInput {
+    Move(Move),
+    Resign,
+    /// `/say TEXT` tells the opponent something
+    Say(String),
+}
+
+#[cfg(feature = "serde")]
+impl FromStr for RemoteInput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(text) = s.trim().strip_prefix("/say") {
+            return match text.trim() {
+                "" => Err("/say needs something to say, e.g. /say gg".to_string()),
+                text => Ok(RemoteInput::Say(text.to_string())),
+            };
+        }
+
+        match s.trim().to_lowercase().as_str() {
+            "resign" => Ok(RemoteInput::Resign),
+            _ => s.parse().map(RemoteInput::Move).map_err(String::from),
+        }
+    }
+}
+
+/// The person at the terminal in a network game, who may talk to the
+/// opponent between moves
+#[cfg(feature = "serde")]
+struct Chatter<'a> {
+    input: &'a mut dyn InputSource,
+    link: Rc<RefCell<Link>>,
+    notifier: &'a mut dyn Notifier,
+    /// A line of chat does not show the board again
+    chatted: bool,
+}
+
+#[cfg(feature = "serde")]
+impl Player for Chatter<'_> {
+    fn choose_move(&mut self, game: &mut TicTacToe, _deadline: Option<Instant>) -> Decision {
+        if !std::mem::take(&mut self.chatted) {
+            println!("{}", game);
+        }
+
+        let me = game.player_char();
+        match read_input(self.input, Text::OnlineTurn.get()) {
+            Some(RemoteInput::Say(text)) => {
+                let mut link = self.link.borrow_mut();
+                if link.send(&Message::Chat(text.clone()), game).is_err() {
+                    return Decision::Leave;
+                }
+                link.log(me, &text, game);
+                self.chatted = true;
+            }
+            Some(RemoteInput::Move(m)) => match game.check_move(&m) {
+                Ok(()) => return Decision::Play(m),
+                Err(e) => println!("{}", fill(Text::NotPossible, &[&localize(&e)])),
+            },
+            Some(RemoteInput::Resign) => {
+                let mut link = self.link.borrow_mut();
+                if link.send(&Message::Resign, game).is_err() {
+                    return Decision::Leave;
+                }
+                link.broadcast(&Message::Resign);
+                game.resign(me);
+            }
+            None => {
+                let _ = self.link.borrow_mut().connection().send(&Message::Bye);
+                return Decision::Leave;
+            }
+        }
+
+        Decision::Changed
+    }
+
+    fn observe(&mut self, game: &TicTacToe, mark: BoardChar, m: &Move) {
+        if mark != game.player_char() {
+            self.notifier.your_turn(&m.to_string());
+        }
+    }
+}
+
+/// This function plays a single game against the peer at the other end of
+/// `link` until it is over. The person at the terminal types moves, chat
+/// and resignations on `input`. It returns false if either side left
+/// first, `Link::failure` tells whether the connection was lost for good.
+#[cfg(feature = "serde")]
+pub fn play_online(input: &mut dyn InputSource, game: &mut TicTacToe, link: &Rc<RefCell<Link>>, notifier: &mut dyn Notifier) -> bool {
+    let mut people = Chatter {
+        input,
+        link: Rc::clone(link),
+        notifier,
+        chatted: false,
+    };
+    let mut opponent = Remote::new(Rc::clone(link), game.machine_char());
+
+    play_turns(game, &mut people, Some(&mut opponent), &mut Casual)
+}
+
+/// This function prints how long every timed move of `game` took, written
+/// in `notation`, and how long each side thought in total
+fn print_timings(game: &TicTacToe, mode: Mode, notation: MoveFormat) {
+    let size = game.board().len();
+    let mut totals = [(BoardChar::X, Duration::ZERO, 0), (BoardChar::O, Duration::ZERO, 0)];
+
+    println!("{:>4}  {:<18}{:>4}  {:>9}", Text::MoveColumn.get(), Text::SideColumn.get(), "", Text::TimeColumn.get());
+    for (i, p) in game.moves().iter().enumerate() {
+        let time = match p.time {
+            Some(time) => time,
+            None => continue,
+        };
+        println!("{:>4}  {:<18}{:>4}  {:>9}", i + 1, name(game, mode, p.mark), p.at.format(notation, size), format_split(time));
+
+        for (c, total, moves) in totals.iter_mut() {
+            if *c == p.mark {
+                *total += time;
+                *moves += 1;
+            }
+        }
+    }
+
+    for (c, total, moves) in totals {
+        if moves > 0 {
+            let side = capitalize(&name(game, mode, c));
+            println!("{}", fill(Text::ThinkingTotal, &[&side, &format_split(total), &moves, &format_split(total / moves)]));
+        }
+    }
+}
+
+/// This function takes back the last move. Against the machine the
+/// machine's reply is taken back together with the player's move.
+fn undo(game: &mut TicTacToe, mode: Mode) {
+    let mut undone = Vec::new();
+
+    while let Some(p) = game.undo() {
+        undone.push(p.at.to_string());
+        if mode == Mode::HotSeat || p.mark == game.player_char() {
+            break;
+        }
+    }
+
+    if undone.is_empty() {
+        println!("{}", Text::NothingToUndo);
+    } else {
+        println!("{}", fill(Text::TookBack, &[&undone.join(", ")]));
+    }
+}
+
+/// This function plays the moves taken back by `undo` again
+fn redo(game: &mut TicTacToe, mode: Mode) {
+    match game.redo() {
+        Some(p) => {
+            let mut redone = vec![p.at.to_string()];
+
+            if mode == Mode::Machine && game.turn() == game.machine_char() {
+                if let Some(reply) = game.redo() {
+                    redone.push(reply.at.to_string());
+                }
+            }
+
+            println!("{}", fill(Text::PlayedAgain, &[&redone.join(", ")]));
+        }
+        None => println!("{}", Text::NothingToRedo),
+    }
+}
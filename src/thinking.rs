@@ -0,0 +1,169 @@
+//! Shows that the machine is thinking. Its search runs on a thread of its
+//! own while a spinner line tells the depth searched, the positions seen
+//! and the best move so far, and any key makes the machine play that move
+//! at once. Keys are read without waiting for Enter by switching the
+//! terminal into cbreak mode with `stty`, so early moves need a Unix
+//! terminal; elsewhere the spinner is shown all the same. Ctrl-C while the
+//! machine thinks stops the search and restores the terminal before the
+//! program ends.
+
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use tic_tac_toe_rs::player::{Decision, Machine, Player};
+use tic_tac_toe_rs::{Progress, SearchInfo, TicTacToe};
+
+use crate::interrupt;
+
+/// The frames of the spinner
+const SPINNER: [char; 4] = ['|', '/', '-', '\\'];
+/// How long a search runs before the spinner shows up, so quick moves
+/// print nothing
+const QUIET: Duration = Duration::from_millis(200);
+
+/// Keeps the terminal from waiting for Enter, a read returns after a tenth
+/// of a second without a key, and restores the settings when dropped
+struct KeyWait {
+    saved: String,
+}
+
+impl KeyWait {
+    fn enable() -> io::Result<KeyWait> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "min", "0", "time", "1"])?;
+
+        Ok(KeyWait { saved })
+    }
+}
+
+impl Drop for KeyWait {
+    fn drop(&mut self) {
+        let _ = stty(&[self.saved.as_str()]);
+    }
+}
+
+/// This function runs `stty` on the terminal and returns what it printed
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("stdin is not a terminal"));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// The machine thinking on a thread of its own while the terminal shows
+/// how far it got
+pub struct Thinking {
+    budget: Duration,
+    /// Where the key that cuts the search short is read from
+    keys: Box<dyn Read>,
+    /// What the search of the last move did
+    searched: Option<SearchInfo>,
+}
+
+impl Thinking {
+    /// This function returns the machine thinking no longer than `budget`
+    /// about a move, or until a key can be read from `keys`. The terminal
+    /// is put into cbreak mode only while `keys` is read.
+    pub fn new(budget: Duration, keys: impl Read + 'static) -> Thinking {
+        Thinking {
+            budget,
+            keys: Box::new(keys),
+            searched: None,
+        }
+    }
+}
+
+/// This function returns the spinner line for the `frame`-th tick
+fn status(progress: &Progress, frame: usize, keys: bool) -> String {
+    let mut line = format!("{} thinking", SPINNER[frame % SPINNER.len()]);
+    if progress.depth() > 0 {
+        line.push_str(&format!(", depth {}", progress.depth()));
+    }
+    line.push_str(&format!(", {} positions", progress.nodes()));
+    if let Some(m) = progress.best() {
+        line.push_str(&format!(", best so far {}", m));
+    }
+    if keys {
+        line.push_str(" (any key plays it now)");
+    }
+
+    line
+}
+
+impl Player for Thinking {
+    fn choose_move(&mut self, game: &mut TicTacToe, deadline: Option<Instant>) -> Decision {
+        let progress = Arc::new(Progress::new());
+        let mut searched = game.clone();
+        searched.set_progress(Some(Arc::clone(&progress)));
+        for observer in game.observers() {
+            searched.add_observer(Arc::clone(observer));
+        }
+        let budget = self.budget;
+        let search = thread::spawn(move || {
+            let mut machine = Machine::new(budget);
+            let decision = machine.choose_move(&mut searched, deadline);
+            (decision, Player::<TicTacToe>::search_info(&machine).cloned())
+        });
+
+        let started = Instant::now();
+        let interrupted = interrupt::Defer::begin();
+        let mut keys = None;
+        let mut frame = 0;
+        while !search.is_finished() {
+            if interrupt::pending() {
+                progress.stop();
+            }
+            if started.elapsed() < QUIET {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+
+            let waiting = keys.get_or_insert_with(|| KeyWait::enable().ok());
+            print!("\r{}\x1b[K", status(&progress, frame, waiting.is_some()));
+            let _ = io::stdout().flush();
+            frame += 1;
+
+            match waiting {
+                Some(_) => {
+                    let mut key = [0; 1];
+                    if matches!(self.keys.read(&mut key), Ok(1)) {
+                        progress.stop();
+                    }
+                }
+                None => thread::sleep(Duration::from_millis(100)),
+            }
+        }
+
+        if keys.is_some() {
+            print!("\r\x1b[K");
+            let _ = io::stdout().flush();
+        }
+        // The terminal is restored before Ctrl-C ends the program
+        drop(keys);
+        drop(interrupted);
+        if interrupt::pending() {
+            interrupt::leave();
+        }
+
+        match search.join() {
+            Ok((decision, info)) => {
+                self.searched = info;
+                decision
+            }
+            Err(_) => Decision::Leave,
+        }
+    }
+
+    fn swap(&mut self, game: &mut TicTacToe) -> bool {
+        game.should_swap(Some(Instant::now() + self.budget))
+    }
+
+    fn search_info(&self) -> Option<&SearchInfo> {
+        self.searched.as_ref()
+    }
+}
@@ -0,0 +1,257 @@
+//! Round-robin tournaments between machine settings and bots, so that a
+//! new bot can be measured against the built-in searches and other bots.
+//!
+//! An entrant is written like a contender of `selfplay`, e.g. `minimax:3`
+//! or `mcts`, or as `bot:` followed by the command that runs a bot, e.g.
+//! `bot:python3 mybot.py`. Every pairing plays the same number of games,
+//! the entrants take turns opening them.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::board::BoardChar;
+use crate::bot::ExternalBot;
+use crate::engine::TicTacToe;
+use crate::error::GameError;
+use crate::rng::Rng;
+use crate::selfplay::Contender;
+
+/// The rating every entrant starts the tournament with
+pub const INITIAL_RATING: f64 = 1500.0;
+
+/// How far a single game moves the ratings at most
+const K_FACTOR: f64 = 16.0;
+
+/// One entrant of a tournament
+#[derive(Debug, PartialEq, Clone)]
+pub enum Entrant {
+    Machine(Contender),
+    /// The command that runs a bot
+    Bot(String),
+}
+
+impl fmt::Display for Entrant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Entrant::Machine(contender) => write!(f, "{}", contender),
+            Entrant::Bot(command) => write!(f, "bot:{}", command),
+        }
+    }
+}
+
+impl FromStr for Entrant {
+    type Err = GameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().strip_prefix("bot:") {
+            Some(command) if command.trim().is_empty() => Err(GameError::InvalidInput("bot: needs the command that runs the bot".to_string())),
+            Some(command) => Ok(Entrant::Bot(command.trim().to_string())),
+            None => Ok(Entrant::Machine(s.parse()?)),
+        }
+    }
+}
+
+/// How an entrant did in a tournament
+#[derive(Debug, PartialEq, Clone)]
+pub struct Standing {
+    pub name: String,
+    pub wins: usize,
+    pub draws: usize,
+    pub losses: usize,
+    /// Games a bot lost because it failed, e.g. was too slow
+    pub failures: usize,
+    pub rating: f64,
+}
+
+impl Standing {
+    pub fn games(&self) -> usize {
+        self.wins + self.draws + self.losses
+    }
+
+    /// This function returns a point for every win and half a point for
+    /// every draw
+    pub fn points(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+}
+
+/// The result of a tournament, the best entrant first
+#[derive(Debug, PartialEq, Clone)]
+pub struct Standings {
+    pub rows: Vec<Standing>,
+    /// The games every pairing played
+    pub games: usize,
+}
+
+impl fmt::Display for Standings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pairings = self.rows.len() * self.rows.len().saturating_sub(1) / 2;
+        writeln!(f, "{} entrants, {} games in each of {} pairings", self.rows.len(), self.games, pairings)?;
+
+        let width = self.rows.iter().map(|row| row.name.chars().count()).max().unwrap_or(0).max("entrant".len());
+        writeln!(
+            f,
+            "{:>2}  {:<width$}  {:>6}  {:>4}  {:>4}  {:>4}  {:>6}  {:>6}",
+            "#", "entrant", "points", "won", "drew", "lost", "failed", "rating",
+            width = width
+        )?;
+        for (i, row) in self.rows.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>2}  {:<width$}  {:>6.1}  {:>4}  {:>4}  {:>4}  {:>6}  {:>6.0}",
+                i + 1,
+                row.name,
+                row.points(),
+                row.wins,
+                row.draws,
+                row.losses,
+                row.failures,
+                row.rating,
+                width = width
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// An entrant at the board: the settings of a machine or a running bot
+enum Seat {
+    Machine(Contender),
+    Bot(ExternalBot),
+}
+
+/// This function plays a game between the entrants at `seats`, the first
+/// one opening with X, and returns the index of the winner in `seats`,
+/// `None` for a draw. A bot that fails loses the game and is counted in
+/// `failures`.
+fn play_game(game: &mut TicTacToe, seats: [&mut Seat; 2], failures: &mut [usize; 2]) -> Option<usize> {
+    let default_depth = game.max_depth();
+    let side_of = |c: BoardChar| if c == BoardChar::X { 0 } else { 1 };
+
+    while !game.is_game_over() {
+        let turn = game.turn();
+        let side = side_of(turn);
+
+        let best = match &mut *seats[side] {
+            Seat::Machine(contender) => {
+                game.set_ai(contender.ai);
+                game.set_max_depth(contender.max_depth.or(default_depth));
+                game.choose_move(None).map(|(m, _)| m)
+            }
+            Seat::Bot(bot) => match bot.best_move(game, None) {
+                Ok(m) => Some(m),
+                Err(_) => {
+                    failures[side] += 1;
+                    game.resign(turn);
+                    None
+                }
+            },
+        };
+
+        match best {
+            Some(m) => {
+                game.play_move(&m).expect("moves are checked before they are played");
+            }
+            None => break,
+        }
+    }
+
+    game.winner().map(side_of)
+}
+
+/// This function returns the rating change of an entrant rated `rating`
+/// that scored `score`, 1 for a win, 0.5 for a draw and 0 for a loss,
+/// against one rated `opponent`
+fn rating_change(rating: f64, opponent: f64, score: f64) -> f64 {
+    let expected = 1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0));
+    K_FACTOR * (score - expected)
+}
+
+/// This function plays `games` games between every two `entrants` on boards
+/// set up like `board` and returns the standings. The ratings start at
+/// `INITIAL_RATING` and are updated after every game. Bots are started once
+/// for the whole tournament and think no longer than `bot_time` about a
+/// move, `rng` seeds the random choices of every game.
+pub fn tournament(board: &TicTacToe, entrants: &[Entrant], games: usize, bot_time: Duration, rng: &mut Rng) -> io::Result<Standings> {
+    let mut seats = Vec::new();
+    let mut rows = Vec::new();
+    for entrant in entrants {
+        let (seat, name) = match entrant {
+            Entrant::Machine(contender) => (Seat::Machine(*contender), contender.to_string()),
+            Entrant::Bot(command) => {
+                let bot = ExternalBot::spawn(command, bot_time)
+                    .map_err(|e| io::Error::new(e.kind(), format!("Could not start the bot '{}': {}", command, e)))?;
+                let name = bot.name().to_string();
+                (Seat::Bot(bot), name)
+            }
+        };
+        seats.push(seat);
+        rows.push(Standing {
+            name,
+            wins: 0,
+            draws: 0,
+            losses: 0,
+            failures: 0,
+            rating: INITIAL_RATING,
+        });
+    }
+
+    for a in 0..seats.len() {
+        for b in a + 1..seats.len() {
+            for number in 0..games {
+                // The entrants take turns opening
+                let (first, second) = if number.is_multiple_of(2) { (a, b) } else { (b, a) };
+
+                let mut game = board.clone();
+                game.reset();
+                game.set_first(BoardChar::X);
+                game.set_seed(rng.next_u64());
+
+                let (low, high) = seats.split_at_mut(b);
+                let (seat_a, seat_b) = (&mut low[a], &mut high[0]);
+                let pair = if first == a { [seat_a, seat_b] } else { [seat_b, seat_a] };
+                let mut failures = [0, 0];
+                let winner = play_game(&mut game, pair, &mut failures);
+
+                let score = match winner {
+                    Some(0) => 1.0,
+                    Some(_) => 0.0,
+                    None => 0.5,
+                };
+                let change = rating_change(rows[first].rating, rows[second].rating, score);
+                rows[first].rating += change;
+                rows[second].rating -= change;
+                rows[first].failures += failures[0];
+                rows[second].failures += failures[1];
+
+                match winner {
+                    Some(0) => {
+                        rows[first].wins += 1;
+                        rows[second].losses += 1;
+                    }
+                    Some(_) => {
+                        rows[second].wins += 1;
+                        rows[first].losses += 1;
+                    }
+                    None => {
+                        rows[first].draws += 1;
+                        rows[second].draws += 1;
+                    }
+                }
+            }
+        }
+    }
+
+    rows.sort_by(|a, b| {
+        b.points()
+            .partial_cmp(&a.points())
+            .unwrap_or(Ordering::Equal)
+            .then(b.rating.partial_cmp(&a.rating).unwrap_or(Ordering::Equal))
+    });
+
+    Ok(Standings { rows, games })
+}
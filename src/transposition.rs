@@ -0,0 +1,87 @@
+//! A memory of the positions a search has already scored.
+//!
+//! Many move orders lead to the same position, and on a square board the
+//! eight rotations and reflections of a position are worth the same. The
+//! table keys every position by its `hash::canonical_hash`, the smallest
+//! Zobrist hash among its eight images, so all of them share one entry.
+
+use std::collections::HashMap;
+
+use crate::board::Board;
+use crate::hash;
+
+/// Largest board side whose positions are remembered. The searches of
+/// larger boards, like that of Gomoku, are cut off too early to find many
+/// positions again.
+pub const MAX_KEYED_SIZE: usize = 8;
+/// Entries kept at most, a few dozen MB. New positions are not remembered
+/// once the table is full.
+const MAX_ENTRIES: usize = 1 << 20;
+
+/// How the stored score relates to the real value of the position
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Bound {
+    /// The score is the value
+    Exact,
+    /// The value is at least the score, the search was cut off above it
+    Lower,
+    /// The value is at most the score, no move reached the window
+    Upper,
+}
+
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub struct Entry {
+    pub score: i16,
+    pub bound: Bound,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct TranspositionTable {
+    entries: HashMap<u64, Entry>,
+    pub hits: u64,
+}
+
+impl TranspositionTable {
+    pub fn new() -> TranspositionTable {
+        TranspositionTable::default()
+    }
+
+    /// This function returns the key shared by `board` and its rotations
+    /// and reflections, `None` for boards too large to key
+    pub fn key(board: &Board) -> Option<u64> {
+        if board.len() > MAX_KEYED_SIZE {
+            return None;
+        }
+
+        Some(hash::canonical_hash(board))
+    }
+
+    /// This function returns what is known about the position with `key`
+    pub fn get(&mut self, key: u64) -> Option<Entry> {
+        let entry = self.entries.get(&key).copied();
+        if entry.is_some() {
+            self.hits += 1;
+        }
+
+        entry
+    }
+
+    /// This function works like `get` but does not count a hit
+    pub fn peek(&self, key: u64) -> Option<Entry> {
+        self.entries.get(&key).copied()
+    }
+
+    pub fn insert(&mut self, key: u64, entry: Entry) {
+        if self.entries.len() < MAX_ENTRIES || self.entries.contains_key(&key) {
+            self.entries.insert(key, entry);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
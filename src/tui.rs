@@ -0,0 +1,376 @@
+//! A full-screen terminal frontend: the arrow keys (or h, j, k, l) move a
+//! highlighted cursor over the board and Enter or space places the mark.
+//! The mouse does the same: the cursor follows the pointer and a click
+//! places the mark. The terminal is switched into cbreak mode with `stty`
+//! and drawn with ANSI escape sequences, so it works in any Unix terminal;
+//! one without mouse reporting ignores the request for it.
+
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use tic_tac_toe_rs::i18n::localize;
+use tic_tac_toe_rs::r#move::column_name;
+use tic_tac_toe_rs::render::draw_row;
+use tic_tac_toe_rs::{BoardChar, Move, TicTacToe};
+
+use tic_tac_toe_rs::terminal::Mode;
+
+/// Keeps the terminal in cbreak mode (no line buffering, no echo) with
+/// mouse reporting and restores the previous settings when dropped
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<RawMode> {
+        let saved = stty(&["-g"])?;
+        stty(&["-icanon", "-echo", "min", "1"])?;
+        // Report every press and every motion of the mouse, in the SGR
+        // encoding that has no limit on the coordinates
+        print!("\x1b[?1003h\x1b[?1006h");
+        io::stdout().flush()?;
+
+        Ok(RawMode { saved })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[self.saved.as_str()]);
+        // Stop the mouse reports and show the cursor again
+        print!("\x1b[?1003l\x1b[?1006l\x1b[?25h");
+        let _ = io::stdout().flush();
+    }
+}
+
+/// This function runs `stty` on the terminal and returns what it printed
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty").args(args).stdin(Stdio::inherit()).output()?;
+    if !output.status.success() {
+        return Err(io::Error::other("stdin is not a terminal"));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// A key press the frontend understands
+#[derive(PartialEq, Copy, Clone)]
+enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Char(char),
+    /// The mouse moved to or was clicked at the 1-based column and row of
+    /// the terminal
+    Mouse { x: usize, y: usize, click: bool },
+    /// Any other mouse report, e.g. a release or the wheel
+    OtherMouse,
+    /// The input was closed
+    End,
+}
+
+/// This function waits for the next key press
+fn read_key(input: &mut impl Read) -> io::Result<Key> {
+    let mut byte = [0u8; 1];
+
+    let mut next = |input: &mut dyn Read| -> io::Result<Option<u8>> {
+        match input.read(&mut byte)? {
+            0 => Ok(None),
+            _ => Ok(Some(byte[0])),
+        }
+    };
+
+    let key = match next(input)? {
+        None => Key::End,
+        // Arrow keys arrive as ESC [ A to ESC [ D
+        Some(0x1b) => match (next(input)?, next(input)?) {
+            (Some(b'['), Some(b'A')) => Key::Up,
+            (Some(b'['), Some(b'B')) => Key::Down,
+            (Some(b'['), Some(b'C')) => Key::Right,
+            (Some(b'['), Some(b'D')) => Key::Left,
+            (Some(b'['), Some(b'<')) => read_mouse(input)?,
+            _ => Key::Char('\x1b'),
+        },
+        Some(b'\r') | Some(b'\n') | Some(b' ') => Key::Enter,
+        Some(b'k') => Key::Up,
+        Some(b'j') => Key::Down,
+        Some(b'l') => Key::Right,
+        Some(b'h') => Key::Left,
+        Some(b) => Key::Char(b as char),
+    };
+
+    Ok(key)
+}
+
+/// This function reads the rest of a mouse report after ESC [ <, which is
+/// the button, the column and the row separated by `;`, then `M` for a
+/// press or motion and `m` for a release
+fn read_mouse(input: &mut impl Read) -> io::Result<Key> {
+    let mut fields = [0usize; 3];
+    let mut field = 0;
+    let mut byte = [0u8; 1];
+
+    let end = loop {
+        if input.read(&mut byte)? == 0 {
+            return Ok(Key::End);
+        }
+        match byte[0] {
+            b @ b'0'..=b'9' if field < fields.len() => {
+                fields[field] = fields[field].saturating_mul(10).saturating_add((b - b'0') as usize);
+            }
+            b';' => field += 1,
+            b => break b,
+        }
+    };
+
+    let [button, x, y] = fields;
+    // Bit 5 of the button is set for a motion, bit 6 for the wheel, the
+    // low bits name the button, 0 the left one and 3 none
+    let key = match end {
+        b'M' if button & 64 != 0 => Key::OtherMouse,
+        b'M' if button & 32 != 0 => Key::Mouse { x, y, click: false },
+        b'M' if button & 3 == 0 => Key::Mouse { x, y, click: true },
+        _ => Key::OtherMouse,
+    };
+
+    Ok(key)
+}
+
+/// This function returns the cell drawn at the 1-based column `x` and row
+/// `y` of the terminal by `draw`, if there is one
+fn cell_at(game: &TicTacToe, x: usize, y: usize) -> Option<Move> {
+    let size = game.board().len();
+    let width = game.theme().cell_width();
+    let margin = size.to_string().len();
+
+    // The column names take the first line and the top border the second,
+    // then every row of cells is followed by a border
+    let line = y.checked_sub(3)?;
+    let offset = x.checked_sub(margin + 2)?;
+    if line % 2 != 0 || offset % (width + 1) == width {
+        return None;
+    }
+
+    let m = Move {
+        row: line / 2,
+        col: offset / (width + 1),
+    };
+    if m.row < size && m.col < size {
+        Some(m)
+    } else {
+        None
+    }
+}
+
+/// Games won by each side during this session, and draws
+#[derive(Default)]
+struct Score {
+    x: usize,
+    o: usize,
+    draws: usize,
+}
+
+impl Score {
+    fn record(&mut self, winner: Option<BoardChar>) {
+        match winner {
+            Some(BoardChar::X) => self.x += 1,
+            Some(BoardChar::O) => self.o += 1,
+            _ => self.draws += 1,
+        }
+    }
+}
+
+/// This function draws the board with the cell under `cursor` highlighted,
+/// followed by the status bar
+fn draw(game: &TicTacToe, mode: Mode, cursor: Move, score: &Score, status: &str) -> String {
+    let grid = game.render();
+    let size = grid.size();
+    let theme = game.theme();
+    let frame = theme.frame();
+    let width = theme.cell_width();
+    let margin = size.to_string().len();
+    let border = |corners: [char; 3]| {
+        let cell = frame.horizontal.to_string().repeat(width);
+        let cells = vec![cell; size].join(&corners[1].to_string());
+        format!("{:margin$}{}{}{}\n", "", corners[0], cells, corners[2], margin = margin)
+    };
+
+    // Home the cursor, clear the screen and hide the terminal's own cursor
+    let mut screen = String::from("\x1b[H\x1b[2J\x1b[?25l");
+    screen.push_str(&format!("{:margin$}", "", margin = margin));
+    for col in 0..size {
+        screen.push_str(&format!(" {:^width$}", column_name(col), width = width));
+    }
+    screen.push('\n');
+    screen.push_str(&border(frame.corners[0]));
+
+    for (i, row) in grid.rows.iter().enumerate() {
+        if i > 0 {
+            screen.push_str(&border(frame.corners[1]));
+        }
+
+        let cells: Vec<(String, bool)> = row
+            .iter()
+            .enumerate()
+            .map(|(j, cell)| {
+                let text = format!("{:^width$}", cell.glyph, width = width);
+                if cursor == (Move { row: i, col: j }) && !game.is_game_over() {
+                    // Reverse video marks the cursor
+                    (format!("\x1b[7m{}\x1b[0m", text), false)
+                } else if cell.win_line {
+                    (theme.highlight_in(&text, cell.color), true)
+                } else {
+                    (theme.paint_in(&text, cell.color), false)
+                }
+            })
+            .collect();
+        screen.push_str(&format!("{:>margin$}{}\n", i + 1, draw_row(theme, &cells), margin = margin));
+    }
+    screen.push_str(&border(frame.corners[2]));
+
+    let symbols = game.symbols();
+    let (x, o) = match mode {
+        Mode::Machine if game.player_char() == BoardChar::X => ("you", "machine"),
+        Mode::Machine => ("machine", "you"),
+        Mode::HotSeat if game.player_char() == BoardChar::X => ("Player 1", "Player 2"),
+        Mode::HotSeat => ("Player 2", "Player 1"),
+    };
+    screen.push_str(&format!(
+        "\n{} {}: {}  {} {}: {}  draws: {}\n",
+        x, symbols.x, score.x, o, symbols.o, score.o, score.draws
+    ));
+    screen.push_str(&format!("{}\n", status));
+    screen.push_str("arrows/hjkl/mouse move, enter/click places, ? suggests a move, u undoes, n starts a new game, q quits\n");
+
+    screen
+}
+
+/// This function plays games in the full-screen frontend until the player
+/// quits. The machine thinks no longer than `budget` about a move. `new_game` sets up every game before its first move, including
+/// the first one if `game` is still empty, `finished` is told about every
+/// game that came to an end.
+pub fn run(
+    game: &mut TicTacToe,
+    mode: Mode,
+    budget: Duration,
+    mut new_game: impl FnMut(&mut TicTacToe),
+    mut finished: impl FnMut(&TicTacToe),
+) -> io::Result<()> {
+    let _raw = RawMode::enable()?;
+    let stdin = io::stdin();
+    let mut input = stdin.lock();
+    let mut stdout = io::stdout();
+
+    if game.is_empty() {
+        new_game(game);
+    }
+
+    let size = game.board().len();
+    let mut cursor = Move {
+        row: size / 2,
+        col: size / 2,
+    };
+    let mut score = Score::default();
+    let mut scored = false;
+    let mut last_machine: Option<Move> = None;
+    let mut message = String::new();
+
+    loop {
+        if mode == Mode::Machine && !game.is_game_over() && game.turn() == game.machine_char() {
+            let status = "the machine is thinking...";
+            write!(stdout, "{}", draw(game, mode, cursor, &score, status))?;
+            stdout.flush()?;
+
+            if let Ok((m, _)) = game.machine_move(budget) {
+                last_machine = Some(m);
+            }
+        }
+
+        if game.is_game_over() && !scored {
+            score.record(game.winner());
+            finished(game);
+            scored = true;
+        }
+
+        let status = if game.is_game_over() {
+            match (mode, game.winner()) {
+                (Mode::Machine, Some(c)) if c == game.player_char() => "Congratulations, you won!".to_string(),
+                (Mode::Machine, Some(_)) => "Sorry, but you lost".to_string(),
+                (Mode::HotSeat, Some(c)) => format!("Congratulations, {} won!", game.symbols().glyph(c)),
+                (_, None) => "Draw".to_string(),
+            }
+        } else {
+            let turn = format!("{} to move", game.symbols().glyph(game.turn()));
+            match last_machine {
+                Some(m) if mode == Mode::Machine => format!("{}, the machine moved to {}  {}", turn, m, message),
+                _ => format!("{}  {}", turn, message),
+            }
+        };
+        write!(stdout, "{}", draw(game, mode, cursor, &score, &status))?;
+        stdout.flush()?;
+        message.clear();
+
+        // The board is only drawn again once the pointer reaches another
+        // cell, not on every motion of the mouse
+        let key = loop {
+            match read_key(&mut input)? {
+                Key::OtherMouse => {}
+                Key::Mouse { x, y, click: false } if cell_at(game, x, y).is_none_or(|m| m == cursor) => {}
+                key => break key,
+            }
+        };
+
+        match key {
+            Key::Up => cursor.row = cursor.row.saturating_sub(1),
+            Key::Down => cursor.row = (cursor.row + 1).min(size - 1),
+            Key::Left => cursor.col = cursor.col.saturating_sub(1),
+            Key::Right => cursor.col = (cursor.col + 1).min(size - 1),
+            Key::Enter if !game.is_game_over() => {
+                if let Err(e) = game.play_move(&cursor) {
+                    message = format!("({})", localize(&e));
+                }
+            }
+            Key::Mouse { x, y, click } => {
+                if let Some(m) = cell_at(game, x, y) {
+                    cursor = m;
+                    if click && !game.is_game_over() {
+                        if let Err(e) = game.play_move(&cursor) {
+                            message = format!("({})", localize(&e));
+                        }
+                    }
+                }
+            }
+            Key::Char('u') if !game.is_game_over() => {
+                // Against the machine its reply is taken back as well
+                while let Some(p) = game.undo() {
+                    if mode == Mode::HotSeat || p.mark == game.player_char() {
+                        break;
+                    }
+                }
+                last_machine = None;
+            }
+            Key::Char('?') if !game.is_game_over() => {
+                if let Some(m) = game.hint(budget) {
+                    cursor = m;
+                    message = format!("(best move: {})", m);
+                }
+            }
+            Key::Char('n') => {
+                game.reset();
+                new_game(game);
+                scored = false;
+                last_machine = None;
+            }
+            Key::Char('q') | Key::End => break,
+            _ => {}
+        }
+    }
+
+    // Leave the final board on the screen below the prompt
+    writeln!(stdout)?;
+
+    Ok(())
+}
@@ -0,0 +1,604 @@
+//! Ultimate Tic-Tac-Toe: a 3x3 grid of small 3x3 boards. The cell a move is
+//! played in sends the opponent to the small board at the same position of
+//! the big grid. Winning a small board claims its square of the big grid,
+//! three claimed squares in a row win the game.
+
+use std::cmp;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::board::BoardChar;
+use crate::engine::{AiConfig, Difficulty, Engine, Minimax, SearchInfo, Search, HEURISTIC_BOUND, MEDIUM_RANDOM_PERCENT, WIN_SCORE};
+use crate::game::Game;
+use crate::player::{Symbols, Table};
+use crate::error::GameError;
+use crate::r#move::{column_name, Move, UltimateMove};
+use crate::render::Theme;
+use crate::rng::Rng;
+
+/// The rows, columns and diagonals of a 3x3 grid, cells counted row by row
+const LINES: [[usize; 3]; 8] = [
+    [0, 1, 2],
+    [3, 4, 5],
+    [6, 7, 8],
+    [0, 3, 6],
+    [1, 4, 7],
+    [2, 5, 8],
+    [0, 4, 8],
+    [2, 4, 6],
+];
+
+/// Worth of a small board won, for the heuristic
+const SMALL_WIN_WEIGHT: i32 = 100;
+/// Worth of two small boards won in a line of the big grid that is still open
+const META_TWO_WEIGHT: i32 = 200;
+
+/// This function returns the index of `m` on a 3x3 grid, if it is on it
+fn index(m: &Move) -> Option<usize> {
+    if m.row < 3 && m.col < 3 {
+        Some(m.row * 3 + m.col)
+    } else {
+        None
+    }
+}
+
+/// This function returns the move naming `index` on a 3x3 grid
+fn position(index: usize) -> Move {
+    Move {
+        row: index / 3,
+        col: index % 3,
+    }
+}
+
+/// This function returns the board and cell index of a move produced by `legal_moves`
+fn indices(m: &UltimateMove) -> (usize, usize) {
+    (m.board.row * 3 + m.board.col, m.cell.row * 3 + m.cell.col)
+}
+
+/// This function returns the side holding a whole line of `grid`, if any
+fn line_winner(grid: &[BoardChar; 9]) -> BoardChar {
+    for line in &LINES {
+        let c = grid[line[0]];
+        if c != BoardChar::Empty && line.iter().all(|&i| grid[i] == c) {
+            return c;
+        }
+    }
+
+    BoardChar::Empty
+}
+
+#[derive(Clone)]
+pub struct UltimateBoard {
+    /// The cells of every small board, `cells[board][cell]`
+    cells: [[BoardChar; 9]; 9],
+    /// The side that won each small board, `Empty` while undecided
+    won: [BoardChar; 9],
+    /// The small board the next move must be played on, `None` for any
+    next: Option<usize>,
+    turn: BoardChar,
+    /// The side that opens every game
+    first: BoardChar,
+    max_depth: Option<usize>,
+    player_char: BoardChar,
+    machine_char: BoardChar,
+    symbols: Symbols,
+    /// How the board is drawn by `Display`, cells keep their width
+    theme: Theme,
+    rng: Rng,
+    ai: AiConfig,
+}
+
+impl UltimateBoard {
+    /// This function starts an empty game. X opens unless `set_first` says otherwise.
+    pub fn new(player_char: BoardChar) -> UltimateBoard {
+        UltimateBoard {
+            cells: [[BoardChar::Empty; 9]; 9],
+            won: [BoardChar::Empty; 9],
+            next: None,
+            turn: BoardChar::X,
+            first: BoardChar::X,
+            max_depth: None,
+            player_char,
+            machine_char: player_char.to_opposite(),
+            symbols: Symbols::default(),
+            theme: Theme::default(),
+            rng: Rng::from_time(),
+            ai: AiConfig::default(),
+        }
+    }
+
+    /// This function limits how many moves ahead the machine looks,
+    /// `None` searches as deep as the time allows
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn set_ai(&mut self, ai: AiConfig) {
+        self.ai = ai;
+    }
+
+    /// This function reseeds the machine's choice among equally good moves
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    pub fn set_symbols(&mut self, symbols: Symbols) {
+        self.symbols = symbols;
+    }
+
+    pub fn symbols(&self) -> Symbols {
+        self.symbols
+    }
+
+    /// This function changes how the board is drawn
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn player_char(&self) -> BoardChar {
+        self.player_char
+    }
+
+    pub fn machine_char(&self) -> BoardChar {
+        self.machine_char
+    }
+
+    /// This function returns the side to move next
+    pub fn turn(&self) -> BoardChar {
+        self.turn
+    }
+
+    /// This function lets `c` open the game. It has no effect once a move
+    /// has been played.
+    pub fn set_first(&mut self, c: BoardChar) {
+        if self.is_empty() && c != BoardChar::Empty {
+            self.turn = c;
+            self.first = c;
+        }
+    }
+
+    /// This function returns true if no mark has been placed yet
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().flatten().all(|&cell| cell == BoardChar::Empty)
+    }
+
+    /// This function clears all boards for a new game, the opener stays the same
+    pub fn reset(&mut self) {
+        self.cells = [[BoardChar::Empty; 9]; 9];
+        self.won = [BoardChar::Empty; 9];
+        self.next = None;
+        self.turn = self.first;
+    }
+
+    /// This function returns the small board the next move must be played
+    /// on, or `None` if any open board may be chosen
+    pub fn next_board(&self) -> Option<Move> {
+        self.next.map(position)
+    }
+
+    /// This function returns the mark on `cell` of small board `board`
+    pub fn cell(&self, m: &UltimateMove) -> Option<BoardChar> {
+        Some(self.cells[index(&m.board)?][index(&m.cell)?])
+    }
+
+    /// This function returns the side that won small board `board`, if any
+    pub fn board_winner(&self, board: &Move) -> Option<BoardChar> {
+        match index(board).map(|b| self.won[b]) {
+            Some(BoardChar::Empty) | None => None,
+            won => won,
+        }
+    }
+
+    /// This function returns true if small board `b` still takes moves
+    fn is_open(&self, b: usize) -> bool {
+        self.won[b] == BoardChar::Empty && self.cells[b].contains(&BoardChar::Empty)
+    }
+
+    /// This function returns the side that claimed three small boards in a row, if any
+    pub fn winner(&self) -> Option<BoardChar> {
+        match line_winner(&self.won) {
+            BoardChar::Empty => None,
+            c => Some(c),
+        }
+    }
+
+    /// This function returns true if a side won or no move is left
+    pub fn is_game_over(&self) -> bool {
+        self.winner().is_some() || !(0..9).any(|b| self.is_open(b))
+    }
+
+    /// This function lists all moves the side to move may play
+    pub fn legal_moves(&self) -> Vec<UltimateMove> {
+        if self.winner().is_some() {
+            return Vec::new();
+        }
+
+        let boards: Vec<usize> = match self.next {
+            Some(b) => vec![b],
+            None => (0..9).filter(|&b| self.is_open(b)).collect(),
+        };
+
+        boards
+            .into_iter()
+            .flat_map(|b| {
+                (0..9)
+                    .filter(move |&i| self.cells[b][i] == BoardChar::Empty)
+                    .map(move |i| UltimateMove {
+                        board: position(b),
+                        cell: position(i),
+                    })
+            })
+            .collect()
+    }
+
+    /// This function places the mark of the side whose turn it is on `m`.
+    /// Moves onto another board than the one the last move sent to, or onto
+    /// a board that is already decided, are refused.
+    pub fn play_move(&mut self, m: &UltimateMove) -> Result<(), GameError> {
+        if self.is_game_over() {
+            return Err(GameError::GameAlreadyOver);
+        }
+
+        let (b, i) = match (index(&m.board), index(&m.cell)) {
+            (Some(b), Some(i)) => (b, i),
+            _ => return Err(GameError::OutOfBounds),
+        };
+
+        if self.next.is_some_and(|next| next != b) || !self.is_open(b) {
+            return Err(GameError::WrongBoard);
+        }
+
+        if self.cells[b][i] != BoardChar::Empty {
+            return Err(GameError::CellOccupied);
+        }
+
+        self.place(b, i);
+
+        Ok(())
+    }
+
+    /// This function plays the mark of the side to move on cell `i` of
+    /// board `b` and returns the board the move was restricted to before,
+    /// which `take_back` needs to undo it
+    fn place(&mut self, b: usize, i: usize) -> Option<usize> {
+        let before = self.next;
+
+        self.cells[b][i] = self.turn;
+        self.won[b] = line_winner(&self.cells[b]);
+        self.next = if self.is_open(i) { Some(i) } else { None };
+        self.turn = self.turn.to_opposite();
+
+        before
+    }
+
+    /// This function undoes `place(b, i)`
+    fn take_back(&mut self, b: usize, i: usize, before: Option<usize>) {
+        self.cells[b][i] = BoardChar::Empty;
+        self.won[b] = line_winner(&self.cells[b]);
+        self.next = before;
+        self.turn = self.turn.to_opposite();
+    }
+
+    /// This function makes the machine's move, thinking no longer than `budget`.
+    /// It returns the chosen move and the search depth that was completed.
+    pub fn machine_move(&mut self, budget: Duration) -> Result<(UltimateMove, usize), GameError> {
+        let (m, depth) = self.best_move(Some(Instant::now() + budget)).ok_or(GameError::GameAlreadyOver)?;
+        self.play_move(&m)?;
+
+        Ok((m, depth))
+    }
+
+    /// This function returns the move the machine would play, thinking
+    /// until `deadline`, and the search depth that was completed. It
+    /// returns `None` once the game is over.
+    pub fn best_move(&mut self, deadline: Option<Instant>) -> Option<(UltimateMove, usize)> {
+        if self.is_game_over() {
+            return None;
+        }
+
+        match self.ai.difficulty {
+            Difficulty::Easy => self.random_move().map(|m| (m, 0)),
+            Difficulty::Medium if self.rng.below(100) < MEDIUM_RANDOM_PERCENT => {
+                self.random_move().map(|m| (m, 0))
+            }
+            Difficulty::Medium | Difficulty::Hard => {
+                let mut engine = self.ai.engine(&mut self.rng, self.max_depth);
+                engine.best_move(self, deadline)
+            }
+        }
+    }
+
+    /// This function returns a random legal move
+    fn random_move(&mut self) -> Option<UltimateMove> {
+        let moves = self.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        Some(moves[self.rng.below(moves.len())])
+    }
+
+    /// This function searches depth 1, 2, ... until either every remaining
+    /// cell (or `max_depth`) is covered or the deadline passes, and returns
+    /// the best move of the deepest search that finished with its depth
+    pub fn iterative_deepening(&mut self, deadline: Option<Instant>, max_depth: Option<usize>) -> Option<(UltimateMove, usize)> {
+        let empty = self.cells.iter().flatten().filter(|&&cell| cell == BoardChar::Empty).count();
+        let full_depth = max_depth.map_or(empty, |max| cmp::min(max, empty));
+        let mut best = None;
+
+        for depth in 1..=full_depth {
+            let limit = if depth == 1 { None } else { deadline };
+            let mut search = Search::new(Some(depth), limit);
+            ai_debug!("iterative deepening to depth {}", depth);
+            let m = self.find_best_move(&mut search);
+
+            if search.aborted {
+                break;
+            }
+
+            match m {
+                Some(m) => best = Some((m, depth)),
+                None => break,
+            }
+        }
+
+        best
+    }
+
+    /// This function returns the best move of the side to move within the
+    /// limits of `search`. Among equally good moves one is picked at random.
+    pub fn find_best_move(&mut self, search: &mut Search) -> Option<UltimateMove> {
+        let me = self.turn;
+        let mut best_val = i16::MIN;
+        let mut best_moves = Vec::new();
+
+        for m in self.legal_moves() {
+            let (b, i) = indices(&m);
+            let before = self.place(b, i);
+            let alpha = best_val.saturating_sub(1);
+            let move_val = self.minimax(search, me, 1, alpha, i16::MAX);
+            self.take_back(b, i, before);
+
+            if search.aborted {
+                ai_debug!("search aborted at the deadline after {} nodes", search.nodes);
+                return None;
+            }
+
+            ai_debug!("candidate {} scores {}", m, move_val);
+
+            if move_val > best_val {
+                best_moves.clear();
+                best_val = move_val;
+            }
+            if move_val == best_val {
+                best_moves.push(m);
+            }
+        }
+
+        if best_moves.is_empty() {
+            return None;
+        }
+
+        let pick = self.rng.below(best_moves.len());
+        Some(best_moves.swap_remove(pick))
+    }
+
+    /// This function returns the minimax score of the position for `me`,
+    /// who maximizes, while the side to move plays on
+    fn minimax(&mut self, search: &mut Search, me: BoardChar, depth: usize, mut alpha: i16, mut beta: i16) -> i16 {
+        if search.visit() {
+            return 0;
+        }
+
+        match self.winner() {
+            Some(c) if c == me => return WIN_SCORE - depth as i16,
+            Some(_) => return -WIN_SCORE + depth as i16,
+            None => {}
+        }
+
+        let moves = self.legal_moves();
+        if moves.is_empty() {
+            return 0;
+        }
+
+        if search.max_depth.is_some_and(|max| depth >= max) {
+            return self.heuristic(me);
+        }
+
+        let maximizing = self.turn == me;
+        let mut best = if maximizing { i16::MIN } else { i16::MAX };
+
+        for m in moves {
+            let (b, i) = indices(&m);
+            let before = self.place(b, i);
+            let next_best = self.minimax(search, me, depth + 1, alpha, beta);
+            self.take_back(b, i, before);
+
+            if search.aborted {
+                return 0;
+            }
+
+            if maximizing {
+                best = cmp::max(best, next_best);
+                alpha = cmp::max(alpha, best);
+            } else {
+                best = cmp::min(best, next_best);
+                beta = cmp::min(beta, best);
+            }
+
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best
+    }
+
+    /// This function scores a position the search could not finish from the
+    /// point of view of `me`. Small boards won count most, then lines of the
+    /// big grid two boards short of a win, then open lines within the small
+    /// boards still in play.
+    pub fn heuristic(&self, me: BoardChar) -> i16 {
+        let other = me.to_opposite();
+        let mut score: i32 = 0;
+
+        for &won in &self.won {
+            if won == me {
+                score += SMALL_WIN_WEIGHT;
+            } else if won == other {
+                score -= SMALL_WIN_WEIGHT;
+            }
+        }
+
+        for line in &LINES {
+            // A drawn board blocks its line of the big grid for both sides
+            let blocked = line.iter().any(|&b| self.won[b] == BoardChar::Empty && !self.is_open(b));
+            let mine = line.iter().filter(|&&b| self.won[b] == me).count();
+            let theirs = line.iter().filter(|&&b| self.won[b] == other).count();
+
+            if !blocked && mine == 2 && theirs == 0 {
+                score += META_TWO_WEIGHT;
+            } else if !blocked && theirs == 2 && mine == 0 {
+                score -= META_TWO_WEIGHT;
+            }
+        }
+
+        for b in (0..9).filter(|&b| self.is_open(b)) {
+            for line in &LINES {
+                let mine = line.iter().filter(|&&i| self.cells[b][i] == me).count();
+                let theirs = line.iter().filter(|&&i| self.cells[b][i] == other).count();
+
+                // One mark counts 1, two marks with the third cell free count 4
+                match (mine, theirs) {
+                    (n, 0) if n > 0 => score += 1 << (2 * (n - 1)),
+                    (0, n) if n > 0 => score -= 1 << (2 * (n - 1)),
+                    _ => {}
+                }
+            }
+        }
+
+        let bound = HEURISTIC_BOUND as i32;
+        cmp::max(-bound, cmp::min(bound, score)) as i16
+    }
+}
+
+impl Engine<UltimateBoard> for Minimax {
+    fn best_move(&mut self, game: &mut UltimateBoard, deadline: Option<Instant>) -> Option<(UltimateMove, usize)> {
+        game.iterative_deepening(deadline, self.max_depth)
+    }
+}
+
+impl Game for UltimateBoard {
+    type Move = UltimateMove;
+
+    fn legal_moves(&self) -> Vec<UltimateMove> {
+        UltimateBoard::legal_moves(self)
+    }
+
+    fn play(&mut self, m: &UltimateMove) -> Result<(), GameError> {
+        self.play_move(m)
+    }
+
+    fn turn(&self) -> BoardChar {
+        UltimateBoard::turn(self)
+    }
+
+    fn winner(&self) -> Option<BoardChar> {
+        UltimateBoard::winner(self)
+    }
+
+    fn is_game_over(&self) -> bool {
+        UltimateBoard::is_game_over(self)
+    }
+
+    fn score(&self, me: BoardChar) -> i16 {
+        self.heuristic(me)
+    }
+}
+
+impl Table for UltimateBoard {
+    fn player_char(&self) -> BoardChar {
+        UltimateBoard::player_char(self)
+    }
+
+    fn machine_char(&self) -> BoardChar {
+        UltimateBoard::machine_char(self)
+    }
+
+    fn glyph(&self, c: BoardChar) -> Option<char> {
+        Some(self.symbols.glyph(c))
+    }
+
+    fn set_first(&mut self, c: BoardChar) {
+        UltimateBoard::set_first(self, c);
+    }
+
+    fn reset(&mut self) {
+        UltimateBoard::reset(self);
+    }
+
+    fn machine_choice(&mut self, deadline: Option<Instant>) -> Option<(UltimateMove, SearchInfo)> {
+        let (m, depth) = self.best_move(deadline)?;
+        Some((m, SearchInfo { depth, ..SearchInfo::default() }))
+    }
+}
+
+impl fmt::Display for UltimateBoard {
+    /// The small boards are drawn side by side inside the grid of the
+    /// theme. The big grid is labeled like a board of its own, the cells of
+    /// each small board with letters and numbers underneath.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frame = self.theme.frame();
+        let border = |corners: [char; 3]| {
+            let segment = frame.horizontal.to_string().repeat(7);
+            format!("     {}{}{}{}{}{}{}\n", corners[0], segment, corners[1], segment, corners[1], segment, corners[2])
+        };
+
+        // Big letters sit above the middle column of their small board
+        let boards: Vec<String> = (0..3).map(|col| format!("    {}", column_name(col))).collect();
+        let mut board_txt = format!("     {}\n     ", boards.join("   "));
+        let letters = format!("  {} {} {}", column_name(0), column_name(1), column_name(2));
+        board_txt.push_str(&[letters.as_str(); 3].join(" "));
+        board_txt.push('\n');
+        board_txt.push_str(&border(frame.corners[0]));
+
+        for row in 0..9 {
+            let (big, small) = (row / 3, row % 3);
+            if big > 0 && small == 0 {
+                board_txt.push_str(&border(frame.corners[1]));
+            }
+
+            let label = if small == 0 { (big + 1).to_string() } else { String::new() };
+            board_txt.push_str(&format!("{:>2} {} ", label, small + 1));
+            for b in big * 3..big * 3 + 3 {
+                board_txt.push(frame.vertical);
+                board_txt.push(' ');
+                for i in small * 3..small * 3 + 3 {
+                    let glyph = match self.cells[b][i] {
+                        BoardChar::Empty => '.',
+                        c => self.symbols.glyph(c),
+                    };
+                    board_txt.push_str(&self.theme.paint(&glyph.to_string(), self.cells[b][i]));
+                    board_txt.push(' ');
+                }
+            }
+            board_txt.push(frame.vertical);
+            board_txt.push('\n');
+        }
+
+        board_txt.push_str(&border(frame.corners[2]));
+
+        for b in (0..9).filter(|&b| self.won[b] != BoardChar::Empty) {
+            board_txt.push_str(&format!("board {} won by {}\n", position(b), self.symbols.glyph(self.won[b])));
+        }
+
+        if !self.is_game_over() {
+            match self.next {
+                Some(b) => board_txt.push_str(&format!("next move on board {}\n", position(b))),
+                None => board_txt.push_str("next move on any open board\n"),
+            }
+        }
+
+        writeln!(f, "{}", board_txt)
+    }
+}
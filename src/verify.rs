@@ -0,0 +1,179 @@
+//! Proofs that an engine plays perfectly. Every game the opponent can bring
+//! about is walked, the engine answering each position the way it would in
+//! a game: it must never lose and it must take every immediate win it is
+//! offered. On the classic board perfect play draws, so the hard machine
+//! passes from the empty board as X and as O.
+//!
+//! The engine is asked once per position. An engine choosing at random
+//! among equally good moves, like the machine with its opening book, is
+//! proven for the choices it made, which depend only on the seed of the
+//! game.
+
+use std::fmt;
+
+use crate::board::BoardChar;
+use crate::engine::Engine;
+use crate::game::Game;
+
+/// What the walk found when the engine passed
+#[derive(Debug, PartialEq, Default, Copy, Clone)]
+pub struct Proof {
+    /// The positions the engine moved in
+    pub positions: usize,
+    /// The games played to their end, won by the engine or drawn
+    pub games: usize,
+    pub wins: usize,
+    pub draws: usize,
+}
+
+impl fmt::Display for Proof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "never lost in {} games ({} won, {} drawn), {} positions checked",
+            self.games, self.wins, self.draws, self.positions
+        )
+    }
+}
+
+/// How the engine failed
+#[derive(Debug, PartialEq, Copy, Clone)]
+pub enum Flaw<M> {
+    /// The opponent won
+    Lost,
+    /// The engine played the move and did not win, though a move winning at
+    /// once was there
+    MissedWin(M),
+    /// The engine found no legal move in a game that is not over
+    NoMove,
+}
+
+/// The game in which the engine failed
+#[derive(Debug, Clone)]
+pub struct Counterexample<G: Game> {
+    /// The moves of both sides from the start, the last one by the engine
+    /// unless it found none
+    pub moves: Vec<G::Move>,
+    pub flaw: Flaw<G::Move>,
+}
+
+impl<G: Game> fmt::Display for Counterexample<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let moves: Vec<String> = self.moves.iter().map(ToString::to_string).collect();
+        write!(f, "after {}: ", if moves.is_empty() { "no move".to_string() } else { moves.join(" ") })?;
+
+        match &self.flaw {
+            Flaw::Lost => write!(f, "the engine lost"),
+            Flaw::MissedWin(m) => write!(f, "the engine played {} and missed a win", m),
+            Flaw::NoMove => write!(f, "the engine found no legal move"),
+        }
+    }
+}
+
+/// This function proves that `engine`, playing `side`, never loses `start`
+/// and always takes an immediate win, or returns the first game in which it
+/// did not
+pub fn verify<G: Game>(start: &G, engine: &mut dyn Engine<G>, side: BoardChar) -> Result<Proof, Counterexample<G>> {
+    let mut proof = Proof::default();
+    walk(start, &mut Vec::new(), engine, side, &mut proof)?;
+
+    Ok(proof)
+}
+
+fn walk<G: Game>(game: &G, moves: &mut Vec<G::Move>, engine: &mut dyn Engine<G>, side: BoardChar, proof: &mut Proof) -> Result<(), Counterexample<G>> {
+    let failed = |moves: &[G::Move], flaw| Counterexample {
+        moves: moves.to_vec(),
+        flaw,
+    };
+
+    if game.is_game_over() {
+        proof.games += 1;
+        return match game.winner() {
+            Some(c) if c == side => {
+                proof.wins += 1;
+                Ok(())
+            }
+            Some(_) => Err(failed(moves, Flaw::Lost)),
+            None => {
+                proof.draws += 1;
+                Ok(())
+            }
+        };
+    }
+
+    if game.turn() != side {
+        for m in game.legal_moves() {
+            let mut next = game.clone();
+            if next.play(&m).is_ok() {
+                moves.push(m);
+                walk(&next, moves, engine, side, proof)?;
+                moves.pop();
+            }
+        }
+        return Ok(());
+    }
+
+    proof.positions += 1;
+    let can_win = game.legal_moves().iter().any(|m| {
+        let mut next = game.clone();
+        next.play(m).is_ok() && next.winner() == Some(side)
+    });
+
+    let mut next = game.clone();
+    let m = match engine.best_move(&mut next.clone(), None) {
+        Some((m, _)) if next.play(&m).is_ok() => m,
+        _ => return Err(failed(moves, Flaw::NoMove)),
+    };
+    moves.push(m);
+    if can_win && next.winner() != Some(side) {
+        return Err(failed(moves, Flaw::MissedWin(m)));
+    }
+
+    walk(&next, moves, engine, side, proof)?;
+    moves.pop();
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::{AiConfig, Difficulty, Minimax, TicTacToe};
+
+    #[test]
+    fn hard_machine_never_loses_the_classic_game() {
+        for seed in 0..3 {
+            for side in [BoardChar::X, BoardChar::O] {
+                let mut game = TicTacToe::new(BoardChar::X);
+                game.set_seed(seed);
+
+                let proof = verify(&game, &mut AiConfig::default(), side).unwrap_or_else(|e| panic!("{}: {}", side, e));
+                assert!(proof.games > 0 && proof.games == proof.wins + proof.draws);
+            }
+        }
+    }
+
+    #[test]
+    fn minimax_never_loses_the_classic_game() {
+        for side in [BoardChar::X, BoardChar::O] {
+            let game = TicTacToe::new(BoardChar::X);
+            if let Err(e) = verify(&game, &mut Minimax { max_depth: None }, side) {
+                panic!("{}: {}", side, e);
+            }
+        }
+    }
+
+    #[test]
+    fn easy_machine_is_caught() {
+        let mut game = TicTacToe::new(BoardChar::X);
+        game.set_seed(0);
+        let mut easy = AiConfig {
+            difficulty: Difficulty::Easy,
+            ..AiConfig::default()
+        };
+
+        let counterexample = verify(&game, &mut easy, BoardChar::O).expect_err("random moves lose some game");
+        assert!(matches!(counterexample.flaw, Flaw::Lost | Flaw::MissedWin(_)));
+        assert!(!counterexample.moves.is_empty());
+    }
+}
@@ -0,0 +1,219 @@
+//! A wrapper around `TicTacToe` for JavaScript frontends, e.g. a web page
+//! running the engine compiled to WebAssembly.
+//!
+//! Every method only takes and returns numbers, booleans, strings, byte
+//! arrays and `Result<_, String>`, the types `wasm-bindgen` converts on its
+//! own, so a wasm crate can export `WebGame` with a `#[wasm_bindgen]`
+//! wrapper and nothing else. Nothing here reads the terminal or the clock.
+//! That wrapper lives outside this crate, which builds offline and so
+//! cannot depend on `wasm-bindgen` itself.
+//!
+//! Cells are numbered row by row from 0, marks are `0` for an empty cell,
+//! `1` for X and `2` for O.
+
+use crate::board::BoardChar;
+use crate::engine::{AiConfig, TicTacToe};
+use crate::json::ToJson;
+use crate::r#move::{Move, MAX_SIZE};
+
+/// This function returns the number JavaScript sees for `c`
+pub(crate) fn mark_code(c: BoardChar) -> u8 {
+    match c {
+        BoardChar::Empty => 0,
+        BoardChar::X => 1,
+        BoardChar::O => 2,
+    }
+}
+
+pub struct WebGame {
+    game: TicTacToe,
+}
+
+impl WebGame {
+    /// This function starts a game on a `size` x `size` board where
+    /// `win_len` marks in a row win. `player` is "X" or "O", the machine
+    /// plays the other mark. There is no clock to seed the machine's random
+    /// choices from, so the page passes a `seed`, e.g. `Date.now()`.
+    pub fn new(size: u32, win_len: u32, player: &str, seed: u64) -> Result<WebGame, String> {
+        let (size, win_len) = (size as usize, win_len as usize);
+        if size == 0 || size > MAX_SIZE {
+            return Err(format!("The size must be between 1 and {}", MAX_SIZE));
+        }
+        if win_len == 0 || win_len > size {
+            return Err(format!("The winning length must be between 1 and the board size {}", size));
+        }
+
+        let mut game = TicTacToe::with_size(player.parse()?, size, win_len);
+        game.set_seed(seed);
+
+        Ok(WebGame { game })
+    }
+
+    /// This function restores a game saved with `to_json`
+    pub fn from_json(json: &str, seed: u64) -> Result<WebGame, String> {
+        let mut game = TicTacToe::from_json(json)?;
+        game.set_seed(seed);
+
+        Ok(WebGame { game })
+    }
+
+    pub fn to_json(&self) -> String {
+        self.game.to_json()
+    }
+
+    /// This function switches to the misère game, where completing a line loses
+    pub fn set_misere(&mut self, misere: bool) {
+        self.game.set_misere(misere);
+    }
+
+    /// This function sets the machine's strength: "easy", "medium" or "hard"
+    pub fn set_difficulty(&mut self, difficulty: &str) -> Result<(), String> {
+        let ai = AiConfig {
+            difficulty: difficulty.parse()?,
+            ..self.game.ai()
+        };
+        self.game.set_ai(ai);
+
+        Ok(())
+    }
+
+    /// This function sets the machine's search: "minimax" or "mcts"
+    pub fn set_algorithm(&mut self, algorithm: &str) -> Result<(), String> {
+        let ai = AiConfig {
+            algorithm: algorithm.parse()?,
+            ..self.game.ai()
+        };
+        self.game.set_ai(ai);
+
+        Ok(())
+    }
+
+    pub fn size(&self) -> u32 {
+        self.game.board().len() as u32
+    }
+
+    /// This function returns the marks of all cells, row by row
+    pub fn cells(&self) -> Vec<u8> {
+        self.game.board().iter().flatten().map(|&c| mark_code(c)).collect()
+    }
+
+    /// This function returns the board as text, rows separated by `/` and
+    /// `.` for an empty cell, as `parse_board` reads it
+    pub fn board(&self) -> String {
+        self.game
+            .board()
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&c| match c {
+                        BoardChar::Empty => '.',
+                        BoardChar::X => 'X',
+                        BoardChar::O => 'O',
+                    })
+                    .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// This function returns the mark of the side to move
+    pub fn turn(&self) -> u8 {
+        mark_code(self.game.turn())
+    }
+
+    pub fn player(&self) -> u8 {
+        mark_code(self.game.player_char())
+    }
+
+    pub fn machine(&self) -> u8 {
+        mark_code(self.game.machine_char())
+    }
+
+    /// This function lets the machine open the game. It has no effect once
+    /// a move has been played.
+    pub fn machine_first(&mut self) {
+        if self.game.is_empty() {
+            let machine = self.game.machine_char();
+            self.game.set_first(machine);
+        }
+    }
+
+    /// This function places the mark of the side to move on `cell`
+    pub fn play(&mut self, cell: u32) -> Result<(), String> {
+        let m = self.position(cell)?;
+
+        self.game.play_move(&m).map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    /// This function places the mark of the side to move on a cell written
+    /// like on the terminal, e.g. "2B"
+    pub fn play_notation(&mut self, m: &str) -> Result<(), String> {
+        let m: Move = m.parse()?;
+
+        self.game.play_move(&m).map(|_| ()).map_err(|e| e.to_string())
+    }
+
+    /// This function lets the machine move and returns the cell it played.
+    /// The search is limited by its depth only, as there is no clock.
+    pub fn machine_move(&mut self) -> Result<u32, String> {
+        let (m, _) = self.game.machine_move_until(None).map_err(|e| e.to_string())?;
+
+        Ok(self.cell(&m))
+    }
+
+    /// This function takes back the last move and returns its cell
+    pub fn undo(&mut self) -> Option<u32> {
+        self.game.undo().map(|p| self.cell(&p.at))
+    }
+
+    /// This function plays the last move taken back again and returns its cell
+    pub fn redo(&mut self) -> Option<u32> {
+        self.game.redo().map(|p| self.cell(&p.at))
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.game.is_game_over()
+    }
+
+    /// This function returns the mark that won, 0 while nobody has
+    pub fn winner(&self) -> u8 {
+        self.game.winner().map_or(0, mark_code)
+    }
+
+    /// This function returns the cells of the line that decided the game,
+    /// empty while nobody has completed one
+    pub fn winning_line(&self) -> Vec<u32> {
+        let line = self.game.completed_line();
+
+        line.map_or_else(Vec::new, |line| {
+            line.cells.iter().map(|&(row, col)| self.cell(&Move { row, col })).collect()
+        })
+    }
+
+    /// This function returns the board as JSON for the page to draw, an
+    /// array of rows of cells like
+    /// `{"mark":"X","glyph":"X","color":"red","win_line":false,"highlight":true}`,
+    /// `highlight` marking the last move
+    pub fn render(&self) -> String {
+        self.game.render().to_json().to_string()
+    }
+
+    /// This function returns the cell numbered for JavaScript
+    fn cell(&self, m: &Move) -> u32 {
+        (m.row * self.game.board().len() + m.col) as u32
+    }
+
+    /// This function returns the move onto `cell`
+    fn position(&self, cell: u32) -> Result<Move, String> {
+        let size = self.game.board().len();
+        let cell = cell as usize;
+        if cell >= size * size {
+            return Err(format!("There is no cell {} on the board", cell));
+        }
+
+        Ok(Move {
+            row: cell / size,
+            col: cell % size,
+        })
+    }
+}
@@ -0,0 +1,323 @@
+//! Wild Tic-Tac-Toe: the classic board, but on every turn the side to move
+//! places either an X or an O, and whoever completes a line of three equal
+//! marks wins, whichever mark it is.
+//!
+//! The marks no longer belong to the sides, so the sides keep the names X
+//! and O only to tell them apart: `turn` and `winner` return a side, the
+//! mark a move places is part of the `WildMove`. The opener can force a
+//! win, which `Minimax` finds by searching to the end of the game.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::board::{BoardChar, WinLine};
+use crate::engine::{AiConfig, Difficulty, Engine, Minimax, SearchInfo, HEURISTIC_BOUND, MEDIUM_RANDOM_PERCENT};
+use crate::error::GameError;
+use crate::game::{self, Game};
+use crate::player::{Symbols, Table};
+use crate::r#move::{Move, WildMove};
+use crate::render::{draw_board, Theme};
+use crate::rng::Rng;
+
+const SIZE: usize = 3;
+const CELLS: usize = SIZE * SIZE;
+/// The center of the board, which lies on more lines than any other cell
+const CENTER: usize = CELLS / 2;
+/// The rows, columns and diagonals of the board
+const LINES: [[usize; SIZE]; 8] = [[0, 1, 2], [3, 4, 5], [6, 7, 8], [0, 3, 6], [1, 4, 7], [2, 5, 8], [0, 4, 8], [2, 4, 6]];
+
+#[derive(Clone)]
+pub struct Wild {
+    cells: [BoardChar; CELLS],
+    /// The side to move, not the mark it places
+    turn: BoardChar,
+    /// The side that opens every game
+    first: BoardChar,
+    /// The line completed and the side that completed it, if any
+    line: Option<([usize; SIZE], BoardChar)>,
+    max_depth: Option<usize>,
+    player_char: BoardChar,
+    machine_char: BoardChar,
+    /// How the board is drawn by `Display`
+    theme: Theme,
+    rng: Rng,
+    ai: AiConfig,
+}
+
+impl Wild {
+    /// This function starts an empty game in which the player is the side
+    /// `player_char`. X opens unless `set_first` says otherwise.
+    pub fn new(player_char: BoardChar) -> Wild {
+        Wild {
+            cells: [BoardChar::Empty; CELLS],
+            turn: BoardChar::X,
+            first: BoardChar::X,
+            line: None,
+            max_depth: None,
+            player_char,
+            machine_char: player_char.to_opposite(),
+            theme: Theme::default(),
+            rng: Rng::from_time(),
+            ai: AiConfig::default(),
+        }
+    }
+
+    /// This function limits how many moves ahead the machine looks, `None`
+    /// searches to the end of the game, which it does by default
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn set_ai(&mut self, ai: AiConfig) {
+        self.ai = ai;
+    }
+
+    /// This function reseeds the machine's random choices
+    pub fn set_seed(&mut self, seed: u64) {
+        self.rng = Rng::new(seed);
+    }
+
+    /// This function changes how the board is drawn
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    pub fn player_char(&self) -> BoardChar {
+        self.player_char
+    }
+
+    pub fn machine_char(&self) -> BoardChar {
+        self.machine_char
+    }
+
+    /// This function returns the side to move next
+    pub fn turn(&self) -> BoardChar {
+        self.turn
+    }
+
+    /// This function lets the side `c` open the game. It has no effect once
+    /// a move has been played.
+    pub fn set_first(&mut self, c: BoardChar) {
+        if self.is_empty() && c != BoardChar::Empty {
+            self.turn = c;
+            self.first = c;
+        }
+    }
+
+    /// This function returns true if no mark has been placed yet
+    pub fn is_empty(&self) -> bool {
+        self.cells.iter().all(|&cell| cell == BoardChar::Empty)
+    }
+
+    /// This function clears the board for a new game, the opener stays the same
+    pub fn reset(&mut self) {
+        self.cells = [BoardChar::Empty; CELLS];
+        self.line = None;
+        self.turn = self.first;
+    }
+
+    /// This function returns the mark on `m`
+    pub fn cell(&self, m: &Move) -> Option<BoardChar> {
+        let i = Wild::cell_index(m)?;
+        Some(self.cells[i])
+    }
+
+    /// This function returns the cell `m` names, if it is on the board
+    fn cell_index(m: &Move) -> Option<usize> {
+        if m.row < SIZE && m.col < SIZE {
+            Some(m.row * SIZE + m.col)
+        } else {
+            None
+        }
+    }
+
+    /// This function returns the side that completed a line, if any
+    pub fn winner(&self) -> Option<BoardChar> {
+        self.line.map(|(_, side)| side)
+    }
+
+    /// This function returns true if a side won or the board is full
+    pub fn is_game_over(&self) -> bool {
+        self.line.is_some() || !self.cells.contains(&BoardChar::Empty)
+    }
+
+    /// This function lists the moves of the side to move, both marks on
+    /// every empty cell, the center first
+    pub fn legal_moves(&self) -> Vec<WildMove> {
+        if self.line.is_some() {
+            return Vec::new();
+        }
+
+        let mut cells: Vec<usize> = (0..CELLS).filter(|&i| self.cells[i] == BoardChar::Empty).collect();
+        if let Some(i) = cells.iter().position(|&i| i == CENTER) {
+            cells.swap(0, i);
+        }
+
+        cells
+            .into_iter()
+            .flat_map(|i| {
+                [BoardChar::X, BoardChar::O].iter().map(move |&mark| WildMove {
+                    cell: Move { row: i / SIZE, col: i % SIZE },
+                    mark,
+                })
+            })
+            .collect()
+    }
+
+    /// This function places the mark of `m` for the side to move
+    pub fn play_move(&mut self, m: &WildMove) -> Result<(), GameError> {
+        if self.is_game_over() {
+            return Err(GameError::GameAlreadyOver);
+        }
+        if m.mark == BoardChar::Empty {
+            return Err(GameError::InvalidInput("Please place an X or an O".to_string()));
+        }
+        let i = Wild::cell_index(&m.cell).ok_or(GameError::OutOfBounds)?;
+        if self.cells[i] != BoardChar::Empty {
+            return Err(GameError::CellOccupied);
+        }
+
+        self.cells[i] = m.mark;
+        self.line = LINES
+            .iter()
+            .find(|line| line.contains(&i) && line.iter().all(|&j| self.cells[j] == m.mark))
+            .map(|&line| (line, self.turn));
+        self.turn = self.turn.to_opposite();
+
+        Ok(())
+    }
+
+    /// This function makes the machine's move, thinking no longer than `budget`.
+    /// It returns the chosen move and the search depth that was completed.
+    pub fn machine_move(&mut self, budget: Duration) -> Result<(WildMove, usize), GameError> {
+        let (m, depth) = self.best_move(Some(Instant::now() + budget)).ok_or(GameError::GameAlreadyOver)?;
+        self.play_move(&m)?;
+
+        Ok((m, depth))
+    }
+
+    /// This function returns the move the machine would play, thinking
+    /// until `deadline`, and the search depth that was completed. It
+    /// returns `None` once the game is over.
+    pub fn best_move(&mut self, deadline: Option<Instant>) -> Option<(WildMove, usize)> {
+        if self.is_game_over() {
+            return None;
+        }
+
+        match self.ai.difficulty {
+            Difficulty::Easy => self.random_move().map(|m| (m, 0)),
+            Difficulty::Medium if self.rng.below(100) < MEDIUM_RANDOM_PERCENT => {
+                self.random_move().map(|m| (m, 0))
+            }
+            Difficulty::Medium | Difficulty::Hard => {
+                let mut engine = self.ai.engine(&mut self.rng, self.max_depth);
+                engine.best_move(self, deadline)
+            }
+        }
+    }
+
+    /// This function returns a random legal move
+    fn random_move(&mut self) -> Option<WildMove> {
+        let moves = self.legal_moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        Some(moves[self.rng.below(moves.len())])
+    }
+
+    /// This function scores a position the search could not finish from the
+    /// point of view of `me`. A line with two equal marks and an empty cell
+    /// is won by whoever moves next, so it counts for the side to move.
+    pub fn heuristic(&self, me: BoardChar) -> i16 {
+        let open = LINES.iter().any(|line| {
+            let marks: Vec<BoardChar> = line.iter().map(|&i| self.cells[i]).filter(|&c| c != BoardChar::Empty).collect();
+            marks.len() == 2 && marks[0] == marks[1]
+        });
+
+        match (open, self.turn == me) {
+            (false, _) => 0,
+            (true, true) => HEURISTIC_BOUND,
+            (true, false) => -HEURISTIC_BOUND,
+        }
+    }
+}
+
+impl Engine<Wild> for Minimax {
+    fn best_move(&mut self, game: &mut Wild, deadline: Option<Instant>) -> Option<(WildMove, usize)> {
+        let empty = game.cells.iter().filter(|&&cell| cell == BoardChar::Empty).count();
+        let full_depth = self.max_depth.map_or(empty, |max| max.min(empty));
+
+        game::iterative_deepening(game, deadline, full_depth)
+    }
+}
+
+impl Game for Wild {
+    type Move = WildMove;
+
+    fn legal_moves(&self) -> Vec<WildMove> {
+        Wild::legal_moves(self)
+    }
+
+    fn play(&mut self, m: &WildMove) -> Result<(), GameError> {
+        self.play_move(m)
+    }
+
+    fn turn(&self) -> BoardChar {
+        Wild::turn(self)
+    }
+
+    fn winner(&self) -> Option<BoardChar> {
+        Wild::winner(self)
+    }
+
+    fn is_game_over(&self) -> bool {
+        Wild::is_game_over(self)
+    }
+
+    fn score(&self, me: BoardChar) -> i16 {
+        self.heuristic(me)
+    }
+}
+
+impl Table for Wild {
+    fn player_char(&self) -> BoardChar {
+        Wild::player_char(self)
+    }
+
+    fn machine_char(&self) -> BoardChar {
+        Wild::machine_char(self)
+    }
+
+    fn glyph(&self, _c: BoardChar) -> Option<char> {
+        None
+    }
+
+    fn set_first(&mut self, c: BoardChar) {
+        Wild::set_first(self, c);
+    }
+
+    fn reset(&mut self) {
+        Wild::reset(self);
+    }
+
+    fn machine_choice(&mut self, deadline: Option<Instant>) -> Option<(WildMove, SearchInfo)> {
+        let (m, depth) = self.best_move(deadline)?;
+        Some((m, SearchInfo { depth, ..SearchInfo::default() }))
+    }
+}
+
+impl fmt::Display for Wild {
+    /// The board is drawn with the plain marks, which belong to neither
+    /// side, and a completed line is highlighted
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let board: Vec<Vec<BoardChar>> = self.cells.chunks(SIZE).map(<[BoardChar]>::to_vec).collect();
+        let cells: Vec<(usize, usize)> = self.line.iter().flat_map(|(line, _)| line.iter().map(|&i| (i / SIZE, i % SIZE))).collect();
+        let line = self.line.map(|(line, _)| WinLine {
+            mark: self.cells[line[0]],
+            cells: &cells,
+        });
+
+        writeln!(f, "{}", draw_board(&board, Symbols::default(), self.theme, line))
+    }
+}
@@ -0,0 +1,88 @@
+//! Plays whole games at the terminal from scripted input.
+
+use tic_tac_toe_rs::notify::Silent;
+use tic_tac_toe_rs::player::{Machine, Scripted};
+use tic_tac_toe_rs::terminal::{self, Casual, Mode, Settings};
+use tic_tac_toe_rs::{BoardChar, Column, ConnectFour, Move, ScriptedInput, TicTacToe};
+
+fn hot_seat() -> Settings {
+    Settings {
+        mode: Mode::HotSeat,
+        ..Settings::default()
+    }
+}
+
+#[test]
+fn two_people_play_a_game_to_its_end() {
+    let mut input = ScriptedInput::new(["1A", "2A", "1B", "2B", "1C"]);
+    let mut game = TicTacToe::new(BoardChar::X);
+    let mut machine = Machine::new(hot_seat().think);
+
+    terminal::play(&mut input, &mut game, &hot_seat(), &mut machine, &mut Silent, &mut Silent);
+
+    assert!(game.is_game_over());
+    assert_eq!(game.winner(), Some(BoardChar::X));
+    assert_eq!(input.remaining(), 0);
+}
+
+#[test]
+fn lines_that_are_no_moves_are_asked_again() {
+    let mut input = ScriptedInput::new(["1A", "1A", "hello", "2A", "undo", "2A", "1B", "2B", "1C"]);
+    let mut game = TicTacToe::new(BoardChar::X);
+    let mut machine = Machine::new(hot_seat().think);
+
+    terminal::play(&mut input, &mut game, &hot_seat(), &mut machine, &mut Silent, &mut Silent);
+
+    assert_eq!(game.winner(), Some(BoardChar::X));
+    assert_eq!(game.moves().len(), 5);
+}
+
+#[test]
+fn the_game_is_left_when_the_input_ends() {
+    let mut input = ScriptedInput::new(["2B"]);
+    let mut game = TicTacToe::new(BoardChar::X);
+    let mut machine = Machine::new(hot_seat().think);
+
+    terminal::play(&mut input, &mut game, &hot_seat(), &mut machine, &mut Silent, &mut Silent);
+
+    assert!(!game.is_game_over());
+    assert_eq!(game.moves().len(), 1);
+}
+
+#[test]
+fn the_machine_answers_and_never_loses() {
+    // Every cell in turn, the ones the machine took are refused
+    let cells = ["2B", "1A", "1B", "1C", "2A", "2C", "3A", "3B", "3C"];
+    let mut input = ScriptedInput::new(cells);
+    let mut game = TicTacToe::new(BoardChar::X);
+    game.set_seed(7);
+    let mut machine = Machine::new(Settings::default().think);
+
+    terminal::play(&mut input, &mut game, &Settings::default(), &mut machine, &mut Silent, &mut Silent);
+
+    assert!(game.is_game_over());
+    assert_ne!(game.winner(), Some(BoardChar::X));
+    assert!(game.moves().iter().any(|p| p.mark == BoardChar::O));
+}
+
+#[test]
+fn any_player_can_take_the_other_side() {
+    let mut input = ScriptedInput::new(["1A", "1B", "1C"]);
+    let mut game = TicTacToe::new(BoardChar::X);
+    let mut opponent = Scripted::new(vec![Move { row: 1, col: 0 }, Move { row: 1, col: 1 }]);
+
+    terminal::play(&mut input, &mut game, &Settings::default(), &mut opponent, &mut Silent, &mut Silent);
+
+    assert_eq!(game.winner(), Some(BoardChar::X));
+    assert_eq!(game.moves().len(), 5);
+}
+
+#[test]
+fn a_variant_is_played_through_the_same_turns() {
+    let mut game = ConnectFour::new(BoardChar::X);
+    let mut people = Scripted::new("AAAA".chars().map(|c| c.to_string().parse::<Column>().unwrap()).collect());
+    let mut opponent = Scripted::new("BBB".chars().map(|c| c.to_string().parse::<Column>().unwrap()).collect());
+
+    assert!(terminal::play_turns(&mut game, &mut people, Some(&mut opponent), &mut Casual));
+    assert_eq!(game.winner(), Some(BoardChar::X));
+}